@@ -21,12 +21,17 @@ pub struct RestArgs {
     /// certificate CA is not present within the webpki certificate bundle.
     #[structopt(long, name = "PATH", env = "JORMUNGANDR_TLS_CERT_PATH")]
     tls_cert_path: Option<PathBuf>,
+    /// An optional API token sent in the `Authorization: Bearer` header,
+    /// required by the admin endpoints of the nodes configured with API tokens.
+    #[structopt(long, env = "JORMUNGANDR_RESTAPI_TOKEN", hide_env_values = true)]
+    token: Option<String>,
 }
 
 pub struct RestClient {
     client: Client,
     debug: bool,
     base_url: Url,
+    token: Option<String>,
 }
 
 pub struct RestRequestBuilder {
@@ -57,6 +62,8 @@ pub enum Error {
     Text(#[source] reqwest::Error),
     #[error("connection with the node timed out")]
     Timeout,
+    #[error("node rejected request because a valid API token is required")]
+    Unauthorized(#[source] reqwest::Error),
     #[error("node rejected request because of invalid parameters")]
     InvalidParams(#[source] reqwest::Error),
     #[error("node internal error")]
@@ -76,6 +83,7 @@ impl RestArgs {
             tls_cert_path,
             host,
             debug,
+            token,
         } = self;
 
         if host.cannot_be_a_base() {
@@ -103,6 +111,7 @@ impl RestArgs {
             client,
             debug,
             base_url: host,
+            token,
         };
 
         Ok(rest_client)
@@ -130,9 +139,14 @@ impl RestClient {
             client,
             base_url,
             debug,
+            token,
         } = self;
         let url = make_url(base_url, address_segments);
         let request_builder = f(&client, url);
+        let request_builder = match token {
+            Some(token) => request_builder.bearer_auth(token),
+            None => request_builder,
+        };
         RestRequestBuilder {
             client,
             request_builder,
@@ -200,7 +214,9 @@ impl RestRequestBuilder {
                 if e.is_timeout() {
                     Error::Timeout
                 } else if let Some(status) = e.status() {
-                    if status.is_client_error() {
+                    if status == reqwest::StatusCode::UNAUTHORIZED {
+                        Error::Unauthorized(e)
+                    } else if status.is_client_error() {
                         Error::InvalidParams(e)
                     } else if status.is_server_error() {
                         Error::InternalError(e)
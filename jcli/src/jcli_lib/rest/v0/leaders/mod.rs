@@ -33,6 +33,9 @@ pub enum Leaders {
 
     /// Leadership log operations
     Logs(GetLogs),
+
+    /// Leadership schedule operations
+    Schedule(GetSchedule),
 }
 
 #[derive(StructOpt)]
@@ -47,6 +50,19 @@ pub enum GetLogs {
     },
 }
 
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub enum GetSchedule {
+    /// Get the slots at which the leaders will create blocks in the current
+    /// epoch, and in the next epoch if it is already known
+    Get {
+        #[structopt(flatten)]
+        args: RestArgs,
+        #[structopt(flatten)]
+        output_format: OutputFormat,
+    },
+}
+
 impl Leaders {
     pub fn exec(self) -> Result<(), Error> {
         match self {
@@ -60,6 +76,10 @@ impl Leaders {
                 args,
                 output_format,
             }) => get_logs(args, output_format),
+            Leaders::Schedule(GetSchedule::Get {
+                args,
+                output_format,
+            }) => get_schedule(args, output_format),
         }
     }
 }
@@ -101,3 +121,14 @@ fn get_logs(args: RestArgs, output_format: OutputFormat) -> Result<(), Error> {
     println!("{}", formatted);
     Ok(())
 }
+
+fn get_schedule(args: RestArgs, output_format: OutputFormat) -> Result<(), Error> {
+    let response = args
+        .client()?
+        .get(&["v0", "leaders", "schedule"])
+        .execute()?
+        .json()?;
+    let formatted = output_format.format_json(response)?;
+    println!("{}", formatted);
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use crate::{
+    crypto::hash::Hash,
+    interfaces::{BlockDate, Value},
+};
+use serde::{Deserialize, Serialize};
+
+/// a transaction of the main chain involving a given address, either
+/// by spending from it or by sending funds to it
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AddressTransaction {
+    pub fragment_id: Hash,
+    /// the block containing the transaction
+    pub block: Hash,
+    pub chain_length: u32,
+    pub date: BlockDate,
+    /// the fee paid by the transaction as a whole
+    pub fee: Value,
+    /// the total of the inputs spent from the address
+    pub sent: Value,
+    /// the total of the outputs sent to the address
+    pub received: Value,
+}
+
+/// a page of the transactions of an address, the most recent first
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AddressTransactions {
+    pub transactions: Vec<AddressTransaction>,
+    /// the cursor to give to get the next (older) page of transactions,
+    /// `None` if this is the last page
+    pub next_cursor: Option<u64>,
+}
@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use crate::time::Duration;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
@@ -13,6 +14,29 @@ pub struct PersistentLog {
     pub dir: PathBuf,
 }
 
+/// algorithm used by the block producer to pick fragments from the mempool
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionAlgorithm {
+    /// fragments are selected in the order they were received
+    OldestFirst,
+    /// fragments paying the highest fee per byte are selected first
+    FeePriority,
+}
+
+/// policy applied when a fragment is received while the mempool is full
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+    /// the new fragment is rejected
+    RejectNew,
+    /// the oldest fragment of the mempool is evicted
+    OldestFirst,
+    /// the fragment paying the lowest fee per byte is evicted, unless the new
+    /// fragment does not pay more
+    LowestFee,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Mempool {
@@ -25,6 +49,18 @@ pub struct Mempool {
     /// path to the persistent log of all incoming fragments
     #[serde(default)]
     pub persistent_log: Option<PersistentLog>,
+    /// algorithm used to select fragments for the blocks produced by the node
+    #[serde(default)]
+    pub selection_algorithm: SelectionAlgorithm,
+    /// policy applied when a fragment is received while the mempool is full
+    #[serde(default)]
+    pub eviction_policy: EvictionPolicy,
+    /// maximum number of pending fragments spending from the same account
+    #[serde(default)]
+    pub account_max_entries: Option<usize>,
+    /// time after which a pending fragment is removed from the mempool
+    #[serde(default)]
+    pub fragment_ttl: Option<Duration>,
 }
 
 impl Default for PoolMaxEntries {
@@ -39,12 +75,28 @@ impl Default for LogMaxEntries {
     }
 }
 
+impl Default for SelectionAlgorithm {
+    fn default() -> Self {
+        SelectionAlgorithm::OldestFirst
+    }
+}
+
+impl Default for EvictionPolicy {
+    fn default() -> Self {
+        EvictionPolicy::RejectNew
+    }
+}
+
 impl Default for Mempool {
     fn default() -> Self {
         Mempool {
             pool_max_entries: PoolMaxEntries::default(),
             log_max_entries: LogMaxEntries::default(),
             persistent_log: None,
+            selection_algorithm: SelectionAlgorithm::default(),
+            eviction_policy: EvictionPolicy::default(),
+            account_max_entries: None,
+            fragment_ttl: None,
         }
     }
 }
@@ -4,9 +4,11 @@ mod node;
 mod secret;
 
 pub use log::{Log, LogEntry, LogOutput};
-pub use mempool::{LogMaxEntries, Mempool, PersistentLog, PoolMaxEntries};
+pub use mempool::{
+    EvictionPolicy, LogMaxEntries, Mempool, PersistentLog, PoolMaxEntries, SelectionAlgorithm,
+};
 pub use node::{
-    Cors, Explorer, LayersConfig, NodeConfig, NodeId, P2p, Policy, PreferredListConfig, Rest, Tls,
-    TopicsOfInterest, TrustedPeer,
+    Cors, Explorer, LayersConfig, NodeConfig, NodeId, P2p, Policy, PreferredListConfig, Rest,
+    RestAdmin, Tls, TopicsOfInterest, TrustedPeer,
 };
 pub use secret::{Bft, GenesisPraos, NodeSecret};
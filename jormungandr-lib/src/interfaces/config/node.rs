@@ -18,6 +18,17 @@ pub struct Rest {
     /// Enables CORS if provided
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cors: Option<Cors>,
+    /// Restricts the admin endpoints to authenticated clients if provided
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin: Option<RestAdmin>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RestAdmin {
+    /// Path to the file listing the bearer tokens allowed to use the admin endpoints,
+    /// one token per line
+    pub tokens_file: PathBuf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
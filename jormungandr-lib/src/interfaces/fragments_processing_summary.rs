@@ -13,6 +13,7 @@ pub enum FragmentRejectionReason {
     FragmentInvalid,
     PreviousFragmentInvalid,
     PoolOverflow { pool_number: usize },
+    AccountLimitReached { pool_number: usize },
 }
 
 /// Information about a fragment rejected by the mempool. This is different from being rejected by
@@ -43,6 +44,7 @@ impl FragmentRejectionReason {
             FragmentRejectionReason::FragmentInvalid
                 | FragmentRejectionReason::PreviousFragmentInvalid
                 | FragmentRejectionReason::PoolOverflow { .. }
+                | FragmentRejectionReason::AccountLimitReached { .. }
         )
     }
 }
@@ -63,13 +65,16 @@ mod tests {
 
     impl Arbitrary for FragmentRejectionReason {
         fn arbitrary<G: Gen>(g: &mut G) -> Self {
-            match g.next_u32() % 4 {
+            match g.next_u32() % 5 {
                 0 => FragmentRejectionReason::FragmentAlreadyInLog,
                 1 => FragmentRejectionReason::FragmentInvalid,
                 2 => FragmentRejectionReason::PreviousFragmentInvalid,
                 3 => FragmentRejectionReason::PoolOverflow {
                     pool_number: g.next_u64() as usize,
                 },
+                4 => FragmentRejectionReason::AccountLimitReached {
+                    pool_number: g.next_u64() as usize,
+                },
                 _ => unreachable!(),
             }
         }
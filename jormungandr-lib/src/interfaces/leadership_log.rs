@@ -1,4 +1,8 @@
-use crate::{crypto::hash::Hash, interfaces::BlockDate, time::SystemTime};
+use crate::{
+    crypto::hash::Hash,
+    interfaces::BlockDate,
+    time::{Duration, SystemTime},
+};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -27,6 +31,37 @@ pub enum LeadershipLogStatus {
     },
 }
 
+/// details on the building of the block of a leadership event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeadershipLogDiagnostics {
+    /// the block the new block is built on, `None` if the tip of the node
+    /// was not usable as a parent
+    pub parent: Option<Hash>,
+    /// the number of fragments taken from the mempool by the fragment
+    /// selection
+    pub fragments_considered: usize,
+    /// the number of fragments included in the block
+    pub fragments_selected: usize,
+    /// the fragments rejected by the fragment selection
+    pub fragments_rejected: Vec<LeadershipRejectedFragment>,
+    /// the end of the slot, the fragment selection stops at this time once
+    /// it has selected some fragments
+    pub soft_deadline: SystemTime,
+    /// the time after which the block is built with the fragments selected
+    /// so far
+    pub hard_deadline: SystemTime,
+    pub soft_deadline_reached: bool,
+    pub hard_deadline_reached: bool,
+    /// the time spent building the block, `None` if it was not started
+    pub elapsed: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeadershipRejectedFragment {
+    pub fragment_id: Hash,
+    pub reason: String,
+}
+
 /// provides information regarding events in the leadership schedule
 ///
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +73,38 @@ pub struct LeadershipLog {
     finished_at_time: Option<SystemTime>,
     status: LeadershipLogStatus,
     enclave_leader_id: EnclaveLeaderId,
+    diagnostics: Option<LeadershipLogDiagnostics>,
+}
+
+/// the slots of an epoch at which the leaders of the enclave are elected to
+/// create a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeadershipSchedule {
+    pub epoch: u32,
+    pub entries: Vec<LeadershipScheduleEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeadershipScheduleEntry {
+    pub enclave_leader_id: EnclaveLeaderId,
+    pub scheduled_at_date: BlockDate,
+    pub scheduled_at_time: SystemTime,
+}
+
+impl LeadershipLogDiagnostics {
+    pub fn new(soft_deadline: SystemTime, hard_deadline: SystemTime) -> Self {
+        LeadershipLogDiagnostics {
+            parent: None,
+            fragments_considered: 0,
+            fragments_selected: 0,
+            fragments_rejected: Vec::new(),
+            soft_deadline,
+            hard_deadline,
+            soft_deadline_reached: false,
+            hard_deadline_reached: false,
+            elapsed: None,
+        }
+    }
 }
 
 impl EnclaveLeaderId {
@@ -64,6 +131,7 @@ impl LeadershipLog {
             finished_at_time: None,
             status: LeadershipLogStatus::Pending,
             enclave_leader_id,
+            diagnostics: None,
         }
     }
 
@@ -93,6 +161,9 @@ impl LeadershipLog {
     pub fn status(&self) -> &LeadershipLogStatus {
         &self.status
     }
+    pub fn diagnostics(&self) -> &Option<LeadershipLogDiagnostics> {
+        &self.diagnostics
+    }
 
     /// make a leadership event as triggered.
     ///
@@ -127,6 +198,11 @@ impl LeadershipLog {
     pub fn set_status(&mut self, status: LeadershipLogStatus) {
         self.status = status
     }
+
+    /// set the diagnostics of the block building of the leadership event.
+    pub fn set_diagnostics(&mut self, diagnostics: LeadershipLogDiagnostics) {
+        self.diagnostics = Some(diagnostics)
+    }
 }
 
 impl fmt::Display for EnclaveLeaderId {
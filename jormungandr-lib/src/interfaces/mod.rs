@@ -1,6 +1,7 @@
 mod account_identifier;
 mod account_state;
 mod address;
+mod address_transactions;
 mod block0_configuration;
 mod blockdate;
 mod certificate;
@@ -13,6 +14,7 @@ mod fragments_batch;
 mod fragments_processing_summary;
 mod leadership_log;
 mod linear_fee;
+mod node_event;
 mod old_address;
 mod peer_stats;
 mod ratio;
@@ -34,6 +36,7 @@ mod vote;
 pub use self::account_identifier::AccountIdentifier;
 pub use self::account_state::AccountState;
 pub use self::address::Address;
+pub use self::address_transactions::{AddressTransaction, AddressTransactions};
 pub use self::block0_configuration::*;
 pub use self::blockdate::BlockDate;
 pub use self::certificate::{
@@ -45,6 +48,7 @@ pub use self::config::*;
 pub use self::fragment::FragmentDef;
 pub use self::fragment_log::{FragmentLog, FragmentOrigin, FragmentStatus};
 pub use self::fragment_log_persistent::{
+    list_persistent_fragment_log_files_from_folder_path,
     load_persistent_fragments_logs_from_folder_path, read_persistent_fragment_logs_from_file_path,
     DeserializeError as FragmentLogDeserializeError, FileFragments, PersistentFragmentLog,
 };
@@ -53,9 +57,11 @@ pub use self::fragments_processing_summary::{
     FragmentRejectionReason, FragmentsProcessingSummary, RejectedFragmentInfo,
 };
 pub use self::leadership_log::{
-    EnclaveLeaderId, LeadershipLog, LeadershipLogId, LeadershipLogStatus,
+    EnclaveLeaderId, LeadershipLog, LeadershipLogDiagnostics, LeadershipLogId, LeadershipLogStatus,
+    LeadershipRejectedFragment, LeadershipSchedule, LeadershipScheduleEntry,
 };
 pub use self::linear_fee::LinearFeeDef;
+pub use self::node_event::NodeEvent;
 pub use self::old_address::OldAddress;
 pub use self::peer_stats::{PeerRecord, PeerStats, Subscription};
 pub use self::ratio::{ParseRatioError, Ratio};
@@ -0,0 +1,29 @@
+use crate::{
+    crypto::hash::Hash,
+    interfaces::{BlockDate, FragmentStatus, LeadershipLog},
+};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// event published by the node to the clients of its events stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NodeEvent {
+    /// the node switched to a new tip
+    Tip {
+        block: Hash,
+        chain_length: u32,
+        date: BlockDate,
+    },
+    /// the status of a fragment changed in the fragment logs
+    FragmentStatus {
+        fragment_id: Hash,
+        status: FragmentStatus,
+    },
+    /// a leadership log was created or its status changed
+    LeadershipLog { log: LeadershipLog },
+    /// the node established a connection with a peer
+    PeerConnected { addr: SocketAddr },
+    /// the connection established with a peer was closed
+    PeerDisconnected { addr: SocketAddr },
+}
@@ -0,0 +1,27 @@
+#!/bin/sh
+# Generates the certificates of the TLS tests of the node to node connections:
+# an authority and the certificates of two nodes, issued for the names derived
+# from their node IDs as with `p2p.tls.pin_node_id`.
+set -e
+cd "$(dirname "$0")"
+
+openssl req -x509 -newkey ec -pkeyopt ec_paramgen_curve:prime256v1 -nodes \
+    -keyout ca.key -out ca.crt -days 36500 -subj "/CN=jormungandr test CA" \
+    -addext "basicConstraints=critical,CA:TRUE" \
+    -addext "keyUsage=critical,keyCertSign,cRLSign"
+
+for node in node1 node2; do
+    openssl genpkey -algorithm ed25519 -out "$node.id.key"
+    id=$(openssl pkey -in "$node.id.key" -pubout -outform DER | tail -c 32 | od -An -tx1 | tr -d ' \n')
+    rm "$node.id.key"
+    echo "$id" > "$node.id"
+    high=$(echo "$id" | cut -c 1-32)
+    low=$(echo "$id" | cut -c 33-64)
+    openssl genpkey -algorithm ec -pkeyopt ec_paramgen_curve:prime256v1 -out "$node.key"
+    openssl req -new -key "$node.key" -subj "/CN=$node" -out "$node.csr"
+    printf "basicConstraints=critical,CA:FALSE\nkeyUsage=critical,digitalSignature\nextendedKeyUsage=serverAuth,clientAuth\nsubjectAltName=DNS:node.jormungandr,DNS:%s.%s.node.jormungandr\n" "$high" "$low" > "$node.ext"
+    openssl x509 -req -in "$node.csr" -CA ca.crt -CAkey ca.key -CAcreateserial \
+        -days 36500 -extfile "$node.ext" -out "$node.crt"
+    rm "$node.csr" "$node.ext"
+done
+rm ca.key ca.srl
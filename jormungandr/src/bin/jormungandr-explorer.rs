@@ -0,0 +1,356 @@
+//! The explorer running in its own process.
+//!
+//! The blockchain is pulled from a node over the node to node gRPC protocol
+//! and is indexed the same way as in the node, the explorer GraphQL API is
+//! served on the configured REST listen address.
+//!
+//! There is no mempool and no node events in this process: the mempool
+//! queries and subscriptions fail with a "not supported in standalone mode"
+//! error, and the fragment confirmation subscription only follows the
+//! blockchain, not the rejections of the mempool.
+
+// Rustc default type_length_limit is too low for complex futures, which generate deeply nested
+// monomorphized structured with long signatures. This value is enough for current project.
+#![type_length_limit = "10000000"]
+
+use ::multiaddr::Multiaddr;
+use futures::prelude::*;
+use jormungandr::{
+    blockcfg::HeaderHash,
+    blockchain::{Blockchain, Snapshots, Storage, Tip},
+    explorer::{Explorer, ExplorerDb, StableStore},
+    intercom::ExplorerMsg,
+    network::bootstrap,
+    rest,
+    settings::{
+        logging::{LogFormat, LogOutput, LogSettings, LogSettingsEntry},
+        start::{
+            config::{ExplorerLimits, P2pTls},
+            network::{Peer, Tls},
+            Rest,
+        },
+        LOG_FILTER_LEVEL_POSSIBLE_VALUES,
+    },
+    start_up,
+    utils::{async_msg, async_msg::MessageBox, task::Services},
+};
+use jormungandr_lib::{crypto::hash::Hash, multiaddr};
+use serde_derive::Deserialize;
+use structopt::StructOpt;
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+use tracing::level_filters::LevelFilter;
+use tracing::{span, Level};
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const EXPLORER_TASK_QUEUE_LEN: usize = 32;
+const BLOCKCHAIN_CACHE_CAPACITY: usize = 102_400;
+const RECONNECT_WAIT: Duration = Duration::from_secs(5);
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "jormungandr-explorer",
+    setting = structopt::clap::AppSettings::ColoredHelp
+)]
+struct CommandLine {
+    /// Set log messages minimum severity, defaults to "info".
+    #[structopt(
+        long = "log-level",
+        parse(try_from_str = log_level_parse),
+        possible_values = &LOG_FILTER_LEVEL_POSSIBLE_VALUES
+    )]
+    log_level: Option<LevelFilter>,
+
+    /// Path to the configuration file of the explorer
+    #[structopt(long = "config", parse(from_os_str))]
+    config: PathBuf,
+
+    /// display full version details (software version, source version, targets and compiler used)
+    #[structopt(long = "full-version")]
+    full_version: bool,
+}
+
+fn log_level_parse(level: &str) -> Result<LevelFilter, String> {
+    level
+        .parse()
+        .map_err(|_| format!("Unknown log level value: '{}'", level))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    /// the public address of the node to pull the blockchain from, the
+    /// same as in the trusted peers of a node
+    node: Multiaddr,
+    /// the hash of the genesis block of the blockchain
+    genesis_block_hash: Hash,
+    /// the directory keeping the blocks and the explorer index across
+    /// restarts, everything is kept in memory if not set
+    storage: Option<PathBuf>,
+    rest: Rest,
+    #[serde(default)]
+    limits: ExplorerLimits,
+    /// the TLS settings of the connection to the node, required if the node
+    /// secures its P2P connections with TLS
+    #[serde(default)]
+    tls: Option<P2pTls>,
+}
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error("Unable to read the configuration file")]
+    ConfigIo(#[source] std::io::Error),
+    #[error("Invalid configuration file")]
+    Config(#[source] serde_yaml::Error),
+    #[error("Invalid node address")]
+    NodeAddress(#[source] multiaddr::Error),
+    #[error("The node address shall consist of a host address and a TCP component")]
+    InvalidNodeAddress,
+    #[error("Cannot read the certificates or the private key of the TLS settings")]
+    Tls(#[source] std::io::Error),
+    #[error(transparent)]
+    Start(#[from] start_up::Error),
+}
+
+impl From<bootstrap::Error> for Error {
+    fn from(error: bootstrap::Error) -> Error {
+        Error::Start(error.into())
+    }
+}
+
+fn start() -> Result<(), Error> {
+    let command_line = CommandLine::from_args();
+
+    if command_line.full_version {
+        println!("{}", env!("FULL_VERSION"));
+        std::process::exit(0);
+    }
+
+    let Config {
+        node,
+        genesis_block_hash,
+        storage,
+        rest,
+        limits,
+        tls,
+    } = std::fs::File::open(&command_line.config)
+        .map_err(Error::ConfigIo)
+        .and_then(|file| serde_yaml::from_reader(file).map_err(Error::Config))?;
+    let node = multiaddr::resolve_dns(&node).map_err(Error::NodeAddress)?;
+    let node = multiaddr::to_tcp_socket_addr(&node).ok_or(Error::InvalidNodeAddress)?;
+    let tls = tls
+        .as_ref()
+        .map(Tls::load)
+        .transpose()
+        .map_err(Error::Tls)?;
+    let peer = Peer::new(node).with_tls(tls, None);
+
+    let log_settings = LogSettings {
+        config: LogSettingsEntry {
+            level: command_line.log_level.unwrap_or(LevelFilter::INFO),
+            format: LogFormat::Default,
+            output: LogOutput::Stderr,
+        },
+        msgs: None,
+    };
+    let (_logger_guards, _, _) = log_settings.init_log().map_err(start_up::Error::from)?;
+
+    tracing::info!("Starting {}", env!("FULL_VERSION"));
+
+    let mut services = Services::new();
+
+    let cancellation_token = CancellationToken::new();
+    {
+        let cancellation_token = cancellation_token.clone();
+        services.spawn_future("sigint_watcher", move |_info| async move {
+            match tokio::signal::ctrl_c().await {
+                Ok(()) => cancellation_token.cancel(),
+                Err(e) => {
+                    tracing::warn!(reason = %e, "failed to install handler for SIGINT");
+                    future::pending().await
+                }
+            }
+        });
+    }
+
+    let (blockchain, blockchain_tip, explorer_db) =
+        services.block_on_task("bootstrap", |_info| {
+            bootstrap(
+                storage.as_deref(),
+                genesis_block_hash.into_hash(),
+                &peer,
+                cancellation_token.clone(),
+            )
+        })?;
+
+    let explorer = Explorer::new(explorer_db);
+    let (explorer_msgbox, explorer_queue) = async_msg::channel(EXPLORER_TASK_QUEUE_LEN);
+    {
+        let explorer = explorer.clone();
+        services.spawn_future("explorer", move |info| async move {
+            explorer.start(info, explorer_queue).await
+        });
+    }
+
+    {
+        let cancellation_token = cancellation_token.clone();
+        services.spawn_future("follow_node", move |_info| {
+            follow_node(
+                peer,
+                blockchain,
+                blockchain_tip,
+                explorer_msgbox,
+                cancellation_token,
+            )
+        });
+    }
+
+    services.spawn_future("rest", move |_info| {
+        rest::start_explorer_server(rest, explorer, limits, async move {
+            cancellation_token.cancelled().await
+        })
+    });
+
+    services
+        .wait_any_finished()
+        .map_err(start_up::Error::ServiceTerminatedWithError)
+        .map_err(Into::into)
+}
+
+/// load the blockchain kept in the storage, bring it up to the tip of the
+/// node and index it
+async fn bootstrap(
+    storage_dir: Option<&Path>,
+    block0_hash: HeaderHash,
+    peer: &Peer,
+    cancellation_token: CancellationToken,
+) -> Result<(Blockchain, Tip, ExplorerDb), Error> {
+    let storage_span = span!(Level::TRACE, "sub_task", kind = "storage");
+    let (storage, snapshots, explorer_store) = match storage_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir).map_err(|source| start_up::Error::Io {
+                source,
+                reason: start_up::ErrorKind::BlockStorage,
+            })?;
+            tracing::info!("storing blockchain in '{:?}'", dir);
+            (
+                Storage::file(dir, storage_span).map_err(start_up::Error::from)?,
+                Some(Snapshots::new(dir.join("snapshots")).map_err(|source| {
+                    start_up::Error::Io {
+                        source,
+                        reason: start_up::ErrorKind::LedgerSnapshots,
+                    }
+                })?),
+                Some(StableStore::new(dir.join("explorer")).map_err(|source| {
+                    start_up::Error::Io {
+                        source,
+                        reason: start_up::ErrorKind::ExplorerStore,
+                    }
+                })?),
+            )
+        }
+        None => (
+            Storage::memory(storage_span).map_err(start_up::Error::from)?,
+            None,
+            None,
+        ),
+    };
+
+    let block0 = match storage.get(block0_hash).map_err(start_up::Error::from)? {
+        Some(block0) => block0,
+        None => bootstrap::fetch_block0(peer, block0_hash).await?,
+    };
+
+    // the explorer reports the rewards of every account
+    let (blockchain, blockchain_tip) = start_up::load_blockchain(
+        block0.clone(),
+        storage,
+        snapshots,
+        BLOCKCHAIN_CACHE_CAPACITY,
+        true,
+    )
+    .await?;
+
+    // the node may not be reachable yet, the blocks missed here are pulled
+    // once following the node
+    match bootstrap::bootstrap_from_peer(
+        peer,
+        blockchain.clone(),
+        blockchain_tip.clone(),
+        cancellation_token.clone(),
+    )
+    .await
+    {
+        Err(bootstrap::Error::Interrupted) => return Err(bootstrap::Error::Interrupted.into()),
+        Err(e) => tracing::warn!(error = ?e, "initial bootstrap from the node failed"),
+        Ok(()) => tracing::info!("initial bootstrap completed"),
+    }
+
+    blockchain
+        .gc(blockchain_tip.get_ref().await)
+        .await
+        .map_err(bootstrap::Error::GcFailed)?;
+
+    let explorer_db = futures::select! {
+        explorer_db = ExplorerDb::bootstrap(block0, &blockchain, blockchain_tip.clone(), explorer_store).fuse() => {
+            explorer_db.map_err(start_up::Error::from)?
+        },
+        _ = cancellation_token.cancelled().fuse() => return Err(start_up::Error::Interrupted.into()),
+    };
+
+    Ok((blockchain, blockchain_tip, explorer_db))
+}
+
+/// keep pulling the new blocks from the node, reconnecting whenever the
+/// connection is lost
+async fn follow_node(
+    peer: Peer,
+    blockchain: Blockchain,
+    blockchain_tip: Tip,
+    explorer_msgbox: MessageBox<ExplorerMsg>,
+    cancellation_token: CancellationToken,
+) {
+    loop {
+        match bootstrap::follow_peer(
+            &peer,
+            blockchain.clone(),
+            blockchain_tip.clone(),
+            explorer_msgbox.clone(),
+            cancellation_token.clone(),
+        )
+        .await
+        {
+            Err(bootstrap::Error::Interrupted) => break,
+            Err(e) => tracing::warn!(error = ?e, "lost the synchronization with the node"),
+            Ok(()) => tracing::info!("the node closed the block subscription"),
+        }
+
+        tracing::info!("reconnecting in {} seconds...", RECONNECT_WAIT.as_secs());
+
+        futures::select! {
+            _ = tokio::time::sleep(RECONNECT_WAIT).fuse() => {},
+            _ = cancellation_token.cancelled().fuse() => break,
+        }
+    }
+}
+
+fn main() {
+    use std::error::Error as _;
+
+    if let Err(error) = start() {
+        eprintln!("{}", error);
+        let mut source = error.source();
+        while let Some(err) = source {
+            eprintln!(" |-> {}", err);
+            source = err.source();
+        }
+
+        let code = match error {
+            Error::Start(error) => error.code(),
+            _ => 1,
+        };
+        std::process::exit(code);
+    }
+}
@@ -0,0 +1,427 @@
+//! Index of the transactions of each address on the main chain
+//!
+//! The index lets the nodes running without the explorer answer the history
+//! queries of the light wallets. It follows the tip of the node: the blocks
+//! of the main chain are read back from the storage and the recent ones are
+//! remembered so the index can be rolled back when the node switches to
+//! another branch.
+
+use crate::{
+    blockcfg::{Block, Fragment, FragmentId, HeaderHash},
+    blockchain::{Storage, StorageError, Tip},
+    intercom,
+};
+use chain_addr::{Address, Discrimination, Kind};
+use chain_core::property::Fragment as _;
+use chain_impl_mockchain::{
+    transaction::{InputEnum, Transaction, Witness},
+    value::Value,
+};
+use jormungandr_lib::interfaces::{AddressTransaction, AddressTransactions, NodeEvent};
+
+use futures::prelude::*;
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryInto,
+    sync::Arc,
+};
+use tokio::sync::{broadcast, RwLock};
+
+type UtxoPointer = (FragmentId, u8);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    #[error("cannot read the blocks from the storage")]
+    Stream(#[from] intercom::Error),
+}
+
+#[derive(Clone)]
+pub struct AddressIndex {
+    inner: Arc<RwLock<Inner>>,
+    block0: HeaderHash,
+    discrimination: Discrimination,
+    /// number of blocks that can be rolled back without rebuilding the index
+    rollback_depth: usize,
+}
+
+#[derive(Default)]
+struct Inner {
+    tip: Option<HeaderHash>,
+    /// the transactions of each address, in chain order
+    transactions: HashMap<Address, Vec<AddressTransaction>>,
+    /// the addresses of the unspent outputs, to find the addresses
+    /// spending from the utxo inputs
+    utxos: HashMap<UtxoPointer, Address>,
+    /// the changes made by the most recent blocks, the most recent last
+    blocks: VecDeque<IndexedBlock>,
+}
+
+/// the changes made to the index by a block
+struct IndexedBlock {
+    parent: HeaderHash,
+    /// the address of each transaction added, in the order they were added
+    addresses: Vec<Address>,
+    created_utxos: Vec<UtxoPointer>,
+    spent_utxos: Vec<(UtxoPointer, Address)>,
+}
+
+impl AddressIndex {
+    pub fn new(block0: HeaderHash, discrimination: Discrimination, rollback_depth: u32) -> Self {
+        AddressIndex {
+            inner: Arc::new(RwLock::new(Inner::default())),
+            block0,
+            discrimination,
+            rollback_depth: rollback_depth as usize,
+        }
+    }
+
+    /// follow the tip of the node until the node events are no longer published
+    pub async fn start(
+        self,
+        storage: Storage,
+        blockchain_tip: Tip,
+        mut events: broadcast::Receiver<NodeEvent>,
+    ) {
+        let tip = blockchain_tip.get_ref().await.hash();
+        self.sync(&storage, tip).await;
+
+        loop {
+            let tip = match events.recv().await {
+                Ok(NodeEvent::Tip { block, .. }) => block.into_hash(),
+                Ok(_) => continue,
+                // some tips were missed, the current one is enough to catch up
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    blockchain_tip.get_ref().await.hash()
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            self.sync(&storage, tip).await;
+        }
+
+        tracing::debug!("no more node events, stopping the address index");
+    }
+
+    async fn sync(&self, storage: &Storage, tip: HeaderHash) {
+        if let Err(e) = self.sync_to(storage, tip).await {
+            // the index is rebuilt from the start on the next tip
+            tracing::error!(reason = %e, "cannot update the address index to {}", tip);
+            *self.inner.write().await = Inner::default();
+        }
+    }
+
+    async fn sync_to(&self, storage: &Storage, tip: HeaderHash) -> Result<(), Error> {
+        let current = self.inner.read().await.tip;
+        let from = match current {
+            Some(current) if current == tip => return Ok(()),
+            Some(current) => {
+                let ancestor = storage.find_common_ancestor(current, tip)?;
+                let mut inner = self.inner.write().await;
+                if inner.rollback_to(ancestor) {
+                    Some(ancestor)
+                } else {
+                    tracing::info!(
+                        "branch switch deeper than the address index history, rebuilding the index"
+                    );
+                    *inner = Inner::default();
+                    None
+                }
+            }
+            None => None,
+        };
+
+        let from = match from {
+            Some(from) => from,
+            None => {
+                let block0 = storage
+                    .get(self.block0)?
+                    .ok_or(StorageError::BlockNotFound)?;
+                self.apply_block(&block0).await;
+                self.block0
+            }
+        };
+
+        if from == tip {
+            return Ok(());
+        }
+
+        let mut blocks = storage.stream_from_to(from, tip).map(Box::pin)?;
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            if block.header.hash() != from {
+                self.apply_block(&block).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn apply_block(&self, block: &Block) {
+        let mut inner = self.inner.write().await;
+        inner.apply_block(self.discrimination, block);
+        while inner.blocks.len() > self.rollback_depth {
+            inner.blocks.pop_front();
+        }
+    }
+
+    /// the transactions of the address older than the cursor, the most
+    /// recent first. The most recent transactions are returned without cursor.
+    pub async fn transactions(
+        &self,
+        address: &Address,
+        cursor: Option<u64>,
+        limit: usize,
+    ) -> AddressTransactions {
+        let inner = self.inner.read().await;
+        let transactions = inner
+            .transactions
+            .get(address)
+            .map_or(&[][..], Vec::as_slice);
+
+        let end = cursor.map_or(transactions.len(), |cursor| {
+            (cursor as usize).min(transactions.len())
+        });
+        let start = end.saturating_sub(limit);
+
+        AddressTransactions {
+            transactions: transactions[start..end].iter().rev().cloned().collect(),
+            next_cursor: if start > 0 { Some(start as u64) } else { None },
+        }
+    }
+}
+
+impl Inner {
+    fn apply_block(&mut self, discrimination: Discrimination, block: &Block) {
+        let mut indexed = IndexedBlock {
+            parent: block.header.block_parent_hash(),
+            addresses: Vec::new(),
+            created_utxos: Vec::new(),
+            spent_utxos: Vec::new(),
+        };
+
+        for fragment in block.contents.iter() {
+            let entry = AddressTransaction {
+                fragment_id: fragment.id().into(),
+                block: block.header.hash().into(),
+                chain_length: block.header.chain_length().into(),
+                date: block.header.block_date().into(),
+                fee: Value::zero().into(),
+                sent: Value::zero().into(),
+                received: Value::zero().into(),
+            };
+            let indexed = &mut indexed;
+            match fragment {
+                Fragment::Transaction(tx) => self.apply_tx(discrimination, entry, tx, indexed),
+                Fragment::OwnerStakeDelegation(tx) => {
+                    self.apply_tx(discrimination, entry, tx, indexed)
+                }
+                Fragment::StakeDelegation(tx) => self.apply_tx(discrimination, entry, tx, indexed),
+                Fragment::PoolRegistration(tx) => self.apply_tx(discrimination, entry, tx, indexed),
+                Fragment::PoolRetirement(tx) => self.apply_tx(discrimination, entry, tx, indexed),
+                Fragment::PoolUpdate(tx) => self.apply_tx(discrimination, entry, tx, indexed),
+                Fragment::VotePlan(tx) => self.apply_tx(discrimination, entry, tx, indexed),
+                Fragment::VoteCast(tx) => self.apply_tx(discrimination, entry, tx, indexed),
+                Fragment::VoteTally(tx) => self.apply_tx(discrimination, entry, tx, indexed),
+                Fragment::EncryptedVoteTally(tx) => {
+                    self.apply_tx(discrimination, entry, tx, indexed)
+                }
+                Fragment::Initial(_)
+                | Fragment::OldUtxoDeclaration(_)
+                | Fragment::UpdateProposal(_)
+                | Fragment::UpdateVote(_) => {}
+            }
+        }
+
+        self.tip = Some(block.header.hash());
+        self.blocks.push_back(indexed);
+    }
+
+    fn apply_tx<T>(
+        &mut self,
+        discrimination: Discrimination,
+        entry: AddressTransaction,
+        tx: &Transaction<T>,
+        indexed: &mut IndexedBlock,
+    ) {
+        let fragment_id = entry.fragment_id.into_hash();
+        // the values sent and received by each address of the transaction
+        let mut involved: HashMap<Address, (u64, u64)> = HashMap::new();
+
+        let fee = match (tx.total_input(), tx.total_output()) {
+            (Ok(input), Ok(output)) => (input - output).unwrap_or_else(|_| Value::zero()),
+            _ => Value::zero(),
+        };
+
+        let tx = tx.as_slice();
+        for (input, witness) in tx.inputs().iter().zip(tx.witnesses().iter()) {
+            let (address, value) = match (input.to_enum(), witness) {
+                (InputEnum::AccountInput(id, value), Witness::Account(_)) => {
+                    let kind = match id.to_single_account() {
+                        Some(id) => Kind::Account(id.into()),
+                        None => continue,
+                    };
+                    (Address(discrimination, kind), value)
+                }
+                (InputEnum::AccountInput(id, value), Witness::Multisig(_)) => {
+                    let kind = match id.to_multi_account().as_ref().try_into() {
+                        Ok(id) => Kind::Multisig(id),
+                        Err(_) => continue,
+                    };
+                    (Address(discrimination, kind), value)
+                }
+                (InputEnum::UtxoInput(pointer), _) => {
+                    let utxo = (pointer.transaction_id, pointer.output_index);
+                    // the outputs of the legacy utxo declarations are not indexed
+                    let address = match self.utxos.remove(&utxo) {
+                        Some(address) => address,
+                        None => continue,
+                    };
+                    indexed.spent_utxos.push((utxo, address.clone()));
+                    (address, pointer.value)
+                }
+                _ => continue,
+            };
+            let (sent, _) = involved.entry(address).or_default();
+            *sent = sent.saturating_add(value.0);
+        }
+
+        for (index, output) in tx.outputs().iter().enumerate() {
+            if !matches!(output.address.kind(), Kind::Account(_) | Kind::Multisig(_)) {
+                let utxo = (fragment_id, index as u8);
+                self.utxos.insert(utxo, output.address.clone());
+                indexed.created_utxos.push(utxo);
+            }
+            let (_, received) = involved.entry(output.address.clone()).or_default();
+            *received = received.saturating_add(output.value.0);
+        }
+
+        for (address, (sent, received)) in involved {
+            self.transactions
+                .entry(address.clone())
+                .or_default()
+                .push(AddressTransaction {
+                    fee: fee.into(),
+                    sent: sent.into(),
+                    received: received.into(),
+                    ..entry.clone()
+                });
+            indexed.addresses.push(address);
+        }
+    }
+
+    /// undo the most recent blocks until the tip is `ancestor`, returns
+    /// `false` if the blocks to undo are not remembered anymore
+    fn rollback_to(&mut self, ancestor: HeaderHash) -> bool {
+        while self.tip != Some(ancestor) {
+            let block = match self.blocks.pop_back() {
+                Some(block) => block,
+                None => return false,
+            };
+            for address in block.addresses {
+                if let Some(transactions) = self.transactions.get_mut(&address) {
+                    transactions.pop();
+                    if transactions.is_empty() {
+                        self.transactions.remove(&address);
+                    }
+                }
+            }
+            for utxo in block.created_utxos {
+                self.utxos.remove(&utxo);
+            }
+            self.utxos.extend(block.spent_utxos);
+            self.tip = Some(block.parent);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jormungandr_lib::{crypto::hash::Hash, interfaces::BlockDate};
+
+    fn address(n: u8) -> Address {
+        Address(Discrimination::Test, Kind::Multisig([n; 32]))
+    }
+
+    fn hash(n: u8) -> HeaderHash {
+        Hash::from([n; 32]).into_hash()
+    }
+
+    fn transaction(block: u8, fragment: u8) -> AddressTransaction {
+        AddressTransaction {
+            fragment_id: hash(fragment).into(),
+            block: hash(block).into(),
+            chain_length: block.into(),
+            date: BlockDate::new(0, block.into()),
+            fee: Value::zero().into(),
+            sent: Value::zero().into(),
+            received: Value(1).into(),
+        }
+    }
+
+    /// add a block with one transaction for each of the given addresses
+    fn push_block(inner: &mut Inner, block: u8, addresses: &[Address]) {
+        let parent = inner.tip.unwrap_or_else(|| hash(0));
+        for (i, address) in addresses.iter().enumerate() {
+            inner
+                .transactions
+                .entry(address.clone())
+                .or_default()
+                .push(transaction(block, block * 10 + i as u8));
+        }
+        inner.blocks.push_back(IndexedBlock {
+            parent,
+            addresses: addresses.to_vec(),
+            created_utxos: Vec::new(),
+            spent_utxos: Vec::new(),
+        });
+        inner.tip = Some(hash(block));
+    }
+
+    #[tokio::test]
+    async fn transactions_are_paginated_most_recent_first() {
+        let index = AddressIndex::new(hash(0), Discrimination::Test, 10);
+        {
+            let mut inner = index.inner.write().await;
+            for block in 1..=5 {
+                push_block(&mut inner, block, &[address(1)]);
+            }
+        }
+
+        let page = index.transactions(&address(1), None, 2).await;
+        let blocks: Vec<u32> = page.transactions.iter().map(|tx| tx.chain_length).collect();
+        assert_eq!(blocks, vec![5, 4]);
+        assert_eq!(page.next_cursor, Some(3));
+
+        let page = index.transactions(&address(1), page.next_cursor, 2).await;
+        let blocks: Vec<u32> = page.transactions.iter().map(|tx| tx.chain_length).collect();
+        assert_eq!(blocks, vec![3, 2]);
+        assert_eq!(page.next_cursor, Some(1));
+
+        let page = index.transactions(&address(1), page.next_cursor, 2).await;
+        assert_eq!(page.transactions.len(), 1);
+        assert_eq!(page.next_cursor, None);
+
+        let page = index.transactions(&address(2), None, 2).await;
+        assert!(page.transactions.is_empty());
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn rollback_removes_the_transactions_of_the_undone_blocks() {
+        let mut inner = Inner::default();
+        push_block(&mut inner, 1, &[address(1)]);
+        push_block(&mut inner, 2, &[address(1), address(2)]);
+        push_block(&mut inner, 3, &[address(2)]);
+
+        assert!(inner.rollback_to(hash(1)));
+        assert_eq!(inner.tip, Some(hash(1)));
+        assert_eq!(inner.transactions[&address(1)].len(), 1);
+        assert!(!inner.transactions.contains_key(&address(2)));
+
+        // the blocks before the first remembered one cannot be undone
+        assert!(!inner.rollback_to(hash(9)));
+    }
+}
@@ -49,7 +49,11 @@ See Internal documentation for more details: doc/internal_design.md
 [`Branch`]: ./struct.Branch.html
 */
 #![allow(clippy::large_enum_variant)]
-use super::{branch::Branches, reference_cache::RefCache};
+use super::{
+    branch::Branches,
+    reference_cache::RefCache,
+    snapshot::{LedgerSnapshot, Snapshots},
+};
 use crate::{
     blockcfg::{
         Block, Block0Error, BlockDate, ChainLength, Epoch, EpochRewardsInfo, Header, HeaderHash,
@@ -60,7 +64,8 @@ use crate::{
 use chain_core::property::HasHeader;
 use chain_impl_mockchain::{leadership::Verification, ledger};
 use chain_time::TimeFrame;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
+use tokio::sync::Mutex;
 use tokio_stream::StreamExt;
 
 #[derive(Debug, thiserror::Error)]
@@ -106,6 +111,12 @@ pub enum Error {
 
     #[error("block cannot be applied on top of the previous block's ledger state")]
     CannotApplyBlock(#[source] ledger::Error),
+
+    #[error("cannot read or write the ledger snapshot")]
+    Snapshot(#[source] std::io::Error),
+
+    #[error("ledger snapshot of block `{0}` does not match the replayed ledger")]
+    SnapshotMismatch(HeaderHash),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -166,6 +177,10 @@ pub fn pre_verify_link(
 /// * `RefCache`: a cache of blocks headers and associated states;
 /// * `Multiverse`: of ledger. It is a cache of different ledger states.
 ///
+/// If the storage is persistent, snapshots of the ledger are also taken at
+/// stable epoch boundaries so the blockchain can be loaded without replaying
+/// every block since the block0.
+///
 #[derive(Clone)]
 pub struct Blockchain {
     branches: Branches,
@@ -176,9 +191,15 @@ pub struct Blockchain {
 
     storage: Storage,
 
+    snapshots: Option<Snapshots>,
+
     block0: HeaderHash,
 
     rewards_report_all: bool,
+
+    /// tip of the last branch refused because switching to it required a
+    /// rollback deeper than the epoch stability depth
+    last_refused_rollback: Arc<Mutex<Option<HeaderHash>>>,
 }
 
 pub enum PreCheckedHeader {
@@ -270,6 +291,7 @@ impl Blockchain {
     pub fn new(
         block0: HeaderHash,
         storage: Storage,
+        snapshots: Option<Snapshots>,
         cache_capacity: usize,
         rewards_report_all: bool,
     ) -> Self {
@@ -278,8 +300,10 @@ impl Blockchain {
             ref_cache: RefCache::new(cache_capacity),
             ledgers: Multiverse::new(),
             storage,
+            snapshots,
             block0,
             rewards_report_all,
+            last_refused_rollback: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -299,10 +323,83 @@ impl Blockchain {
         &mut self.branches
     }
 
+    /// record that switching to the branch with the given tip has been refused
+    /// because of a rollback deeper than the epoch stability depth.
+    ///
+    /// Returns `false` if the branch with this tip was the last one refused.
+    pub async fn refuse_rollback(&self, candidate: HeaderHash) -> bool {
+        let mut last_refused = self.last_refused_rollback.lock().await;
+        last_refused.replace(candidate) != Some(candidate)
+    }
+
     pub async fn gc(&self, tip: Arc<Ref>) -> Result<()> {
         let depth = tip.epoch_ledger_parameters().epoch_stability_depth;
         self.ledgers.gc(depth).await;
         self.storage.gc(depth, tip.hash().as_ref())?;
+        // a failure to take the snapshot only makes the next start slower
+        if let Err(error) = self.snapshot(tip).await {
+            tracing::error!(%error, "cannot take the ledger snapshot");
+        }
+        Ok(())
+    }
+
+    /// take a snapshot of the ledger at the end of the most recent epoch
+    /// that is deeper than the epoch stability depth from the given tip.
+    ///
+    /// Nothing is done if the snapshot already exists or if there is no
+    /// such epoch yet.
+    async fn snapshot(&self, tip: Arc<Ref>) -> Result<()> {
+        let snapshots = match &self.snapshots {
+            Some(snapshots) => snapshots.clone(),
+            None => return Ok(()),
+        };
+
+        let depth = tip.epoch_ledger_parameters().epoch_stability_depth;
+        let tip_length = u32::from(tip.chain_length());
+
+        let mut epoch_end = tip.last_ref_previous_epoch().cloned();
+        while let Some(candidate) = epoch_end.as_ref() {
+            if tip_length - u32::from(candidate.chain_length()) >= depth {
+                break;
+            }
+            epoch_end = candidate.last_ref_previous_epoch().cloned();
+        }
+
+        // the snapshot needs the state of the previous epoch to recompute
+        // the leadership schedule, so nothing is taken for the first epoch
+        let (epoch_end, previous_epoch_end) = match epoch_end {
+            Some(epoch_end) => match epoch_end.last_ref_previous_epoch().cloned() {
+                Some(previous_epoch_end) => (epoch_end, previous_epoch_end),
+                None => return Ok(()),
+            },
+            None => return Ok(()),
+        };
+
+        if snapshots.contains(epoch_end.chain_length(), epoch_end.hash()) {
+            return Ok(());
+        }
+
+        let snapshot = LedgerSnapshot {
+            block_id: epoch_end.hash(),
+            chain_length: epoch_end.chain_length(),
+            ledger: epoch_end.ledger().as_ref().clone(),
+            previous_epoch_block_id: previous_epoch_end.hash(),
+            previous_epoch_ledger: previous_epoch_end.ledger().as_ref().clone(),
+            second_previous_epoch_ledger: previous_epoch_end
+                .last_ref_previous_epoch()
+                .map(|r| r.ledger().as_ref().clone()),
+        };
+
+        tokio::task::spawn_blocking(move || snapshots.store(&snapshot))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::new(std::io::ErrorKind::Other, e)))
+            .map_err(Error::Snapshot)?;
+
+        tracing::info!(
+            "ledger snapshot taken at {}",
+            epoch_end.header().description()
+        );
+
         Ok(())
     }
 
@@ -634,17 +731,7 @@ impl Blockchain {
 
         let mut branches = self.branches.clone();
 
-        let time_frame = {
-            use crate::blockcfg::Block0DataSource as _;
-
-            let start_time = block0.start_time().map_err(Error::Block0)?;
-            let slot_duration = block0.slot_duration().map_err(Error::Block0)?;
-
-            TimeFrame::new(
-                chain_time::Timeline::new(start_time),
-                chain_time::SlotDuration::from_secs(slot_duration.as_secs() as u32),
-            )
-        };
+        let time_frame = block0_time_frame(block0)?;
 
         // we lift the creation of the ledger in the future type
         // this allow chaining of the operation and lifting the error handling
@@ -706,7 +793,8 @@ impl Blockchain {
 
     /// returns a future that will propagate the initial states and leadership
     /// from the block0 to the `Head` of the storage (the last known block which
-    /// made consensus).
+    /// made consensus). If a ledger snapshot of the main branch is available,
+    /// only the blocks following the most recent one are applied.
     ///
     /// The Future will returns a branch pointing to the `Head`.
     ///
@@ -720,28 +808,219 @@ impl Blockchain {
     ///
     pub async fn load_from_storage(&self, block0: Block) -> Result<Branch> {
         let block0_id = block0.header.hash();
+        let head_hash = self.storage_head(block0_id)?;
+
+        let (branch, from) = match self.load_from_snapshot(&block0, head_hash).await? {
+            Some(branch) => {
+                let from = branch.get_ref().await.hash();
+                (branch, from)
+            }
+            None => (self.apply_block0(&block0).await?, block0_id),
+        };
+
+        self.replay_from_storage(branch, from, head_hash, &HashSet::new())
+            .await
+    }
+
+    /// replay the whole blockchain from the block0 to the `Head` of the storage
+    /// and check that every ledger snapshot on the way matches the replayed
+    /// ledger states.
+    ///
+    /// Returns the number of snapshots that have been checked.
+    ///
+    /// # Errors
+    ///
+    /// The resulted future may fail with the same errors as
+    /// [`load_from_storage`](Self::load_from_storage) or if a snapshot
+    /// cannot be read or does not match: `Error::Snapshot` and
+    /// `Error::SnapshotMismatch`.
+    ///
+    pub async fn verify_snapshots(&self, block0: Block) -> Result<usize> {
+        let block0_id = block0.header.hash();
+        let head_hash = self.storage_head(block0_id)?;
+
+        let snapshots = match &self.snapshots {
+            Some(snapshots) => snapshots.list().map_err(Error::Snapshot)?,
+            None => Vec::new(),
+        };
+
+        let mut to_verify = HashSet::new();
+        for (_, block_id) in snapshots {
+            if self
+                .storage
+                .find_closest_ancestor(vec![block_id], head_hash)?
+                .is_some()
+            {
+                to_verify.insert(block_id);
+            } else {
+                tracing::warn!("ledger snapshot {} is not on the main branch", block_id);
+            }
+        }
+
+        let block0_branch = self.apply_block0(&block0).await?;
+        self.replay_from_storage(block0_branch, block0_id, head_hash, &to_verify)
+            .await?;
+
+        Ok(to_verify.len())
+    }
+
+    fn storage_head(&self, block0_id: HeaderHash) -> Result<HeaderHash> {
         let already_exist = self.storage.block_exists(block0_id)?;
 
         if !already_exist {
             return Err(Error::Block0NotAlreadyInStorage);
         }
 
-        let opt = self.storage.get_tag(MAIN_BRANCH_TAG)?;
+        self.storage
+            .get_tag(MAIN_BRANCH_TAG)?
+            .ok_or_else(|| Error::NoTag(MAIN_BRANCH_TAG.to_owned()))
+    }
 
-        let head_hash = if let Some(id) = opt {
-            id
-        } else {
-            return Err(Error::NoTag(MAIN_BRANCH_TAG.to_owned()));
+    /// load the most recent ledger snapshot taken on the main branch and
+    /// create the branch pointing to its block.
+    ///
+    /// Snapshots that cannot be read are skipped, `None` is returned if
+    /// no snapshot could be used.
+    async fn load_from_snapshot(
+        &self,
+        block0: &Block,
+        head_hash: HeaderHash,
+    ) -> Result<Option<Branch>> {
+        let snapshots = match &self.snapshots {
+            Some(snapshots) => snapshots.clone(),
+            None => return Ok(None),
         };
 
-        let block0_branch = self.apply_block0(&block0).await?;
+        let available = match snapshots.list() {
+            Ok(available) => available,
+            Err(error) => {
+                tracing::warn!(%error, "cannot list the ledger snapshots");
+                return Ok(None);
+            }
+        };
+
+        for (chain_length, block_id) in available {
+            if self
+                .storage
+                .find_closest_ancestor(vec![block_id], head_hash)?
+                .is_none()
+            {
+                tracing::debug!("ledger snapshot {} is not on the main branch", block_id);
+                continue;
+            }
+
+            let snapshots = snapshots.clone();
+            let loaded =
+                tokio::task::spawn_blocking(move || snapshots.load(chain_length, block_id))
+                    .await
+                    .unwrap_or_else(|e| Err(std::io::Error::new(std::io::ErrorKind::Other, e)));
+
+            match loaded {
+                Ok(snapshot) => {
+                    tracing::info!("loading the ledger from the snapshot at {}", block_id);
+                    return self.restore_snapshot(block0, snapshot).await.map(Some);
+                }
+                Err(error) => {
+                    tracing::warn!(%error, "cannot read the ledger snapshot at {}", block_id);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// recreate the `Ref` of the snapshot's block and of the last block of the
+    /// previous epoch, the leadership schedule is recomputed the same way it is
+    /// done on epoch transition (see [`new_epoch_leadership_from`]).
+    ///
+    /// The rewards info of the snapshot's epoch are not recovered.
+    async fn restore_snapshot(&self, block0: &Block, snapshot: LedgerSnapshot) -> Result<Branch> {
+        use chain_impl_mockchain::chaintypes::ConsensusVersion;
 
-        let mut block_stream = self
+        let LedgerSnapshot {
+            block_id,
+            chain_length: _,
+            ledger,
+            previous_epoch_block_id,
+            previous_epoch_ledger,
+            second_previous_epoch_ledger,
+        } = snapshot;
+
+        let time_frame = Arc::new(block0_time_frame(block0)?);
+        let header = self
+            .storage
+            .get(block_id)?
+            .ok_or(StorageError::BlockNotFound)?
+            .header;
+        let previous_epoch_header = self
             .storage
-            .stream_from_to(block0_id, head_hash)
-            .map(Box::pin)?;
+            .get(previous_epoch_block_id)?
+            .ok_or(StorageError::BlockNotFound)?
+            .header;
+
+        let transition_state = previous_epoch_ledger.apply_protocol_changes()?;
+        let leadership = {
+            let epoch_state =
+                if transition_state.consensus_version() == ConsensusVersion::GenesisPraos {
+                    second_previous_epoch_ledger
+                        .as_ref()
+                        .unwrap_or(&previous_epoch_ledger)
+                } else {
+                    &transition_state
+                };
+            Arc::new(Leadership::new(header.block_date().epoch, epoch_state))
+        };
+        let ledger_parameters = Arc::new(leadership.ledger_parameters().clone());
+
+        // the previous epoch `Ref` is only used for its ledger state when
+        // computing the next epoch's leadership, it shares the leadership
+        // of the snapshot's epoch
+        let previous_epoch_ref = self
+            .create_and_store_reference(
+                previous_epoch_block_id,
+                previous_epoch_header,
+                previous_epoch_ledger,
+                Arc::clone(&time_frame),
+                Arc::clone(&leadership),
+                None,
+                Arc::clone(&ledger_parameters),
+                None,
+            )
+            .await;
+        let snapshot_ref = self
+            .create_and_store_reference(
+                block_id,
+                header,
+                ledger,
+                time_frame,
+                leadership,
+                None,
+                ledger_parameters,
+                Some(previous_epoch_ref),
+            )
+            .await;
+
+        let branch = Branch::new(snapshot_ref);
+        self.branches.clone().add(branch.clone()).await;
+        Ok(branch)
+    }
+
+    /// apply the blocks of the storage from `from` (excluded) to `to` on top
+    /// of the given branch. The ledger states of the blocks in `to_verify`
+    /// are checked against their snapshot.
+    async fn replay_from_storage(
+        &self,
+        mut branch: Branch,
+        from: HeaderHash,
+        to: HeaderHash,
+        to_verify: &HashSet<HeaderHash>,
+    ) -> Result<Branch> {
+        if from == to {
+            return Ok(branch);
+        }
+
+        let mut block_stream = self.storage.stream_from_to(from, to).map(Box::pin)?;
 
-        let mut branch = block0_branch;
         let mut count = 0u64;
 
         let mut block_processing = std::time::Duration::from_secs(0);
@@ -788,6 +1067,10 @@ impl Blockchain {
                 .apply_block_finalize(post_checked_header, new_ledger)
                 .await;
 
+            if to_verify.contains(&new_ref.hash()) {
+                self.verify_snapshot(&new_ref).await?;
+            }
+
             count += 1;
             let _: Arc<Ref> = branch.update_ref(new_ref).await;
 
@@ -800,11 +1083,55 @@ impl Blockchain {
         Ok(branch)
     }
 
+    async fn verify_snapshot(&self, reference: &Ref) -> Result<()> {
+        let snapshots = match &self.snapshots {
+            Some(snapshots) => snapshots.clone(),
+            None => return Ok(()),
+        };
+        let block_id = reference.hash();
+        let chain_length = reference.chain_length();
+
+        let snapshot = tokio::task::spawn_blocking(move || snapshots.load(chain_length, block_id))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::new(std::io::ErrorKind::Other, e)))
+            .map_err(Error::Snapshot)?;
+
+        let previous_epoch_matches =
+            reference
+                .last_ref_previous_epoch()
+                .map_or(false, |previous| {
+                    previous.hash() == snapshot.previous_epoch_block_id
+                        && *previous.ledger() == snapshot.previous_epoch_ledger
+                });
+
+        if *reference.ledger() != snapshot.ledger || !previous_epoch_matches {
+            return Err(Error::SnapshotMismatch(block_id));
+        }
+
+        tracing::info!(
+            "ledger snapshot at {} matches the replayed ledger",
+            reference.header().description()
+        );
+        Ok(())
+    }
+
     pub async fn get_checkpoints(&self, branch: &Branch) -> Checkpoints {
         Checkpoints::new_from(branch.get_ref().await)
     }
 }
 
+fn block0_time_frame(block0: &Block) -> Result<TimeFrame> {
+    use crate::blockcfg::Block0DataSource as _;
+
+    let start_time = block0.start_time().map_err(Error::Block0)?;
+    let slot_duration = block0.slot_duration().map_err(Error::Block0)?;
+
+    Ok(TimeFrame::new(
+        chain_time::Timeline::new(start_time),
+        chain_time::SlotDuration::from_secs(slot_duration.as_secs() as u32),
+    ))
+}
+
 fn write_reward_info(
     epoch: Epoch,
     parent_hash: HeaderHash,
@@ -944,3 +1271,27 @@ pub fn new_epoch_leadership_from(
         }
     }
 }
+
+/// the leadership of the epoch following the epoch of `tip`, if it is
+/// already fixed.
+///
+/// With the genesis praos consensus the leadership of an epoch is computed
+/// from the ledger at the end of the epoch before the previous one, so it is
+/// not known while the tip is still in the first epoch. The rewards
+/// distributed at the epoch transition do not change the leadership, they are
+/// not computed here. A protocol update adopted at the epoch transition may
+/// still change the actual leadership.
+pub fn next_epoch_leadership_preview(tip: &Ref) -> Option<Arc<Leadership>> {
+    use chain_impl_mockchain::chaintypes::ConsensusVersion;
+
+    let epoch = tip.block_date().epoch + 1;
+    let transition_state = tip.ledger().apply_protocol_changes().ok()?;
+
+    let epoch_state = if transition_state.consensus_version() == ConsensusVersion::GenesisPraos {
+        tip.last_ref_previous_epoch()?.ledger()
+    } else {
+        Arc::new(transition_state)
+    };
+
+    Some(Arc::new(Leadership::new(epoch, &epoch_state)))
+}
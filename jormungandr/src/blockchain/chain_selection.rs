@@ -1,4 +1,4 @@
-use crate::blockchain::{Ref, Storage};
+use crate::blockchain::{Ref, Storage, StorageError};
 use std::time::Duration;
 
 const ALLOWED_TIME_DISCREPANCY: Duration = Duration::from_secs(1);
@@ -7,6 +7,9 @@ const ALLOWED_TIME_DISCREPANCY: Duration = Duration::from_secs(1);
 pub enum ComparisonResult {
     PreferCurrent,
     PreferCandidate,
+    /// the candidate would have been preferred but switching to it requires
+    /// to roll back the current branch deeper than the epoch stability depth
+    RollbackTooDeep,
 }
 
 /// chose which of the two Ref is the most interesting to keep as a branch
@@ -16,9 +19,6 @@ pub enum ComparisonResult {
 pub fn compare_against(storage: &Storage, current: &Ref, candidate: &Ref) -> ComparisonResult {
     let epoch_stability_depth = current.epoch_ledger_parameters().epoch_stability_depth;
 
-    let rollback_possible =
-        check_rollback_up_to(epoch_stability_depth, storage, current, candidate);
-
     // returns `true` if the candidate is set in what appears to be in the future
     // relative to this node, with a little buffer to accomodate for small inconsistencies
     // in time
@@ -34,18 +34,186 @@ pub fn compare_against(storage: &Storage, current: &Ref, candidate: &Ref) -> Com
         _ => false,
     };
 
-    if rollback_possible && !in_future && current.chain_length() < candidate.chain_length() {
+    if in_future || current.chain_length() >= candidate.chain_length() {
+        ComparisonResult::PreferCurrent
+    } else if check_rollback_up_to(epoch_stability_depth, storage, current, candidate) {
         ComparisonResult::PreferCandidate
     } else {
-        ComparisonResult::PreferCurrent
+        ComparisonResult::RollbackTooDeep
     }
 }
 
+/// check that switching from `current` to `candidate` does not roll back
+/// more than `epoch_stability_depth` blocks of the current branch
 fn check_rollback_up_to(
-    _epoch_stability_depth: u32,
-    _: &Storage,
-    _ref1: &Ref,
-    _ref2: &Ref,
+    epoch_stability_depth: u32,
+    storage: &Storage,
+    current: &Ref,
+    candidate: &Ref,
 ) -> bool {
-    true
+    match rollback_depth(storage, current, candidate) {
+        Ok(depth) => depth <= epoch_stability_depth,
+        Err(error) => {
+            tracing::warn!(
+                reason = %error,
+                "cannot find the common ancestor of {} and {}",
+                current.header().description(),
+                candidate.header().description(),
+            );
+            false
+        }
+    }
+}
+
+/// number of blocks of the current branch that are rolled back
+/// when switching to the candidate
+fn rollback_depth(storage: &Storage, current: &Ref, candidate: &Ref) -> Result<u32, StorageError> {
+    if candidate.block_parent_hash() == current.hash() {
+        return Ok(0);
+    }
+
+    let common_ancestor = storage.find_common_ancestor(current.hash(), candidate.hash())?;
+    let ancestor_length = storage
+        .get(common_ancestor)?
+        .ok_or(StorageError::BlockNotFound)?
+        .header
+        .chain_length();
+
+    Ok(u32::from(current.chain_length()) - u32::from(ancestor_length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blockcfg::{Block, BlockDate, BlockVersion, Contents, HeaderBuilderNew},
+        blockchain::{Blockchain, CheckHeaderProof},
+    };
+    use chain_addr::Discrimination;
+    use chain_core::property::ChainLength as _;
+    use chain_impl_mockchain::{chaintypes::ConsensusVersion, fee::LinearFee};
+    use jormungandr_lib::interfaces::{Block0Configuration, BlockchainConfiguration};
+    use std::sync::Arc;
+
+    const EPOCH_STABILITY_DEPTH: u32 = 2;
+
+    async fn blockchain() -> (Blockchain, Arc<Ref>) {
+        let mut blockchain_configuration = BlockchainConfiguration::new(
+            Discrimination::Test,
+            ConsensusVersion::GenesisPraos,
+            LinearFee::new(0, 0, 0),
+        );
+        blockchain_configuration.epoch_stability_depth = EPOCH_STABILITY_DEPTH.into();
+        let block0 = Block0Configuration {
+            blockchain_configuration,
+            initial: Vec::new(),
+        }
+        .to_block();
+
+        let storage = Storage::memory(tracing::Span::none()).unwrap();
+        let blockchain = Blockchain::new(block0.header.hash(), storage, None, 1024, false);
+        let block0_ref = blockchain
+            .load_from_block0(block0)
+            .await
+            .unwrap()
+            .get_ref()
+            .await;
+        (blockchain, block0_ref)
+    }
+
+    /// extend the branch ending at `parent` with `length` empty blocks. The
+    /// `slot_offset` allows to create distinct forks from the same parent.
+    async fn extend(
+        blockchain: &Blockchain,
+        mut parent: Arc<Ref>,
+        length: u32,
+        slot_offset: u32,
+    ) -> Arc<Ref> {
+        for _ in 0..length {
+            let contents = Contents::empty();
+            let chain_length = parent.chain_length().next();
+            let date = BlockDate {
+                epoch: 0,
+                slot_id: u32::from(chain_length) + slot_offset,
+            };
+            let header = HeaderBuilderNew::new(BlockVersion::Genesis, &contents)
+                .set_parent(&parent.hash(), chain_length)
+                .set_date(date)
+                .into_unsigned_header()
+                .unwrap()
+                .generalize();
+            let post_checked_header = blockchain
+                .post_check_header(header.clone(), parent, CheckHeaderProof::SkipFromStorage)
+                .await
+                .unwrap();
+            parent = blockchain
+                .apply_and_store_block(post_checked_header, Block { header, contents })
+                .await
+                .unwrap()
+                .cached_ref();
+        }
+        parent
+    }
+
+    #[tokio::test]
+    async fn prefer_candidate_extending_current() {
+        let (blockchain, block0) = blockchain().await;
+        let current = extend(&blockchain, block0, 4, 0).await;
+        let candidate = extend(&blockchain, Arc::clone(&current), 1, 0).await;
+
+        assert_eq!(
+            compare_against(blockchain.storage(), &current, &candidate),
+            ComparisonResult::PreferCandidate
+        );
+        assert_eq!(
+            compare_against(blockchain.storage(), &candidate, &current),
+            ComparisonResult::PreferCurrent
+        );
+    }
+
+    #[tokio::test]
+    async fn prefer_longer_fork_within_stability_depth() {
+        let (blockchain, block0) = blockchain().await;
+        let fork_point = extend(&blockchain, block0, 2, 0).await;
+        let current = extend(
+            &blockchain,
+            Arc::clone(&fork_point),
+            EPOCH_STABILITY_DEPTH,
+            0,
+        )
+        .await;
+        let candidate = extend(&blockchain, fork_point, EPOCH_STABILITY_DEPTH + 1, 10).await;
+
+        assert_eq!(
+            rollback_depth(blockchain.storage(), &current, &candidate).unwrap(),
+            EPOCH_STABILITY_DEPTH
+        );
+        assert_eq!(
+            compare_against(blockchain.storage(), &current, &candidate),
+            ComparisonResult::PreferCandidate
+        );
+    }
+
+    #[tokio::test]
+    async fn refuse_fork_deeper_than_stability_depth() {
+        let (blockchain, block0) = blockchain().await;
+        let fork_point = extend(&blockchain, block0, 1, 0).await;
+        let current = extend(
+            &blockchain,
+            Arc::clone(&fork_point),
+            EPOCH_STABILITY_DEPTH + 1,
+            0,
+        )
+        .await;
+        let candidate = extend(&blockchain, fork_point, EPOCH_STABILITY_DEPTH + 2, 10).await;
+
+        assert_eq!(
+            rollback_depth(blockchain.storage(), &current, &candidate).unwrap(),
+            EPOCH_STABILITY_DEPTH + 1
+        );
+        assert_eq!(
+            compare_against(blockchain.storage(), &current, &candidate),
+            ComparisonResult::RollbackTooDeep
+        );
+    }
 }
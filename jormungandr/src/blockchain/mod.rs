@@ -1,3 +1,4 @@
+mod address_index;
 mod branch;
 mod candidate;
 mod chain;
@@ -7,6 +8,7 @@ mod multiverse;
 mod process;
 mod reference;
 mod reference_cache;
+mod snapshot;
 mod storage;
 mod tip;
 
@@ -25,16 +27,18 @@ mod chunk_sizes {
 // Re-exports
 
 pub use self::{
+    address_index::AddressIndex,
     branch::Branch,
     chain::{
-        new_epoch_leadership_from, Blockchain, CheckHeaderProof, EpochLeadership, Error,
-        LeadershipBlock, PreCheckedHeader, MAIN_BRANCH_TAG,
+        new_epoch_leadership_from, next_epoch_leadership_preview, Blockchain, CheckHeaderProof,
+        EpochLeadership, Error, LeadershipBlock, PreCheckedHeader, MAIN_BRANCH_TAG,
     },
     chain_selection::{compare_against, ComparisonResult},
     checkpoints::Checkpoints,
     multiverse::Multiverse,
     process::{process_new_ref, Process},
     reference::Ref,
+    snapshot::Snapshots,
     storage::{Error as StorageError, Storage},
     tip::Tip,
 };
@@ -7,6 +7,7 @@ use super::{
 use crate::{
     blockcfg::{Block, FragmentId, Header, HeaderHash},
     blockchain::Checkpoints,
+    events::NodeEvents,
     intercom::{self, BlockMsg, ExplorerMsg, NetworkMsg, PropagateMsg, TransactionMsg},
     network::p2p::Address,
     stats_counter::StatsCounter,
@@ -19,13 +20,16 @@ use crate::{
     },
 };
 use chain_core::property::{Block as _, Fragment as _, HasHeader as _, Header as _};
-use jormungandr_lib::interfaces::FragmentStatus;
+use jormungandr_lib::interfaces::{FragmentStatus, NodeEvent};
 
 use futures::prelude::*;
 use tracing::{span, Level};
 use tracing_futures::Instrument;
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 type PullHeadersScheduler = FireForgetScheduler<HeaderHash, Address, Checkpoints>;
 type GetNextBlockScheduler = FireForgetScheduler<HeaderHash, Address, ()>;
@@ -55,6 +59,7 @@ pub struct Process {
     pub blockchain: Blockchain,
     pub blockchain_tip: Tip,
     pub stats_counter: StatsCounter,
+    pub events: NodeEvents,
     pub network_msgbox: MessageBox<NetworkMsg>,
     pub fragment_msgbox: MessageBox<TransactionMsg>,
     pub explorer_msgbox: Option<MessageBox<ExplorerMsg>>,
@@ -94,6 +99,7 @@ impl Process {
         let explorer_msg_box = self.explorer_msgbox.clone();
         let tx_msg_box = self.fragment_msgbox.clone();
         let stats_counter = self.stats_counter.clone();
+        let events = self.events.clone();
 
         match input {
             BlockMsg::LeadershipBlock(leadership_block) => {
@@ -119,6 +125,7 @@ impl Process {
                         explorer_msg_box,
                         leadership_block,
                         stats_counter,
+                        events,
                     )
                     .instrument(span.clone()),
                 );
@@ -166,6 +173,7 @@ impl Process {
                         get_next_block_scheduler,
                         handle,
                         stats_counter,
+                        events,
                     ),
                 );
             }
@@ -194,6 +202,7 @@ impl Process {
         let blockchain = self.blockchain.clone();
         let explorer = self.explorer_msgbox.clone();
         let tx_msg_box = self.fragment_msgbox.clone();
+        let events = self.events.clone();
 
         info.run_periodic_fallible(
             "branch reprocessing",
@@ -204,6 +213,7 @@ impl Process {
                     tip.clone(),
                     explorer.clone(),
                     tx_msg_box.clone(),
+                    events.clone(),
                 )
             },
         )
@@ -291,6 +301,7 @@ async fn reprocess_tip(
     tip: Tip,
     explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
     tx_msg_box: MessageBox<TransactionMsg>,
+    events: NodeEvents,
 ) -> Result<(), Error> {
     let branches: Vec<Arc<Ref>> = blockchain.branches().branches().await;
 
@@ -308,6 +319,10 @@ async fn reprocess_tip(
             Arc::clone(other),
             explorer_msg_box.clone(),
             Some(tx_msg_box.clone()),
+            // the branches are reprocessed periodically, the refused
+            // rollbacks would be counted again at every run
+            None,
+            Some(&events),
         )
         .await?
     }
@@ -324,12 +339,18 @@ async fn reprocess_tip(
 /// If the current tip is not the one being updated we will then trigger
 /// chain selection after updating that other branch as it may be possible that
 /// this branch just became more interesting for the current consensus algorithm.
+///
+/// Candidates requiring to roll back the current tip deeper than the epoch
+/// stability depth are refused and counted in the given stats counter.
+/// Tip updates are published to the given node events.
 pub async fn process_new_ref(
     blockchain: &mut Blockchain,
     mut tip: Tip,
     candidate: Arc<Ref>,
     explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
     mut tx_msg_box: Option<MessageBox<TransactionMsg>>,
+    stats_counter: Option<&StatsCounter>,
+    events: Option<&NodeEvents>,
 ) -> Result<(), Error> {
     let candidate_hash = candidate.hash();
     let tip_ref = tip.get_ref().await;
@@ -342,6 +363,26 @@ pub async fn process_new_ref(
                 tip_ref.header().description(),
             );
         }
+        ComparisonResult::RollbackTooDeep => {
+            // the branches are reprocessed periodically, only the first refusal
+            // of a branch tip is worth a warning
+            if blockchain.refuse_rollback(candidate_hash).await {
+                tracing::warn!(
+                    "refusing to switch to branch with tip {}, the rollback from current-tip {} is deeper than the epoch stability depth",
+                    candidate.header().description(),
+                    tip_ref.header().description(),
+                );
+            } else {
+                tracing::debug!(
+                    "still refusing to switch to branch with tip {}, current-tip {}",
+                    candidate.header().description(),
+                    tip_ref.header().description(),
+                );
+            }
+            if let Some(stats_counter) = stats_counter {
+                stats_counter.add_rollback_refused_cnt(1);
+            }
+        }
         ComparisonResult::PreferCandidate => {
             if tip_ref.hash() == candidate.block_parent_hash() {
                 tracing::info!(
@@ -402,6 +443,15 @@ pub async fn process_new_ref(
                 tip.swap(branch).await;
             }
 
+            if let Some(events) = events {
+                let new_tip = tip.get_ref().await;
+                events.publish(|| NodeEvent::Tip {
+                    block: new_tip.hash().into(),
+                    chain_length: new_tip.chain_length().into(),
+                    date: new_tip.block_date().into(),
+                });
+            }
+
             if let Some(mut msg_box) = explorer_msg_box {
                 msg_box
                     .send(ExplorerMsg::NewTip(candidate_hash))
@@ -423,6 +473,8 @@ async fn process_and_propagate_new_ref(
     mut network_msg_box: MessageBox<NetworkMsg>,
     explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
     tx_msg_box: MessageBox<TransactionMsg>,
+    stats_counter: &StatsCounter,
+    events: &NodeEvents,
 ) -> chain::Result<()> {
     let header = new_block_ref.header().clone();
     tracing::debug!("processing the new block and propagating");
@@ -432,6 +484,8 @@ async fn process_and_propagate_new_ref(
         new_block_ref,
         explorer_msg_box,
         Some(tx_msg_box),
+        Some(stats_counter),
+        Some(events),
     )
     .await?;
 
@@ -453,6 +507,7 @@ async fn process_leadership_block(
     explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
     leadership_block: LeadershipBlock,
     stats_counter: StatsCounter,
+    events: NodeEvents,
 ) -> chain::Result<()> {
     let block = leadership_block.block.clone();
     let new_block_ref = process_leadership_block_inner(&mut blockchain, leadership_block).await?;
@@ -464,6 +519,8 @@ async fn process_leadership_block(
         network_msg_box,
         explorer_msg_box.clone(),
         tx_msg_box,
+        &stats_counter,
+        &events,
     )
     .await?;
 
@@ -547,6 +604,7 @@ async fn process_network_blocks(
     mut get_next_block_scheduler: GetNextBlockScheduler,
     handle: intercom::RequestStreamHandle<Block, ()>,
     stats_counter: StatsCounter,
+    events: NodeEvents,
 ) -> Result<(), Error> {
     let (mut stream, reply) = handle.into_stream_and_reply();
     let mut candidate = None;
@@ -557,6 +615,7 @@ async fn process_network_blocks(
         match maybe_block {
             Some(block) => {
                 latest_block = Some(Arc::new(block.clone()));
+                let started = Instant::now();
                 let res = process_network_block(
                     &blockchain,
                     block.clone(),
@@ -567,6 +626,7 @@ async fn process_network_blocks(
                 match res {
                     Ok(Some(r)) => {
                         stats_counter.add_block_recv_cnt(1);
+                        stats_counter.add_block_apply_time(started.elapsed());
                         stream = stream_tail;
                         candidate = Some(r);
                     }
@@ -600,6 +660,8 @@ async fn process_network_blocks(
                 network_msg_box,
                 explorer_msg_box,
                 tx_msg_box,
+                &stats_counter,
+                &events,
             )
             .await?;
 
@@ -0,0 +1,272 @@
+//! Ledger snapshots, stored next to the blocks storage, allowing the node
+//! to restore its ledger state without replaying the whole blockchain from
+//! the block0 at every start.
+//!
+//! A snapshot is taken at the last block of an epoch and keeps the ledger
+//! states needed to recompute the leadership schedule of the following
+//! epochs: the ledger after this block, the ledger at the end of the
+//! previous epoch and, if any, the ledger at the end of the epoch before.
+//!
+//! The rewards info of the epochs are not part of a snapshot: they are not
+//! needed to validate the following blocks, so the rewards info history of a
+//! node restored from a snapshot starts at the first epoch it has applied.
+
+use crate::{
+    blockcfg::{ChainLength, HeaderHash, Ledger},
+    utils::block_files::{invalid_data, BlockFiles},
+};
+use chain_core::property::{Deserialize as _, Serialize as _};
+use std::{
+    io::{self, Read, Write},
+    path::Path,
+};
+
+const SNAPSHOT_VERSION: u8 = 1;
+const SNAPSHOT_EXTENSION: &str = "snapshot";
+const SNAPSHOTS_TO_KEEP: usize = 2;
+
+pub struct LedgerSnapshot {
+    /// the last block of the epoch the snapshot has been taken at
+    pub block_id: HeaderHash,
+    pub chain_length: ChainLength,
+    /// the ledger state after `block_id`
+    pub ledger: Ledger,
+    /// the last block of the previous epoch
+    pub previous_epoch_block_id: HeaderHash,
+    /// the ledger state after `previous_epoch_block_id`
+    pub previous_epoch_ledger: Ledger,
+    /// the ledger state at the end of the epoch preceding the previous
+    /// epoch, if it was known when the snapshot was taken
+    pub second_previous_epoch_ledger: Option<Ledger>,
+}
+
+/// handle on the directory containing the ledger snapshots
+#[derive(Clone)]
+pub struct Snapshots {
+    files: BlockFiles,
+}
+
+impl Snapshots {
+    pub fn new<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        Ok(Snapshots {
+            files: BlockFiles::new(dir, SNAPSHOT_EXTENSION)?,
+        })
+    }
+
+    pub fn contains(&self, chain_length: ChainLength, block_id: HeaderHash) -> bool {
+        self.files.contains(chain_length, block_id)
+    }
+
+    /// list the available snapshots, the most recent one first
+    pub fn list(&self) -> io::Result<Vec<(ChainLength, HeaderHash)>> {
+        self.files.list()
+    }
+
+    pub fn load(
+        &self,
+        chain_length: ChainLength,
+        block_id: HeaderHash,
+    ) -> io::Result<LedgerSnapshot> {
+        let snapshot = read_snapshot(&mut self.files.open(chain_length, block_id)?)?;
+
+        if snapshot.block_id != block_id || snapshot.chain_length != chain_length {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the snapshot content does not match its file name",
+            ));
+        }
+
+        Ok(snapshot)
+    }
+
+    /// write the snapshot on disk and remove the oldest snapshots
+    pub fn store(&self, snapshot: &LedgerSnapshot) -> io::Result<()> {
+        self.files.write(
+            snapshot.chain_length,
+            snapshot.block_id,
+            SNAPSHOTS_TO_KEEP,
+            |writer| write_snapshot(writer, snapshot),
+        )
+    }
+}
+
+fn write_snapshot<W: Write>(writer: &mut W, snapshot: &LedgerSnapshot) -> io::Result<()> {
+    writer.write_all(&[SNAPSHOT_VERSION])?;
+    writer.write_all(snapshot.block_id.as_bytes())?;
+    writer.write_all(&u32::from(snapshot.chain_length).to_be_bytes())?;
+    write_ledger(writer, &snapshot.ledger)?;
+    writer.write_all(snapshot.previous_epoch_block_id.as_bytes())?;
+    write_ledger(writer, &snapshot.previous_epoch_ledger)?;
+    match &snapshot.second_previous_epoch_ledger {
+        None => writer.write_all(&[0]),
+        Some(ledger) => {
+            writer.write_all(&[1])?;
+            write_ledger(writer, ledger)
+        }
+    }
+}
+
+fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<LedgerSnapshot> {
+    let mut version = [0; 1];
+    reader.read_exact(&mut version)?;
+    if version[0] != SNAPSHOT_VERSION {
+        return Err(invalid_data(format!(
+            "unsupported snapshot version {}",
+            version[0]
+        )));
+    }
+
+    let block_id = read_header_hash(reader)?;
+    let mut chain_length = [0; 4];
+    reader.read_exact(&mut chain_length)?;
+    let chain_length = ChainLength::from(u32::from_be_bytes(chain_length));
+    let ledger = read_ledger(reader)?;
+    let previous_epoch_block_id = read_header_hash(reader)?;
+    let previous_epoch_ledger = read_ledger(reader)?;
+
+    let mut tag = [0; 1];
+    reader.read_exact(&mut tag)?;
+    let second_previous_epoch_ledger = match tag[0] {
+        0 => None,
+        1 => Some(read_ledger(reader)?),
+        tag => return Err(invalid_data(format!("invalid optional tag {}", tag))),
+    };
+
+    Ok(LedgerSnapshot {
+        block_id,
+        chain_length,
+        ledger,
+        previous_epoch_block_id,
+        previous_epoch_ledger,
+        second_previous_epoch_ledger,
+    })
+}
+
+fn read_header_hash<R: Read>(reader: &mut R) -> io::Result<HeaderHash> {
+    let mut bytes = [0; 32];
+    reader.read_exact(&mut bytes)?;
+    HeaderHash::deserialize(&bytes[..])
+}
+
+fn write_ledger<W: Write>(writer: &mut W, ledger: &Ledger) -> io::Result<()> {
+    let bytes = ledger.serialize_as_vec().map_err(invalid_data)?;
+    writer.write_all(&(bytes.len() as u64).to_be_bytes())?;
+    writer.write_all(&bytes)
+}
+
+fn read_ledger<R: Read>(reader: &mut R) -> io::Result<Ledger> {
+    let mut len = [0; 8];
+    reader.read_exact(&mut len)?;
+    let mut bytes = vec![0; u64::from_be_bytes(len) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ledger::deserialize(bytes.as_slice()).map_err(invalid_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_addr::Discrimination;
+    use chain_crypto::{Ed25519, SecretKey};
+    use chain_impl_mockchain::{chaintypes::ConsensusVersion, fee::LinearFee};
+    use jormungandr_lib::{
+        crypto::hash::Hash,
+        interfaces::{Block0Configuration, BlockchainConfiguration},
+    };
+
+    fn ledger() -> Ledger {
+        let leader = SecretKey::<Ed25519>::generate(&mut rand::rngs::OsRng).to_public();
+        let mut blockchain_configuration = BlockchainConfiguration::new(
+            Discrimination::Test,
+            ConsensusVersion::Bft,
+            LinearFee::new(0, 0, 0),
+        );
+        blockchain_configuration.consensus_leader_ids = vec![leader.into()];
+        let block0 = Block0Configuration {
+            blockchain_configuration,
+            initial: Vec::new(),
+        }
+        .to_block();
+        Ledger::new(block0.header.hash(), block0.contents.iter()).unwrap()
+    }
+
+    fn hash(n: u8) -> HeaderHash {
+        Hash::from([n; 32]).into_hash()
+    }
+
+    fn snapshot(chain_length: u32, second_previous_epoch_ledger: Option<Ledger>) -> LedgerSnapshot {
+        LedgerSnapshot {
+            block_id: hash(chain_length as u8),
+            chain_length: chain_length.into(),
+            ledger: ledger(),
+            previous_epoch_block_id: hash(chain_length as u8 - 1),
+            previous_epoch_ledger: ledger(),
+            second_previous_epoch_ledger,
+        }
+    }
+
+    fn ledger_bytes(ledger: &Ledger) -> Vec<u8> {
+        ledger.serialize_as_vec().unwrap()
+    }
+
+    fn assert_same_snapshot(expected: &LedgerSnapshot, actual: &LedgerSnapshot) {
+        assert_eq!(expected.block_id, actual.block_id);
+        assert_eq!(expected.chain_length, actual.chain_length);
+        assert_eq!(ledger_bytes(&expected.ledger), ledger_bytes(&actual.ledger));
+        assert_eq!(
+            expected.previous_epoch_block_id,
+            actual.previous_epoch_block_id
+        );
+        assert_eq!(
+            ledger_bytes(&expected.previous_epoch_ledger),
+            ledger_bytes(&actual.previous_epoch_ledger)
+        );
+        assert_eq!(
+            expected
+                .second_previous_epoch_ledger
+                .as_ref()
+                .map(ledger_bytes),
+            actual
+                .second_previous_epoch_ledger
+                .as_ref()
+                .map(ledger_bytes)
+        );
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        for snapshot in vec![snapshot(10, None), snapshot(20, Some(ledger()))] {
+            let mut bytes = Vec::new();
+            write_snapshot(&mut bytes, &snapshot).unwrap();
+            let read = read_snapshot(&mut bytes.as_slice()).unwrap();
+            assert_same_snapshot(&snapshot, &read);
+        }
+    }
+
+    #[test]
+    fn store_keeps_the_most_recent_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let snapshots = Snapshots::new(dir.path()).unwrap();
+
+        let stored: Vec<_> = vec![10, 20, 30]
+            .into_iter()
+            .map(|chain_length| snapshot(chain_length, None))
+            .collect();
+        for snapshot in &stored {
+            snapshots.store(snapshot).unwrap();
+        }
+
+        let expected: Vec<_> = stored
+            .iter()
+            .rev()
+            .take(SNAPSHOTS_TO_KEEP)
+            .map(|snapshot| (snapshot.chain_length, snapshot.block_id))
+            .collect();
+        assert_eq!(snapshots.list().unwrap(), expected);
+        assert!(!snapshots.contains(stored[0].chain_length, stored[0].block_id));
+
+        let loaded = snapshots
+            .load(stored[2].chain_length, stored[2].block_id)
+            .unwrap();
+        assert_same_snapshot(&stored[2], &loaded);
+    }
+}
@@ -0,0 +1,39 @@
+//! Events published by the node services and streamed to the REST clients
+
+use jormungandr_lib::interfaces::NodeEvent;
+use tokio::sync::broadcast;
+
+/// number of events kept for the subscribers lagging behind before
+/// they start missing events
+const EVENTS_BUFFER_SIZE: usize = 1024;
+
+#[derive(Clone)]
+pub struct NodeEvents {
+    sender: broadcast::Sender<NodeEvent>,
+}
+
+impl Default for NodeEvents {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENTS_BUFFER_SIZE);
+        NodeEvents { sender }
+    }
+}
+
+impl NodeEvents {
+    /// publish the event built by `event` to the current subscribers, the
+    /// event is not built at all if nobody listens
+    pub fn publish<F>(&self, event: F)
+    where
+        F: FnOnce() -> NodeEvent,
+    {
+        if self.sender.receiver_count() > 0 {
+            // the subscribers may have gone in between, it is fine to
+            // drop the event then
+            let _ = self.sender.send(event());
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.sender.subscribe()
+    }
+}
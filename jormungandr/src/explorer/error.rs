@@ -20,6 +20,10 @@ pub enum ExplorerError {
     StorageError(#[from] StorageError),
     #[error("streaming error")]
     StreamingError(#[from] intercom::Error),
+    #[error("the stake distribution of epoch {0} is not known, the ledger state of the epoch was not known to the node when the epoch was indexed")]
+    StakeDistributionUnavailable(crate::blockcfg::Epoch),
+    #[error("cannot access the stable store of the explorer")]
+    StableStoreError(#[from] std::io::Error),
 }
 
 pub type Result<T> = std::result::Result<T, ExplorerError>;
@@ -1,5 +1,4 @@
-use super::error::ApiError;
-use async_graphql::{Context, FieldResult, Object, Union};
+use async_graphql::{Context, Object, Union};
 use chain_impl_mockchain::certificate;
 use std::convert::TryFrom;
 
@@ -43,7 +42,7 @@ pub struct EncryptedVoteTally(certificate::EncryptedVoteTally);
 #[Object]
 impl StakeDelegation {
     // FIXME: Maybe a new Account type would be better?
-    pub async fn account(&self, context: &Context<'_>) -> FieldResult<Address> {
+    pub async fn account(&self, context: &Context<'_>) -> Address {
         let discrimination = context
             .data_unchecked::<RestContext>()
             .get()
@@ -52,16 +51,10 @@ impl StakeDelegation {
             .db
             .blockchain_config
             .discrimination;
-        self.0
-            .account_id
-            .to_single_account()
-            .ok_or_else(||
-                // TODO: Multisig address?
-                ApiError::Unimplemented.into())
-            .map(|single| {
-                chain_addr::Address(discrimination, chain_addr::Kind::Account(single.into()))
-            })
-            .map(|addr| Address::from(&ExplorerAddress::New(addr)))
+        Address::from(&ExplorerAddress::from_account_identifier(
+            discrimination,
+            &self.0.account_id,
+        ))
     }
 
     pub async fn pools(&self) -> Vec<Pool> {
@@ -113,7 +106,6 @@ impl PoolRegistration {
 
     /// Reward account
     pub async fn reward_account(&self, context: &Context<'_>) -> Option<Address> {
-        use chain_impl_mockchain::transaction::AccountIdentifier;
         let discrimination = context
             .data_unchecked::<RestContext>()
             .get()
@@ -123,28 +115,10 @@ impl PoolRegistration {
             .blockchain_config
             .discrimination;
 
-        // FIXME: Move this transformation to a point earlier
-
         self.0
             .reward_account
-            .clone()
-            .map(|acc_id| match acc_id {
-                AccountIdentifier::Single(d) => ExplorerAddress::New(chain_addr::Address(
-                    discrimination,
-                    chain_addr::Kind::Account(d.into()),
-                )),
-                AccountIdentifier::Multi(d) => {
-                    let mut bytes = [0u8; 32];
-                    bytes.copy_from_slice(&d.as_ref()[0..32]);
-                    ExplorerAddress::New(chain_addr::Address(
-                        discrimination,
-                        chain_addr::Kind::Multisig(bytes),
-                    ))
-                }
-            })
-            .map(|explorer_address| Address {
-                id: explorer_address,
-            })
+            .as_ref()
+            .map(|acc_id| Address::from(&ExplorerAddress::from_account(discrimination, acc_id)))
     }
 
     // Genesis Praos keys
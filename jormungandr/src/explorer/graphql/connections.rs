@@ -1,5 +1,12 @@
-use async_graphql::{FieldResult, OutputType, SimpleObject};
-use std::convert::TryFrom;
+use super::error::ApiError;
+use super::scalars::IndexCursor;
+use async_graphql::connection::{query, Connection, Edge, EmptyFields};
+use async_graphql::{Context, FieldResult, OutputType, SimpleObject};
+use std::convert::{TryFrom, TryInto};
+
+/// The maximum number of items in a page of a connection, given to the
+/// schema as data when it is configured
+pub struct MaxPageSize(pub usize);
 
 #[derive(SimpleObject)]
 pub struct ConnectionFields<C: OutputType + Send + Sync> {
@@ -19,6 +26,101 @@ pub struct PageMeta {
     pub total_count: u64,
 }
 
+/// check the requested page size against the configured maximum. The
+/// `first` argument is returned, set to the maximum when no page size is
+/// requested at all
+pub fn limit_page_size(
+    context: &Context<'_>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> FieldResult<Option<i32>> {
+    let max = match context.data_opt::<MaxPageSize>() {
+        Some(MaxPageSize(max)) => *max,
+        None => return Ok(first),
+    };
+
+    let too_large = |size: Option<i32>| {
+        size.and_then(|size| usize::try_from(size).ok())
+            .map_or(false, |size| size > max)
+    };
+    if too_large(first) || too_large(last) {
+        return Err(
+            ApiError::ArgumentError(format!("the page size is limited to {} items", max)).into(),
+        );
+    }
+
+    match (first, last) {
+        (None, None) => Ok(Some(i32::try_from(max).unwrap_or(i32::MAX))),
+        _ => Ok(first),
+    }
+}
+
+/// a page of the connection to the given items, the index of an item in the
+/// vector being its cursor
+pub async fn vec_connection<T>(
+    context: &Context<'_>,
+    items: Vec<T>,
+    first: Option<i32>,
+    last: Option<i32>,
+    before: Option<String>,
+    after: Option<String>,
+) -> FieldResult<Connection<IndexCursor, T, ConnectionFields<u64>, EmptyFields>>
+where
+    T: OutputType + Send + Sync,
+{
+    let first = limit_page_size(context, first, last)?;
+    query(
+        after,
+        before,
+        first,
+        last,
+        |after, before, first, last| async move {
+            let boundaries = if !items.is_empty() {
+                PaginationInterval::Inclusive(InclusivePaginationInterval {
+                    lower_bound: 0u32,
+                    upper_bound: (items.len() - 1)
+                        .try_into()
+                        .expect("tried to paginate more than 2^32 elements"),
+                })
+            } else {
+                PaginationInterval::Empty
+            };
+
+            let pagination_arguments = ValidatedPaginationArguments {
+                first,
+                last,
+                before: before.map(u32::try_from).transpose()?,
+                after: after.map(u32::try_from).transpose()?,
+            };
+
+            let (range, page_meta) = compute_interval(boundaries, pagination_arguments)?;
+            let mut connection = Connection::with_additional_fields(
+                page_meta.has_previous_page,
+                page_meta.has_next_page,
+                ConnectionFields {
+                    total_count: page_meta.total_count,
+                },
+            );
+
+            if let PaginationInterval::Inclusive(range) = range {
+                let from = range.lower_bound as usize;
+                let to = range.upper_bound as usize;
+                connection.append(
+                    items
+                        .into_iter()
+                        .enumerate()
+                        .skip(from)
+                        .take(to - from + 1)
+                        .map(|(i, item)| Edge::new(IndexCursor::from(i as u32), item)),
+                );
+            }
+
+            Ok(connection)
+        },
+    )
+    .await
+}
+
 fn compute_range_boundaries(
     total_elements: InclusivePaginationInterval<u64>,
     pagination_arguments: ValidatedPaginationArguments<u64>,
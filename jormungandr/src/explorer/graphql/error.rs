@@ -6,6 +6,8 @@ pub enum ApiError {
     InternalError(String),
     #[error("resource not found {0}")]
     NotFound(String),
+    #[error("resource not available {0}")]
+    Unavailable(String),
     #[error("feature not implemented yet")]
     Unimplemented,
     #[error("invalid argument {0}")]
@@ -14,4 +16,8 @@ pub enum ApiError {
     InvalidCursor(String),
     #[error("invalid address {0}")]
     InvalidAddress(String),
+    #[error(
+        "not supported in standalone mode, {0} is only known to an explorer running in a node"
+    )]
+    NotSupportedInStandaloneMode(String),
 }
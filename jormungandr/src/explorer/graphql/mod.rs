@@ -5,12 +5,14 @@ mod scalars;
 
 use async_graphql::connection::{query, Connection, Edge, EmptyFields};
 use async_graphql::{
-    Context, EmptyMutation, FieldError, FieldResult, Object, SimpleObject, Subscription,
+    Context, EmptyMutation, Enum, FieldError, FieldResult, InputObject, Object, SimpleObject,
+    Subscription,
 };
 
+pub use self::connections::MaxPageSize;
 use self::connections::{
-    compute_interval, ConnectionFields, InclusivePaginationInterval, PaginationInterval,
-    ValidatedPaginationArguments,
+    compute_interval, limit_page_size, vec_connection, ConnectionFields,
+    InclusivePaginationInterval, PaginationInterval, ValidatedPaginationArguments,
 };
 use self::error::ApiError;
 use self::scalars::{
@@ -19,17 +21,24 @@ use self::scalars::{
     VotePlanStatusCount, Weight,
 };
 use super::indexing::{
-    BlockProducer, EpochData, ExplorerAddress, ExplorerBlock, ExplorerTransaction, StakePoolData,
+    BlockProducer, EpochData, ExplorerAddress, ExplorerBlock, ExplorerPoolRewards,
+    ExplorerTransaction, StakePoolData,
 };
 use super::persistent_sequence::PersistentSequence;
 use crate::blockcfg::{self, FragmentId, HeaderHash};
+use crate::events::NodeEvents;
 use crate::explorer::indexing::ExplorerVote;
 use crate::explorer::{ExplorerDb, Settings as ChainSettings};
+use crate::intercom::{self, TransactionMsg};
+use crate::utils::async_msg::MessageBox;
 use cardano_legacy_address::Addr as OldAddress;
 use certificates::*;
+use chain_impl_mockchain::account::DelegationType;
 use chain_impl_mockchain::certificate;
 use chain_impl_mockchain::key::BftLeaderId;
 use chain_impl_mockchain::vote::{EncryptedVote, ProofOfCorrectVote};
+use futures::SinkExt;
+use jormungandr_lib::interfaces::{AccountIdentifier, FragmentLog, FragmentStatus, NodeEvent};
 use std::convert::{TryFrom, TryInto};
 use std::str::FromStr;
 use std::sync::Arc;
@@ -70,14 +79,26 @@ impl Branch {
 
     pub async fn blocks(
         &self,
+        context: &Context<'_>,
         first: Option<i32>,
         last: Option<i32>,
         before: Option<String>,
         after: Option<String>,
     ) -> FieldResult<Connection<IndexCursor, Block, ConnectionFields<BlockCount>, EmptyFields>>
     {
+        let first = limit_page_size(context, first, last)?;
+        let db = &extract_context(&context).await.db;
         let block0 = 0u32;
-        let chain_length = self.state.state().blocks.size();
+        // the blocks moved to the stable store are not in the branch state
+        // anymore, the branch has a block per chain length up to its tip
+        let chain_length = u32::from(
+            self.state
+                .state()
+                .blocks
+                .lookup(&self.id)
+                .unwrap()
+                .chain_length,
+        ) + 1;
 
         query(
             after,
@@ -87,9 +108,7 @@ impl Branch {
             |after, before, first, last| async move {
                 let boundaries = PaginationInterval::Inclusive(InclusivePaginationInterval {
                     lower_bound: block0,
-                    // this try_from cannot fail, as there can't be more than 2^32
-                    // blocks (because ChainLength is u32)
-                    upper_bound: u32::try_from(chain_length).unwrap(),
+                    upper_bound: chain_length,
                 });
 
                 let pagination_arguments = ValidatedPaginationArguments {
@@ -114,7 +133,7 @@ impl Branch {
                     PaginationInterval::Inclusive(range) => {
                         let a = range.lower_bound.into();
                         let b = range.upper_bound.checked_add(1).unwrap().into();
-                        self.state.state().get_block_hash_range(a, b)
+                        db.get_block_hash_range(&self.state, a, b)
                     }
                 };
 
@@ -133,6 +152,7 @@ impl Branch {
 
     async fn transactions_by_address(
         &self,
+        context: &Context<'_>,
         address_bech32: String,
         first: Option<i32>,
         last: Option<i32>,
@@ -141,6 +161,7 @@ impl Branch {
     ) -> FieldResult<
         Connection<IndexCursor, Transaction, ConnectionFields<TransactionCount>, EmptyFields>,
     > {
+        let first = limit_page_size(context, first, last)?;
         let address = chain_addr::AddressReadable::from_string_anyprefix(&address_bech32)
             .map(|adr| ExplorerAddress::New(adr.to_address()))
             .or_else(|_| OldAddress::from_str(&address_bech32).map(ExplorerAddress::Old))
@@ -205,6 +226,7 @@ impl Branch {
 
     pub async fn all_vote_plans(
         &self,
+        context: &Context<'_>,
         first: Option<i32>,
         last: Option<i32>,
         before: Option<String>,
@@ -212,6 +234,7 @@ impl Branch {
     ) -> FieldResult<
         Connection<IndexCursor, VotePlanStatus, ConnectionFields<VotePlanStatusCount>, EmptyFields>,
     > {
+        let first = limit_page_size(context, first, last)?;
         let mut vote_plans = self.state.state().get_vote_plans();
 
         vote_plans.sort_unstable_by_key(|(id, _data)| id.clone());
@@ -285,11 +308,13 @@ impl Branch {
 
     pub async fn all_stake_pools(
         &self,
+        context: &Context<'_>,
         first: Option<i32>,
         last: Option<i32>,
         before: Option<String>,
         after: Option<String>,
     ) -> FieldResult<Connection<IndexCursor, Pool, ConnectionFields<PoolCount>, EmptyFields>> {
+        let first = limit_page_size(context, first, last)?;
         let mut stake_pools = self.state.state().get_stake_pools();
 
         // Although it's probably not a big performance concern
@@ -381,10 +406,22 @@ impl Branch {
     ) -> FieldResult<
         Option<Connection<IndexCursor, Block, ConnectionFields<BlockCount>, EmptyFields>>,
     > {
-        let epoch_data = match extract_context(&context).await.db.get_epoch(epoch.0).await {
+        let first = limit_page_size(context, first, last)?;
+        let db = &extract_context(&context).await.db;
+        let epoch_data = match db.get_epoch(epoch.0).await {
             Some(epoch_data) => epoch_data,
             None => return Ok(None),
         };
+        let epoch_lower_bound = db
+            .get_block(&epoch_data.first_block)
+            .await
+            .map(|block| u32::from(block.chain_length))
+            .expect("Epoch lower bound");
+        let epoch_upper_bound = db
+            .get_block(&epoch_data.last_block)
+            .await
+            .map(|block| u32::from(block.chain_length))
+            .expect("Epoch upper bound");
 
         Some(
             query(
@@ -393,22 +430,6 @@ impl Branch {
                 first,
                 last,
                 |after, before, first, last| async move {
-                    let epoch_lower_bound = self
-                        .state
-                        .state()
-                        .blocks
-                        .lookup(&epoch_data.first_block)
-                        .map(|block| u32::from(block.chain_length))
-                        .expect("Epoch lower bound");
-
-                    let epoch_upper_bound = self
-                        .state
-                        .state()
-                        .blocks
-                        .lookup(&epoch_data.last_block)
-                        .map(|block| u32::from(block.chain_length))
-                        .expect("Epoch upper bound");
-
                     let boundaries = PaginationInterval::Inclusive(InclusivePaginationInterval {
                         lower_bound: 0,
                         upper_bound: epoch_upper_bound.checked_sub(epoch_lower_bound).expect(
@@ -436,10 +457,9 @@ impl Branch {
                         PaginationInterval::Empty => {
                             unreachable!("No blocks found (not even genesis)")
                         }
-                        PaginationInterval::Inclusive(range) => self
-                            .state
-                            .state()
+                        PaginationInterval::Inclusive(range) => db
                             .get_block_hash_range(
+                                &self.state,
                                 (range.lower_bound + epoch_lower_bound).into(),
                                 (range.upper_bound + epoch_lower_bound + 1u32).into(),
                             )
@@ -547,6 +567,7 @@ impl Block {
         before: Option<String>,
         after: Option<String>,
     ) -> FieldResult<Connection<IndexCursor, Transaction, EmptyFields, EmptyFields>> {
+        let first = limit_page_size(context, first, last)?;
         let explorer_block = self
             .fetch_explorer_block(&extract_context(&context).await.db)
             .await?;
@@ -862,14 +883,24 @@ impl Transaction {
         Ok(blocks.iter().map(|b| Block::from(Arc::clone(b))).collect())
     }
 
+    /// The inputs of the transaction, in the order of the fragment. The
+    /// address and the amount of an input of a pending transaction are `null`
+    /// if the spent output is not in the longest branch
     pub async fn inputs(&self, context: &Context<'_>) -> FieldResult<Vec<TransactionInput>> {
         let transaction = self.get_contents(context).await?;
-        Ok(transaction
-            .inputs()
-            .iter()
-            .map(|input| TransactionInput {
-                address: Address::from(&input.address),
-                amount: Value(input.value),
+        let mut resolved_inputs = transaction.inputs().iter();
+        let count = transaction.inputs().len() + transaction.unresolved_inputs.len();
+        Ok((0..count)
+            .map(|position| {
+                let input = if transaction.unresolved_inputs.contains(&position) {
+                    None
+                } else {
+                    resolved_inputs.next()
+                };
+                TransactionInput {
+                    address: input.map(|input| Address::from(&input.address)),
+                    amount: input.map(|input| Value(input.value)),
+                }
             })
             .collect())
     }
@@ -900,8 +931,8 @@ impl Transaction {
 
 #[derive(SimpleObject)]
 pub struct TransactionInput {
-    amount: Value,
-    address: Address,
+    amount: Option<Value>,
+    address: Option<Address>,
 }
 
 #[derive(SimpleObject)]
@@ -924,6 +955,23 @@ impl Address {
 
         Ok(Address { id: addr })
     }
+
+    /// the account address of a single key or multisig account identifier,
+    /// the identifier is not bound to a discrimination so it is taken from
+    /// the blockchain settings
+    async fn from_account_identifier(id: &str, context: &Context<'_>) -> FieldResult<Address> {
+        let id = AccountIdentifier::from_str(id)
+            .map_err(|_| ApiError::InvalidAddress(id.to_string()))?;
+        let discrimination = extract_context(&context)
+            .await
+            .db
+            .blockchain_config
+            .discrimination;
+
+        Ok(Address {
+            id: ExplorerAddress::from_account(discrimination, id.as_ref()),
+        })
+    }
 }
 
 impl From<&ExplorerAddress> for Address {
@@ -949,8 +997,95 @@ impl Address {
         }
     }
 
-    async fn delegation(&self, _context: &Context<'_>) -> FieldResult<Pool> {
-        Err(ApiError::Unimplemented.into())
+    /// The pools the stake of the address is delegated to, `null` if the
+    /// stake is not delegated
+    async fn delegation(&self, context: &Context<'_>) -> Option<Delegation> {
+        extract_context(&context)
+            .await
+            .db
+            .get_delegation(&self.id)
+            .await
+            .and_then(Delegation::from_delegation_type)
+    }
+
+    /// The rewards received by the account holding the stake of the address,
+    /// the oldest first
+    async fn rewards(
+        &self,
+        context: &Context<'_>,
+        first: Option<i32>,
+        last: Option<i32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> FieldResult<Connection<IndexCursor, AccountReward, ConnectionFields<u64>, EmptyFields>>
+    {
+        let rewards: Vec<AccountReward> = extract_context(&context)
+            .await
+            .db
+            .get_account_rewards(&self.id)
+            .await
+            .map(|rewards| {
+                (0..rewards.len())
+                    .filter_map(|i| rewards.get(i))
+                    .map(|reward| {
+                        let (epoch, amount) = reward.as_ref();
+                        AccountReward {
+                            epoch: Epoch::from_epoch_number(*epoch),
+                            amount: Value(*amount),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        vec_connection(context, rewards, first, last, before, after).await
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct AccountReward {
+    /// The epoch at the start of which the reward was distributed
+    epoch: Epoch,
+    amount: Value,
+}
+
+#[derive(SimpleObject)]
+pub struct Delegation {
+    /// The number of parts the stake is split into
+    parts: i32,
+    pools: Vec<PoolDelegation>,
+}
+
+#[derive(SimpleObject)]
+pub struct PoolDelegation {
+    pool: Pool,
+    /// The number of parts of the stake delegated to the pool
+    parts: i32,
+}
+
+impl Delegation {
+    fn from_delegation_type(delegation: DelegationType) -> Option<Delegation> {
+        match delegation {
+            DelegationType::NonDelegated => None,
+            DelegationType::Full(pool_id) => Some(Delegation {
+                parts: 1,
+                pools: vec![PoolDelegation {
+                    pool: Pool::from_valid_id(pool_id),
+                    parts: 1,
+                }],
+            }),
+            DelegationType::Ratio(ratio) => Some(Delegation {
+                parts: ratio.parts().into(),
+                pools: ratio
+                    .pools()
+                    .iter()
+                    .map(|(pool_id, parts)| PoolDelegation {
+                        pool: Pool::from_valid_id(pool_id.clone()),
+                        parts: (*parts).into(),
+                    })
+                    .collect(),
+            }),
+        }
     }
 }
 
@@ -1062,6 +1197,7 @@ impl Pool {
         before: Option<String>,
         after: Option<String>,
     ) -> FieldResult<Connection<IndexCursor, Block, ConnectionFields<BlockCount>>> {
+        let first = limit_page_size(context, first, last)?;
         let blocks = match &self.blocks {
             Some(b) => b.clone(),
             None => extract_context(&context)
@@ -1130,6 +1266,34 @@ impl Pool {
         .await
     }
 
+    /// The rewards of the pool distributed at the start of the epochs in the
+    /// given range, or of all the epochs, the oldest first
+    pub async fn rewards(
+        &self,
+        context: &Context<'_>,
+        epoch_range: Option<EpochRange>,
+        first: Option<i32>,
+        last: Option<i32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> FieldResult<Connection<IndexCursor, PoolRewards, ConnectionFields<u64>, EmptyFields>> {
+        let rewards: Vec<PoolRewards> = extract_context(&context)
+            .await
+            .db
+            .get_pool_rewards(&self.id)
+            .await
+            .into_iter()
+            .filter(|(epoch, _)| {
+                epoch_range
+                    .as_ref()
+                    .map_or(true, |range| range.from.0 <= *epoch && *epoch <= range.to.0)
+            })
+            .map(|(epoch, rewards)| PoolRewards::new(self.id.clone(), epoch, &rewards))
+            .collect();
+
+        vec_connection(context, rewards, first, last, before, after).await
+    }
+
     pub async fn registration(&self, context: &Context<'_>) -> FieldResult<PoolRegistration> {
         match &self.data {
             Some(data) => Ok(data.registration.clone().into()),
@@ -1261,9 +1425,55 @@ impl Epoch {
         EpochNumber(self.id)
     }
 
-    /// Not yet implemented
-    pub async fn stake_distribution(&self) -> FieldResult<StakeDistribution> {
-        Err(ApiError::Unimplemented.into())
+    /// The stake delegated to each pool in the leader election of the epoch.
+    /// The distribution is not available if the ledger state of the epoch was
+    /// not known to the node when the epoch was indexed, e.g. when the node
+    /// was started from a later ledger snapshot
+    pub async fn stake_distribution(
+        &self,
+        context: &Context<'_>,
+    ) -> FieldResult<StakeDistribution> {
+        let distribution = extract_context(&context)
+            .await
+            .db
+            .get_stake_distribution(self.id)
+            .await
+            .map_err(|error| ApiError::Unavailable(error.to_string()))?
+            .ok_or_else(|| ApiError::NotFound(format!("epoch {}", self.id)))?;
+
+        Ok(StakeDistribution {
+            pools: distribution
+                .pools
+                .iter()
+                .map(|(pool_id, stake)| PoolStakeDistribution {
+                    pool: Pool::from_valid_id(pool_id.clone()),
+                    delegated_stake: Value(*stake),
+                })
+                .collect(),
+        })
+    }
+
+    /// The rewards distributed at the start of the epoch, for the blocks of
+    /// the previous epoch
+    pub async fn rewards(&self, context: &Context<'_>) -> Option<EpochRewards> {
+        let rewards = extract_context(&context)
+            .await
+            .db
+            .get_epoch_rewards(self.id)
+            .await?;
+
+        Some(EpochRewards {
+            drawn: Value(rewards.drawn),
+            fees: Value(rewards.fees),
+            treasury: Value(rewards.treasury),
+            pools: rewards
+                .pools
+                .iter()
+                .map(|(pool_id, pool_rewards)| {
+                    PoolRewards::new(pool_id.clone(), self.id, pool_rewards)
+                })
+                .collect(),
+        })
     }
 
     pub async fn first_block(&self, context: &Context<'_>) -> Option<Block> {
@@ -1296,6 +1506,50 @@ pub struct PoolStakeDistribution {
     delegated_stake: Value,
 }
 
+/// A range of epochs, both ends included
+#[derive(InputObject)]
+pub struct EpochRange {
+    from: EpochNumber,
+    to: EpochNumber,
+}
+
+#[derive(SimpleObject)]
+pub struct EpochRewards {
+    /// The total drawn from the reward escrow pot
+    drawn: Value,
+    /// The fees collected in the previous epoch
+    fees: Value,
+    /// The part of the rewards sent to the treasury
+    treasury: Value,
+    pools: Vec<PoolRewards>,
+}
+
+#[derive(SimpleObject)]
+pub struct PoolRewards {
+    pool: Pool,
+    /// The epoch at the start of which the rewards were distributed
+    epoch: Epoch,
+    /// The rewards kept by the pool owners as the pool tax
+    taxed: Value,
+    /// The rewards distributed to the accounts delegating to the pool
+    distributed: Value,
+}
+
+impl PoolRewards {
+    fn new(
+        pool_id: certificate::PoolId,
+        epoch: blockcfg::Epoch,
+        rewards: &ExplorerPoolRewards,
+    ) -> PoolRewards {
+        PoolRewards {
+            pool: Pool::from_valid_id(pool_id),
+            epoch: Epoch::from_epoch_number(epoch),
+            taxed: Value(rewards.taxed),
+            distributed: Value(rewards.distributed),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct VotePayloadPublicStatus {
     choice: i32,
@@ -1479,11 +1733,13 @@ impl VoteProposalStatus {
 
     pub async fn votes(
         &self,
+        context: &Context<'_>,
         first: Option<i32>,
         last: Option<i32>,
         before: Option<String>,
         after: Option<String>,
     ) -> FieldResult<Connection<IndexCursor, VoteStatus, ConnectionFields<u64>, EmptyFields>> {
+        let first = limit_page_size(context, first, last)?;
         query(
             after,
             before,
@@ -1546,6 +1802,124 @@ impl VoteProposalStatus {
     }
 }
 
+/// The status of a fragment in the mempool logs
+#[derive(Clone, Copy, PartialEq, Eq, Enum)]
+pub enum MempoolFragmentStatus {
+    /// The fragment is waiting in the mempool to be included in a block
+    Pending,
+    /// The fragment was rejected and won't be included in a block
+    Rejected,
+    /// The fragment was included in a block
+    InABlock,
+}
+
+impl From<&FragmentStatus> for MempoolFragmentStatus {
+    fn from(status: &FragmentStatus) -> Self {
+        match status {
+            FragmentStatus::Pending => MempoolFragmentStatus::Pending,
+            FragmentStatus::Rejected { .. } => MempoolFragmentStatus::Rejected,
+            FragmentStatus::InABlock { .. } => MempoolFragmentStatus::InABlock,
+        }
+    }
+}
+
+/// A fragment known to the mempool of the node, with its contents while it
+/// is pending or shortly after it was rejected
+pub struct MempoolFragment {
+    log: FragmentLog,
+    contents: Option<blockcfg::Fragment>,
+}
+
+impl MempoolFragment {
+    async fn fetch(
+        context: &EContext,
+        fragment_ids: Option<Vec<FragmentId>>,
+    ) -> FieldResult<Vec<MempoolFragment>> {
+        let mut transaction_task = context.mempool()?;
+        let (reply_handle, reply_future) = intercom::unary_reply();
+        transaction_task
+            .send(TransactionMsg::GetLogsWithContents(
+                fragment_ids,
+                reply_handle,
+            ))
+            .await
+            .map_err(|e| ApiError::InternalError(e.to_string()))?;
+        let logs = reply_future
+            .await
+            .map_err(|e| ApiError::InternalError(e.to_string()))?;
+
+        Ok(logs
+            .into_iter()
+            .map(|(log, contents)| MempoolFragment { log, contents })
+            .collect())
+    }
+}
+
+#[Object]
+impl MempoolFragment {
+    /// The hash that identifies the fragment
+    pub async fn id(&self) -> String {
+        format!("{}", self.log.fragment_id())
+    }
+
+    pub async fn status(&self) -> MempoolFragmentStatus {
+        self.log.status().into()
+    }
+
+    /// The reason of the rejection, if the fragment was rejected
+    pub async fn rejection_reason(&self) -> Option<String> {
+        match self.log.status() {
+            FragmentStatus::Rejected { reason } => Some(reason.clone()),
+            _ => None,
+        }
+    }
+
+    /// The block including the fragment, if it was included in a block
+    pub async fn block(&self) -> Option<Block> {
+        match self.log.status() {
+            FragmentStatus::InABlock { block, .. } => {
+                Some(Block::from_valid_hash(block.into_hash()))
+            }
+            _ => None,
+        }
+    }
+
+    /// When the fragment was received by the node
+    pub async fn received_at(&self) -> String {
+        self.log.received_at().to_string()
+    }
+
+    /// When the status of the fragment last changed
+    pub async fn last_updated_at(&self) -> String {
+        self.log.last_updated_at().to_string()
+    }
+
+    /// The transaction carried by the fragment, `null` if the fragment is not
+    /// a transaction or if its contents are not known to the mempool anymore.
+    /// The inputs spending outputs that are not in the longest branch yet
+    /// have no address and amount.
+    pub async fn transaction(&self, context: &Context<'_>) -> FieldResult<Option<Transaction>> {
+        if let FragmentStatus::InABlock { block, .. } = self.log.status() {
+            return Ok(Some(Transaction {
+                id: self.log.fragment_id().into_hash(),
+                block_hashes: vec![block.into_hash()],
+                contents: None,
+            }));
+        }
+
+        let fragment = match &self.contents {
+            Some(fragment) => fragment,
+            None => return Ok(None),
+        };
+        Ok(extract_context(&context)
+            .await
+            .db
+            .resolve_pending_fragment(fragment)
+            .await
+            .map(Transaction::from_contents))
+    }
+}
+
 pub struct Query;
 
 #[Object]
@@ -1611,6 +1985,14 @@ impl Query {
         Address::from_bech32(&bech32)
     }
 
+    /// get a single key or multisig account from its identifier, given as an
+    /// account address of any discrimination. Multisig declarations are not
+    /// part of the blocks, only the transactions, delegation and rewards of
+    /// the account are known
+    pub async fn account(&self, context: &Context<'_>, id: String) -> FieldResult<Address> {
+        Address::from_account_identifier(&id, context).await
+    }
+
     pub async fn stake_pool(&self, context: &Context<'_>, id: PoolId) -> FieldResult<Pool> {
         Pool::from_string_id(&id.0.to_string(), &extract_context(&context).await.db).await
     }
@@ -1626,6 +2008,46 @@ impl Query {
     ) -> FieldResult<VotePlanStatus> {
         VotePlanStatus::vote_plan_from_id(VotePlanId(id), context).await
     }
+
+    /// The fragments in the mempool logs of the node, optionally only the
+    /// ones with the given status. Not supported by a standalone explorer
+    pub async fn mempool_fragments(
+        &self,
+        context: &Context<'_>,
+        status: Option<MempoolFragmentStatus>,
+        first: Option<i32>,
+        last: Option<i32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> FieldResult<Connection<IndexCursor, MempoolFragment, ConnectionFields<u64>, EmptyFields>>
+    {
+        let fragments = MempoolFragment::fetch(&extract_context(&context).await, None)
+            .await?
+            .into_iter()
+            .filter(|fragment| {
+                status.map_or(true, |status| {
+                    MempoolFragmentStatus::from(fragment.log.status()) == status
+                })
+            })
+            .collect();
+
+        vec_connection(context, fragments, first, last, before, after).await
+    }
+
+    /// A fragment in the mempool logs of the node. Not supported by a
+    /// standalone explorer
+    pub async fn mempool_fragment(
+        &self,
+        context: &Context<'_>,
+        id: String,
+    ) -> FieldResult<MempoolFragment> {
+        let id = FragmentId::from_str(&id)?;
+
+        MempoolFragment::fetch(&extract_context(&context).await, Some(vec![id]))
+            .await?
+            .pop()
+            .ok_or_else(|| ApiError::NotFound(format!("fragment not found: {}", &id)).into())
+    }
 }
 
 pub struct Subscription;
@@ -1648,15 +2070,485 @@ impl Subscription {
                     .map(|(hash, state)| Branch::from_id_and_state(hash, state))
             })
     }
+
+    /// The transactions spending from or sending funds to the given address,
+    /// as they are included in the blocks of the longest branch
+    async fn transactions_by_address(
+        &self,
+        context: &Context<'_>,
+        address_bech32: String,
+    ) -> FieldResult<impl futures::Stream<Item = Transaction>> {
+        use futures::StreamExt;
+        let address = Address::from_bech32(&address_bech32)?.id;
+        Ok(extract_context(&context)
+            .await
+            .db
+            .new_blocks_subscription()
+            .flat_map(move |(blocks, _)| {
+                let mut transactions = Vec::new();
+                for block in blocks {
+                    let mut block_transactions: Vec<_> = block
+                        .transactions
+                        .values()
+                        .filter(|tx| {
+                            tx.inputs().iter().any(|input| input.address == address)
+                                || tx.outputs().iter().any(|output| output.address == address)
+                        })
+                        .collect();
+                    block_transactions.sort_by_key(|tx| tx.offset_in_block);
+                    transactions.extend(block_transactions.into_iter().map(|tx| Transaction {
+                        id: tx.id(),
+                        block_hashes: vec![block.id()],
+                        contents: Some(tx.clone()),
+                    }));
+                }
+                futures::stream::iter(transactions)
+            }))
+    }
+
+    /// The inclusion of the fragment in the longest branch, given when the
+    /// fragment is included in a block, when it is not in the branch anymore
+    /// after a branch switch, and when its block reaches the stability depth.
+    /// The subscription ends once the fragment is stable, or once the mempool
+    /// of the node rejects the fragment or drops it after the fragment TTL
+    /// while it is not in the longest branch. A standalone explorer has no
+    /// mempool, the rejections are then not given.
+    async fn fragment_confirmation(
+        &self,
+        context: &Context<'_>,
+        id: String,
+    ) -> FieldResult<impl futures::Stream<Item = FragmentConfirmation>> {
+        use futures::{future, stream, StreamExt};
+        let fragment_id = FragmentId::from_str(&id)?;
+        let context = extract_context(&context).await;
+        let db = context.db.clone();
+        let stability_depth = db.blockchain_config.epoch_stability_depth;
+
+        // subscribe before looking at the current state, so no update is missed
+        let tips = db
+            .tip_subscription()
+            .filter_map(|tip| future::ready(tip.ok().map(ConfirmationUpdate::Tip)));
+        // a standalone explorer has no mempool, only the blockchain is followed
+        let rejections = stream::iter(
+            context
+                .events
+                .as_ref()
+                .map(|events| tokio_stream::wrappers::BroadcastStream::new(events.subscribe())),
+        )
+        .flatten()
+        .filter_map(move |event| {
+            let rejection = match event {
+                Ok(NodeEvent::FragmentStatus {
+                    fragment_id: id,
+                    status: FragmentStatus::Rejected { reason },
+                }) if id.into_hash() == fragment_id => Some(ConfirmationUpdate::Rejected(reason)),
+                _ => None,
+            };
+            future::ready(rejection)
+        });
+
+        let mut current = vec![ConfirmationUpdate::Tip(db.get_tip().await)];
+        // the fragment may have been rejected before the subscription, there
+        // is no mempool to ask in a standalone explorer
+        if let Ok(mut fragments) = MempoolFragment::fetch(&context, Some(vec![fragment_id])).await {
+            if let Some(FragmentStatus::Rejected { reason }) = fragments
+                .pop()
+                .map(|fragment| fragment.log.status().clone())
+            {
+                current.push(ConfirmationUpdate::Rejected(reason));
+            }
+        }
+
+        let events = stream::iter(current)
+            .chain(stream::select(tips, rejections))
+            .then(move |update| {
+                let db = db.clone();
+                async move {
+                    match update {
+                        ConfirmationUpdate::Tip((hash, tip)) => ConfirmationEvent::Inclusion(
+                            fragment_inclusion(&db, &fragment_id, &hash, &tip, stability_depth)
+                                .await,
+                        ),
+                        ConfirmationUpdate::Rejected(reason) => ConfirmationEvent::Rejected(reason),
+                    }
+                }
+            });
+
+        // the stream ends right after the final status, without waiting for
+        // another update
+        Ok(stream::unfold(
+            (Box::pin(events), ConfirmationTracker::default()),
+            |(mut events, mut tracker)| async move {
+                if tracker.ended() {
+                    return None;
+                }
+                while let Some(event) = events.next().await {
+                    if let Some(confirmation) = tracker.confirmation(event) {
+                        return Some((confirmation, (events, tracker)));
+                    }
+                }
+                None
+            },
+        ))
+    }
+
+    /// The status of the vote plan, each time votes or a tally for it are
+    /// included in the longest branch
+    async fn vote_plan(
+        &self,
+        context: &Context<'_>,
+        id: String,
+    ) -> FieldResult<impl futures::Stream<Item = VotePlanStatus>> {
+        use futures::{future, StreamExt};
+        let vote_plan_id = certificate::VotePlanId::from_str(&id)
+            .map_err(|err| -> FieldError { ApiError::ArgumentError(err.to_string()).into() })?;
+        Ok(extract_context(&context)
+            .await
+            .db
+            .new_blocks_subscription()
+            .filter_map(move |(blocks, tip)| {
+                let updated = blocks
+                    .iter()
+                    .flat_map(|block| block.transactions.values())
+                    .any(|tx| match &tx.certificate {
+                        Some(certificate::Certificate::VoteCast(vote_cast)) => {
+                            vote_cast.vote_plan() == &vote_plan_id
+                        }
+                        Some(certificate::Certificate::VoteTally(vote_tally)) => {
+                            vote_tally.id() == &vote_plan_id
+                        }
+                        Some(certificate::Certificate::EncryptedVoteTally(vote_tally)) => {
+                            vote_tally.id() == &vote_plan_id
+                        }
+                        _ => false,
+                    });
+                let status = if updated {
+                    tip.state()
+                        .vote_plans
+                        .lookup(&vote_plan_id)
+                        .map(|vote_plan| VotePlanStatus::vote_plan_from_data(Arc::clone(vote_plan)))
+                } else {
+                    None
+                };
+                future::ready(status)
+            }))
+    }
+
+    /// The epochs, as their first block is included in the longest branch
+    async fn epochs(&self, context: &Context<'_>) -> impl futures::Stream<Item = Epoch> {
+        use futures::StreamExt;
+        extract_context(&context)
+            .await
+            .db
+            .new_blocks_subscription()
+            .flat_map(|(blocks, tip)| {
+                let epochs: Vec<_> = blocks
+                    .iter()
+                    .filter(|block| {
+                        tip.state()
+                            .epochs
+                            .lookup(&block.date().epoch)
+                            .map_or(false, |epoch| epoch.first_block == block.id())
+                    })
+                    .map(|block| Epoch::from_epoch_number(block.date().epoch))
+                    .collect();
+                futures::stream::iter(epochs)
+            })
+    }
+
+    /// The fragments of the mempool logs, each time their status changes,
+    /// optionally only when they get the given status. Not supported by a
+    /// standalone explorer
+    async fn mempool_fragments(
+        &self,
+        context: &Context<'_>,
+        status: Option<MempoolFragmentStatus>,
+    ) -> FieldResult<impl futures::Stream<Item = MempoolFragment>> {
+        use futures::{future, StreamExt};
+        let context = extract_context(&context).await;
+        let events = context.node_events()?.subscribe();
+        Ok(tokio_stream::wrappers::BroadcastStream::new(events)
+            // the fragments missed by a lagging subscriber are skipped, like
+            // the missed tips
+            .filter_map(move |event| {
+                let fragment_id = match event {
+                    Ok(NodeEvent::FragmentStatus {
+                        fragment_id,
+                        status: fragment_status,
+                    }) if status.map_or(true, |status| {
+                        MempoolFragmentStatus::from(&fragment_status) == status
+                    }) =>
+                    {
+                        Some(fragment_id.into_hash())
+                    }
+                    _ => None,
+                };
+                future::ready(fragment_id)
+            })
+            .then(move |fragment_id| {
+                let context = context.clone();
+                async move {
+                    MempoolFragment::fetch(&context, Some(vec![fragment_id]))
+                        .await
+                        .ok()
+                        .and_then(|mut fragments| fragments.pop())
+                }
+            })
+            .filter_map(future::ready))
+    }
+}
+
+/// The inclusion of a fragment in the longest branch
+#[derive(SimpleObject)]
+pub struct FragmentConfirmation {
+    status: FragmentConfirmationStatus,
+    /// The block including the fragment, `null` if the fragment is not in the
+    /// longest branch
+    block: Option<Block>,
+    /// Whether the block is deeper than the epoch stability depth, the
+    /// fragment cannot be rolled back anymore
+    stable: bool,
+    /// The reason given by the mempool, if the fragment was rejected or expired
+    rejection_reason: Option<String>,
+}
+
+/// The status of a fragment given by the fragment confirmation subscription
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Enum)]
+pub enum FragmentConfirmationStatus {
+    /// The fragment is in a block of the longest branch
+    InABlock,
+    /// The fragment is not in the longest branch anymore after a branch switch
+    RolledBack,
+    /// The block including the fragment is deeper than the epoch stability
+    /// depth, the subscription ends
+    Stable,
+    /// The fragment was rejected by the mempool, the subscription ends
+    Rejected,
+    /// The fragment was pending in the mempool for longer than the fragment
+    /// TTL and was dropped, the subscription ends
+    Expired,
+}
+
+enum ConfirmationUpdate {
+    Tip((HeaderHash, super::multiverse::Ref)),
+    Rejected(String),
+}
+
+enum ConfirmationEvent {
+    /// the block of the longest branch including the fragment, with whether
+    /// it is stable
+    Inclusion(Option<(Arc<ExplorerBlock>, bool)>),
+    Rejected(String),
+}
+
+/// the block of the branch including the fragment, with whether it is deeper
+/// than the stability depth. The blocks are looked up in the database as the
+/// stable ones are not in the branch state anymore
+async fn fragment_inclusion(
+    db: &ExplorerDb,
+    fragment_id: &FragmentId,
+    tip_hash: &HeaderHash,
+    tip: &super::multiverse::Ref,
+    stability_depth: u32,
+) -> Option<(Arc<ExplorerBlock>, bool)> {
+    let tip_block = db.get_block(tip_hash).await?;
+    for block_id in db.find_blocks_by_transaction(fragment_id).await {
+        let block = match db.get_block(&block_id).await {
+            Some(block) => block,
+            None => continue,
+        };
+        let in_branch = db
+            .get_block_hash_range(tip, block.chain_length, block.chain_length.increase())
+            .first()
+            .map_or(false, |(hash, _)| *hash == block_id);
+        if in_branch {
+            let stable = u32::from(tip_block.chain_length)
+                .saturating_sub(u32::from(block.chain_length))
+                >= stability_depth;
+            return Some((block, stable));
+        }
+    }
+    None
+}
+
+/// the changes of the status of a fragment, until it is stable, or rejected
+/// or expired while out of the longest branch
+#[derive(Default)]
+struct ConfirmationTracker {
+    inclusion: Option<(HeaderHash, bool)>,
+    ended: bool,
+}
+
+impl ConfirmationTracker {
+    fn ended(&self) -> bool {
+        self.ended
+    }
+
+    /// the new status if the inclusion of the fragment in the longest branch
+    /// changed, the block is given with whether it is stable
+    fn included(
+        &mut self,
+        inclusion: Option<(HeaderHash, bool)>,
+    ) -> Option<FragmentConfirmationStatus> {
+        if self.inclusion == inclusion {
+            return None;
+        }
+        let was_included = self.inclusion.is_some();
+        self.inclusion = inclusion;
+        match inclusion {
+            Some((_, true)) => {
+                self.ended = true;
+                Some(FragmentConfirmationStatus::Stable)
+            }
+            Some((_, false)) => Some(FragmentConfirmationStatus::InABlock),
+            None if was_included => Some(FragmentConfirmationStatus::RolledBack),
+            None => None,
+        }
+    }
+
+    fn confirmation(&mut self, event: ConfirmationEvent) -> Option<FragmentConfirmation> {
+        match event {
+            ConfirmationEvent::Inclusion(inclusion) => {
+                let status = self.included(
+                    inclusion
+                        .as_ref()
+                        .map(|(block, stable)| (block.id(), *stable)),
+                )?;
+                Some(FragmentConfirmation {
+                    status,
+                    stable: status == FragmentConfirmationStatus::Stable,
+                    block: inclusion.map(|(block, _)| Block::from_contents(block)),
+                    rejection_reason: None,
+                })
+            }
+            ConfirmationEvent::Rejected(reason) => {
+                let status = self.rejected(&reason)?;
+                Some(FragmentConfirmation {
+                    status,
+                    block: None,
+                    stable: false,
+                    rejection_reason: Some(reason),
+                })
+            }
+        }
+    }
+
+    /// the status of a fragment rejected by the mempool, a fragment in the
+    /// longest branch is not affected
+    fn rejected(&mut self, reason: &str) -> Option<FragmentConfirmationStatus> {
+        if self.inclusion.is_some() {
+            return None;
+        }
+        self.ended = true;
+        if reason.starts_with(crate::fragment::EXPIRED_REJECTION_REASON) {
+            Some(FragmentConfirmationStatus::Expired)
+        } else {
+            Some(FragmentConfirmationStatus::Rejected)
+        }
+    }
 }
 
 pub type Schema = async_graphql::Schema<Query, EmptyMutation, Subscription>;
 
+#[derive(Clone)]
 pub struct EContext {
     pub db: ExplorerDb,
     pub settings: ChainSettings,
+    /// the mempool of the node, `None` if the explorer runs in its own process
+    pub transaction_task: Option<MessageBox<TransactionMsg>>,
+    /// the events of the node, `None` if the explorer runs in its own process
+    pub events: Option<NodeEvents>,
+}
+
+impl EContext {
+    fn mempool(&self) -> Result<MessageBox<TransactionMsg>, ApiError> {
+        self.transaction_task.clone().ok_or_else(|| {
+            ApiError::NotSupportedInStandaloneMode("the mempool of the node".to_owned())
+        })
+    }
+
+    fn node_events(&self) -> Result<&NodeEvents, ApiError> {
+        self.events.as_ref().ok_or_else(|| {
+            ApiError::NotSupportedInStandaloneMode("the events of the node".to_owned())
+        })
+    }
 }
 
 async fn extract_context(context: &Context<'_>) -> EContext {
     context.data_unchecked::<RestContext>().get().await.unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(byte: u8) -> HeaderHash {
+        jormungandr_lib::crypto::hash::Hash::from([byte; 32]).into_hash()
+    }
+
+    #[test]
+    fn confirmation_should_end_once_stable() {
+        let mut tracker = ConfirmationTracker::default();
+
+        assert_eq!(tracker.included(None), None);
+        assert_eq!(
+            tracker.included(Some((hash(1), false))),
+            Some(FragmentConfirmationStatus::InABlock)
+        );
+        assert_eq!(tracker.included(Some((hash(1), false))), None);
+        assert!(!tracker.ended());
+        assert_eq!(
+            tracker.included(Some((hash(1), true))),
+            Some(FragmentConfirmationStatus::Stable)
+        );
+        assert!(tracker.ended());
+    }
+
+    #[test]
+    fn confirmation_should_follow_branch_switches() {
+        let mut tracker = ConfirmationTracker::default();
+
+        tracker.included(Some((hash(1), false)));
+        assert_eq!(
+            tracker.included(None),
+            Some(FragmentConfirmationStatus::RolledBack)
+        );
+        assert_eq!(
+            tracker.included(Some((hash(2), false))),
+            Some(FragmentConfirmationStatus::InABlock)
+        );
+        assert!(!tracker.ended());
+    }
+
+    #[test]
+    fn confirmation_should_end_once_rejected() {
+        let mut tracker = ConfirmationTracker::default();
+
+        assert_eq!(
+            tracker.rejected("the fragment is invalid"),
+            Some(FragmentConfirmationStatus::Rejected)
+        );
+        assert!(tracker.ended());
+    }
+
+    #[test]
+    fn confirmation_should_end_once_expired() {
+        let mut tracker = ConfirmationTracker::default();
+
+        let reason = format!("{} 10s", crate::fragment::EXPIRED_REJECTION_REASON);
+        assert_eq!(
+            tracker.rejected(&reason),
+            Some(FragmentConfirmationStatus::Expired)
+        );
+        assert!(tracker.ended());
+    }
+
+    #[test]
+    fn rejection_should_not_affect_an_included_fragment() {
+        let mut tracker = ConfirmationTracker::default();
+
+        tracker.included(Some((hash(1), false)));
+        assert_eq!(tracker.rejected("the fragment is invalid"), None);
+        assert!(!tracker.ended());
+    }
+}
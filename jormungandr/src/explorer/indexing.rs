@@ -1,18 +1,22 @@
 use super::persistent_sequence::PersistentSequence;
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::blockcfg::{Block, BlockDate, ChainLength, Epoch, Fragment, FragmentId, HeaderHash};
 use cardano_legacy_address::Addr as OldAddress;
-use chain_addr::{Address, Discrimination};
+use chain_addr::{Address, Discrimination, Kind};
 use chain_core::property::Block as _;
 use chain_core::property::Fragment as _;
+use chain_impl_mockchain::account::DelegationType;
 use chain_impl_mockchain::block::Proof;
 use chain_impl_mockchain::certificate::{
     Certificate, ExternalProposalId, PoolId, PoolRegistration, PoolRetirement, VotePlanId,
 };
 use chain_impl_mockchain::key::BftLeaderId;
-use chain_impl_mockchain::transaction::{InputEnum, TransactionSlice, Witness};
+use chain_impl_mockchain::transaction::{
+    AccountIdentifier, InputEnum, Payload, Transaction, TransactionSlice,
+    UnspecifiedAccountIdentifier, Witness,
+};
 use chain_impl_mockchain::value::Value;
 use chain_impl_mockchain::vote::{
     Choice, EncryptedVote, Options, PayloadType, ProofOfCorrectVote, Weight,
@@ -33,6 +37,15 @@ pub type StakePool = Hamt<PoolId, StakePoolData>;
 
 pub type VotePlans = Hamt<VotePlanId, ExplorerVotePlan>;
 
+/// the delegation of the accounts, indexed by their account address
+pub type Delegations = Hamt<ExplorerAddress, DelegationType>;
+pub type StakeDistributions = Hamt<Epoch, ExplorerStakeDistribution>;
+
+/// the rewards distributed at the start of each epoch
+pub type EpochRewards = Hamt<Epoch, ExplorerEpochRewards>;
+/// the rewards received by the accounts, indexed by their account address
+pub type AccountRewards = Hamt<ExplorerAddress, PersistentSequence<(Epoch, Value)>>;
+
 #[derive(Clone)]
 pub struct StakePoolData {
     pub registration: PoolRegistration,
@@ -65,6 +78,9 @@ pub enum BlockProducer {
 pub struct ExplorerTransaction {
     pub id: FragmentId,
     pub inputs: Vec<ExplorerInput>,
+    /// the positions in the fragment of the inputs of a pending transaction
+    /// that cannot be resolved, they are not in `inputs`
+    pub unresolved_inputs: Vec<usize>,
     pub outputs: Vec<ExplorerOutput>,
     pub certificate: Option<Certificate>,
     pub offset_in_block: u32,
@@ -96,6 +112,77 @@ pub enum ExplorerAddress {
     Old(OldAddress),
 }
 
+/// the stake delegated to each pool, as used in the leader election of an epoch
+#[derive(Clone)]
+pub struct ExplorerStakeDistribution {
+    pub pools: Vec<(PoolId, Value)>,
+}
+
+/// the rewards distributed at the start of an epoch, for the blocks of the
+/// previous epoch
+#[derive(Clone)]
+pub struct ExplorerEpochRewards {
+    /// the total drawn from the reward escrow pot
+    pub drawn: Value,
+    /// the fees collected in the previous epoch
+    pub fees: Value,
+    /// the part of the rewards sent to the treasury
+    pub treasury: Value,
+    pub pools: BTreeMap<PoolId, ExplorerPoolRewards>,
+}
+
+#[derive(Clone)]
+pub struct ExplorerPoolRewards {
+    /// the rewards kept by the pool owners as the pool tax
+    pub taxed: Value,
+    /// the rewards distributed to the accounts delegating to the pool
+    pub distributed: Value,
+}
+
+impl ExplorerAddress {
+    /// the address of the account given in a stake delegation certificate
+    pub fn from_account_identifier(
+        discrimination: Discrimination,
+        id: &UnspecifiedAccountIdentifier,
+    ) -> ExplorerAddress {
+        let kind = match id.to_single_account() {
+            Some(account) => Kind::Account(account.into()),
+            None => Kind::Multisig(
+                id.to_multi_account()
+                    .as_ref()
+                    .try_into()
+                    .expect("multisig identifier size doesn't match address kind"),
+            ),
+        };
+        ExplorerAddress::New(Address(discrimination, kind))
+    }
+
+    /// the address of a single key or a multisig account
+    pub fn from_account(discrimination: Discrimination, id: &AccountIdentifier) -> ExplorerAddress {
+        let kind = match id {
+            AccountIdentifier::Single(account) => Kind::Account(account.clone().into()),
+            AccountIdentifier::Multi(account) => Kind::Multisig(account.clone().into()),
+        };
+        ExplorerAddress::New(Address(discrimination, kind))
+    }
+
+    /// the address of the account holding the stake of this address, the
+    /// group addresses delegate with the account of their group key
+    pub fn delegation_account(&self) -> Option<ExplorerAddress> {
+        match self {
+            ExplorerAddress::New(Address(discrimination, kind)) => match kind {
+                Kind::Account(_) | Kind::Multisig(_) => Some(self.clone()),
+                Kind::Group(_, account) => Some(ExplorerAddress::New(Address(
+                    *discrimination,
+                    Kind::Account(account.clone()),
+                ))),
+                Kind::Single(_) | Kind::Script(_) => None,
+            },
+            ExplorerAddress::Old(_) => None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ExplorerVotePlan {
     pub id: VotePlanId,
@@ -140,6 +227,12 @@ pub struct ExplorerBlockBuildingContext<'a> {
     pub discrimination: Discrimination,
     pub prev_transactions: &'a Transactions,
     pub prev_blocks: &'a Blocks,
+    /// the transactions of the blocks moved to the stable store that are
+    /// spent by the utxo inputs, see `spent_transactions`
+    pub stable_transactions: &'a HashMap<FragmentId, ExplorerTransaction>,
+    /// the transactions are not in a block yet and may be invalid, so the
+    /// inputs that cannot be resolved are left out instead of being a bug
+    pub pending: bool,
 }
 
 impl ExplorerBlock {
@@ -158,127 +251,12 @@ impl ExplorerBlock {
             |mut current_block_txs, (offset, fragment)| {
                 let fragment_id = fragment.id();
                 let offset: u32 = offset.try_into().unwrap();
-                let metx = match fragment {
-                    Fragment::Transaction(tx) => {
-                        let tx = tx.as_slice();
-                        Some(ExplorerTransaction::from(
-                            &context,
-                            &fragment_id,
-                            &tx,
-                            None,
-                            offset,
-                            &current_block_txs,
-                        ))
-                    }
-                    Fragment::OwnerStakeDelegation(tx) => {
-                        let tx = tx.as_slice();
-                        Some(ExplorerTransaction::from(
-                            &context,
-                            &fragment_id,
-                            &tx,
-                            Some(Certificate::OwnerStakeDelegation(
-                                tx.payload().into_payload(),
-                            )),
-                            offset,
-                            &current_block_txs,
-                        ))
-                    }
-                    Fragment::StakeDelegation(tx) => {
-                        let tx = tx.as_slice();
-                        Some(ExplorerTransaction::from(
-                            &context,
-                            &fragment_id,
-                            &tx,
-                            Some(Certificate::StakeDelegation(tx.payload().into_payload())),
-                            offset,
-                            &current_block_txs,
-                        ))
-                    }
-                    Fragment::PoolRegistration(tx) => {
-                        let tx = tx.as_slice();
-                        Some(ExplorerTransaction::from(
-                            &context,
-                            &fragment_id,
-                            &tx,
-                            Some(Certificate::PoolRegistration(tx.payload().into_payload())),
-                            offset,
-                            &current_block_txs,
-                        ))
-                    }
-                    Fragment::PoolRetirement(tx) => {
-                        let tx = tx.as_slice();
-                        Some(ExplorerTransaction::from(
-                            &context,
-                            &fragment_id,
-                            &tx,
-                            Some(Certificate::PoolRetirement(tx.payload().into_payload())),
-                            offset,
-                            &current_block_txs,
-                        ))
-                    }
-                    Fragment::PoolUpdate(tx) => {
-                        let tx = tx.as_slice();
-                        Some(ExplorerTransaction::from(
-                            &context,
-                            &fragment_id,
-                            &tx,
-                            Some(Certificate::PoolUpdate(tx.payload().into_payload())),
-                            offset,
-                            &current_block_txs,
-                        ))
-                    }
-                    Fragment::VotePlan(tx) => {
-                        let tx = tx.as_slice();
-                        Some(ExplorerTransaction::from(
-                            &context,
-                            &fragment_id,
-                            &tx,
-                            Some(Certificate::VotePlan(tx.payload().into_payload())),
-                            offset,
-                            &current_block_txs,
-                        ))
-                    }
-                    Fragment::VoteCast(tx) => {
-                        let tx = tx.as_slice();
-                        Some(ExplorerTransaction::from(
-                            &context,
-                            &fragment_id,
-                            &tx,
-                            Some(Certificate::VoteCast(tx.payload().into_payload())),
-                            offset,
-                            &current_block_txs,
-                        ))
-                    }
-                    Fragment::VoteTally(tx) => {
-                        let tx = tx.as_slice();
-                        Some(ExplorerTransaction::from(
-                            &context,
-                            &fragment_id,
-                            &tx,
-                            Some(Certificate::VoteTally(tx.payload().into_payload())),
-                            offset,
-                            &current_block_txs,
-                        ))
-                    }
-                    Fragment::OldUtxoDeclaration(decl) => {
-                        let outputs = decl
-                            .addrs
-                            .iter()
-                            .map(|(old_address, value)| ExplorerOutput {
-                                address: ExplorerAddress::Old(old_address.clone()),
-                                value: *value,
-                            })
-                            .collect();
-                        Some(ExplorerTransaction {
-                            id: fragment_id,
-                            inputs: vec![],
-                            outputs,
-                            certificate: None,
-                            offset_in_block: offset,
-                        })
-                    }
-                    _ => None,
-                };
+                let metx = ExplorerTransaction::from_fragment(
+                    &context,
+                    fragment,
+                    offset,
+                    &current_block_txs,
+                );
 
                 if let Some(etx) = metx {
                     current_block_txs.insert(fragment_id, etx);
@@ -341,6 +319,50 @@ impl ExplorerBlock {
     }
 }
 
+/// the ids of the transactions spent by the utxo inputs of the fragment
+pub fn spent_transactions(fragment: &Fragment) -> Vec<FragmentId> {
+    fn utxo_inputs<P: Payload>(tx: &Transaction<P>) -> Vec<FragmentId> {
+        tx.as_slice()
+            .inputs()
+            .iter()
+            .filter_map(|input| match input.to_enum() {
+                InputEnum::UtxoInput(utxo_pointer) => Some(utxo_pointer.transaction_id),
+                InputEnum::AccountInput(_, _) => None,
+            })
+            .collect()
+    }
+
+    match fragment {
+        Fragment::Transaction(tx) => utxo_inputs(tx),
+        Fragment::OwnerStakeDelegation(tx) => utxo_inputs(tx),
+        Fragment::StakeDelegation(tx) => utxo_inputs(tx),
+        Fragment::PoolRegistration(tx) => utxo_inputs(tx),
+        Fragment::PoolRetirement(tx) => utxo_inputs(tx),
+        Fragment::PoolUpdate(tx) => utxo_inputs(tx),
+        Fragment::VotePlan(tx) => utxo_inputs(tx),
+        Fragment::VoteCast(tx) => utxo_inputs(tx),
+        Fragment::VoteTally(tx) => utxo_inputs(tx),
+        Fragment::EncryptedVoteTally(tx) => utxo_inputs(tx),
+        Fragment::Initial(_)
+        | Fragment::OldUtxoDeclaration(_)
+        | Fragment::UpdateProposal(_)
+        | Fragment::UpdateVote(_) => Vec::new(),
+    }
+}
+
+/// the data of a validated input, which may be missing from an input of a
+/// pending transaction only
+fn validated<T>(
+    context: &ExplorerBlockBuildingContext,
+    value: Option<T>,
+    expected: &str,
+) -> Option<T> {
+    if value.is_none() && !context.pending {
+        panic!("{}", expected);
+    }
+    value
+}
+
 impl ExplorerTransaction {
     /// Map the given AuthenticatedTransaction to the ExplorerTransaction API representation
     /// type.
@@ -369,26 +391,24 @@ impl ExplorerTransaction {
             })
             .collect();
 
-        let new_inputs = inputs
+        let resolved_inputs: Vec<Option<ExplorerInput>> = inputs
             .map(|i| i.to_enum())
             .zip(witnesses)
-            .filter_map(|input_with_witness| match input_with_witness {
+            .map(|input_with_witness| match input_with_witness {
                 (InputEnum::AccountInput(id, value), Witness::Account(_)) => {
                     let kind = chain_addr::Kind::Account(
-                        id.to_single_account()
-                            .expect("the input to be validated")
+                        validated(context, id.to_single_account(), "the input to be validated")?
                             .into(),
                     );
                     let address = ExplorerAddress::New(Address(context.discrimination, kind));
                     Some(ExplorerInput { address, value })
                 }
                 (InputEnum::AccountInput(id, value), Witness::Multisig(_)) => {
-                    let kind = chain_addr::Kind::Multisig(
-                        id.to_multi_account()
-                            .as_ref()
-                            .try_into()
-                            .expect("multisig identifier size doesn't match address kind"),
-                    );
+                    let kind = chain_addr::Kind::Multisig(validated(
+                        context,
+                        id.to_multi_account().as_ref().try_into().ok(),
+                        "multisig identifier size doesn't match address kind",
+                    )?);
                     let address = ExplorerAddress::New(Address(context.discrimination, kind));
                     Some(ExplorerInput { address, value })
                 }
@@ -403,33 +423,175 @@ impl ExplorerTransaction {
                             context
                                 .prev_blocks
                                 .lookup(&block_id)
-                                .map(|block| &block.transactions[&tx].outputs[index as usize])
+                                .and_then(|block| block.transactions.get(&tx))
                         })
-                        .or_else(|| {
-                            transactions_in_current_block
-                                .get(&tx)
-                                .map(|fragment| &fragment.outputs[index as usize])
-                        })
-                        .expect("transaction not found for utxo input");
-
-                    Some(ExplorerInput {
-                        address: output.address.clone(),
-                        value: output.value,
-                    })
+                        .or_else(|| context.stable_transactions.get(&tx))
+                        .or_else(|| transactions_in_current_block.get(&tx))
+                        .and_then(|transaction| transaction.outputs.get(index as usize));
+
+                    match output {
+                        Some(output) => Some(ExplorerInput {
+                            address: output.address.clone(),
+                            value: output.value,
+                        }),
+                        None if context.pending => None,
+                        None => panic!("output not found for utxo input"),
+                    }
                 }
                 _ => None,
             })
             .collect();
+        let unresolved_inputs = resolved_inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, input)| input.is_none())
+            .map(|(position, _)| position)
+            .collect();
+        let new_inputs = resolved_inputs.into_iter().flatten().collect();
 
         ExplorerTransaction {
             id: *id,
             inputs: new_inputs,
+            unresolved_inputs,
             outputs: new_outputs,
             certificate,
             offset_in_block,
         }
     }
 
+    /// Map the given fragment to the ExplorerTransaction API representation type,
+    /// `None` if the fragment is not a transaction
+    pub fn from_fragment(
+        context: &ExplorerBlockBuildingContext,
+        fragment: &Fragment,
+        offset: u32,
+        transactions_in_current_block: &HashMap<FragmentId, ExplorerTransaction>,
+    ) -> Option<ExplorerTransaction> {
+        let fragment_id = fragment.id();
+        match fragment {
+            Fragment::Transaction(tx) => {
+                let tx = tx.as_slice();
+                Some(ExplorerTransaction::from(
+                    context,
+                    &fragment_id,
+                    &tx,
+                    None,
+                    offset,
+                    transactions_in_current_block,
+                ))
+            }
+            Fragment::OwnerStakeDelegation(tx) => {
+                let tx = tx.as_slice();
+                Some(ExplorerTransaction::from(
+                    context,
+                    &fragment_id,
+                    &tx,
+                    Some(Certificate::OwnerStakeDelegation(
+                        tx.payload().into_payload(),
+                    )),
+                    offset,
+                    transactions_in_current_block,
+                ))
+            }
+            Fragment::StakeDelegation(tx) => {
+                let tx = tx.as_slice();
+                Some(ExplorerTransaction::from(
+                    context,
+                    &fragment_id,
+                    &tx,
+                    Some(Certificate::StakeDelegation(tx.payload().into_payload())),
+                    offset,
+                    transactions_in_current_block,
+                ))
+            }
+            Fragment::PoolRegistration(tx) => {
+                let tx = tx.as_slice();
+                Some(ExplorerTransaction::from(
+                    context,
+                    &fragment_id,
+                    &tx,
+                    Some(Certificate::PoolRegistration(tx.payload().into_payload())),
+                    offset,
+                    transactions_in_current_block,
+                ))
+            }
+            Fragment::PoolRetirement(tx) => {
+                let tx = tx.as_slice();
+                Some(ExplorerTransaction::from(
+                    context,
+                    &fragment_id,
+                    &tx,
+                    Some(Certificate::PoolRetirement(tx.payload().into_payload())),
+                    offset,
+                    transactions_in_current_block,
+                ))
+            }
+            Fragment::PoolUpdate(tx) => {
+                let tx = tx.as_slice();
+                Some(ExplorerTransaction::from(
+                    context,
+                    &fragment_id,
+                    &tx,
+                    Some(Certificate::PoolUpdate(tx.payload().into_payload())),
+                    offset,
+                    transactions_in_current_block,
+                ))
+            }
+            Fragment::VotePlan(tx) => {
+                let tx = tx.as_slice();
+                Some(ExplorerTransaction::from(
+                    context,
+                    &fragment_id,
+                    &tx,
+                    Some(Certificate::VotePlan(tx.payload().into_payload())),
+                    offset,
+                    transactions_in_current_block,
+                ))
+            }
+            Fragment::VoteCast(tx) => {
+                let tx = tx.as_slice();
+                Some(ExplorerTransaction::from(
+                    context,
+                    &fragment_id,
+                    &tx,
+                    Some(Certificate::VoteCast(tx.payload().into_payload())),
+                    offset,
+                    transactions_in_current_block,
+                ))
+            }
+            Fragment::VoteTally(tx) => {
+                let tx = tx.as_slice();
+                Some(ExplorerTransaction::from(
+                    context,
+                    &fragment_id,
+                    &tx,
+                    Some(Certificate::VoteTally(tx.payload().into_payload())),
+                    offset,
+                    transactions_in_current_block,
+                ))
+            }
+            Fragment::OldUtxoDeclaration(decl) => {
+                let outputs = decl
+                    .addrs
+                    .iter()
+                    .map(|(old_address, value)| ExplorerOutput {
+                        address: ExplorerAddress::Old(old_address.clone()),
+                        value: *value,
+                    })
+                    .collect();
+                Some(ExplorerTransaction {
+                    id: fragment_id,
+                    inputs: vec![],
+                    unresolved_inputs: vec![],
+                    outputs,
+                    certificate: None,
+                    offset_in_block: offset,
+                })
+            }
+            _ => None,
+        }
+    }
+
     pub fn id(&self) -> FragmentId {
         self.id
     }
@@ -442,3 +604,117 @@ impl ExplorerTransaction {
         &self.outputs
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_crypto::{Ed25519, SecretKey};
+    use chain_impl_mockchain::transaction::{Input, TxBuilder};
+
+    #[test]
+    fn pending_transaction_with_unknown_output_should_keep_the_input_unresolved() {
+        let spent_id = jormungandr_lib::crypto::hash::Hash::from([7; 32]).into_hash();
+        let spent = ExplorerTransaction {
+            id: spent_id,
+            inputs: Vec::new(),
+            unresolved_inputs: Vec::new(),
+            outputs: Vec::new(),
+            certificate: None,
+            offset_in_block: 0,
+        };
+        let transactions_in_current_block = vec![(spent_id, spent)].into_iter().collect();
+
+        // the input spends an output that the transaction does not have
+        let builder = TxBuilder::new()
+            .set_nopayload()
+            .set_ios(&[Input::new(3, Value(1), [7; 32])], &[]);
+        let sign_data_hash = builder.get_auth_data_for_witness().hash();
+        let key = SecretKey::<Ed25519>::generate(rand::rngs::OsRng);
+        let witness = Witness::new_utxo(&HeaderHash::zero_hash(), &sign_data_hash, |data| {
+            key.sign(data)
+        });
+        let fragment = Fragment::Transaction(
+            builder
+                .set_witnesses_unchecked(&[witness])
+                .set_payload_auth(&()),
+        );
+
+        let context = ExplorerBlockBuildingContext {
+            discrimination: Discrimination::Test,
+            prev_transactions: &Transactions::new(),
+            prev_blocks: &Blocks::new(),
+            stable_transactions: &HashMap::new(),
+            pending: true,
+        };
+        let transaction = ExplorerTransaction::from_fragment(
+            &context,
+            &fragment,
+            0,
+            &transactions_in_current_block,
+        )
+        .unwrap();
+
+        assert_eq!(transaction.id, fragment.id());
+        assert!(transaction.inputs.is_empty());
+        assert_eq!(transaction.unresolved_inputs, vec![0]);
+    }
+
+    #[test]
+    fn pending_transaction_should_keep_the_positions_of_the_unresolved_inputs() {
+        let key = SecretKey::<Ed25519>::generate(rand::rngs::OsRng);
+        let spent_id = jormungandr_lib::crypto::hash::Hash::from([7; 32]).into_hash();
+        let spent = ExplorerTransaction {
+            id: spent_id,
+            inputs: Vec::new(),
+            unresolved_inputs: Vec::new(),
+            outputs: vec![ExplorerOutput {
+                address: ExplorerAddress::New(Address(
+                    Discrimination::Test,
+                    Kind::Single(key.to_public()),
+                )),
+                value: Value(2),
+            }],
+            certificate: None,
+            offset_in_block: 0,
+        };
+        let transactions_in_current_block = vec![(spent_id, spent)].into_iter().collect();
+
+        // the first input spends an unknown transaction, the second one the
+        // output of the spent transaction
+        let builder = TxBuilder::new().set_nopayload().set_ios(
+            &[
+                Input::new(0, Value(1), [8; 32]),
+                Input::new(0, Value(2), [7; 32]),
+            ],
+            &[],
+        );
+        let sign_data_hash = builder.get_auth_data_for_witness().hash();
+        let witness = Witness::new_utxo(&HeaderHash::zero_hash(), &sign_data_hash, |data| {
+            key.sign(data)
+        });
+        let fragment = Fragment::Transaction(
+            builder
+                .set_witnesses_unchecked(&[witness.clone(), witness])
+                .set_payload_auth(&()),
+        );
+
+        let context = ExplorerBlockBuildingContext {
+            discrimination: Discrimination::Test,
+            prev_transactions: &Transactions::new(),
+            prev_blocks: &Blocks::new(),
+            stable_transactions: &HashMap::new(),
+            pending: true,
+        };
+        let transaction = ExplorerTransaction::from_fragment(
+            &context,
+            &fragment,
+            0,
+            &transactions_in_current_block,
+        )
+        .unwrap();
+
+        assert_eq!(transaction.unresolved_inputs, vec![0]);
+        assert_eq!(transaction.inputs.len(), 1);
+        assert_eq!(transaction.inputs[0].value, Value(2));
+    }
+}
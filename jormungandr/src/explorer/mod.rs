@@ -3,33 +3,40 @@ pub mod graphql;
 mod indexing;
 mod multiverse;
 mod persistent_sequence;
+mod stable_store;
 
 use self::error::{ExplorerError as Error, Result};
 use self::graphql::EContext;
 use self::indexing::{
-    Addresses, Blocks, ChainLengths, EpochData, Epochs, ExplorerAddress, ExplorerBlock,
-    ExplorerVotePlan, ExplorerVoteProposal, ExplorerVoteTally, StakePool, StakePoolBlocks,
-    StakePoolData, Transactions, VotePlans,
+    AccountRewards, Addresses, Blocks, ChainLengths, Delegations, EpochData, EpochRewards, Epochs,
+    ExplorerAddress, ExplorerBlock, ExplorerEpochRewards, ExplorerPoolRewards,
+    ExplorerStakeDistribution, ExplorerVotePlan, ExplorerVoteProposal, ExplorerVoteTally, Hamt,
+    StakeDistributions, StakePool, StakePoolBlocks, StakePoolData, Transactions, VotePlans,
 };
 use self::persistent_sequence::PersistentSequence;
+use self::stable_store::ArchivedEpoch;
+pub use self::stable_store::StableStore;
 use tracing::{span, Level};
 use tracing_futures::Instrument;
 
 use crate::blockcfg::{
     Block, ChainLength, ConfigParam, ConfigParams, ConsensusVersion, Epoch, Fragment, FragmentId,
-    HeaderHash,
+    HeaderHash, Value,
 };
 use crate::blockchain::{self, Blockchain, MAIN_BRANCH_TAG};
+use crate::events::NodeEvents;
 use crate::explorer::indexing::ExplorerVote;
-use crate::intercom::ExplorerMsg;
-use crate::utils::async_msg::MessageQueue;
+use crate::intercom::{ExplorerMsg, TransactionMsg};
+use crate::utils::async_msg::{MessageBox, MessageQueue};
 use crate::utils::task::TokioServiceInfo;
 use chain_addr::Discrimination;
 use chain_core::property::Block as _;
+use chain_impl_mockchain::account::DelegationType;
 use chain_impl_mockchain::certificate::{Certificate, PoolId, VotePlanId};
 use chain_impl_mockchain::fee::LinearFee;
 use futures::prelude::*;
 use multiverse::Multiverse;
+use std::collections::HashMap;
 use std::convert::Infallible;
 use std::sync::{
     atomic::{AtomicU32, Ordering},
@@ -65,6 +72,13 @@ pub struct ExplorerDb {
 #[derive(Clone)]
 pub struct StableIndex {
     confirmed_block_chain_length: Arc<AtomicU32>,
+    /// the on-disk store of the confirmed states, if the node has a storage
+    store: Option<StableStore>,
+    /// the epoch of the last confirmed state written in the store
+    stored_epoch: Arc<Mutex<Option<Epoch>>>,
+    /// the stake distributions and the rewards of the epochs before this one
+    /// are archived in the store and dropped from the states in memory
+    archived_epochs: Arc<AtomicU32>,
 }
 
 #[derive(Clone)]
@@ -90,6 +104,10 @@ pub(self) struct State {
     stake_pool_data: StakePool,
     stake_pool_blocks: StakePoolBlocks,
     vote_plans: VotePlans,
+    delegations: Delegations,
+    stake_distributions: StakeDistributions,
+    epoch_rewards: EpochRewards,
+    account_rewards: AccountRewards,
 }
 
 #[derive(Clone)]
@@ -106,9 +124,15 @@ impl Explorer {
         Explorer { db }
     }
 
-    pub fn context(&self) -> EContext {
+    pub fn context(
+        &self,
+        transaction_task: Option<MessageBox<TransactionMsg>>,
+        events: Option<NodeEvents>,
+    ) -> EContext {
         EContext {
             db: self.db.clone(),
+            transaction_task,
+            events,
             settings: Settings {
                 // Hardcoded bech32 prefix
                 address_bech32_prefix: "addr".to_owned(),
@@ -176,11 +200,14 @@ impl Explorer {
 impl ExplorerDb {
     /// Apply all the blocks in the [block0, MAIN_BRANCH_TAG], also extract the static
     /// Blockchain settings from the Block0 (Discrimination)
+    /// When a confirmed state of the main branch is found in the stable store,
+    /// only the blocks following it are applied.
     /// This function is only called once on the node's bootstrap phase
     pub async fn bootstrap(
         block0: Block,
         blockchain: &Blockchain,
         blockchain_tip: blockchain::Tip,
+        stable_store: Option<StableStore>,
     ) -> Result<Self> {
         let blockchain_config = BlockchainConfig::from_config_params(
             block0
@@ -194,43 +221,8 @@ impl ExplorerDb {
                 .expect("the Initial fragment to be present in the genesis block"),
         );
 
-        let block = ExplorerBlock::resolve_from(
-            &block0,
-            indexing::ExplorerBlockBuildingContext {
-                discrimination: blockchain_config.discrimination,
-                prev_transactions: &Transactions::new(),
-                prev_blocks: &Blocks::new(),
-            },
-        );
-
-        let blocks = apply_block_to_blocks(Blocks::new(), &block)?;
-        let epochs = apply_block_to_epochs(Epochs::new(), &block);
-        let chain_lengths = apply_block_to_chain_lengths(ChainLengths::new(), &block)?;
-        let transactions = apply_block_to_transactions(Transactions::new(), &block)?;
-        let addresses = apply_block_to_addresses(Addresses::new(), &block);
-        let (stake_pool_data, stake_pool_blocks) =
-            apply_block_to_stake_pools(StakePool::new(), StakePoolBlocks::new(), &block);
-        let vote_plans = apply_block_to_vote_plans(VotePlans::new(), &blockchain_tip, &block);
-
-        let initial_state = State {
-            transactions,
-            blocks,
-            addresses,
-            epochs,
-            chain_lengths,
-            stake_pool_data,
-            stake_pool_blocks,
-            vote_plans,
-        };
-
-        let block0_id = block0.id();
-        let (_, multiverse) = Multiverse::new(block0.chain_length(), block0_id, initial_state);
-
-        let block0_id = block0.id();
-
-        let maybe_head = blockchain.storage().get_tag(MAIN_BRANCH_TAG)?;
-        let (stream, hash) = match maybe_head {
-            Some(head) => (blockchain.storage().stream_from_to(block0_id, head)?, head),
+        let head = match blockchain.storage().get_tag(MAIN_BRANCH_TAG)? {
+            Some(head) => head,
             None => {
                 return Err(Error::BootstrapError(
                     "Couldn't read the HEAD tag from storage".to_owned(),
@@ -238,16 +230,101 @@ impl ExplorerDb {
             }
         };
 
+        let stable_state = stable_store
+            .as_ref()
+            .and_then(|store| load_stable_state(store, blockchain, head));
+
+        let (multiverse, from, stable_chain_length, stored_epoch) = match stable_state {
+            Some((chain_length, block_id, state)) => {
+                // the blocks of the loaded state are all in the stable store,
+                // the last epoch is the one of its block
+                let epoch = state.epochs.iter().map(|(epoch, _)| *epoch).max();
+                let (_, multiverse) = Multiverse::new(chain_length, block_id, state);
+                (multiverse, block_id, Some(chain_length), epoch)
+            }
+            None => {
+                if let Some(store) = &stable_store {
+                    store.clear_blocks()?;
+                }
+
+                let block = ExplorerBlock::resolve_from(
+                    &block0,
+                    indexing::ExplorerBlockBuildingContext {
+                        discrimination: blockchain_config.discrimination,
+                        prev_transactions: &Transactions::new(),
+                        prev_blocks: &Blocks::new(),
+                        stable_transactions: &HashMap::new(),
+                        pending: false,
+                    },
+                );
+
+                let blocks = apply_block_to_blocks(Blocks::new(), &block)?;
+                let epochs = apply_block_to_epochs(Epochs::new(), &block);
+                let chain_lengths = apply_block_to_chain_lengths(ChainLengths::new(), &block)?;
+                let transactions = apply_block_to_transactions(Transactions::new(), &block)?;
+                let addresses = apply_block_to_addresses(Addresses::new(), &block);
+                let (stake_pool_data, stake_pool_blocks) =
+                    apply_block_to_stake_pools(StakePool::new(), StakePoolBlocks::new(), &block);
+                let vote_plans =
+                    apply_block_to_vote_plans(VotePlans::new(), &blockchain_tip, &block);
+                let delegations = apply_block_to_delegations(
+                    Delegations::new(),
+                    blockchain_config.discrimination,
+                    &block,
+                );
+                let epoch_ref = epoch_ref(blockchain, &blockchain_tip, &block).await;
+                let stake_distributions = apply_block_to_stake_distributions(
+                    StakeDistributions::new(),
+                    &block,
+                    epoch_ref.as_deref().map(epoch_stake_distribution),
+                );
+                let (epoch_rewards, account_rewards) = apply_block_to_rewards(
+                    EpochRewards::new(),
+                    AccountRewards::new(),
+                    &block,
+                    epoch_ref.as_deref().and_then(|epoch_ref| {
+                        rewards_distribution(epoch_ref, blockchain_config.discrimination)
+                    }),
+                );
+
+                let initial_state = State {
+                    transactions,
+                    blocks,
+                    addresses,
+                    epochs,
+                    chain_lengths,
+                    stake_pool_data,
+                    stake_pool_blocks,
+                    vote_plans,
+                    delegations,
+                    stake_distributions,
+                    epoch_rewards,
+                    account_rewards,
+                };
+
+                let block0_id = block0.id();
+                let (_, multiverse) =
+                    Multiverse::new(block0.chain_length(), block0_id, initial_state);
+                (multiverse, block0_id, None, None)
+            }
+        };
+
+        let stream = blockchain.storage().stream_from_to(from, head)?;
+
         let (tx, _) = broadcast::channel(10);
 
         let bootstraped_db = ExplorerDb {
             multiverse,
-            longest_chain_tip: Tip::new(hash),
+            longest_chain_tip: Tip::new(head),
             blockchain_config,
             blockchain: blockchain.clone(),
             blockchain_tip,
             stable_store: StableIndex {
                 confirmed_block_chain_length: Arc::new(AtomicU32::default()),
+                store: stable_store,
+                stored_epoch: Arc::new(Mutex::new(stored_epoch)),
+                // the loaded state does not hold the epochs archived with it
+                archived_epochs: Arc::new(AtomicU32::new(stored_epoch.unwrap_or(0))),
             },
             tip_broadcast: tx,
         };
@@ -260,7 +337,7 @@ impl ExplorerDb {
             })
             .await?;
 
-        for branch in blockchain.branches().branches().await.iter() {
+        'branches: for branch in blockchain.branches().branches().await.iter() {
             let mut hash = branch.hash();
             let mut blocks = vec![];
             loop {
@@ -270,6 +347,15 @@ impl ExplorerDb {
                 let block = blockchain.storage().get(hash)?.ok_or_else(|| {
                     Error::BootstrapError(format!("couldn't get block {} from the storage", hash))
                 })?;
+                if stable_chain_length.map_or(false, |length| block.chain_length() <= length) {
+                    // the branch forks off the main branch before the loaded
+                    // confirmed state, it cannot become the main branch anymore
+                    tracing::debug!(
+                        "not indexing branch {} forking before the confirmed state",
+                        branch.hash()
+                    );
+                    continue 'branches;
+                }
                 hash = block.header.block_parent_hash();
                 blocks.push(block);
             }
@@ -297,6 +383,10 @@ impl ExplorerDb {
             .get_ref(&previous_block)
             .await
             .ok_or_else(|| Error::AncestorNotFound(block.id()))?;
+        let mut state = previous_state.state().clone();
+        if let Some(store) = &self.stable_store.store {
+            drop_stored_blocks(&mut state, store);
+        }
         let State {
             transactions,
             blocks,
@@ -306,18 +396,45 @@ impl ExplorerDb {
             stake_pool_data,
             stake_pool_blocks,
             vote_plans,
-        } = previous_state.state().clone();
-
+            delegations,
+            stake_distributions,
+            epoch_rewards,
+            account_rewards,
+        } = state;
+        let archived_epochs = self.stable_store.archived_epochs.load(Ordering::Acquire);
+        let stake_distributions = drop_archived_epochs(stake_distributions, archived_epochs);
+        let epoch_rewards = drop_archived_epochs(epoch_rewards, archived_epochs);
+
+        let stable_transactions = self
+            .get_stable_transactions(&transactions, block.contents.iter())
+            .await?;
         let explorer_block = ExplorerBlock::resolve_from(
             &block,
             indexing::ExplorerBlockBuildingContext {
                 discrimination,
                 prev_transactions: &transactions,
                 prev_blocks: &blocks,
+                stable_transactions: &stable_transactions,
+                pending: false,
             },
         );
         let (stake_pool_data, stake_pool_blocks) =
             apply_block_to_stake_pools(stake_pool_data, stake_pool_blocks, &explorer_block);
+        // the stake distribution and the rewards are recorded with the first
+        // block of the epoch
+        let epoch_ref = if epochs.lookup(&explorer_block.date().epoch).is_none() {
+            epoch_ref(&self.blockchain, &self.blockchain_tip, &explorer_block).await
+        } else {
+            None
+        };
+        let (epoch_rewards, account_rewards) = apply_block_to_rewards(
+            epoch_rewards,
+            account_rewards,
+            &explorer_block,
+            epoch_ref
+                .as_deref()
+                .and_then(|epoch_ref| rewards_distribution(epoch_ref, discrimination)),
+        );
 
         let state_ref = multiverse
             .insert(
@@ -337,6 +454,18 @@ impl ExplorerDb {
                         &self.blockchain_tip,
                         &explorer_block,
                     ),
+                    delegations: apply_block_to_delegations(
+                        delegations,
+                        discrimination,
+                        &explorer_block,
+                    ),
+                    stake_distributions: apply_block_to_stake_distributions(
+                        stake_distributions,
+                        &explorer_block,
+                        epoch_ref.as_deref().map(epoch_stake_distribution),
+                    ),
+                    epoch_rewards,
+                    account_rewards,
                 },
             )
             .await;
@@ -351,7 +480,50 @@ impl ExplorerDb {
             }
         }
 
-        None
+        self.get_stable_block(block_id).await
+    }
+
+    /// read a block moved to the stable store
+    async fn get_stable_block(&self, block_id: &HeaderHash) -> Option<Arc<ExplorerBlock>> {
+        let store = self
+            .stable_store
+            .store
+            .clone()
+            .filter(|store| store.contains_block(block_id))?;
+        let block_id = *block_id;
+        tokio::task::spawn_blocking(move || store.load_block(&block_id))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::new(std::io::ErrorKind::Other, e)))
+            .unwrap_or_else(|error| {
+                tracing::warn!(reason = %error, "cannot load the stored block {}", block_id);
+                None
+            })
+            .map(Arc::new)
+    }
+
+    /// read the transactions of the stable store spent by the utxo inputs of
+    /// the fragments, the ones in the given transactions are in memory
+    async fn get_stable_transactions<'a>(
+        &self,
+        transactions: &Transactions,
+        fragments: impl Iterator<Item = &'a Fragment>,
+    ) -> Result<HashMap<FragmentId, indexing::ExplorerTransaction>> {
+        let store = match &self.stable_store.store {
+            Some(store) => store.clone(),
+            None => return Ok(HashMap::new()),
+        };
+        let spent: Vec<FragmentId> = fragments
+            .flat_map(indexing::spent_transactions)
+            .filter(|transaction_id| transactions.lookup(transaction_id).is_none())
+            .collect();
+        if spent.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        tokio::task::spawn_blocking(move || store.load_transactions(&spent))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::new(std::io::ErrorKind::Other, e)))
+            .map_err(Error::from)
     }
 
     pub(self) async fn set_tip(&self, hash: HeaderHash) -> bool {
@@ -383,6 +555,11 @@ impl ExplorerDb {
                 .confirmed_block_chain_length
                 .store(confirmed_block_chain_length.into(), Ordering::Release);
 
+            self.store_confirmed_state(state, confirmed_block_chain_length)
+                .await;
+
+            // the states of the blocks deeper than the confirmed one are
+            // dropped, the queries of the stored blocks read the stable store
             self.multiverse
                 .gc(self.blockchain_config.epoch_stability_depth)
                 .await;
@@ -397,21 +574,117 @@ impl ExplorerDb {
         true
     }
 
+    /// write the state at the confirmed block in the stable store, once per
+    /// epoch, along with the blocks up to the confirmed one and the data of
+    /// the epochs preceding the one of the confirmed block, which are then
+    /// dropped from the states in memory
+    async fn store_confirmed_state(&self, tip_state: &State, chain_length: ChainLength) {
+        let store = match &self.stable_store.store {
+            Some(store) => store.clone(),
+            None => return,
+        };
+        let block = match tip_state
+            .chain_lengths
+            .lookup(&chain_length)
+            .and_then(|block_id| tip_state.blocks.lookup(block_id))
+        {
+            Some(block) => Arc::clone(block),
+            None => return,
+        };
+        let epoch = block.date().epoch;
+
+        // a state still being written will be written again at a later tip
+        let mut stored_epoch = match self.stable_store.stored_epoch.clone().try_lock_owned() {
+            Ok(stored_epoch) => stored_epoch,
+            Err(_) => return,
+        };
+        if stored_epoch.map_or(false, |stored_epoch| stored_epoch >= epoch) {
+            return;
+        }
+        let mut state = match self.multiverse.get_ref(&block.id()).await {
+            Some(state_ref) => state_ref.state().clone(),
+            None => return,
+        };
+
+        // the blocks of the branch that are not in the store yet, the oldest
+        // first
+        let stored_blocks = store.stored_blocks();
+        let mut blocks = Vec::new();
+        let mut next = Some(Arc::clone(&block));
+        while let Some(block) =
+            next.filter(|block| u32::from(block.chain_length()) >= stored_blocks)
+        {
+            next = tip_state.blocks.lookup(&block.parent_hash).cloned();
+            blocks.push(block);
+        }
+        blocks.reverse();
+
+        let block_id = block.id();
+        let archived_epochs = Arc::clone(&self.stable_store.archived_epochs);
+        tokio::spawn(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                store.store_blocks(&blocks)?;
+                archive_epochs(&store, &state, epoch)?;
+                state.stake_distributions = drop_archived_epochs(state.stake_distributions, epoch);
+                state.epoch_rewards = drop_archived_epochs(state.epoch_rewards, epoch);
+                store.store(chain_length, block_id, &state)
+            })
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::new(std::io::ErrorKind::Other, e)));
+
+            match result {
+                Ok(()) => {
+                    tracing::debug!("explorer state at block {} stored", block_id);
+                    *stored_epoch = Some(epoch);
+                    archived_epochs.fetch_max(epoch, Ordering::AcqRel);
+                }
+                Err(error) => tracing::warn!(
+                    reason = %error,
+                    "cannot store the explorer state at block {}",
+                    block_id
+                ),
+            }
+        });
+    }
+
+    /// the data of an epoch archived in the stable store
+    async fn get_archived_epoch(&self, epoch: Epoch) -> Option<ArchivedEpoch> {
+        if epoch >= self.stable_store.archived_epochs.load(Ordering::Acquire) {
+            return None;
+        }
+        let store = self.stable_store.store.clone()?;
+        tokio::task::spawn_blocking(move || store.load_epoch(epoch))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::new(std::io::ErrorKind::Other, e)))
+            .unwrap_or_else(|error| {
+                tracing::warn!(reason = %error, "cannot load the archived epoch {}", epoch);
+                None
+            })
+    }
+
     pub(self) async fn get_block_with_branches(
         &self,
         block_id: &HeaderHash,
     ) -> Option<(Arc<ExplorerBlock>, Vec<(HeaderHash, multiverse::Ref)>)> {
         let mut block = None;
         let mut tips = Vec::new();
+        let all_tips = self.multiverse.tips().await;
 
-        for (hash, state_ref) in self.multiverse.tips().await.drain(..) {
+        for (hash, state_ref) in all_tips.iter() {
             if let Some(b) = state_ref.state().blocks.lookup(&block_id) {
                 block = block.or_else(|| Some(Arc::clone(b)));
-                tips.push((hash, state_ref));
+                tips.push((*hash, state_ref.clone()));
             }
         }
 
-        block.map(|b| (b, tips))
+        match block {
+            Some(block) => Some((block, tips)),
+            // a block moved to the stable store is in all the branches
+            None => self
+                .get_stable_block(block_id)
+                .await
+                .map(|block| (block, all_tips)),
+        }
     }
 
     pub async fn get_epoch(&self, epoch: Epoch) -> Option<EpochData> {
@@ -425,6 +698,152 @@ impl ExplorerDb {
             .map(|e| e.as_ref().clone())
     }
 
+    /// the delegation of the account holding the stake of the given address
+    pub async fn get_delegation(&self, address: &ExplorerAddress) -> Option<DelegationType> {
+        let account = address.delegation_account()?;
+        let tips = self.multiverse.tips().await;
+        let (_, state_ref) = &tips[0];
+
+        state_ref
+            .state()
+            .delegations
+            .lookup(&account)
+            .map(|delegation| delegation.as_ref().clone())
+    }
+
+    /// the stake distribution of the leader election of the epoch, `None` if
+    /// the epoch is not indexed yet. An indexed epoch has no distribution if
+    /// its ledger state was not known to the node when it was indexed
+    pub async fn get_stake_distribution(
+        &self,
+        epoch: Epoch,
+    ) -> Result<Option<Arc<ExplorerStakeDistribution>>> {
+        let tips = self.multiverse.tips().await;
+        let (_, state_ref) = &tips[0];
+
+        if let Some(distribution) = state_ref.state().stake_distributions.lookup(&epoch) {
+            return Ok(Some(Arc::clone(distribution)));
+        }
+        if let Some(distribution) = self
+            .get_archived_epoch(epoch)
+            .await
+            .and_then(|archived| archived.stake_distribution)
+        {
+            return Ok(Some(Arc::new(distribution)));
+        }
+
+        match state_ref.state().epochs.lookup(&epoch) {
+            Some(_) => Err(Error::StakeDistributionUnavailable(epoch)),
+            None => Ok(None),
+        }
+    }
+
+    /// the rewards distributed at the start of the given epoch
+    pub async fn get_epoch_rewards(&self, epoch: Epoch) -> Option<Arc<ExplorerEpochRewards>> {
+        let tips = self.multiverse.tips().await;
+        let (_, state_ref) = &tips[0];
+
+        if let Some(rewards) = state_ref.state().epoch_rewards.lookup(&epoch) {
+            return Some(Arc::clone(rewards));
+        }
+        self.get_archived_epoch(epoch)
+            .await
+            .and_then(|archived| archived.rewards)
+            .map(Arc::new)
+    }
+
+    /// the rewards of the pool at the start of each epoch, the oldest first
+    pub async fn get_pool_rewards(&self, pool: &PoolId) -> Vec<(Epoch, ExplorerPoolRewards)> {
+        let tips = self.multiverse.tips().await;
+        let (_, state_ref) = &tips[0];
+
+        let mut rewards: Vec<_> = state_ref
+            .state()
+            .epoch_rewards
+            .iter()
+            .filter_map(|(epoch, rewards)| {
+                rewards
+                    .pools
+                    .get(pool)
+                    .map(|pool_rewards| (*epoch, pool_rewards.clone()))
+            })
+            .collect();
+
+        let archived_epochs = self.stable_store.archived_epochs.load(Ordering::Acquire);
+        if let Some(store) = self
+            .stable_store
+            .store
+            .clone()
+            .filter(|_| archived_epochs > 0)
+        {
+            let pool = pool.clone();
+            let archived = tokio::task::spawn_blocking(move || {
+                let mut rewards = Vec::new();
+                for epoch in store.archived_epochs()? {
+                    if epoch >= archived_epochs {
+                        break;
+                    }
+                    let pool_rewards = store
+                        .load_epoch(epoch)?
+                        .and_then(|archived| archived.rewards)
+                        .and_then(|mut rewards| rewards.pools.remove(&pool));
+                    if let Some(pool_rewards) = pool_rewards {
+                        rewards.push((epoch, pool_rewards));
+                    }
+                }
+                Ok(rewards)
+            })
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::new(std::io::ErrorKind::Other, e)));
+            match archived {
+                // the epochs archived while the tip state was created are
+                // both in memory and in the store
+                Ok(archived) => {
+                    let in_memory: Vec<Epoch> = rewards.iter().map(|(epoch, _)| *epoch).collect();
+                    rewards.extend(
+                        archived
+                            .into_iter()
+                            .filter(|(epoch, _)| !in_memory.contains(epoch)),
+                    )
+                }
+                Err(error) => {
+                    tracing::warn!(reason = %error, "cannot load the archived pool rewards")
+                }
+            }
+        }
+        rewards.sort_unstable_by_key(|(epoch, _)| *epoch);
+        rewards
+    }
+
+    /// the rewards received by the account holding the stake of the given
+    /// address, the oldest first
+    pub async fn get_account_rewards(
+        &self,
+        address: &ExplorerAddress,
+    ) -> Option<Arc<PersistentSequence<(Epoch, Value)>>> {
+        let account = address.delegation_account()?;
+        let tips = self.multiverse.tips().await;
+        let (_, state_ref) = &tips[0];
+
+        state_ref
+            .state()
+            .account_rewards
+            .lookup(&account)
+            .map(Arc::clone)
+    }
+
+    /// chain length of the tip of the longest branch indexed so far
+    pub async fn tip_chain_length(&self) -> Option<ChainLength> {
+        let tip = self.longest_chain_tip.get_block_id().await;
+        self.multiverse
+            .get_ref(&tip)
+            .await?
+            .state()
+            .blocks
+            .lookup(&tip)
+            .map(|block| block.chain_length())
+    }
+
     pub async fn is_block_confirmed(&self, block_id: &HeaderHash) -> bool {
         let current_branch = self
             .multiverse
@@ -440,7 +859,10 @@ impl ExplorerDb {
                 .into();
             block.chain_length <= confirmed_block_chain_length
         } else {
-            false
+            self.stable_store
+                .store
+                .as_ref()
+                .map_or(false, |store| store.contains_block(block_id))
         }
     }
 
@@ -452,6 +874,14 @@ impl ExplorerDb {
                 hashes.push(**hash);
             }
         }
+        if let Some(hash) = self
+            .stable_store
+            .store
+            .as_ref()
+            .and_then(|store| store.block_id(chain_length))
+        {
+            hashes.push(hash);
+        }
 
         hashes.sort_unstable();
         hashes.dedup();
@@ -473,6 +903,14 @@ impl ExplorerDb {
                     .map(|arc| *arc.clone())
             })
             .collect();
+        if let Some(block_id) = self
+            .stable_store
+            .store
+            .as_ref()
+            .and_then(|store| store.find_block_by_transaction(transaction_id))
+        {
+            txs.push(block_id);
+        }
 
         txs.sort_unstable();
         txs.dedup();
@@ -528,6 +966,54 @@ impl ExplorerDb {
         None
     }
 
+    /// decode a fragment that is not in a block yet, resolving its utxo
+    /// inputs with the state of the longest branch. `None` if the fragment
+    /// is not a transaction
+    pub async fn resolve_pending_fragment(
+        &self,
+        fragment: &Fragment,
+    ) -> Option<indexing::ExplorerTransaction> {
+        let (_, tip) = self.get_tip().await;
+        let state = tip.state();
+        // a pending transaction may be invalid, the inputs that cannot be
+        // read from the stable store are left unresolved
+        let stable_transactions = self
+            .get_stable_transactions(&state.transactions, std::iter::once(fragment))
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!(reason = %error, "cannot load the spent stored transactions");
+                HashMap::new()
+            });
+        let context = indexing::ExplorerBlockBuildingContext {
+            discrimination: self.blockchain_config.discrimination,
+            prev_transactions: &state.transactions,
+            prev_blocks: &state.blocks,
+            stable_transactions: &stable_transactions,
+            pending: true,
+        };
+
+        indexing::ExplorerTransaction::from_fragment(&context, fragment, 0, &HashMap::new())
+    }
+
+    /// the hashes of the blocks of the branch in the range [from, to), the
+    /// blocks moved to the stable store are not in the branch state anymore
+    pub(self) fn get_block_hash_range(
+        &self,
+        branch: &multiverse::Ref,
+        from: ChainLength,
+        to: ChainLength,
+    ) -> Vec<(HeaderHash, ChainLength)> {
+        let mut range = match &self.stable_store.store {
+            Some(store) => store.block_hash_range(from, to),
+            None => Vec::new(),
+        };
+        let from = range
+            .last()
+            .map_or(from, |(_, chain_length)| chain_length.increase());
+        range.extend(branch.state().get_block_hash_range(from, to));
+        range
+    }
+
     pub(self) async fn get_branch(&self, hash: &HeaderHash) -> Option<multiverse::Ref> {
         self.multiverse.get_ref(hash).await
     }
@@ -555,6 +1041,50 @@ impl ExplorerDb {
     > {
         tokio_stream::wrappers::BroadcastStream::new(self.tip_broadcast.subscribe())
     }
+
+    /// the blocks added to the longest branch by each tip update, the oldest
+    /// first, with the state of the new tip. After a branch switch, these are
+    /// the blocks of the new branch following the fork point. The blocks of
+    /// the missed tip updates are given with the next update.
+    pub(self) fn new_blocks_subscription(
+        &self,
+    ) -> impl Stream<Item = (Vec<Arc<ExplorerBlock>>, multiverse::Ref)> {
+        self.tip_subscription()
+            .filter_map(|tip| future::ready(tip.ok()))
+            .scan(
+                None,
+                |previous_tip: &mut Option<multiverse::Ref>, (hash, tip)| {
+                    let blocks = new_blocks(previous_tip.as_ref(), hash, &tip);
+                    *previous_tip = Some(tip.clone());
+                    future::ready(Some((blocks, tip)))
+                },
+            )
+            .filter(|(blocks, _)| future::ready(!blocks.is_empty()))
+    }
+}
+
+/// the blocks of the new tip branch that are not in the previous tip branch,
+/// only the tip block if there is no previous tip
+fn new_blocks(
+    previous_tip: Option<&multiverse::Ref>,
+    tip_hash: HeaderHash,
+    tip: &multiverse::Ref,
+) -> Vec<Arc<ExplorerBlock>> {
+    let mut blocks = Vec::new();
+    let mut hash = tip_hash;
+    while let Some(block) = tip.state().blocks.lookup(&hash) {
+        let known = match previous_tip {
+            Some(previous_tip) => previous_tip.state().blocks.lookup(&hash).is_some(),
+            None => !blocks.is_empty(),
+        };
+        if known {
+            break;
+        }
+        hash = block.parent_hash;
+        blocks.push(Arc::clone(block));
+    }
+    blocks.reverse();
+    blocks
 }
 
 fn apply_block_to_transactions(
@@ -856,6 +1386,296 @@ fn apply_block_to_vote_plans(
     vote_plans
 }
 
+fn apply_block_to_delegations(
+    mut delegations: Delegations,
+    discrimination: Discrimination,
+    block: &ExplorerBlock,
+) -> Delegations {
+    // an account may change its delegation more than once in a block
+    let mut transactions: Vec<_> = block.transactions.values().collect();
+    transactions.sort_by_key(|tx| tx.offset_in_block);
+
+    for tx in transactions {
+        let (account, delegation) = match &tx.certificate {
+            Some(Certificate::StakeDelegation(certificate)) => (
+                ExplorerAddress::from_account_identifier(discrimination, &certificate.account_id),
+                certificate.get_delegation_type().clone(),
+            ),
+            Some(Certificate::OwnerStakeDelegation(certificate)) => match tx.inputs().first() {
+                Some(input) => (
+                    input.address.clone(),
+                    certificate.get_delegation_type().clone(),
+                ),
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        let delegation = Arc::new(delegation);
+        delegations = delegations.insert_or_update_simple(account, Arc::clone(&delegation), |_| {
+            Some(Arc::clone(&delegation))
+        });
+    }
+
+    delegations
+}
+
+fn apply_block_to_stake_distributions(
+    stake_distributions: StakeDistributions,
+    block: &ExplorerBlock,
+    stake_distribution: Option<ExplorerStakeDistribution>,
+) -> StakeDistributions {
+    match stake_distribution {
+        Some(stake_distribution) => {
+            let stake_distribution = Arc::new(stake_distribution);
+            stake_distributions.insert_or_update_simple(
+                block.date().epoch,
+                Arc::clone(&stake_distribution),
+                |_| Some(Arc::clone(&stake_distribution)),
+            )
+        }
+        None => stake_distributions,
+    }
+}
+
+fn apply_block_to_rewards(
+    mut epoch_rewards: EpochRewards,
+    mut account_rewards: AccountRewards,
+    block: &ExplorerBlock,
+    rewards: Option<(ExplorerEpochRewards, Vec<(ExplorerAddress, Value)>)>,
+) -> (EpochRewards, AccountRewards) {
+    let (rewards, accounts) = match rewards {
+        Some(rewards) => rewards,
+        None => return (epoch_rewards, account_rewards),
+    };
+    let epoch = block.date().epoch;
+
+    let rewards = Arc::new(rewards);
+    epoch_rewards = epoch_rewards
+        .insert_or_update_simple(epoch, Arc::clone(&rewards), |_| Some(Arc::clone(&rewards)));
+
+    for (account, value) in accounts {
+        account_rewards = account_rewards.insert_or_update_simple(
+            account,
+            Arc::new(PersistentSequence::new().append((epoch, value))),
+            |rewards| Some(Arc::new(rewards.append((epoch, value)))),
+        );
+    }
+
+    (epoch_rewards, account_rewards)
+}
+
+/// get the blockchain state of the epoch of the given block.
+///
+/// The states of the old blocks are not kept in the blockchain cache, the
+/// ones ending the previous epochs are still reachable from the tip. `None` is
+/// returned if the state of the epoch is not known to the node, e.g. when it
+/// was started from a ledger snapshot, the stake distribution and the rewards
+/// of the epoch are then not indexed.
+async fn epoch_ref(
+    blockchain: &Blockchain,
+    blockchain_tip: &blockchain::Tip,
+    block: &ExplorerBlock,
+) -> Option<Arc<blockchain::Ref>> {
+    let epoch = block.date().epoch;
+    let mut block_ref = match blockchain.get_ref(block.id()).await {
+        Ok(Some(block_ref)) => block_ref,
+        _ => blockchain_tip.get_ref().await,
+    };
+    while block_ref.block_date().epoch > epoch {
+        match block_ref.last_ref_previous_epoch() {
+            Some(previous_ref) => block_ref = Arc::clone(previous_ref),
+            None => break,
+        }
+    }
+    if block_ref.block_date().epoch != epoch {
+        tracing::warn!(
+            "the ledger state of epoch {} is not known, its stake distribution and rewards are not indexed",
+            epoch
+        );
+        return None;
+    }
+
+    Some(block_ref)
+}
+
+/// the stake distribution used in the leader election of the epoch
+fn epoch_stake_distribution(epoch_ref: &blockchain::Ref) -> ExplorerStakeDistribution {
+    let pools = match epoch_ref.epoch_leadership_schedule().stake_distribution() {
+        Some(distribution) => distribution
+            .to_pools
+            .iter()
+            .map(|(pool_id, pool)| (pool_id.clone(), Value(pool.stake.total.0)))
+            .collect(),
+        // there is no stake involved in the BFT leader election
+        None => Vec::new(),
+    };
+
+    ExplorerStakeDistribution { pools }
+}
+
+/// the rewards distributed at the start of the epoch, with the rewards of
+/// each account. The rewards of the accounts are only reported by the
+/// ledger with `--rewards-report-all`, which is always set with the explorer
+fn rewards_distribution(
+    epoch_ref: &blockchain::Ref,
+    discrimination: Discrimination,
+) -> Option<(ExplorerEpochRewards, Vec<(ExplorerAddress, Value)>)> {
+    let rewards_info = epoch_ref.epoch_rewards_info()?;
+
+    let pools = rewards_info
+        .stake_pools
+        .iter()
+        .map(|(pool_id, (taxed, distributed))| {
+            (
+                pool_id.clone(),
+                ExplorerPoolRewards {
+                    taxed: *taxed,
+                    distributed: *distributed,
+                },
+            )
+        })
+        .collect();
+    let accounts = rewards_info
+        .accounts
+        .iter()
+        .map(|(account, value)| {
+            let kind = chain_addr::Kind::Account(account.clone().into());
+            (
+                ExplorerAddress::New(chain_addr::Address(discrimination, kind)),
+                *value,
+            )
+        })
+        .collect();
+
+    Some((
+        ExplorerEpochRewards {
+            drawn: rewards_info.drawn,
+            fees: rewards_info.fees,
+            treasury: rewards_info.treasury,
+            pools,
+        },
+        accounts,
+    ))
+}
+
+/// write the data of the epochs preceding `epoch` held by the state
+fn archive_epochs(store: &StableStore, state: &State, epoch: Epoch) -> std::io::Result<()> {
+    let mut epochs: Vec<Epoch> = state
+        .stake_distributions
+        .iter()
+        .map(|(epoch, _)| *epoch)
+        .chain(state.epoch_rewards.iter().map(|(epoch, _)| *epoch))
+        .filter(|archived| *archived < epoch)
+        .collect();
+    epochs.sort_unstable();
+    epochs.dedup();
+
+    for epoch in epochs {
+        let archived = ArchivedEpoch {
+            stake_distribution: state
+                .stake_distributions
+                .lookup(&epoch)
+                .map(|distribution| distribution.as_ref().clone()),
+            rewards: state
+                .epoch_rewards
+                .lookup(&epoch)
+                .map(|rewards| rewards.as_ref().clone()),
+        };
+        store.store_epoch(epoch, &archived)?;
+    }
+
+    Ok(())
+}
+
+/// drop the blocks written in the blocks file of the stable store, with their
+/// transactions and chain lengths, which are read from the store. The blocks
+/// are dropped from the last stored one, until one was already dropped
+fn drop_stored_blocks(state: &mut State, store: &StableStore) {
+    let mut chain_length = store.stored_blocks();
+    while chain_length > 0 {
+        chain_length -= 1;
+        let block_id = match state.chain_lengths.lookup(&chain_length.into()) {
+            Some(block_id) => **block_id,
+            None => break,
+        };
+        // a branch forking off before the stored blocks keeps its own blocks
+        if store.block_id(chain_length.into()) != Some(block_id) {
+            break;
+        }
+
+        if let Some(block) = state.blocks.lookup(&block_id).cloned() {
+            for transaction_id in block.transactions.keys() {
+                if let Ok(transactions) = state.transactions.remove(transaction_id) {
+                    state.transactions = transactions;
+                }
+            }
+        }
+        if let Ok(blocks) = state.blocks.remove(&block_id) {
+            state.blocks = blocks;
+        }
+        if let Ok(chain_lengths) = state.chain_lengths.remove(&chain_length.into()) {
+            state.chain_lengths = chain_lengths;
+        }
+    }
+}
+
+/// drop the entries of the epochs preceding `archived`, which are read from
+/// the stable store
+fn drop_archived_epochs<V>(mut hamt: Hamt<Epoch, V>, archived: Epoch) -> Hamt<Epoch, V> {
+    let epochs: Vec<Epoch> = hamt
+        .iter()
+        .map(|(epoch, _)| *epoch)
+        .filter(|epoch| *epoch < archived)
+        .collect();
+    for epoch in epochs {
+        hamt = hamt.remove(&epoch).unwrap();
+    }
+    hamt
+}
+
+/// load the most recent stored state that is an ancestor of the main branch
+fn load_stable_state(
+    store: &StableStore,
+    blockchain: &Blockchain,
+    head: HeaderHash,
+) -> Option<(ChainLength, HeaderHash, State)> {
+    let states = match store.list() {
+        Ok(states) => states,
+        Err(error) => {
+            tracing::warn!(reason = %error, "cannot list the stored explorer states");
+            return None;
+        }
+    };
+
+    for (chain_length, block_id) in states {
+        match blockchain
+            .storage()
+            .find_closest_ancestor(vec![block_id], head)
+        {
+            Ok(Some(_)) => {}
+            // the state belongs to a branch that is not the main branch anymore
+            Ok(None) => continue,
+            Err(error) => {
+                tracing::warn!(reason = %error, "cannot check the stored explorer state at block {}", block_id);
+                continue;
+            }
+        }
+
+        match store.load(chain_length, block_id) {
+            Ok(state) => {
+                tracing::info!("loaded the explorer state at block {}", block_id);
+                return Some((chain_length, block_id, state));
+            }
+            Err(error) => {
+                tracing::warn!(reason = %error, "cannot load the explorer state at block {}", block_id);
+            }
+        }
+    }
+
+    None
+}
+
 impl BlockchainConfig {
     fn from_config_params(params: &ConfigParams) -> BlockchainConfig {
         let mut discrimination: Option<Discrimination> = None;
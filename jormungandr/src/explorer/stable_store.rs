@@ -0,0 +1,1519 @@
+//! On-disk store of the confirmed part of the explorer index.
+//!
+//! Once per epoch, the confirmed blocks of the main branch (deeper than the
+//! epoch stability depth) are appended to a blocks file and the explorer state
+//! at the last one is written next to the blocks storage. The stored blocks
+//! are then dropped from the explorer states kept in memory, the queries read
+//! them from the blocks file through an index of their ids, chain lengths and
+//! transactions. On start, the explorer loads the most recent state of the
+//! main branch and only indexes the blocks that follow it instead of indexing
+//! the whole blockchain again from the block0.
+//!
+//! The epochs are not written, they are rebuilt from the blocks file on load.
+//!
+//! The stake distribution and the rewards of the epochs preceding the one of
+//! the confirmed block do not change anymore. They are written once in a file
+//! per epoch and dropped from the explorer states kept in memory, the queries
+//! read them from this file.
+
+use super::{
+    apply_block_to_epochs,
+    indexing::{
+        AccountRewards, Addresses, BlockProducer, Blocks, ChainLengths, Delegations, EpochRewards,
+        Epochs, ExplorerAddress, ExplorerBlock, ExplorerEpochRewards, ExplorerInput,
+        ExplorerOutput, ExplorerPoolRewards, ExplorerStakeDistribution, ExplorerTransaction,
+        ExplorerVote, ExplorerVotePlan, ExplorerVoteProposal, ExplorerVoteTally, Hamt,
+        StakeDistributions, StakePool, StakePoolBlocks, StakePoolData, Transactions, VotePlans,
+    },
+    persistent_sequence::PersistentSequence,
+    State,
+};
+use crate::{
+    blockcfg::{BlockDate, ChainLength, Epoch, FragmentId, HeaderHash, Value},
+    utils::block_files::{self, invalid_data, BlockFiles},
+};
+use chain_addr::Address;
+use chain_core::{
+    mempack::{ReadBuf, Readable},
+    property::{Deserialize as _, Serialize},
+};
+use chain_crypto::{bech32::Bech32 as _, Ed25519, PublicKey};
+use chain_impl_mockchain::{
+    account::{DelegationRatio, DelegationType},
+    certificate::{Certificate, PoolId, VoteCast},
+    key::BftLeaderId,
+    vote::{Choice, Options, Payload, PayloadType, Weight},
+};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::{self, File, OpenOptions},
+    hash::Hash,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+const STORE_VERSION: u8 = 1;
+const STORE_EXTENSION: &str = "explorer";
+const STATES_TO_KEEP: usize = 2;
+const EPOCHS_DIR: &str = "epochs";
+const EPOCH_EXTENSION: &str = "epoch";
+const BLOCKS_FILE: &str = "blocks.log";
+
+/// handle on the directory containing the stored explorer states
+#[derive(Clone)]
+pub struct StableStore {
+    files: BlockFiles,
+    epochs_dir: PathBuf,
+    blocks_path: PathBuf,
+    blocks: Arc<RwLock<StableBlocks>>,
+}
+
+/// index of the blocks file, which holds the confirmed blocks of the main
+/// branch from the block0 up to the block of the last stored state
+#[derive(Default)]
+struct StableBlocks {
+    /// the id of the blocks and their offset in the file, by chain length
+    blocks: Vec<(HeaderHash, u64)>,
+    chain_lengths: HashMap<HeaderHash, ChainLength>,
+    transactions: HashMap<FragmentId, ChainLength>,
+    /// the offset following the last block
+    end: u64,
+}
+
+/// the data of a confirmed epoch that is not kept in memory
+pub(super) struct ArchivedEpoch {
+    pub stake_distribution: Option<ExplorerStakeDistribution>,
+    pub rewards: Option<ExplorerEpochRewards>,
+}
+
+impl StableStore {
+    pub fn new<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let epochs_dir = dir.as_ref().join(EPOCHS_DIR);
+        fs::create_dir_all(&epochs_dir)?;
+        Ok(StableStore {
+            files: BlockFiles::new(&dir, STORE_EXTENSION)?,
+            epochs_dir,
+            blocks_path: dir.as_ref().join(BLOCKS_FILE),
+            blocks: Arc::new(RwLock::new(StableBlocks::default())),
+        })
+    }
+
+    /// list the stored states, the most recent one first
+    pub fn list(&self) -> io::Result<Vec<(ChainLength, HeaderHash)>> {
+        self.files.list()
+    }
+
+    pub(super) fn load(
+        &self,
+        chain_length: ChainLength,
+        block_id: HeaderHash,
+    ) -> io::Result<State> {
+        let reader = &mut self.files.open(chain_length, block_id)?;
+
+        let version = read_u8(reader)?;
+        if version != STORE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported explorer store version {}",
+                version
+            )));
+        }
+        if read_hash(reader)? != block_id || ChainLength::from(read_u32(reader)?) != chain_length {
+            return Err(invalid_data(
+                "the explorer state does not match its file name",
+            ));
+        }
+
+        let mut state = read_state(reader)?;
+        state.epochs = self.load_blocks(chain_length, block_id)?;
+        Ok(state)
+    }
+
+    /// write the explorer state after the given block and remove the oldest
+    /// stored states. The blocks of the state up to the given one are expected
+    /// to be in the blocks file already, they are not written with the state
+    pub(super) fn store(
+        &self,
+        chain_length: ChainLength,
+        block_id: HeaderHash,
+        state: &State,
+    ) -> io::Result<()> {
+        self.files
+            .write(chain_length, block_id, STATES_TO_KEEP, |writer| {
+                write_u8(writer, STORE_VERSION)?;
+                writer.write_all(block_id.as_bytes())?;
+                write_u32(writer, chain_length.into())?;
+                write_state(writer, state)
+            })
+    }
+
+    fn epoch_path(&self, epoch: Epoch) -> PathBuf {
+        self.epochs_dir
+            .join(format!("{}.{}", epoch, EPOCH_EXTENSION))
+    }
+
+    /// the data of the given epoch, `None` if the epoch has not been archived
+    pub(super) fn load_epoch(&self, epoch: Epoch) -> io::Result<Option<ArchivedEpoch>> {
+        let file = match File::open(self.epoch_path(epoch)) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let reader = &mut BufReader::new(file);
+
+        let version = read_u8(reader)?;
+        if version != STORE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported explorer epoch version {}",
+                version
+            )));
+        }
+        if read_u32(reader)? != epoch {
+            return Err(invalid_data(
+                "the explorer epoch does not match its file name",
+            ));
+        }
+
+        Ok(Some(ArchivedEpoch {
+            stake_distribution: read_option(reader, read_stake_distribution)?,
+            rewards: read_option(reader, read_epoch_rewards)?,
+        }))
+    }
+
+    /// write the data of a confirmed epoch, which is not expected to change
+    pub(super) fn store_epoch(&self, epoch: Epoch, archived: &ArchivedEpoch) -> io::Result<()> {
+        block_files::write_file(&self.epoch_path(epoch), |writer| {
+            write_u8(writer, STORE_VERSION)?;
+            write_u32(writer, epoch)?;
+            write_option(
+                writer,
+                &archived.stake_distribution,
+                |writer, distribution| write_stake_distribution(writer, distribution),
+            )?;
+            write_option(writer, &archived.rewards, |writer, rewards| {
+                write_epoch_rewards(writer, rewards)
+            })
+        })
+    }
+
+    /// the archived epochs, the oldest first
+    pub(super) fn archived_epochs(&self) -> io::Result<Vec<Epoch>> {
+        let mut epochs = Vec::new();
+        for entry in fs::read_dir(&self.epochs_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(EPOCH_EXTENSION) {
+                continue;
+            }
+            if let Some(epoch) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<Epoch>().ok())
+            {
+                epochs.push(epoch);
+            }
+        }
+        epochs.sort_unstable();
+        Ok(epochs)
+    }
+
+    /// index the blocks file up to the given block and rebuild the epochs of
+    /// these blocks. The blocks following it were written for a state that
+    /// could not be loaded, they are removed
+    fn load_blocks(&self, chain_length: ChainLength, block_id: HeaderHash) -> io::Result<Epochs> {
+        let reader = &mut BufReader::new(File::open(&self.blocks_path)?);
+
+        let version = read_u8(reader)?;
+        if version != STORE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported explorer blocks version {}",
+                version
+            )));
+        }
+
+        let mut index = StableBlocks {
+            end: 1,
+            ..Default::default()
+        };
+        let mut epochs = Epochs::new();
+        while index.next_chain_length() <= chain_length {
+            let bytes = read_bytes(reader)?;
+            let block = read_block(&mut &bytes[..])?;
+            if block.chain_length != index.next_chain_length() {
+                return Err(invalid_data("the explorer blocks do not follow each other"));
+            }
+            epochs = apply_block_to_epochs(epochs, &block);
+            index.push(&block, 8 + bytes.len() as u64);
+        }
+        if index.blocks.last().map(|(id, _)| *id) != Some(block_id) {
+            return Err(invalid_data(
+                "the explorer blocks do not lead to the stored state",
+            ));
+        }
+
+        OpenOptions::new()
+            .write(true)
+            .open(&self.blocks_path)?
+            .set_len(index.end)?;
+        *self.blocks.write().unwrap() = index;
+
+        Ok(epochs)
+    }
+
+    /// remove the stored blocks, when no stored state can be loaded and the
+    /// blocks are indexed again from the block0
+    pub(super) fn clear_blocks(&self) -> io::Result<()> {
+        block_files::write_file(&self.blocks_path, |writer| write_u8(writer, STORE_VERSION))?;
+        *self.blocks.write().unwrap() = StableBlocks {
+            end: 1,
+            ..Default::default()
+        };
+        Ok(())
+    }
+
+    /// append the given confirmed blocks, the oldest first, to the blocks
+    /// file. The first one must follow the last stored block
+    pub(super) fn store_blocks(&self, blocks: &[Arc<ExplorerBlock>]) -> io::Result<()> {
+        let (mut chain_length, start) = {
+            let index = self.blocks.read().unwrap();
+            (index.next_chain_length(), index.end)
+        };
+
+        let mut file = OpenOptions::new().write(true).open(&self.blocks_path)?;
+        // the end of an interrupted write is overwritten
+        file.seek(SeekFrom::Start(start))?;
+        let mut writer = BufWriter::new(file);
+        let mut lengths = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            if block.chain_length != chain_length {
+                return Err(invalid_data(
+                    "the confirmed blocks do not follow the stored ones",
+                ));
+            }
+            let mut bytes = Vec::new();
+            write_block(&mut bytes, block)?;
+            write_bytes(&mut writer, &bytes)?;
+            lengths.push(8 + bytes.len() as u64);
+            chain_length = chain_length.increase();
+        }
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+
+        let mut index = self.blocks.write().unwrap();
+        for (block, length) in blocks.iter().zip(lengths) {
+            index.push(block, length);
+        }
+        Ok(())
+    }
+
+    /// the number of blocks in the blocks file, the blocks of the main branch
+    /// with a lower chain length are read from the store
+    pub(super) fn stored_blocks(&self) -> u32 {
+        self.blocks.read().unwrap().blocks.len() as u32
+    }
+
+    /// the id of the stored block of the main branch at the given chain length
+    pub(super) fn block_id(&self, chain_length: ChainLength) -> Option<HeaderHash> {
+        self.blocks
+            .read()
+            .unwrap()
+            .blocks
+            .get(u32::from(chain_length) as usize)
+            .map(|(block_id, _)| *block_id)
+    }
+
+    pub(super) fn contains_block(&self, block_id: &HeaderHash) -> bool {
+        self.blocks
+            .read()
+            .unwrap()
+            .chain_lengths
+            .contains_key(block_id)
+    }
+
+    /// the id of the stored block including the given transaction
+    pub(super) fn find_block_by_transaction(
+        &self,
+        transaction_id: &FragmentId,
+    ) -> Option<HeaderHash> {
+        let index = self.blocks.read().unwrap();
+        let chain_length = index.transactions.get(transaction_id)?;
+        index
+            .blocks
+            .get(u32::from(*chain_length) as usize)
+            .map(|(block_id, _)| *block_id)
+    }
+
+    /// the ids of the stored blocks in the range [from, to)
+    pub(super) fn block_hash_range(
+        &self,
+        from: ChainLength,
+        to: ChainLength,
+    ) -> Vec<(HeaderHash, ChainLength)> {
+        let index = self.blocks.read().unwrap();
+        let to = std::cmp::min(u32::from(to) as usize, index.blocks.len());
+        (u32::from(from) as usize..to)
+            .map(|i| (index.blocks[i].0, ChainLength::from(i as u32)))
+            .collect()
+    }
+
+    /// read a stored block, `None` if the block is not in the blocks file
+    pub(super) fn load_block(&self, block_id: &HeaderHash) -> io::Result<Option<ExplorerBlock>> {
+        let offset = {
+            let index = self.blocks.read().unwrap();
+            match index.chain_lengths.get(block_id) {
+                Some(chain_length) => index.blocks[u32::from(*chain_length) as usize].1,
+                None => return Ok(None),
+            }
+        };
+
+        let mut file = File::open(&self.blocks_path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let bytes = read_bytes(&mut BufReader::new(file))?;
+        read_block(&mut &bytes[..]).map(Some)
+    }
+
+    /// read the given transactions from the stored blocks, the transactions
+    /// that are not in the blocks file are left out
+    pub(super) fn load_transactions(
+        &self,
+        transaction_ids: &[FragmentId],
+    ) -> io::Result<HashMap<FragmentId, ExplorerTransaction>> {
+        let mut transactions = HashMap::new();
+        for transaction_id in transaction_ids {
+            let block = match self.find_block_by_transaction(transaction_id) {
+                Some(block_id) => self.load_block(&block_id)?,
+                None => None,
+            };
+            if let Some(mut block) = block {
+                if let Some(transaction) = block.transactions.remove(transaction_id) {
+                    transactions.insert(*transaction_id, transaction);
+                }
+            }
+        }
+        Ok(transactions)
+    }
+}
+
+impl StableBlocks {
+    fn next_chain_length(&self) -> ChainLength {
+        ChainLength::from(self.blocks.len() as u32)
+    }
+
+    /// index a block written at the end of the file with the given length
+    fn push(&mut self, block: &ExplorerBlock, length: u64) {
+        for transaction_id in block.transactions.keys() {
+            self.transactions
+                .insert(*transaction_id, block.chain_length);
+        }
+        self.chain_lengths.insert(block.id, block.chain_length);
+        self.blocks.push((block.id, self.end));
+        self.end += length;
+    }
+}
+
+/// write the state without its blocks and the indexes rebuilt from them
+fn write_state<W: Write>(writer: &mut W, state: &State) -> io::Result<()> {
+    write_hamt(writer, &state.addresses, |writer, address, transactions| {
+        write_address(writer, address)?;
+        write_sequence(writer, transactions, |writer, id| write_hash(writer, id))
+    })?;
+    write_hamt(writer, &state.stake_pool_data, |writer, pool_id, data| {
+        write_str(writer, pool_id)?;
+        write_serialized(writer, &data.registration)?;
+        write_option(writer, &data.retirement, write_serialized)
+    })?;
+    write_hamt(
+        writer,
+        &state.stake_pool_blocks,
+        |writer, pool_id, blocks| {
+            write_str(writer, pool_id)?;
+            write_sequence(writer, blocks, |writer, id| write_hash(writer, id))
+        },
+    )?;
+    write_hamt(writer, &state.vote_plans, |writer, _, vote_plan| {
+        write_vote_plan(writer, vote_plan)
+    })?;
+    write_hamt(writer, &state.delegations, |writer, account, delegation| {
+        write_address(writer, account)?;
+        write_delegation(writer, delegation)
+    })?;
+    write_hamt(
+        writer,
+        &state.stake_distributions,
+        |writer, epoch, distribution| {
+            write_u32(writer, *epoch)?;
+            write_stake_distribution(writer, distribution)
+        },
+    )?;
+    write_hamt(writer, &state.epoch_rewards, |writer, epoch, rewards| {
+        write_u32(writer, *epoch)?;
+        write_epoch_rewards(writer, rewards)
+    })?;
+    write_hamt(
+        writer,
+        &state.account_rewards,
+        |writer, account, rewards| {
+            write_address(writer, account)?;
+            write_sequence(writer, rewards, |writer, (epoch, value)| {
+                write_u32(writer, *epoch)?;
+                write_u64(writer, value.0)
+            })
+        },
+    )
+}
+
+/// read a state without blocks, its epochs are rebuilt from the blocks file
+fn read_state<R: Read>(reader: &mut R) -> io::Result<State> {
+    let addresses: Addresses = read_hamt(reader, |reader| {
+        let address = read_address(reader)?;
+        let transactions = read_sequence(reader, read_hash)?;
+        Ok((address, transactions))
+    })?;
+    let stake_pool_data: StakePool = read_hamt(reader, |reader| {
+        let pool_id: PoolId = read_str(reader)?;
+        let registration = read_readable(reader)?;
+        let retirement = read_option(reader, read_readable)?;
+        Ok((
+            pool_id,
+            StakePoolData {
+                registration,
+                retirement,
+            },
+        ))
+    })?;
+    let stake_pool_blocks: StakePoolBlocks = read_hamt(reader, |reader| {
+        let pool_id: PoolId = read_str(reader)?;
+        let blocks = read_sequence(reader, read_hash)?;
+        Ok((pool_id, blocks))
+    })?;
+    let vote_plans: VotePlans = read_hamt(reader, |reader| {
+        let vote_plan = read_vote_plan(reader)?;
+        Ok((vote_plan.id.clone(), vote_plan))
+    })?;
+    let delegations: Delegations = read_hamt(reader, |reader| {
+        let account = read_address(reader)?;
+        let delegation = read_delegation(reader)?;
+        Ok((account, delegation))
+    })?;
+    let stake_distributions: StakeDistributions = read_hamt(reader, |reader| {
+        let epoch = read_u32(reader)?;
+        Ok((epoch, read_stake_distribution(reader)?))
+    })?;
+    let epoch_rewards: EpochRewards = read_hamt(reader, |reader| {
+        let epoch = read_u32(reader)?;
+        Ok((epoch, read_epoch_rewards(reader)?))
+    })?;
+    let account_rewards: AccountRewards = read_hamt(reader, |reader| {
+        let account = read_address(reader)?;
+        let rewards = read_sequence(reader, |reader| {
+            let epoch = read_u32(reader)?;
+            Ok((epoch, Value(read_u64(reader)?)))
+        })?;
+        Ok((account, rewards))
+    })?;
+
+    Ok(State {
+        transactions: Transactions::new(),
+        blocks: Blocks::new(),
+        addresses,
+        epochs: Epochs::new(),
+        chain_lengths: ChainLengths::new(),
+        stake_pool_data,
+        stake_pool_blocks,
+        vote_plans,
+        delegations,
+        stake_distributions,
+        epoch_rewards,
+        account_rewards,
+    })
+}
+
+/* ---------------- Explorer types ----------------------------------------- */
+
+fn write_stake_distribution<W: Write>(
+    writer: &mut W,
+    distribution: &ExplorerStakeDistribution,
+) -> io::Result<()> {
+    write_u64(writer, distribution.pools.len() as u64)?;
+    for (pool_id, stake) in &distribution.pools {
+        write_str(writer, pool_id)?;
+        write_u64(writer, stake.0)?;
+    }
+    Ok(())
+}
+
+fn read_stake_distribution<R: Read>(reader: &mut R) -> io::Result<ExplorerStakeDistribution> {
+    let pools = read_vec(reader, |reader| {
+        let pool_id: PoolId = read_str(reader)?;
+        Ok((pool_id, Value(read_u64(reader)?)))
+    })?;
+    Ok(ExplorerStakeDistribution { pools })
+}
+
+fn write_epoch_rewards<W: Write>(writer: &mut W, rewards: &ExplorerEpochRewards) -> io::Result<()> {
+    write_u64(writer, rewards.drawn.0)?;
+    write_u64(writer, rewards.fees.0)?;
+    write_u64(writer, rewards.treasury.0)?;
+    write_u64(writer, rewards.pools.len() as u64)?;
+    for (pool_id, pool_rewards) in &rewards.pools {
+        write_str(writer, pool_id)?;
+        write_u64(writer, pool_rewards.taxed.0)?;
+        write_u64(writer, pool_rewards.distributed.0)?;
+    }
+    Ok(())
+}
+
+fn read_epoch_rewards<R: Read>(reader: &mut R) -> io::Result<ExplorerEpochRewards> {
+    let drawn = Value(read_u64(reader)?);
+    let fees = Value(read_u64(reader)?);
+    let treasury = Value(read_u64(reader)?);
+    let pools = read_vec(reader, |reader| {
+        let pool_id: PoolId = read_str(reader)?;
+        let taxed = Value(read_u64(reader)?);
+        let distributed = Value(read_u64(reader)?);
+        Ok((pool_id, ExplorerPoolRewards { taxed, distributed }))
+    })?
+    .into_iter()
+    .collect();
+    Ok(ExplorerEpochRewards {
+        drawn,
+        fees,
+        treasury,
+        pools,
+    })
+}
+
+fn write_block<W: Write>(writer: &mut W, block: &ExplorerBlock) -> io::Result<()> {
+    write_hash(writer, &block.id)?;
+    write_block_date(writer, &block.date)?;
+    write_u32(writer, block.chain_length.into())?;
+    write_hash(writer, &block.parent_hash)?;
+    match &block.producer {
+        BlockProducer::None => write_u8(writer, 0)?,
+        BlockProducer::StakePool(pool_id) => {
+            write_u8(writer, 1)?;
+            write_str(writer, pool_id)?;
+        }
+        BlockProducer::BftLeader(leader_id) => {
+            write_u8(writer, 2)?;
+            write_bytes(writer, leader_id.as_public_key().to_bech32_str().as_bytes())?;
+        }
+    }
+    write_u64(writer, block.total_input.0)?;
+    write_u64(writer, block.total_output.0)?;
+    write_u64(writer, block.transactions.len() as u64)?;
+    for transaction in block.transactions.values() {
+        write_transaction(writer, transaction)?;
+    }
+    Ok(())
+}
+
+fn read_block<R: Read>(reader: &mut R) -> io::Result<ExplorerBlock> {
+    let id = read_hash(reader)?;
+    let date = read_block_date(reader)?;
+    let chain_length = ChainLength::from(read_u32(reader)?);
+    let parent_hash = read_hash(reader)?;
+    let producer = match read_u8(reader)? {
+        0 => BlockProducer::None,
+        1 => BlockProducer::StakePool(read_str(reader)?),
+        2 => {
+            let bech32 = String::from_utf8(read_bytes(reader)?).map_err(invalid_data)?;
+            let public_key: PublicKey<Ed25519> =
+                PublicKey::try_from_bech32_str(&bech32).map_err(invalid_data)?;
+            BlockProducer::BftLeader(BftLeaderId::from(public_key))
+        }
+        tag => return Err(invalid_data(format!("invalid block producer tag {}", tag))),
+    };
+    let total_input = Value(read_u64(reader)?);
+    let total_output = Value(read_u64(reader)?);
+    let transactions = read_vec(reader, read_transaction)?
+        .into_iter()
+        .map(|transaction| (transaction.id, transaction))
+        .collect::<HashMap<_, _>>();
+
+    Ok(ExplorerBlock {
+        transactions,
+        id,
+        date,
+        chain_length,
+        parent_hash,
+        producer,
+        total_input,
+        total_output,
+    })
+}
+
+fn write_transaction<W: Write>(
+    writer: &mut W,
+    transaction: &ExplorerTransaction,
+) -> io::Result<()> {
+    write_hash(writer, &transaction.id)?;
+    write_u32(writer, transaction.offset_in_block)?;
+    write_u64(writer, transaction.inputs.len() as u64)?;
+    for input in &transaction.inputs {
+        write_address(writer, &input.address)?;
+        write_u64(writer, input.value.0)?;
+    }
+    write_u64(writer, transaction.outputs.len() as u64)?;
+    for output in &transaction.outputs {
+        write_address(writer, &output.address)?;
+        write_u64(writer, output.value.0)?;
+    }
+    write_option(writer, &transaction.certificate, write_certificate)
+}
+
+fn read_transaction<R: Read>(reader: &mut R) -> io::Result<ExplorerTransaction> {
+    let id = read_hash(reader)?;
+    let offset_in_block = read_u32(reader)?;
+    let inputs = read_vec(reader, |reader| {
+        Ok(ExplorerInput {
+            address: read_address(reader)?,
+            value: Value(read_u64(reader)?),
+        })
+    })?;
+    let outputs = read_vec(reader, |reader| {
+        Ok(ExplorerOutput {
+            address: read_address(reader)?,
+            value: Value(read_u64(reader)?),
+        })
+    })?;
+    let certificate = read_option(reader, read_certificate)?;
+
+    // only the pending transactions have unresolved inputs, they are not stored
+    Ok(ExplorerTransaction {
+        id,
+        inputs,
+        unresolved_inputs: Vec::new(),
+        outputs,
+        certificate,
+        offset_in_block,
+    })
+}
+
+fn write_certificate<W: Write>(writer: &mut W, certificate: &Certificate) -> io::Result<()> {
+    match certificate {
+        Certificate::StakeDelegation(c) => {
+            write_u8(writer, 0)?;
+            write_serialized(writer, c)
+        }
+        Certificate::OwnerStakeDelegation(c) => {
+            write_u8(writer, 1)?;
+            write_serialized(writer, c)
+        }
+        Certificate::PoolRegistration(c) => {
+            write_u8(writer, 2)?;
+            write_serialized(writer, c)
+        }
+        Certificate::PoolRetirement(c) => {
+            write_u8(writer, 3)?;
+            write_serialized(writer, c)
+        }
+        Certificate::PoolUpdate(c) => {
+            write_u8(writer, 4)?;
+            write_serialized(writer, c)
+        }
+        Certificate::VotePlan(c) => {
+            write_u8(writer, 5)?;
+            write_serialized(writer, c)
+        }
+        Certificate::VoteCast(c) => {
+            write_u8(writer, 6)?;
+            write_serialized(writer, c)
+        }
+        Certificate::VoteTally(c) => {
+            write_u8(writer, 7)?;
+            write_serialized(writer, c)
+        }
+        Certificate::EncryptedVoteTally(c) => {
+            write_u8(writer, 8)?;
+            write_serialized(writer, c)
+        }
+    }
+}
+
+fn read_certificate<R: Read>(reader: &mut R) -> io::Result<Certificate> {
+    Ok(match read_u8(reader)? {
+        0 => Certificate::StakeDelegation(read_readable(reader)?),
+        1 => Certificate::OwnerStakeDelegation(read_readable(reader)?),
+        2 => Certificate::PoolRegistration(read_readable(reader)?),
+        3 => Certificate::PoolRetirement(read_readable(reader)?),
+        4 => Certificate::PoolUpdate(read_readable(reader)?),
+        5 => Certificate::VotePlan(read_readable(reader)?),
+        6 => Certificate::VoteCast(read_readable(reader)?),
+        7 => Certificate::VoteTally(read_readable(reader)?),
+        8 => Certificate::EncryptedVoteTally(read_readable(reader)?),
+        tag => return Err(invalid_data(format!("invalid certificate tag {}", tag))),
+    })
+}
+
+fn write_address<W: Write>(writer: &mut W, address: &ExplorerAddress) -> io::Result<()> {
+    match address {
+        ExplorerAddress::New(address) => {
+            write_u8(writer, 0)?;
+            write_bytes(writer, &address.to_bytes())
+        }
+        ExplorerAddress::Old(address) => {
+            write_u8(writer, 1)?;
+            write_str(writer, address)
+        }
+    }
+}
+
+fn read_address<R: Read>(reader: &mut R) -> io::Result<ExplorerAddress> {
+    match read_u8(reader)? {
+        0 => Address::from_bytes(&read_bytes(reader)?)
+            .map(ExplorerAddress::New)
+            .map_err(invalid_data),
+        1 => read_str(reader).map(ExplorerAddress::Old),
+        tag => Err(invalid_data(format!("invalid address tag {}", tag))),
+    }
+}
+
+fn write_delegation<W: Write>(writer: &mut W, delegation: &DelegationType) -> io::Result<()> {
+    match delegation {
+        DelegationType::NonDelegated => write_u8(writer, 0),
+        DelegationType::Full(pool_id) => {
+            write_u8(writer, 1)?;
+            write_str(writer, pool_id)
+        }
+        DelegationType::Ratio(ratio) => {
+            write_u8(writer, 2)?;
+            write_u8(writer, ratio.parts())?;
+            write_u64(writer, ratio.pools().len() as u64)?;
+            for (pool_id, parts) in ratio.pools() {
+                write_str(writer, pool_id)?;
+                write_u8(writer, *parts)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn read_delegation<R: Read>(reader: &mut R) -> io::Result<DelegationType> {
+    match read_u8(reader)? {
+        0 => Ok(DelegationType::NonDelegated),
+        1 => Ok(DelegationType::Full(read_str(reader)?)),
+        2 => {
+            let parts = read_u8(reader)?;
+            let pools = read_vec(reader, |reader| {
+                let pool_id: PoolId = read_str(reader)?;
+                Ok((pool_id, read_u8(reader)?))
+            })?;
+            DelegationRatio::new(parts, pools)
+                .map(DelegationType::Ratio)
+                .ok_or_else(|| invalid_data("invalid delegation ratio"))
+        }
+        tag => Err(invalid_data(format!("invalid delegation tag {}", tag))),
+    }
+}
+
+fn write_vote_plan<W: Write>(writer: &mut W, vote_plan: &ExplorerVotePlan) -> io::Result<()> {
+    write_str(writer, &vote_plan.id)?;
+    write_block_date(writer, &vote_plan.vote_start)?;
+    write_block_date(writer, &vote_plan.vote_end)?;
+    write_block_date(writer, &vote_plan.committee_end)?;
+    write_payload_type(writer, &vote_plan.payload_type)?;
+    write_u64(writer, vote_plan.proposals.len() as u64)?;
+    for (index, proposal) in vote_plan.proposals.iter().enumerate() {
+        write_str(writer, &proposal.proposal_id)?;
+        write_options(writer, &proposal.options)?;
+        write_option(writer, &proposal.tally, |writer, tally| match tally {
+            ExplorerVoteTally::Public { results, options } => {
+                write_u8(writer, 0)?;
+                write_weights(writer, results)?;
+                write_options(writer, options)
+            }
+            ExplorerVoteTally::Private { results, options } => {
+                write_u8(writer, 1)?;
+                write_option(writer, results, |writer, results| {
+                    write_weights(writer, results)
+                })?;
+                write_options(writer, options)
+            }
+        })?;
+        write_hamt(writer, &proposal.votes, |writer, voter, vote| {
+            write_address(writer, voter)?;
+            match vote {
+                ExplorerVote::Public(choice) => {
+                    write_u8(writer, 0)?;
+                    write_u8(writer, choice.as_byte())
+                }
+                ExplorerVote::Private {
+                    proof,
+                    encrypted_vote,
+                } => {
+                    // the encrypted votes are stored as the vote cast
+                    // certificates they come from
+                    write_u8(writer, 1)?;
+                    let vote_cast = VoteCast::new(
+                        vote_plan.id.clone(),
+                        index as u8,
+                        Payload::Private {
+                            encrypted_vote: encrypted_vote.clone(),
+                            proof: proof.clone(),
+                        },
+                    );
+                    write_serialized(writer, &vote_cast)
+                }
+            }
+        })?;
+    }
+    Ok(())
+}
+
+fn read_vote_plan<R: Read>(reader: &mut R) -> io::Result<ExplorerVotePlan> {
+    let id = read_str(reader)?;
+    let vote_start = read_block_date(reader)?;
+    let vote_end = read_block_date(reader)?;
+    let committee_end = read_block_date(reader)?;
+    let payload_type = read_payload_type(reader)?;
+    let proposals = read_vec(reader, |reader| {
+        let proposal_id = read_str(reader)?;
+        let options = read_options(reader)?;
+        let tally = read_option(reader, |reader| match read_u8(reader)? {
+            0 => Ok(ExplorerVoteTally::Public {
+                results: read_weights(reader)?,
+                options: read_options(reader)?,
+            }),
+            1 => Ok(ExplorerVoteTally::Private {
+                results: read_option(reader, read_weights)?,
+                options: read_options(reader)?,
+            }),
+            tag => Err(invalid_data(format!("invalid vote tally tag {}", tag))),
+        })?;
+        let votes = read_hamt(reader, |reader| {
+            let voter = read_address(reader)?;
+            let vote = match read_u8(reader)? {
+                0 => ExplorerVote::Public(Choice::new(read_u8(reader)?)),
+                1 => {
+                    let vote_cast: VoteCast = read_readable(reader)?;
+                    match vote_cast.payload() {
+                        Payload::Private {
+                            encrypted_vote,
+                            proof,
+                        } => ExplorerVote::Private {
+                            proof: proof.clone(),
+                            encrypted_vote: encrypted_vote.clone(),
+                        },
+                        Payload::Public { .. } => {
+                            return Err(invalid_data("expected a private vote"))
+                        }
+                    }
+                }
+                tag => return Err(invalid_data(format!("invalid vote tag {}", tag))),
+            };
+            Ok((voter, vote))
+        })?;
+        Ok(ExplorerVoteProposal {
+            proposal_id,
+            options,
+            tally,
+            votes,
+        })
+    })?;
+
+    Ok(ExplorerVotePlan {
+        id,
+        vote_start,
+        vote_end,
+        committee_end,
+        payload_type,
+        proposals,
+    })
+}
+
+fn write_payload_type<W: Write>(writer: &mut W, payload_type: &PayloadType) -> io::Result<()> {
+    match payload_type {
+        PayloadType::Public => write_u8(writer, 0),
+        PayloadType::Private => write_u8(writer, 1),
+    }
+}
+
+fn read_payload_type<R: Read>(reader: &mut R) -> io::Result<PayloadType> {
+    match read_u8(reader)? {
+        0 => Ok(PayloadType::Public),
+        1 => Ok(PayloadType::Private),
+        tag => Err(invalid_data(format!("invalid payload type tag {}", tag))),
+    }
+}
+
+fn write_options<W: Write>(writer: &mut W, options: &Options) -> io::Result<()> {
+    write_u8(writer, options.choice_range().end)
+}
+
+fn read_options<R: Read>(reader: &mut R) -> io::Result<Options> {
+    Options::new_length(read_u8(reader)?).map_err(|e| invalid_data(format!("{:?}", e)))
+}
+
+fn write_weights<W: Write>(writer: &mut W, weights: &[Weight]) -> io::Result<()> {
+    write_u64(writer, weights.len() as u64)?;
+    for weight in weights {
+        write_str(writer, weight)?;
+    }
+    Ok(())
+}
+
+fn read_weights<R: Read>(reader: &mut R) -> io::Result<Vec<Weight>> {
+    read_vec(reader, |reader| {
+        let weight: u64 = read_str(reader)?;
+        Ok(Weight::from(weight))
+    })
+}
+
+fn write_block_date<W: Write>(writer: &mut W, date: &BlockDate) -> io::Result<()> {
+    write_u32(writer, date.epoch)?;
+    write_u32(writer, date.slot_id)
+}
+
+fn read_block_date<R: Read>(reader: &mut R) -> io::Result<BlockDate> {
+    Ok(BlockDate {
+        epoch: read_u32(reader)?,
+        slot_id: read_u32(reader)?,
+    })
+}
+
+/* ---------------- Collections -------------------------------------------- */
+
+fn write_hamt<W, K, V, F>(writer: &mut W, hamt: &Hamt<K, V>, mut write: F) -> io::Result<()>
+where
+    W: Write,
+    K: Hash + Eq + Clone,
+    F: FnMut(&mut W, &K, &V) -> io::Result<()>,
+{
+    write_u64(writer, hamt.iter().count() as u64)?;
+    for (key, value) in hamt.iter() {
+        write(writer, key, &**value)?;
+    }
+    Ok(())
+}
+
+fn read_hamt<R, K, V, F>(reader: &mut R, mut read: F) -> io::Result<Hamt<K, V>>
+where
+    R: Read,
+    K: Hash + Eq + Clone,
+    F: FnMut(&mut R) -> io::Result<(K, V)>,
+{
+    let len = read_u64(reader)?;
+    let mut hamt = Hamt::new();
+    for _ in 0..len {
+        let (key, value) = read(reader)?;
+        hamt = hamt
+            .insert(key, Arc::new(value))
+            .map_err(|_| invalid_data("duplicated entry in the explorer state"))?;
+    }
+    Ok(hamt)
+}
+
+fn write_sequence<W, T, F>(
+    writer: &mut W,
+    sequence: &PersistentSequence<T>,
+    mut write: F,
+) -> io::Result<()>
+where
+    W: Write,
+    F: FnMut(&mut W, &T) -> io::Result<()>,
+{
+    write_u64(writer, sequence.len())?;
+    for i in 0..sequence.len() {
+        let element = sequence
+            .get(i)
+            .ok_or_else(|| invalid_data("missing element in a sequence"))?;
+        write(writer, element)?;
+    }
+    Ok(())
+}
+
+fn read_sequence<R, T, F>(reader: &mut R, read: F) -> io::Result<PersistentSequence<T>>
+where
+    R: Read,
+    F: FnMut(&mut R) -> io::Result<T>,
+{
+    Ok(read_vec(reader, read)?
+        .into_iter()
+        .fold(PersistentSequence::new(), |sequence, element| {
+            sequence.append(element)
+        }))
+}
+
+fn read_vec<R, T, F>(reader: &mut R, mut read: F) -> io::Result<Vec<T>>
+where
+    R: Read,
+    F: FnMut(&mut R) -> io::Result<T>,
+{
+    let len = read_u64(reader)?;
+    (0..len).map(|_| read(reader)).collect()
+}
+
+fn write_option<W, T, F>(writer: &mut W, option: &Option<T>, mut write: F) -> io::Result<()>
+where
+    W: Write,
+    F: FnMut(&mut W, &T) -> io::Result<()>,
+{
+    match option {
+        None => write_u8(writer, 0),
+        Some(value) => {
+            write_u8(writer, 1)?;
+            write(writer, value)
+        }
+    }
+}
+
+fn read_option<R, T, F>(reader: &mut R, mut read: F) -> io::Result<Option<T>>
+where
+    R: Read,
+    F: FnMut(&mut R) -> io::Result<T>,
+{
+    match read_u8(reader)? {
+        0 => Ok(None),
+        1 => read(reader).map(Some),
+        tag => Err(invalid_data(format!("invalid optional tag {}", tag))),
+    }
+}
+
+/* ---------------- Primitives --------------------------------------------- */
+
+fn write_serialized<W, T>(writer: &mut W, value: &T) -> io::Result<()>
+where
+    W: Write,
+    T: Serialize,
+    T::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let bytes = value.serialize_as_vec().map_err(invalid_data)?;
+    write_bytes(writer, &bytes)
+}
+
+fn read_readable<R: Read, T: Readable>(reader: &mut R) -> io::Result<T> {
+    let bytes = read_bytes(reader)?;
+    T::read(&mut ReadBuf::from(&bytes[..])).map_err(invalid_data)
+}
+
+/// the identifiers without a binary decoder at hand are stored
+/// in their text representation
+fn write_str<W: Write, T: Display>(writer: &mut W, value: &T) -> io::Result<()> {
+    write_bytes(writer, value.to_string().as_bytes())
+}
+
+fn read_str<R, T>(reader: &mut R) -> io::Result<T>
+where
+    R: Read,
+    T: FromStr,
+    T::Err: Display,
+{
+    let s = String::from_utf8(read_bytes(reader)?).map_err(invalid_data)?;
+    s.parse().map_err(|e: T::Err| invalid_data(e.to_string()))
+}
+
+fn write_hash<W: Write>(writer: &mut W, hash: &HeaderHash) -> io::Result<()> {
+    writer.write_all(hash.as_bytes())
+}
+
+fn read_hash<R: Read>(reader: &mut R) -> io::Result<HeaderHash> {
+    let mut bytes = [0; 32];
+    reader.read_exact(&mut bytes)?;
+    HeaderHash::deserialize(&bytes[..])
+}
+
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    write_u64(writer, bytes.len() as u64)?;
+    writer.write_all(bytes)
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let len = read_u64(reader)?;
+    let mut bytes = Vec::new();
+    Read::take(&mut *reader, len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+fn write_u8<W: Write>(writer: &mut W, value: u8) -> io::Result<()> {
+    writer.write_all(&[value])
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_be_bytes())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+fn write_u64<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_be_bytes())
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_addr::{Discrimination, Kind};
+    use chain_crypto::SecretKey;
+    use chain_impl_mockchain::{
+        certificate::{PoolRegistration, PoolRetirement, StakeDelegation, VotePlanId},
+        transaction::UnspecifiedAccountIdentifier,
+    };
+    use quickcheck_macros::quickcheck;
+    use std::collections::BTreeMap;
+
+    /// encode the value, decode it and encode the decoded value again, the
+    /// whole encoding must be read and both encodings must be the same
+    fn assert_round_trip<T>(
+        value: &T,
+        write: impl Fn(&mut Vec<u8>, &T) -> io::Result<()>,
+        read: impl Fn(&mut io::Cursor<Vec<u8>>) -> io::Result<T>,
+    ) -> T {
+        let mut bytes = Vec::new();
+        write(&mut bytes, value).unwrap();
+        let reader = &mut io::Cursor::new(bytes.clone());
+        let decoded = read(reader).unwrap();
+        assert_eq!(reader.position(), bytes.len() as u64);
+        let mut encoded_again = Vec::new();
+        write(&mut encoded_again, &decoded).unwrap();
+        assert_eq!(bytes, encoded_again);
+        decoded
+    }
+
+    fn hash(byte: u8) -> HeaderHash {
+        jormungandr_lib::crypto::hash::Hash::from([byte; 32]).into_hash()
+    }
+
+    fn pool_id(byte: u8) -> PoolId {
+        hex::encode([byte; 32]).parse().unwrap()
+    }
+
+    fn account() -> ExplorerAddress {
+        let key = SecretKey::<Ed25519>::generate(rand::rngs::OsRng).to_public();
+        ExplorerAddress::New(Address(Discrimination::Test, Kind::Account(key)))
+    }
+
+    /// a block with a single transaction, the transactions of a block are
+    /// written in the order of a `HashMap`
+    fn block(chain_length: u32, producer: BlockProducer) -> ExplorerBlock {
+        let transaction = ExplorerTransaction {
+            id: hash(100 + chain_length as u8),
+            inputs: vec![ExplorerInput {
+                address: account(),
+                value: Value(10),
+            }],
+            unresolved_inputs: Vec::new(),
+            outputs: vec![ExplorerOutput {
+                address: account(),
+                value: Value(9),
+            }],
+            certificate: Some(Certificate::StakeDelegation(StakeDelegation {
+                account_id: UnspecifiedAccountIdentifier::from([chain_length as u8; 32]),
+                delegation: DelegationType::Full(pool_id(1)),
+            })),
+            offset_in_block: 0,
+        };
+        ExplorerBlock {
+            transactions: vec![(transaction.id, transaction)].into_iter().collect(),
+            id: hash(chain_length as u8),
+            date: BlockDate {
+                epoch: chain_length / 10,
+                slot_id: chain_length % 10,
+            },
+            chain_length: ChainLength::from(chain_length),
+            parent_hash: hash(chain_length.wrapping_sub(1) as u8),
+            producer,
+            total_input: Value(10),
+            total_output: Value(9),
+        }
+    }
+
+    fn vote_plan() -> ExplorerVotePlan {
+        let options = Options::new_length(3).unwrap();
+        let votes = Hamt::new()
+            .insert(account(), Arc::new(ExplorerVote::Public(Choice::new(1))))
+            .unwrap();
+        ExplorerVotePlan {
+            id: hex::encode([1; 32]).parse::<VotePlanId>().unwrap(),
+            vote_start: BlockDate {
+                epoch: 1,
+                slot_id: 0,
+            },
+            vote_end: BlockDate {
+                epoch: 2,
+                slot_id: 0,
+            },
+            committee_end: BlockDate {
+                epoch: 3,
+                slot_id: 0,
+            },
+            payload_type: PayloadType::Public,
+            proposals: vec![
+                ExplorerVoteProposal {
+                    proposal_id: hex::encode([2; 32]).parse().unwrap(),
+                    options: options.clone(),
+                    tally: Some(ExplorerVoteTally::Public {
+                        results: vec![Weight::from(1u64), Weight::from(0u64), Weight::from(0u64)],
+                        options: options.clone(),
+                    }),
+                    votes,
+                },
+                ExplorerVoteProposal {
+                    proposal_id: hex::encode([3; 32]).parse().unwrap(),
+                    options: options.clone(),
+                    tally: Some(ExplorerVoteTally::Private {
+                        results: None,
+                        options,
+                    }),
+                    votes: Hamt::new(),
+                },
+            ],
+        }
+    }
+
+    fn stake_distribution() -> ExplorerStakeDistribution {
+        ExplorerStakeDistribution {
+            pools: vec![(pool_id(1), Value(100)), (pool_id(2), Value(50))],
+        }
+    }
+
+    fn epoch_rewards() -> ExplorerEpochRewards {
+        let pools: BTreeMap<_, _> = vec![(
+            pool_id(1),
+            ExplorerPoolRewards {
+                taxed: Value(1),
+                distributed: Value(9),
+            },
+        )]
+        .into_iter()
+        .collect();
+        ExplorerEpochRewards {
+            drawn: Value(12),
+            fees: Value(2),
+            treasury: Value(2),
+            pools,
+        }
+    }
+
+    fn empty_state() -> State {
+        State {
+            transactions: Transactions::new(),
+            blocks: Blocks::new(),
+            addresses: Addresses::new(),
+            epochs: Epochs::new(),
+            chain_lengths: ChainLengths::new(),
+            stake_pool_data: StakePool::new(),
+            stake_pool_blocks: StakePoolBlocks::new(),
+            vote_plans: VotePlans::new(),
+            delegations: Delegations::new(),
+            stake_distributions: StakeDistributions::new(),
+            epoch_rewards: EpochRewards::new(),
+            account_rewards: AccountRewards::new(),
+        }
+    }
+
+    #[test]
+    fn blocks_should_round_trip() {
+        let leader_id =
+            BftLeaderId::from(SecretKey::<Ed25519>::generate(rand::rngs::OsRng).to_public());
+        let producers = vec![
+            BlockProducer::None,
+            BlockProducer::StakePool(pool_id(1)),
+            BlockProducer::BftLeader(leader_id),
+        ];
+        for (chain_length, producer) in producers.into_iter().enumerate() {
+            let block = block(chain_length as u32, producer);
+            let decoded = assert_round_trip(&block, write_block, read_block);
+            assert_eq!(decoded.id, block.id);
+            assert_eq!(decoded.chain_length, block.chain_length);
+            assert!(block
+                .transactions
+                .keys()
+                .all(|id| decoded.transactions.contains_key(id)));
+        }
+    }
+
+    #[quickcheck]
+    fn certificates_should_round_trip(certificate: Certificate) {
+        assert_round_trip(&certificate, write_certificate, read_certificate);
+    }
+
+    #[test]
+    fn addresses_should_round_trip() {
+        assert_round_trip(&account(), write_address, read_address);
+    }
+
+    #[test]
+    fn delegations_should_round_trip() {
+        let delegations = vec![
+            DelegationType::NonDelegated,
+            DelegationType::Full(pool_id(1)),
+            DelegationType::Ratio(
+                DelegationRatio::new(4, vec![(pool_id(1), 3), (pool_id(2), 1)]).unwrap(),
+            ),
+        ];
+        for delegation in delegations {
+            assert_round_trip(&delegation, write_delegation, read_delegation);
+        }
+    }
+
+    #[test]
+    fn vote_plans_should_round_trip() {
+        let decoded = assert_round_trip(&vote_plan(), write_vote_plan, read_vote_plan);
+        assert_eq!(decoded.proposals.len(), 2);
+        assert_eq!(decoded.proposals[0].votes.iter().count(), 1);
+    }
+
+    #[test]
+    fn stake_distributions_should_round_trip() {
+        let decoded = assert_round_trip(
+            &stake_distribution(),
+            write_stake_distribution,
+            read_stake_distribution,
+        );
+        assert_eq!(decoded.pools.len(), 2);
+    }
+
+    #[test]
+    fn epoch_rewards_should_round_trip() {
+        let decoded = assert_round_trip(&epoch_rewards(), write_epoch_rewards, read_epoch_rewards);
+        assert_eq!(decoded.drawn, Value(12));
+        assert_eq!(decoded.pools.len(), 1);
+    }
+
+    #[quickcheck]
+    fn states_should_round_trip(registration: PoolRegistration, retirement: PoolRetirement) {
+        let address = account();
+        let pool_id = registration.to_id();
+        let vote_plan = vote_plan();
+        let state = State {
+            addresses: Addresses::new()
+                .insert(
+                    address.clone(),
+                    Arc::new(PersistentSequence::new().append(hash(1))),
+                )
+                .unwrap(),
+            stake_pool_data: StakePool::new()
+                .insert(
+                    pool_id.clone(),
+                    Arc::new(StakePoolData {
+                        registration,
+                        retirement: Some(retirement),
+                    }),
+                )
+                .unwrap(),
+            stake_pool_blocks: StakePoolBlocks::new()
+                .insert(
+                    pool_id.clone(),
+                    Arc::new(PersistentSequence::new().append(hash(2))),
+                )
+                .unwrap(),
+            vote_plans: VotePlans::new()
+                .insert(vote_plan.id.clone(), Arc::new(vote_plan))
+                .unwrap(),
+            delegations: Delegations::new()
+                .insert(address.clone(), Arc::new(DelegationType::Full(pool_id)))
+                .unwrap(),
+            stake_distributions: StakeDistributions::new()
+                .insert(1, Arc::new(stake_distribution()))
+                .unwrap(),
+            epoch_rewards: EpochRewards::new()
+                .insert(1, Arc::new(epoch_rewards()))
+                .unwrap(),
+            account_rewards: AccountRewards::new()
+                .insert(
+                    address,
+                    Arc::new(PersistentSequence::new().append((1, Value(9)))),
+                )
+                .unwrap(),
+            ..empty_state()
+        };
+
+        assert_round_trip(&state, write_state, read_state);
+    }
+
+    #[test]
+    fn stored_blocks_should_be_loaded_with_the_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StableStore::new(dir.path()).unwrap();
+        store.clear_blocks().unwrap();
+        let blocks: Vec<_> = (0..3)
+            .map(|chain_length| Arc::new(block(chain_length, BlockProducer::None)))
+            .collect();
+        store.store_blocks(&blocks[..2]).unwrap();
+        store.store_blocks(&blocks[2..]).unwrap();
+        // the blocks must follow the stored ones
+        assert!(store.store_blocks(&blocks[..1]).is_err());
+        let last = &blocks[2];
+        store
+            .store(last.chain_length, last.id, &empty_state())
+            .unwrap();
+
+        let store = StableStore::new(dir.path()).unwrap();
+        let state = store.load(last.chain_length, last.id).unwrap();
+        assert_eq!(store.stored_blocks(), 3);
+        assert_eq!(
+            state.epochs.lookup(&0).map(|epoch| epoch.last_block),
+            Some(last.id)
+        );
+        assert_eq!(
+            store.block_hash_range(ChainLength::from(1), ChainLength::from(5)),
+            vec![
+                (blocks[1].id, ChainLength::from(1)),
+                (blocks[2].id, ChainLength::from(2))
+            ]
+        );
+
+        let transaction_ids: Vec<_> = blocks
+            .iter()
+            .flat_map(|block| block.transactions.keys().copied())
+            .collect();
+        for (block, transaction_id) in blocks.iter().zip(&transaction_ids) {
+            assert!(store.contains_block(&block.id));
+            assert_eq!(store.block_id(block.chain_length), Some(block.id));
+            assert_eq!(
+                store.find_block_by_transaction(transaction_id),
+                Some(block.id)
+            );
+            let loaded = store.load_block(&block.id).unwrap().unwrap();
+            assert_eq!(loaded.chain_length, block.chain_length);
+        }
+        assert_eq!(
+            store.load_transactions(&transaction_ids).unwrap().len(),
+            transaction_ids.len()
+        );
+    }
+
+    #[test]
+    fn blocks_following_the_loaded_state_should_be_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StableStore::new(dir.path()).unwrap();
+        store.clear_blocks().unwrap();
+        let blocks: Vec<_> = (0..3)
+            .map(|chain_length| Arc::new(block(chain_length, BlockProducer::None)))
+            .collect();
+        store.store_blocks(&blocks[..2]).unwrap();
+        let stored = &blocks[1];
+        store
+            .store(stored.chain_length, stored.id, &empty_state())
+            .unwrap();
+        // the state of the last block could not be written
+        store.store_blocks(&blocks[2..]).unwrap();
+
+        let store = StableStore::new(dir.path()).unwrap();
+        store.load(stored.chain_length, stored.id).unwrap();
+        assert_eq!(store.stored_blocks(), 2);
+        assert!(!store.contains_block(&blocks[2].id));
+        store.store_blocks(&blocks[2..]).unwrap();
+        assert_eq!(store.block_id(ChainLength::from(2)), Some(blocks[2].id));
+    }
+}
@@ -1,22 +1,53 @@
-use crate::fragment::FragmentId;
+use crate::{
+    events::NodeEvents,
+    fragment::{Fragment, FragmentId},
+};
+use chain_core::property::Fragment as _;
 use jormungandr_lib::{
     crypto::hash::Hash,
-    interfaces::{BlockDate, FragmentLog, FragmentOrigin, FragmentStatus},
+    interfaces::{BlockDate, FragmentLog, FragmentOrigin, FragmentStatus, NodeEvent},
 };
 use lru::LruCache;
 use std::collections::HashMap;
 
+/// number of rejected fragments whose contents are kept
+const REJECTED_CONTENTS_MAX_ENTRIES: usize = 1024;
+
 pub struct Logs {
     entries: LruCache<Hash, (FragmentLog, Option<BlockDate>)>,
+    /// contents of the last rejected fragments, the pending fragments
+    /// contents are in the pools
+    rejected_contents: LruCache<Hash, Fragment>,
+    events: NodeEvents,
 }
 
 impl Logs {
-    pub fn new(max_entries: usize) -> Self {
+    pub fn new(max_entries: usize, events: NodeEvents) -> Self {
         Logs {
             entries: LruCache::new(max_entries),
+            rejected_contents: LruCache::new(REJECTED_CONTENTS_MAX_ENTRIES),
+            events,
         }
     }
 
+    fn publish_status(&self, log: &FragmentLog) {
+        self.events.publish(|| NodeEvent::FragmentStatus {
+            fragment_id: *log.fragment_id(),
+            status: log.status().clone(),
+        });
+    }
+
+    /// change the number of logs kept, the least recently used logs are
+    /// dropped if there are more logs than the new limit
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.entries.resize(max_entries);
+    }
+
+    /// number of fragment logs currently kept
+    pub fn entries_count(&self) -> usize {
+        self.entries.len()
+    }
+
     pub fn exists(&self, fragment_id: FragmentId) -> bool {
         let fragment_id: Hash = fragment_id.into();
         self.entries.contains(&fragment_id)
@@ -36,6 +67,7 @@ impl Logs {
         if self.entries.contains(&fragment_id) {
             false
         } else {
+            self.publish_status(&log);
             self.entries.put(fragment_id, (log, None));
             true
         }
@@ -62,6 +94,10 @@ impl Logs {
                     tracing::debug!("the fragment log update was refused: cannot mark the fragment as invalid if it was already committed to a block");
                 } else {
                     *date = Some(ledger_date);
+                    self.events.publish(|| NodeEvent::FragmentStatus {
+                        fragment_id,
+                        status: entry.status().clone(),
+                    });
                 }
             }
             None => {
@@ -78,11 +114,29 @@ impl Logs {
                 // state transition.
                 let mut entry = FragmentLog::new(fragment_id.into_hash(), FragmentOrigin::Network);
                 entry.modify(status);
+                self.publish_status(&entry);
                 self.entries.put(fragment_id, (entry, Some(ledger_date)));
             }
         }
     }
 
+    /// mark the fragment as rejected and keep its contents for a while
+    pub fn reject(&mut self, fragment: Fragment, reason: String, ledger_date: BlockDate) {
+        let fragment_id = fragment.id();
+        self.rejected_contents.put(fragment_id.into(), fragment);
+        self.modify(
+            fragment_id,
+            FragmentStatus::Rejected { reason },
+            ledger_date,
+        );
+    }
+
+    /// contents of the fragment if it was rejected recently
+    pub fn rejected_contents(&self, fragment_id: FragmentId) -> Option<&Fragment> {
+        let fragment_id: Hash = fragment_id.into();
+        self.rejected_contents.peek(&fragment_id)
+    }
+
     pub fn modify_all(
         &mut self,
         fragment_ids: impl IntoIterator<Item = FragmentId>,
@@ -111,6 +165,11 @@ impl Logs {
         result
     }
 
+    pub fn get(&self, fragment_id: FragmentId) -> Option<&FragmentLog> {
+        let fragment_id: Hash = fragment_id.into();
+        self.entries.peek(&fragment_id).map(|(log, _date)| log)
+    }
+
     pub fn logs(&self) -> impl Iterator<Item = &FragmentLog> {
         self.entries.iter().map(|(_, (log, _date))| log)
     }
@@ -6,7 +6,7 @@ pub mod selection;
 
 pub use self::entry::PoolEntry;
 pub use self::logs::Logs;
-pub use self::pool::Pools;
+pub use self::pool::{Pools, EXPIRED_REJECTION_REASON};
 pub use self::process::Process;
 
 pub use crate::blockcfg::{Fragment, FragmentId};
@@ -1,20 +1,24 @@
 use crate::{
     blockcfg::{ApplyBlockLedger, LedgerParameters},
+    blockchain::Ref,
     fragment::{
-        selection::{FragmentSelectionAlgorithm, FragmentSelectionAlgorithmParams, OldestFirst},
+        selection::{
+            FeePriority, FragmentSelectionAlgorithm, FragmentSelectionAlgorithmParams, OldestFirst,
+            SelectionReport,
+        },
         Fragment, FragmentId, Logs,
     },
     intercom::{NetworkMsg, PropagateMsg},
     utils::async_msg::MessageBox,
 };
 use chain_core::property::Fragment as _;
-use chain_impl_mockchain::{fragment::Contents, transaction::Transaction};
+use chain_impl_mockchain::{fee::LinearFee, fragment::Contents, transaction::Transaction};
 use futures::channel::mpsc::SendError;
 use futures::sink::SinkExt;
 use jormungandr_lib::{
     interfaces::{
-        BlockDate, FragmentLog, FragmentOrigin, FragmentRejectionReason, FragmentStatus,
-        FragmentsProcessingSummary, PersistentFragmentLog, RejectedFragmentInfo,
+        BlockDate, EvictionPolicy, FragmentLog, FragmentOrigin, FragmentRejectionReason,
+        FragmentStatus, FragmentsProcessingSummary, PersistentFragmentLog, RejectedFragmentInfo,
     },
     time::SecondsSinceUnixEpoch,
 };
@@ -23,6 +27,13 @@ use thiserror::Error;
 
 use std::fs::File;
 use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// the start of the rejection reason of the fragments removed from the pools
+/// after pending for longer than the fragment TTL
+pub const EXPIRED_REJECTION_REASON: &str =
+    "fragment has been pending in the mempool for longer than";
 
 pub struct Pools {
     logs: Logs,
@@ -40,6 +51,8 @@ pub enum Error {
 impl Pools {
     pub fn new(
         max_entries: usize,
+        eviction_policy: EvictionPolicy,
+        account_max_entries: Option<usize>,
         n_pools: usize,
         logs: Logs,
         network_msg_box: MessageBox<NetworkMsg>,
@@ -49,7 +62,7 @@ impl Pools {
         // the fragments dissemination protocol
         let n_pools = std::cmp::max(1, n_pools);
         let pools = (0..n_pools)
-            .map(|_| internal::Pool::new(max_entries))
+            .map(|_| internal::Pool::new(max_entries, eviction_policy, account_max_entries))
             .collect();
         Pools {
             logs,
@@ -63,6 +76,72 @@ impl Pools {
         &mut self.logs
     }
 
+    /// Changes the size limits of the pools. The pools holding more fragments than
+    /// the new limit are trimmed according to the eviction policy, the fragments
+    /// evicted are marked as rejected in the logs.
+    pub fn set_limits(
+        &mut self,
+        max_entries: usize,
+        account_max_entries: Option<usize>,
+        date: BlockDate,
+    ) {
+        let mut evicted = Vec::new();
+        for pool in &mut self.pools {
+            evicted.extend(pool.set_limits(max_entries, account_max_entries));
+        }
+        self.reject_evicted(evicted, date);
+    }
+
+    fn reject_evicted(&mut self, evicted: Vec<Fragment>, date: BlockDate) {
+        for fragment in evicted {
+            // the fragment may still be pending in the pool of another leader
+            if !self.pools.iter().any(|pool| pool.contains(&fragment.id())) {
+                self.logs
+                    .reject(fragment, "evicted from the mempool".to_string(), date);
+            }
+        }
+    }
+
+    /// Returns the contents of the fragment if it is pending in one of the pools.
+    pub fn fragment(&self, fragment_id: FragmentId) -> Option<&Fragment> {
+        self.pools.iter().find_map(|pool| pool.get(&fragment_id))
+    }
+
+    /// Returns the fragments pending in the pools with the given ids, or all the
+    /// pending fragments if no id is given. A fragment pending in several pools
+    /// is returned once.
+    pub fn pending_fragments(&self, fragment_ids: Vec<FragmentId>) -> Vec<Fragment> {
+        if !fragment_ids.is_empty() {
+            return fragment_ids
+                .into_iter()
+                .filter_map(|fragment_id| self.fragment(fragment_id).cloned())
+                .collect();
+        }
+        let mut seen = HashSet::new();
+        self.pools
+            .iter()
+            .flat_map(|pool| pool.fragments())
+            .filter(|fragment| seen.insert(fragment.id()))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the contents of the fragment if it is pending in one of the pools
+    /// or was rejected recently.
+    pub fn fragment_contents(&self, fragment_id: FragmentId) -> Option<&Fragment> {
+        self.fragment(fragment_id)
+            .or_else(|| self.logs.rejected_contents(fragment_id))
+    }
+
+    /// Returns the number of fragments pending in the fullest pool.
+    pub fn pending_count(&self) -> usize {
+        self.pools
+            .iter()
+            .map(|pool| pool.entries_count())
+            .max()
+            .unwrap_or(0)
+    }
+
     /// Sets the persistent log to a file.
     /// The file must be opened for writing.
     pub fn set_persistent_log(&mut self, file: File) {
@@ -84,6 +163,8 @@ impl Pools {
         origin: FragmentOrigin,
         fragments: Vec<Fragment>,
         fail_fast: bool,
+        fees: &LinearFee,
+        date: BlockDate,
     ) -> (Vec<Fragment>, FragmentsProcessingSummary) {
         use bincode::Options;
 
@@ -157,13 +238,47 @@ impl Pools {
         }
 
         let mut accepted = HashSet::new();
+        let mut evicted = Vec::new();
+        let received_at = SystemTime::now();
 
         for (pool_number, pool) in self.pools.iter_mut().enumerate() {
             let span = tracing::trace_span!("pool_insert_fragment", pool_number=?pool_number);
             let _enter = span.enter();
 
-            let mut fragments = filtered_fragments.clone().into_iter();
-            let new_fragments = pool.insert_all(fragments.by_ref());
+            let mut new_fragments = Vec::new();
+
+            for fragment in filtered_fragments.iter().cloned() {
+                let id = fragment.id();
+                match pool.insert(fragment.clone(), fees, received_at) {
+                    Ok(evicted_fragment) => {
+                        if let Some(evicted_fragment) = evicted_fragment {
+                            let evicted_id = evicted_fragment.id();
+                            tracing::debug!(fragment_id=?evicted_id, "evicted fragment from the pool");
+                            evicted.push(evicted_fragment);
+                        }
+                        new_fragments.push(fragment);
+                    }
+                    Err(internal::InsertError::AlreadyInPool) => (),
+                    Err(internal::InsertError::PoolOverflow) => {
+                        tracing::debug!(fragment_id=?id, "rejecting fragment due to pool overflow");
+                        rejected.push(RejectedFragmentInfo {
+                            id,
+                            reason: FragmentRejectionReason::PoolOverflow { pool_number },
+                        })
+                    }
+                    Err(internal::InsertError::AccountLimitReached) => {
+                        tracing::debug!(
+                            fragment_id=?id,
+                            "rejecting fragment due to the account pending fragments limit"
+                        );
+                        rejected.push(RejectedFragmentInfo {
+                            id,
+                            reason: FragmentRejectionReason::AccountLimitReached { pool_number },
+                        })
+                    }
+                }
+            }
+
             let count = new_fragments.len();
             tracing::debug!("{} of the received fragments were added to the pool", count,);
             let fragment_logs: Vec<_> = new_fragments
@@ -177,17 +292,10 @@ impl Pools {
                 tracing::debug!(fragment_id=?id, "inserted fragment to the pool");
                 accepted.insert(id);
             }
-
-            for fragment in fragments {
-                let id = fragment.id();
-                tracing::debug!(fragment_id=?id, "rejecting fragment due to pool overflow");
-                rejected.push(RejectedFragmentInfo {
-                    id,
-                    reason: FragmentRejectionReason::PoolOverflow { pool_number },
-                })
-            }
         }
 
+        self.reject_evicted(evicted, date);
+
         let accepted = accepted.into_iter().collect();
 
         (
@@ -198,15 +306,21 @@ impl Pools {
 
     /// Returns number of registered fragments. Setting `fail_fast` to `true` will force this
     /// method to reject all fragments after the first invalid fragments was met.
+    ///
+    /// The fee settings are used to prioritize the fragments when the pools are full, and
+    /// the date is the one recorded in the logs of the fragments evicted from the pools.
     pub async fn insert_and_propagate_all(
         &mut self,
         origin: FragmentOrigin,
         fragments: Vec<Fragment>,
         fail_fast: bool,
+        fees: &LinearFee,
+        date: BlockDate,
     ) -> Result<FragmentsProcessingSummary, Error> {
         tracing::debug!(origin = ?origin, "received {} fragments", fragments.len());
 
-        let (filtered_fragments, summary) = self.filter_fragments(origin, fragments, fail_fast);
+        let (filtered_fragments, summary) =
+            self.filter_fragments(origin, fragments, fail_fast, fees, date);
 
         let mut network_msg_box = self.network_msg_box.clone();
 
@@ -221,6 +335,76 @@ impl Pools {
         Ok(summary)
     }
 
+    /// Inserts the fragments restored from the persistent log of a previous run of the node.
+    /// The fragments are applied in order on top of the ledger of the given tip, and only
+    /// those that are still valid are inserted in the pools. The restored fragments are
+    /// written to the persistent log of this run, so they are restored again if the node
+    /// restarts before they are included in a block.
+    pub async fn restore_from_persistent_log(
+        &mut self,
+        fragments: Vec<Fragment>,
+        tip: Arc<Ref>,
+    ) -> Result<(), Error> {
+        let date = tip.block_date().next(tip.epoch_leadership_schedule().era());
+        let ledger_params = tip.epoch_ledger_parameters().clone();
+        let ledger = match tip.ledger().begin_block(
+            ledger_params.as_ref().clone(),
+            tip.chain_length().increase(),
+            date,
+        ) {
+            Ok(ledger) => ledger,
+            Err(error) => {
+                tracing::warn!(
+                    %error,
+                    "cannot validate the fragments of the persistent log against the tip ledger"
+                );
+                return Ok(());
+            }
+        };
+
+        let n_fragments = fragments.len();
+        let valid_fragments = tokio::task::spawn_blocking(move || {
+            let mut ledger = ledger;
+            let mut valid_fragments = Vec::new();
+            for fragment in fragments {
+                match ledger.apply_fragment(&fragment) {
+                    Ok(ledger_new) => {
+                        ledger = ledger_new;
+                        valid_fragments.push(fragment);
+                    }
+                    Err(error) => {
+                        tracing::debug!(
+                            fragment_id = ?fragment.id(),
+                            %error,
+                            "not restoring fragment, it is not valid against the tip ledger"
+                        );
+                    }
+                }
+            }
+            valid_fragments
+        })
+        .await
+        .unwrap();
+
+        let summary = self
+            .insert_and_propagate_all(
+                FragmentOrigin::Restored,
+                valid_fragments,
+                false,
+                &ledger_params.fees,
+                tip.block_date().into(),
+            )
+            .await?;
+
+        tracing::info!(
+            "restored {} of the {} fragments found in the persistent log",
+            summary.accepted.len(),
+            n_fragments
+        );
+
+        Ok(())
+    }
+
     pub fn remove_added_to_block(&mut self, fragment_ids: Vec<FragmentId>, status: FragmentStatus) {
         let date = if let FragmentStatus::InABlock { date, .. } = status {
             date
@@ -243,7 +427,7 @@ impl Pools {
         selection_alg: FragmentSelectionAlgorithmParams,
         soft_deadline_future: futures::channel::oneshot::Receiver<()>,
         hard_deadline_future: futures::channel::oneshot::Receiver<()>,
-    ) -> (Contents, ApplyBlockLedger) {
+    ) -> (Contents, ApplyBlockLedger, SelectionReport) {
         let Pools { logs, pools, .. } = self;
         let pool = &mut pools[pool_idx];
         match selection_alg {
@@ -260,6 +444,19 @@ impl Pools {
                     )
                     .await
             }
+            FragmentSelectionAlgorithmParams::FeePriority => {
+                let mut selection_alg = FeePriority::new();
+                selection_alg
+                    .select(
+                        ledger,
+                        &ledger_params,
+                        logs,
+                        pool,
+                        soft_deadline_future,
+                        hard_deadline_future,
+                    )
+                    .await
+            }
         }
     }
 
@@ -267,6 +464,48 @@ impl Pools {
     pub fn prune_after_ledger_branch(&mut self, branch_date: BlockDate) {
         self.logs.remove_logs_after_date(branch_date)
     }
+
+    /// Removes from the pools the fragments that have been pending for longer than `ttl`
+    /// at the time `now` and marks them as rejected in the logs.
+    pub fn remove_expired(&mut self, ttl: Duration, now: SystemTime, date: BlockDate) {
+        let Pools { logs, pools, .. } = self;
+
+        let expired: HashSet<FragmentId> = pools
+            .iter()
+            .flat_map(|pool| pool.expired(ttl, now))
+            .collect();
+
+        if expired.is_empty() {
+            return;
+        }
+
+        tracing::debug!(
+            "removing {} expired fragments from the pools",
+            expired.len()
+        );
+
+        let expired_fragments: Vec<Fragment> = expired
+            .iter()
+            .filter_map(|fragment_id| pools.iter().find_map(|pool| pool.get(fragment_id)))
+            .cloned()
+            .collect();
+
+        for pool in pools.iter_mut() {
+            pool.remove_all(expired.iter());
+        }
+
+        for fragment in expired_fragments {
+            logs.reject(
+                fragment,
+                format!(
+                    "{} {}",
+                    EXPIRED_REJECTION_REASON,
+                    jormungandr_lib::time::Duration::from(ttl)
+                ),
+                date,
+            );
+        }
+    }
 }
 
 fn is_fragment_valid(fragment: &Fragment) -> bool {
@@ -297,6 +536,8 @@ fn is_transaction_valid<E>(tx: &Transaction<E>) -> bool {
 
 pub(super) mod internal {
     use super::*;
+    use crate::fragment::selection::fee_per_byte;
+    use chain_impl_mockchain::transaction::{InputEnum, Payload, UnspecifiedAccountIdentifier};
 
     use std::{
         collections::HashMap,
@@ -414,9 +655,25 @@ pub(super) mod internal {
             self.index.len()
         }
 
+        /// Iterates over the entries from the front to the back of the queue.
+        fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+            let mut current = self.head;
+            std::iter::from_fn(move || {
+                let entry = unsafe { current.as_ref() }?;
+                current = entry.next;
+                Some((&entry.key, &entry.value))
+            })
+        }
+
         fn contains(&self, key: &K) -> bool {
             self.index.contains_key(&IndexedDequeueKeyRef(key))
         }
+
+        fn get(&self, key: &K) -> Option<&V> {
+            self.index
+                .get(&IndexedDequeueKeyRef(key))
+                .map(|entry| &entry.value)
+        }
     }
 
     unsafe impl<K: Send, V: Send> Send for IndexedDeqeue<K, V> {}
@@ -438,59 +695,345 @@ pub(super) mod internal {
     pub struct Pool {
         entries: IndexedDeqeue<FragmentId, Fragment>,
         max_entries: usize,
+        eviction_policy: EvictionPolicy,
+        account_max_entries: Option<usize>,
+        /// fee per byte paid by each fragment of the pool
+        fees_per_byte: HashMap<FragmentId, u128>,
+        /// time each fragment of the pool has been received at, it is kept while
+        /// a fragment is taken out of the pool for a selection, so the fragments
+        /// returned to the pool keep their time
+        received_at: HashMap<FragmentId, SystemTime>,
+        /// number of fragments of the pool spending from each account
+        account_entries: HashMap<UnspecifiedAccountIdentifier, usize>,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum InsertError {
+        AlreadyInPool,
+        PoolOverflow,
+        AccountLimitReached,
     }
 
     impl Pool {
-        pub fn new(max_entries: usize) -> Self {
+        pub fn new(
+            max_entries: usize,
+            eviction_policy: EvictionPolicy,
+            account_max_entries: Option<usize>,
+        ) -> Self {
             Pool {
                 entries: IndexedDeqeue::new(),
                 max_entries,
+                eviction_policy,
+                account_max_entries,
+                fees_per_byte: HashMap::new(),
+                received_at: HashMap::new(),
+                account_entries: HashMap::new(),
             }
         }
 
-        /// Returns clones of registered fragments
-        pub fn insert_all(
+        /// Changes the size limits of the pool. If the pool holds more fragments than
+        /// the new limit, the fragments are evicted according to the eviction policy,
+        /// the newest ones first when new fragments are rejected, and are returned.
+        pub fn set_limits(
             &mut self,
-            fragments: impl IntoIterator<Item = Fragment>,
+            max_entries: usize,
+            account_max_entries: Option<usize>,
         ) -> Vec<Fragment> {
-            let max_fragments = self.max_entries - self.entries.len();
-            fragments
-                .into_iter()
-                .filter(|fragment| {
-                    let fragment_id = fragment.id();
-                    if self.entries.contains(&fragment_id) {
-                        false
-                    } else {
-                        self.entries.push_front(fragment_id, fragment.clone());
-                        true
+            self.max_entries = max_entries;
+            self.account_max_entries = account_max_entries;
+
+            let mut evicted = Vec::new();
+            while self.entries.len() > self.max_entries {
+                let fragment = match self.eviction_policy {
+                    EvictionPolicy::RejectNew => self.remove_newest(),
+                    EvictionPolicy::OldestFirst => self.remove_oldest(),
+                    EvictionPolicy::LowestFee => self
+                        .lowest_fee_entry()
+                        .and_then(|(fragment_id, _)| self.remove(&fragment_id)),
+                };
+                match fragment {
+                    Some(fragment) => {
+                        self.received_at.remove(&fragment.id());
+                        evicted.push(fragment);
+                    }
+                    None => break,
+                }
+            }
+            evicted
+        }
+
+        /// Inserts the fragment received at `received_at` to the pool. If the pool is full,
+        /// a fragment may be evicted according to the eviction policy to make room for the
+        /// new one, in which case the evicted fragment is returned.
+        pub fn insert(
+            &mut self,
+            fragment: Fragment,
+            fees: &LinearFee,
+            received_at: SystemTime,
+        ) -> Result<Option<Fragment>, InsertError> {
+            let fragment_id = fragment.id();
+            if self.entries.contains(&fragment_id) {
+                return Err(InsertError::AlreadyInPool);
+            }
+
+            if let Some(account_max_entries) = self.account_max_entries {
+                let limit_reached = fragment_accounts(&fragment).iter().any(|account| {
+                    self.account_entries.get(account).copied().unwrap_or(0) >= account_max_entries
+                });
+                if limit_reached {
+                    return Err(InsertError::AccountLimitReached);
+                }
+            }
+
+            let fee_per_byte = fee_per_byte(&fragment, fees);
+            let evicted = if self.entries.len() >= self.max_entries {
+                Some(self.evict(fee_per_byte)?)
+            } else {
+                None
+            };
+
+            self.register(fragment_id, &fragment, fee_per_byte);
+            self.received_at.insert(fragment_id, received_at);
+            self.entries.push_front(fragment_id, fragment);
+            Ok(evicted)
+        }
+
+        /// Removes a fragment according to the eviction policy to make room for a new
+        /// fragment paying `fee_per_byte`.
+        fn evict(&mut self, fee_per_byte: u128) -> Result<Fragment, InsertError> {
+            let evicted = match self.eviction_policy {
+                EvictionPolicy::RejectNew => None,
+                EvictionPolicy::OldestFirst => self.remove_oldest(),
+                EvictionPolicy::LowestFee => self
+                    .lowest_fee_entry()
+                    .filter(|(_, lowest_fee_per_byte)| *lowest_fee_per_byte < fee_per_byte)
+                    .and_then(|(fragment_id, _)| self.remove(&fragment_id)),
+            };
+            let evicted = evicted.ok_or(InsertError::PoolOverflow)?;
+            self.received_at.remove(&evicted.id());
+            Ok(evicted)
+        }
+
+        /// Returns the fragment paying the lowest fee per byte, the oldest one if several
+        /// fragments pay the same fee.
+        fn lowest_fee_entry(&self) -> Option<(FragmentId, u128)> {
+            // entries are iterated from the newest to the oldest
+            self.entries
+                .iter()
+                .map(|(fragment_id, _)| (*fragment_id, self.fees_per_byte[fragment_id]))
+                .fold(None, |lowest, (fragment_id, fee_per_byte)| match lowest {
+                    Some((_, lowest_fee_per_byte)) if lowest_fee_per_byte < fee_per_byte => lowest,
+                    _ => Some((fragment_id, fee_per_byte)),
+                })
+        }
+
+        fn register(&mut self, fragment_id: FragmentId, fragment: &Fragment, fee_per_byte: u128) {
+            self.fees_per_byte.insert(fragment_id, fee_per_byte);
+            for account in fragment_accounts(fragment) {
+                *self.account_entries.entry(account).or_insert(0) += 1;
+            }
+        }
+
+        fn unregister(&mut self, fragment_id: &FragmentId, fragment: &Fragment) {
+            self.fees_per_byte.remove(fragment_id);
+            for account in fragment_accounts(fragment) {
+                if let Some(count) = self.account_entries.get_mut(&account) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.account_entries.remove(&account);
                     }
+                }
+            }
+        }
+
+        fn remove(&mut self, fragment_id: &FragmentId) -> Option<Fragment> {
+            let fragment = self.entries.remove(fragment_id)?;
+            self.unregister(fragment_id, &fragment);
+            Some(fragment)
+        }
+
+        pub fn contains(&self, fragment_id: &FragmentId) -> bool {
+            self.entries.contains(fragment_id)
+        }
+
+        pub fn get(&self, fragment_id: &FragmentId) -> Option<&Fragment> {
+            self.entries.get(fragment_id)
+        }
+
+        pub fn entries_count(&self) -> usize {
+            self.entries.len()
+        }
+
+        pub fn fragments(&self) -> impl Iterator<Item = &Fragment> {
+            self.entries.iter().map(|(_, fragment)| fragment)
+        }
+
+        /// Returns the fragments that have been pending for longer than `ttl` at the
+        /// time `now`.
+        pub fn expired(&self, ttl: Duration, now: SystemTime) -> Vec<FragmentId> {
+            self.entries
+                .iter()
+                .map(|(fragment_id, _)| fragment_id)
+                .filter(|fragment_id| {
+                    self.received_at
+                        .get(fragment_id)
+                        .map_or(false, |received_at| {
+                            now.duration_since(*received_at)
+                                .map_or(false, |pending_for| pending_for > ttl)
+                        })
                 })
-                .take(max_fragments)
+                .copied()
                 .collect()
         }
 
         pub fn remove_all<'a>(&mut self, fragment_ids: impl IntoIterator<Item = &'a FragmentId>) {
             for fragment_id in fragment_ids {
-                self.entries.remove(fragment_id);
+                self.remove(fragment_id);
+                self.received_at.remove(fragment_id);
             }
         }
 
+        /// Removes the oldest fragment of the pool, its receive time is kept until the
+        /// fragments taken for a selection are returned to the pool.
         pub fn remove_oldest(&mut self) -> Option<Fragment> {
-            self.entries.pop_back().map(|(_, value)| value)
+            let (fragment_id, fragment) = self.entries.pop_back()?;
+            self.unregister(&fragment_id, &fragment);
+            Some(fragment)
+        }
+
+        fn remove_newest(&mut self) -> Option<Fragment> {
+            let fragment_id = self
+                .entries
+                .iter()
+                .next()
+                .map(|(fragment_id, _)| *fragment_id)?;
+            self.remove(&fragment_id)
         }
 
-        pub fn return_to_pool(&mut self, fragments: impl IntoIterator<Item = Fragment>) {
+        /// Removes fragments from the pool in the order of decreasing priority, as computed by
+        /// `priority` for each fragment when this method is called. Fragments of the same
+        /// priority are removed from the oldest to the newest. Fragments are removed lazily as
+        /// the returned iterator advances, so the ones that are not consumed stay in the pool.
+        pub fn remove_by_priority<P, F>(
+            &mut self,
+            mut priority: F,
+        ) -> impl Iterator<Item = Fragment> + '_
+        where
+            P: Ord,
+            F: FnMut(&Fragment) -> P,
+        {
+            // entries are iterated from the newest to the oldest, so the greater the
+            // position the older the fragment
+            let mut queue: Vec<_> = self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(position, (fragment_id, fragment))| {
+                    (priority(fragment), position, *fragment_id)
+                })
+                .collect();
+            queue.sort_unstable_by(|(priority1, position1, _), (priority2, position2, _)| {
+                (priority2, position2).cmp(&(priority1, position1))
+            });
+            queue
+                .into_iter()
+                .filter_map(move |(_, _, fragment_id)| self.remove(&fragment_id))
+        }
+
+        pub fn return_to_pool(
+            &mut self,
+            fragments: impl IntoIterator<Item = Fragment>,
+            fees: &LinearFee,
+        ) {
             for fragment in fragments.into_iter() {
-                self.entries.push_back(fragment.id(), fragment);
+                let fragment_id = fragment.id();
+                self.register(fragment_id, &fragment, fee_per_byte(&fragment, fees));
+                self.received_at
+                    .entry(fragment_id)
+                    .or_insert_with(SystemTime::now);
+                self.entries.push_back(fragment_id, fragment);
+            }
+            // forget the receive time of the fragments taken for the selection and not
+            // returned to the pool
+            let entries = &self.entries;
+            self.received_at
+                .retain(|fragment_id, _| entries.contains(fragment_id));
+        }
+    }
+
+    /// Returns the accounts the fragment is spending from.
+    fn fragment_accounts(fragment: &Fragment) -> Vec<UnspecifiedAccountIdentifier> {
+        fn transaction_accounts<P: Payload>(
+            tx: &Transaction<P>,
+        ) -> Vec<UnspecifiedAccountIdentifier> {
+            let mut accounts = Vec::new();
+            for input in tx.as_slice().inputs().iter() {
+                if let InputEnum::AccountInput(account, _) = input.to_enum() {
+                    if !accounts.contains(&account) {
+                        accounts.push(account);
+                    }
+                }
             }
+            accounts
+        }
+
+        match fragment {
+            Fragment::Transaction(tx) => transaction_accounts(tx),
+            Fragment::StakeDelegation(tx) => transaction_accounts(tx),
+            Fragment::OwnerStakeDelegation(tx) => transaction_accounts(tx),
+            Fragment::PoolRegistration(tx) => transaction_accounts(tx),
+            Fragment::PoolRetirement(tx) => transaction_accounts(tx),
+            Fragment::PoolUpdate(tx) => transaction_accounts(tx),
+            Fragment::VotePlan(tx) => transaction_accounts(tx),
+            Fragment::VoteCast(tx) => transaction_accounts(tx),
+            Fragment::VoteTally(tx) => transaction_accounts(tx),
+            Fragment::EncryptedVoteTally(tx) => transaction_accounts(tx),
+            Fragment::Initial(_)
+            | Fragment::OldUtxoDeclaration(_)
+            | Fragment::UpdateProposal(_)
+            | Fragment::UpdateVote(_) => Vec::new(),
         }
     }
 
     #[cfg(test)]
     mod tests {
         use super::*;
+        use chain_impl_mockchain::{
+            transaction::{Input, TxBuilder},
+            value::Value,
+        };
         use quickcheck_macros::quickcheck;
 
+        /// a transaction spending `value` from each of the `accounts`
+        fn account_transaction(accounts: &[[u8; 32]], value: u64) -> Fragment {
+            let inputs: Vec<_> = accounts
+                .iter()
+                .map(|account| {
+                    Input::from_enum(InputEnum::AccountInput(
+                        UnspecifiedAccountIdentifier::from(*account),
+                        Value(value),
+                    ))
+                })
+                .collect();
+            let tx = TxBuilder::new()
+                .set_nopayload()
+                .set_ios(&inputs, &[])
+                .set_witnesses_unchecked(&[])
+                .set_payload_auth(&());
+            Fragment::Transaction(tx)
+        }
+
+        fn insert_all(pool: &mut Pool, fragments: Vec<Fragment>) -> Vec<Fragment> {
+            let fees = LinearFee::new(0, 0, 0);
+            fragments
+                .into_iter()
+                .filter(|fragment| {
+                    pool.insert(fragment.clone(), &fees, SystemTime::now())
+                        .is_ok()
+                })
+                .collect()
+        }
+
         #[quickcheck]
         fn overflowing_pool_should_reject_new_fragments(
             fragments1_in: (Fragment, Fragment, Fragment),
@@ -513,33 +1056,334 @@ pub(super) mod internal {
                 fragments1_in.2,
                 fragments2_in.0,
             ];
-            let mut pool = Pool::new(4);
-            assert_eq!(fragments1, pool.insert_all(fragments1.clone()));
-            assert_eq!(fragments2_expected, pool.insert_all(fragments2));
+            let mut pool = Pool::new(4, EvictionPolicy::RejectNew, None);
+            assert_eq!(fragments1, insert_all(&mut pool, fragments1.clone()));
+            assert_eq!(fragments2_expected, insert_all(&mut pool, fragments2));
             for expected in final_expected.into_iter() {
                 assert_eq!(expected, pool.remove_oldest().unwrap());
             }
             assert!(pool.remove_oldest().is_none());
         }
+
+        #[quickcheck]
+        fn overflowing_pool_should_evict_oldest_fragments(
+            fragments_in: (Fragment, Fragment, Fragment),
+        ) {
+            let fees = LinearFee::new(0, 0, 0);
+            let mut pool = Pool::new(2, EvictionPolicy::OldestFirst, None);
+            assert_eq!(
+                Ok(None),
+                pool.insert(fragments_in.0.clone(), &fees, SystemTime::now())
+            );
+            assert_eq!(
+                Ok(None),
+                pool.insert(fragments_in.1.clone(), &fees, SystemTime::now())
+            );
+            assert_eq!(
+                Ok(Some(fragments_in.0.clone())),
+                pool.insert(fragments_in.2.clone(), &fees, SystemTime::now())
+            );
+            assert!(!pool.contains(&fragments_in.0.id()));
+            assert_eq!(fragments_in.1, pool.remove_oldest().unwrap());
+            assert_eq!(fragments_in.2, pool.remove_oldest().unwrap());
+            assert!(pool.remove_oldest().is_none());
+        }
+
+        #[quickcheck]
+        fn pool_should_remove_by_priority(fragments_in: (Fragment, Fragment, Fragment, Fragment)) {
+            let fragments = vec![
+                fragments_in.0.clone(),
+                fragments_in.1.clone(),
+                fragments_in.2.clone(),
+                fragments_in.3.clone(),
+            ];
+            let priorities: HashMap<_, _> = vec![
+                (fragments_in.0.id(), 1),
+                (fragments_in.1.id(), 3),
+                (fragments_in.2.id(), 1),
+                (fragments_in.3.id(), 2),
+            ]
+            .into_iter()
+            .collect();
+            let expected = vec![fragments_in.1, fragments_in.3];
+            let mut pool = Pool::new(4, EvictionPolicy::RejectNew, None);
+            assert_eq!(fragments, insert_all(&mut pool, fragments.clone()));
+            let removed: Vec<_> = pool
+                .remove_by_priority(|fragment| priorities[&fragment.id()])
+                .take(2)
+                .collect();
+            assert_eq!(expected, removed);
+            // fragments not consumed from the iterator should stay in the pool
+            assert_eq!(fragments_in.0, pool.remove_oldest().unwrap());
+            assert_eq!(fragments_in.2, pool.remove_oldest().unwrap());
+            assert!(pool.remove_oldest().is_none());
+        }
+
+        #[test]
+        fn overflowing_pool_should_evict_lowest_fee_fragments() {
+            // with a constant fee, the larger the transaction the lower the fee per byte
+            let fees = LinearFee::new(100, 0, 0);
+            let cheap = account_transaction(&[[1; 32], [2; 32]], 1);
+            let expensive1 = account_transaction(&[[3; 32]], 1);
+            let expensive2 = account_transaction(&[[4; 32]], 1);
+            let expensive3 = account_transaction(&[[5; 32]], 1);
+            let mut pool = Pool::new(2, EvictionPolicy::LowestFee, None);
+            assert_eq!(
+                Ok(None),
+                pool.insert(cheap.clone(), &fees, SystemTime::now())
+            );
+            assert_eq!(
+                Ok(None),
+                pool.insert(expensive1.clone(), &fees, SystemTime::now())
+            );
+            assert_eq!(
+                Ok(Some(cheap.clone())),
+                pool.insert(expensive2.clone(), &fees, SystemTime::now())
+            );
+            assert!(!pool.contains(&cheap.id()));
+            // a fragment paying the same fee per byte as the cheapest one in the pool
+            // or less does not evict it
+            assert_eq!(
+                Err(InsertError::PoolOverflow),
+                pool.insert(expensive3.clone(), &fees, SystemTime::now())
+            );
+            assert_eq!(
+                Err(InsertError::PoolOverflow),
+                pool.insert(cheap, &fees, SystemTime::now())
+            );
+            assert_eq!(expensive1, pool.remove_oldest().unwrap());
+            assert_eq!(expensive2, pool.remove_oldest().unwrap());
+            assert!(pool.remove_oldest().is_none());
+        }
+
+        #[test]
+        fn pool_should_limit_entries_per_account() {
+            let fees = LinearFee::new(0, 0, 0);
+            let account1_tx1 = account_transaction(&[[1; 32]], 1);
+            let account1_tx2 = account_transaction(&[[1; 32]], 2);
+            let account2_tx = account_transaction(&[[2; 32]], 1);
+            let accounts1_2_tx = account_transaction(&[[1; 32], [2; 32]], 1);
+            let mut pool = Pool::new(4, EvictionPolicy::RejectNew, Some(1));
+            assert_eq!(
+                Ok(None),
+                pool.insert(account1_tx1.clone(), &fees, SystemTime::now())
+            );
+            assert_eq!(
+                Err(InsertError::AccountLimitReached),
+                pool.insert(account1_tx2.clone(), &fees, SystemTime::now())
+            );
+            assert_eq!(Ok(None), pool.insert(account2_tx, &fees, SystemTime::now()));
+            assert_eq!(
+                Err(InsertError::AccountLimitReached),
+                pool.insert(accounts1_2_tx, &fees, SystemTime::now())
+            );
+            // removing a fragment frees a slot for its accounts
+            assert_eq!(account1_tx1, pool.remove_oldest().unwrap());
+            assert_eq!(
+                Ok(None),
+                pool.insert(account1_tx2, &fees, SystemTime::now())
+            );
+            assert_eq!(2, pool.entries_count());
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::NodeEvents;
 
     #[test]
     fn correct_pools_number() {
         let (fake_msgbox, _) = crate::async_msg::channel(1);
         // a passive node still has 1 pool
-        let pools = Pools::new(0, 0, Logs::new(1), fake_msgbox.clone(), None);
+        let pools = Pools::new(
+            0,
+            EvictionPolicy::RejectNew,
+            None,
+            0,
+            Logs::new(1, NodeEvents::default()),
+            fake_msgbox.clone(),
+            None,
+        );
         assert_eq!(pools.pools.len(), 1);
 
         // a leader node should have as many pools as leaders
-        let pools = Pools::new(0, 1, Logs::new(1), fake_msgbox.clone(), None);
+        let pools = Pools::new(
+            0,
+            EvictionPolicy::RejectNew,
+            None,
+            1,
+            Logs::new(1, NodeEvents::default()),
+            fake_msgbox.clone(),
+            None,
+        );
         assert_eq!(pools.pools.len(), 1);
 
-        let pools = Pools::new(0, 5, Logs::new(1), fake_msgbox, None);
+        let pools = Pools::new(
+            0,
+            EvictionPolicy::RejectNew,
+            None,
+            5,
+            Logs::new(1, NodeEvents::default()),
+            fake_msgbox,
+            None,
+        );
         assert_eq!(pools.pools.len(), 5);
     }
+
+    fn transaction(value: u64) -> Fragment {
+        use chain_impl_mockchain::{
+            transaction::{Input, TxBuilder},
+            value::Value,
+        };
+
+        let tx = TxBuilder::new()
+            .set_nopayload()
+            .set_ios(&[Input::new(0, Value(value), [0; 32])], &[])
+            .set_witnesses_unchecked(&[])
+            .set_payload_auth(&());
+        Fragment::Transaction(tx)
+    }
+
+    fn insert(pools: &mut Pools, fragment: &Fragment, received_at: SystemTime) {
+        let fees = LinearFee::new(0, 0, 0);
+        pools.pools[0]
+            .insert(fragment.clone(), &fees, received_at)
+            .unwrap();
+        pools
+            .logs
+            .insert_pending(FragmentLog::new(fragment.id(), FragmentOrigin::Rest));
+    }
+
+    #[test]
+    fn expired_fragments_should_be_rejected() {
+        let (fake_msgbox, _) = crate::async_msg::channel(1);
+        let mut pools = Pools::new(
+            4,
+            EvictionPolicy::RejectNew,
+            None,
+            1,
+            Logs::new(4, NodeEvents::default()),
+            fake_msgbox,
+            None,
+        );
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(3600);
+
+        let old = transaction(1);
+        let recent = transaction(2);
+        insert(&mut pools, &old, now - Duration::from_secs(200));
+        insert(&mut pools, &recent, now - Duration::from_secs(50));
+        pools.remove_expired(Duration::from_secs(100), now, BlockDate::new(0, 0));
+
+        assert!(!pools.pools[0].contains(&old.id()));
+        match pools.logs.get(old.id()).unwrap().status() {
+            FragmentStatus::Rejected { reason } => {
+                assert!(reason.starts_with(EXPIRED_REJECTION_REASON))
+            }
+            status => panic!("unexpected status of an expired fragment: {:?}", status),
+        }
+        assert!(pools.pools[0].contains(&recent.id()));
+        assert!(pools.logs.get(recent.id()).unwrap().is_pending());
+    }
+
+    #[test]
+    fn returned_fragments_should_keep_their_receive_time() {
+        let (fake_msgbox, _) = crate::async_msg::channel(1);
+        let mut pools = Pools::new(
+            4,
+            EvictionPolicy::RejectNew,
+            None,
+            1,
+            Logs::new(4, NodeEvents::default()),
+            fake_msgbox,
+            None,
+        );
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(3600);
+        let fees = LinearFee::new(0, 0, 0);
+
+        let old = transaction(1);
+        insert(&mut pools, &old, now - Duration::from_secs(200));
+        let taken: Vec<Fragment> = pools.pools[0].remove_oldest().into_iter().collect();
+        pools.pools[0].return_to_pool(taken, &fees);
+        pools.remove_expired(Duration::from_secs(100), now, BlockDate::new(0, 0));
+
+        assert!(!pools.pools[0].contains(&old.id()));
+        assert!(pools.logs.get(old.id()).unwrap().is_rejected());
+    }
+
+    #[test]
+    fn lowering_the_limit_should_trim_the_pool() {
+        let (fake_msgbox, _) = crate::async_msg::channel(1);
+        let mut pools = Pools::new(
+            4,
+            EvictionPolicy::OldestFirst,
+            None,
+            1,
+            Logs::new(4, NodeEvents::default()),
+            fake_msgbox,
+            None,
+        );
+        let now = SystemTime::now();
+
+        let fragments: Vec<Fragment> = (1..=4).map(transaction).collect();
+        for fragment in &fragments {
+            insert(&mut pools, fragment, now);
+        }
+        pools.set_limits(2, None, BlockDate::new(0, 0));
+
+        assert_eq!(pools.pools[0].entries_count(), 2);
+        for evicted in &fragments[..2] {
+            assert!(!pools.pools[0].contains(&evicted.id()));
+            assert!(pools.logs.get(evicted.id()).unwrap().is_rejected());
+        }
+        for kept in &fragments[2..] {
+            assert!(pools.pools[0].contains(&kept.id()));
+            assert!(pools.logs.get(kept.id()).unwrap().is_pending());
+        }
+    }
+
+    #[test]
+    fn pending_fragments_should_be_selected_by_id_or_all() {
+        let (fake_msgbox, _) = crate::async_msg::channel(1);
+        let mut pools = Pools::new(
+            4,
+            EvictionPolicy::RejectNew,
+            None,
+            2,
+            Logs::new(4, NodeEvents::default()),
+            fake_msgbox,
+            None,
+        );
+        let now = SystemTime::now();
+        let fees = LinearFee::new(0, 0, 0);
+
+        let fragments: Vec<Fragment> = (1..=3).map(transaction).collect();
+        for fragment in &fragments {
+            insert(&mut pools, fragment, now);
+        }
+        // the same fragment pending in the pool of another leader
+        pools.pools[1]
+            .insert(fragments[0].clone(), &fees, now)
+            .unwrap();
+        let unknown = transaction(4);
+
+        let selected: Vec<FragmentId> = pools
+            .pending_fragments(vec![fragments[1].id(), unknown.id()])
+            .iter()
+            .map(|fragment| fragment.id())
+            .collect();
+        assert_eq!(selected, vec![fragments[1].id()]);
+
+        let mut all: Vec<FragmentId> = pools
+            .pending_fragments(Vec::new())
+            .iter()
+            .map(|fragment| fragment.id())
+            .collect();
+        all.sort();
+        let mut expected: Vec<FragmentId> =
+            fragments.iter().map(|fragment| fragment.id()).collect();
+        expected.sort();
+        assert_eq!(all, expected);
+    }
 }
@@ -1,6 +1,8 @@
 use crate::{
-    fragment::{Logs, Pools},
-    intercom::{NetworkMsg, TransactionMsg},
+    blockchain::Tip,
+    events::NodeEvents,
+    fragment::{Fragment, Logs, Pools},
+    intercom::{NetworkMsg, ReplySendError, ReplyStreamHandle, TransactionMsg},
     stats_counter::StatsCounter,
     utils::{
         async_msg::{MessageBox, MessageQueue},
@@ -8,22 +10,34 @@ use crate::{
     },
 };
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use chrono::{Duration, DurationRound, Utc};
-use futures::future;
+use chain_core::property::Fragment as _;
+use chrono::{Duration, DurationRound, NaiveDateTime, Utc};
+use futures::{future, SinkExt};
+use jormungandr_lib::interfaces::{
+    list_persistent_fragment_log_files_from_folder_path, EvictionPolicy, FileFragments, FragmentLog,
+};
 use thiserror::Error;
 use tokio_stream::StreamExt;
 use tracing::{span, Level};
 use tracing_futures::Instrument;
 
+// how often the pools are checked for fragments pending for longer than the TTL
+const EXPIRED_FRAGMENTS_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
 pub struct Process {
     pool_max_entries: usize,
     logs_max_entries: usize,
+    eviction_policy: EvictionPolicy,
+    account_max_entries: Option<usize>,
+    fragment_ttl: Option<std::time::Duration>,
     network_msg_box: MessageBox<NetworkMsg>,
+    events: NodeEvents,
 }
 
 #[derive(Debug, Error)]
@@ -38,12 +52,20 @@ impl Process {
     pub fn new(
         pool_max_entries: usize,
         logs_max_entries: usize,
+        eviction_policy: EvictionPolicy,
+        account_max_entries: Option<usize>,
+        fragment_ttl: Option<std::time::Duration>,
         network_msg_box: MessageBox<NetworkMsg>,
+        events: NodeEvents,
     ) -> Self {
         Process {
             pool_max_entries,
             logs_max_entries,
+            eviction_policy,
+            account_max_entries,
+            fragment_ttl,
             network_msg_box,
+            events,
         }
     }
 
@@ -52,6 +74,7 @@ impl Process {
         n_pools: usize,
         service_info: TokioServiceInfo,
         stats_counter: StatsCounter,
+        blockchain_tip: Tip,
         mut input: MessageQueue<TransactionMsg>,
         persistent_log_dir: Option<P>,
     ) -> Result<(), Error> {
@@ -83,17 +106,25 @@ impl Process {
                 .map_err(Error::PersistentLog)
         }
 
-        let min_logs_size = n_pools * self.pool_max_entries;
-        if self.logs_max_entries < min_logs_size {
-            tracing::warn!(
-                "Having 'log_max_entries' < 'pool_max_entries' * n_pools is not recommendend. Overriding 'log_max_entries' to {}", min_logs_size
-            );
-        }
-        let logs = Logs::new(std::cmp::max(self.logs_max_entries, min_logs_size));
+        let logs = Logs::new(
+            logs_capacity(n_pools, self.pool_max_entries, self.logs_max_entries),
+            self.events.clone(),
+        );
 
         let mut wakeup = Box::pin(hourly_wakeup(persistent_log_dir.is_some()));
+        let mut expired_fragments_check = tokio::time::interval(EXPIRED_FRAGMENTS_CHECK_INTERVAL);
+        let service_info = &service_info;
 
         async move {
+            // the log files of the previous runs must be read before opening the log file of
+            // this run, since it may be the same file
+            let restored_fragments = match &persistent_log_dir {
+                Some(dir) if dir.as_ref().exists() => {
+                    read_log_files(dir.as_ref(), self.fragment_ttl, SystemTime::now())?
+                }
+                _ => Vec::new(),
+            };
+
             let persistent_log = match &persistent_log_dir {
                 None => None,
                 Some(dir) => {
@@ -104,12 +135,20 @@ impl Process {
 
             let mut pool = Pools::new(
                 self.pool_max_entries,
+                self.eviction_policy,
+                self.account_max_entries,
                 n_pools,
                 logs,
                 self.network_msg_box,
                 persistent_log,
             );
 
+            if !restored_fragments.is_empty() {
+                let tip = blockchain_tip.get_ref().await;
+                pool.restore_from_persistent_log(restored_fragments, tip)
+                    .await?;
+            }
+
             loop {
                 tokio::select! {
                     maybe_msg = input.next() => {
@@ -129,14 +168,21 @@ impl Process {
                                     // put them in another pool.
 
                                     let stats_counter = stats_counter.clone();
+                                    let tip = blockchain_tip.get_ref().await;
 
                                     let summary = pool
-                            .insert_and_propagate_all(origin, fragments, fail_fast)
-                            .await?;
+                                        .insert_and_propagate_all(
+                                            origin,
+                                            fragments,
+                                            fail_fast,
+                                            &tip.epoch_ledger_parameters().fees,
+                                            tip.block_date().into(),
+                                        )
+                                        .await?;
 
-                        stats_counter.add_tx_recv_cnt(summary.accepted.len());
+                                    stats_counter.add_tx_recv_cnt(summary.accepted.len());
 
-                        reply_handle.reply_ok(summary);
+                                    reply_handle.reply_ok(summary);
                                 }
                                 TransactionMsg::RemoveTransactions(fragment_ids, status) => {
                                     tracing::debug!(
@@ -150,6 +196,27 @@ impl Process {
                                     let logs = pool.logs().logs().cloned().collect();
                                     reply_handle.reply_ok(logs);
                                 }
+                                TransactionMsg::GetLogsWithContents(fragment_ids, reply_handle) => {
+                                    let logs: Vec<FragmentLog> = match fragment_ids {
+                                        Some(fragment_ids) => pool
+                                            .logs()
+                                            .logs_by_ids(fragment_ids)
+                                            .into_iter()
+                                            .map(|(_, log)| log.clone())
+                                            .collect(),
+                                        None => pool.logs().logs().cloned().collect(),
+                                    };
+                                    let logs = logs
+                                        .into_iter()
+                                        .map(|log| {
+                                            let contents = pool
+                                                .fragment_contents(log.fragment_id().into_hash())
+                                                .cloned();
+                                            (log, contents)
+                                        })
+                                        .collect();
+                                    reply_handle.reply_ok(logs);
+                                }
                                 TransactionMsg::GetStatuses(fragment_ids, reply_handle) => {
                                     let mut statuses = HashMap::new();
                                     pool.logs().logs_by_ids(fragment_ids).into_iter().for_each(
@@ -159,6 +226,14 @@ impl Process {
                                     );
                                     reply_handle.reply_ok(statuses);
                                 }
+                                TransactionMsg::GetFragments(fragment_ids, handle) => {
+                                    let fragments = pool.pending_fragments(fragment_ids);
+                                    // do not block the processing of the mempool on a slow peer
+                                    service_info.spawn_fallible(
+                                        "get fragments",
+                                        send_fragments(fragments, handle),
+                                    );
+                                }
                                 TransactionMsg::BranchSwitch(fork_date) => {
                                     pool.prune_after_ledger_branch(fork_date);
                                 }
@@ -183,9 +258,40 @@ impl Process {
                                         .await;
                                     reply_handle.reply_ok(contents);
                                 }
+                                TransactionMsg::SetLimits {
+                                    pool_max_entries,
+                                    logs_max_entries,
+                                    account_max_entries,
+                                } => {
+                                    tracing::info!(
+                                        pool_max_entries,
+                                        logs_max_entries,
+                                        ?account_max_entries,
+                                        "changing the mempool limits"
+                                    );
+                                    let tip = blockchain_tip.get_ref().await;
+                                    pool.set_limits(
+                                        pool_max_entries,
+                                        account_max_entries,
+                                        tip.block_date().into(),
+                                    );
+                                    pool.logs().set_max_entries(logs_capacity(
+                                        n_pools,
+                                        pool_max_entries,
+                                        logs_max_entries,
+                                    ));
+                                }
                             }
                         }
                     }
+                    _ = expired_fragments_check.tick(), if self.fragment_ttl.is_some() => {
+                        let tip = blockchain_tip.get_ref().await;
+                        pool.remove_expired(
+                            self.fragment_ttl.unwrap(),
+                            SystemTime::now(),
+                            tip.block_date().into(),
+                        );
+                    }
                     _ = &mut wakeup => {
                         pool.close_persistent_log();
                         let dir = persistent_log_dir.as_ref().unwrap();
@@ -194,6 +300,9 @@ impl Process {
                         wakeup = Box::pin(hourly_wakeup(true));
                     }
                 }
+
+                stats_counter.set_mempool_usage_cnt(pool.pending_count());
+                stats_counter.set_fragment_logs_cnt(pool.logs().entries_count());
             }
             Ok(())
         }
@@ -201,3 +310,160 @@ impl Process {
         .await
     }
 }
+
+async fn send_fragments(
+    fragments: Vec<Fragment>,
+    handle: ReplyStreamHandle<Fragment>,
+) -> Result<(), ReplySendError> {
+    let mut sink = handle.start_sending();
+    for fragment in fragments {
+        sink.feed(Ok(fragment)).await?;
+    }
+    sink.close().await
+}
+
+/// Reads the fragments of the persistent log files of the previous runs that were logged
+/// within the fragment TTL before `now`, or those of the last log file if the fragments do
+/// not expire. A fragment logged several times is restored once.
+fn read_log_files(
+    dir: &Path,
+    fragment_ttl: Option<std::time::Duration>,
+    now: SystemTime,
+) -> Result<Vec<Fragment>, Error> {
+    let mut paths: Vec<PathBuf> = list_persistent_fragment_log_files_from_folder_path(dir)
+        .map_err(Error::PersistentLog)?
+        .collect();
+    let oldest_secs = match fragment_ttl {
+        Some(ttl) => now
+            .checked_sub(ttl)
+            .and_then(|oldest| oldest.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |oldest| oldest.as_secs()),
+        None => {
+            paths.drain(..paths.len().saturating_sub(1));
+            0
+        }
+    };
+
+    let mut fragment_ids = HashSet::new();
+    let mut fragments = Vec::new();
+    for path in paths {
+        // a log file only holds the fragments logged within the hour it is named after
+        let logged_until = log_file_hour(&path).map(|hour| (hour + Duration::hours(1)).timestamp());
+        if matches!(logged_until, Some(secs) if secs < oldest_secs as i64) {
+            continue;
+        }
+        tracing::debug!("restoring fragments from log file `{:?}`", path);
+        for entry in FileFragments::from_path(path).map_err(Error::PersistentLog)? {
+            match entry {
+                Ok(entry) => {
+                    if entry.time.to_secs() >= oldest_secs
+                        && fragment_ids.insert(entry.fragment.id())
+                    {
+                        fragments.push(entry.fragment);
+                    }
+                }
+                Err(error) => {
+                    // the last entry may have been partially written if the node was not
+                    // shut down properly
+                    tracing::warn!(%error, "stopping reading the persistent log file");
+                    break;
+                }
+            }
+        }
+    }
+    Ok(fragments)
+}
+
+/// Returns the hour a log file is named after, if it is named like the log files of the node.
+fn log_file_hour(path: &Path) -> Option<NaiveDateTime> {
+    let stem = path.file_stem()?.to_str()?;
+    NaiveDateTime::parse_from_str(&format!("{}:00", stem), "%Y-%m-%d_%H:%M").ok()
+}
+
+fn logs_capacity(n_pools: usize, pool_max_entries: usize, logs_max_entries: usize) -> usize {
+    let min_logs_size = n_pools * pool_max_entries;
+    if logs_max_entries < min_logs_size {
+        tracing::warn!(
+            "Having 'log_max_entries' < 'pool_max_entries' * n_pools is not recommendend. Overriding 'log_max_entries' to {}", min_logs_size
+        );
+    }
+    std::cmp::max(logs_max_entries, min_logs_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bincode::Options;
+    use chain_impl_mockchain::{
+        transaction::{Input, TxBuilder},
+        value::Value,
+    };
+    use jormungandr_lib::{interfaces::PersistentFragmentLog, time::SecondsSinceUnixEpoch};
+
+    fn transaction(value: u64) -> Fragment {
+        let tx = TxBuilder::new()
+            .set_nopayload()
+            .set_ios(&[Input::new(0, Value(value), [0; 32])], &[])
+            .set_witnesses_unchecked(&[])
+            .set_payload_auth(&());
+        Fragment::Transaction(tx)
+    }
+
+    fn write_log_file(dir: &Path, hour: &str, entries: &[(u64, &Fragment)]) {
+        let codec = bincode::DefaultOptions::new().with_fixint_encoding();
+        let mut file = File::create(dir.join(format!("{}.log", hour))).unwrap();
+        for (secs, fragment) in entries {
+            let entry = PersistentFragmentLog {
+                time: SecondsSinceUnixEpoch::from_secs(*secs),
+                fragment: (*fragment).clone(),
+            };
+            codec.serialize_into(&mut file, &entry).unwrap();
+        }
+    }
+
+    #[test]
+    fn fragments_should_be_restored_across_log_files() {
+        // 2021-01-01 10:00:00 UTC
+        const TEN: u64 = 1_609_495_200;
+        const MINUTE: u64 = 60;
+
+        let stale = transaction(1);
+        let expired = transaction(2);
+        let previous = transaction(3);
+        let last = transaction(4);
+
+        let dir = tempfile::tempdir().unwrap();
+        write_log_file(dir.path(), "2021-01-01_08", &[(TEN - 110 * MINUTE, &stale)]);
+        write_log_file(
+            dir.path(),
+            "2021-01-01_09",
+            &[
+                (TEN - 50 * MINUTE, &expired),
+                (TEN - 15 * MINUTE, &previous),
+            ],
+        );
+        // the node restarted at 10:00 and logged the restored fragments again
+        write_log_file(
+            dir.path(),
+            "2021-01-01_10",
+            &[(TEN, &previous), (TEN + 20 * MINUTE, &last)],
+        );
+
+        let now = UNIX_EPOCH + std::time::Duration::from_secs(TEN + 30 * MINUTE);
+        let ttl = std::time::Duration::from_secs(60 * MINUTE);
+        let restored: Vec<_> = read_log_files(dir.path(), Some(ttl), now)
+            .unwrap()
+            .iter()
+            .map(|fragment| fragment.id())
+            .collect();
+        assert_eq!(restored, vec![previous.id(), last.id()]);
+
+        // without a TTL only the last log file is replayed
+        let restored: Vec<_> = read_log_files(dir.path(), None, now)
+            .unwrap()
+            .iter()
+            .map(|fragment| fragment.id())
+            .collect();
+        assert_eq!(restored, vec![previous.id(), last.id()]);
+    }
+}
@@ -1,11 +1,15 @@
 use super::logs::Logs;
 use super::pool::internal::Pool;
 use crate::{
-    blockcfg::{ApplyBlockLedger, Contents, ContentsBuilder, LedgerParameters},
-    fragment::FragmentId,
+    blockcfg::{ApplyBlockLedger, Contents, ContentsBuilder, LedgerParameters, Value},
+    fragment::{Fragment, FragmentId},
 };
 use chain_core::property::Fragment as _;
-use jormungandr_lib::interfaces::{BlockDate, FragmentStatus};
+use chain_impl_mockchain::{
+    fee::{FeeAlgorithm, LinearFee},
+    transaction::{Payload, Transaction},
+};
+use jormungandr_lib::interfaces::{BlockDate, SelectionAlgorithm};
 
 use async_trait::async_trait;
 use futures::prelude::*;
@@ -21,6 +25,17 @@ pub enum SelectionOutput {
     Reject { reason: String },
 }
 
+/// what happened to the fragments taken from the pool by a selection
+#[derive(Debug, Default)]
+pub struct SelectionReport {
+    /// the number of fragments taken from the pool
+    pub considered: usize,
+    /// the number of fragments included in the block contents
+    pub selected: usize,
+    /// the fragments rejected and the reason of their rejection
+    pub rejected: Vec<(FragmentId, String)>,
+}
+
 #[async_trait]
 pub trait FragmentSelectionAlgorithm {
     async fn select(
@@ -31,12 +46,22 @@ pub trait FragmentSelectionAlgorithm {
         pool: &mut Pool,
         soft_deadline_future: futures::channel::oneshot::Receiver<()>,
         hard_deadline_future: futures::channel::oneshot::Receiver<()>,
-    ) -> (Contents, ApplyBlockLedger);
+    ) -> (Contents, ApplyBlockLedger, SelectionReport);
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum FragmentSelectionAlgorithmParams {
     OldestFirst,
+    FeePriority,
+}
+
+impl From<SelectionAlgorithm> for FragmentSelectionAlgorithmParams {
+    fn from(algorithm: SelectionAlgorithm) -> Self {
+        match algorithm {
+            SelectionAlgorithm::OldestFirst => FragmentSelectionAlgorithmParams::OldestFirst,
+            SelectionAlgorithm::FeePriority => FragmentSelectionAlgorithmParams::FeePriority,
+        }
+    }
 }
 
 pub struct OldestFirst;
@@ -57,116 +82,230 @@ impl Default for OldestFirst {
 impl FragmentSelectionAlgorithm for OldestFirst {
     async fn select(
         &mut self,
-        mut ledger: ApplyBlockLedger,
+        ledger: ApplyBlockLedger,
         ledger_params: &LedgerParameters,
         logs: &mut Logs,
         pool: &mut Pool,
         soft_deadline_future: futures::channel::oneshot::Receiver<()>,
         hard_deadline_future: futures::channel::oneshot::Receiver<()>,
-    ) -> (Contents, ApplyBlockLedger) {
-        use futures::future::{select, Either};
-
-        let date: BlockDate = ledger.block_date().into();
-        let mut current_total_size = 0;
-        let mut contents_builder = ContentsBuilder::new();
-        let mut return_to_pool = Vec::new();
-
-        let soft_deadline_future = soft_deadline_future.shared();
-        let hard_deadline_future = hard_deadline_future.shared();
-
-        while let Some(fragment) = pool.remove_oldest() {
-            let id = fragment.id();
-            let fragment_raw = fragment.to_raw(); // TODO: replace everything to FragmentRaw in the node
-            let fragment_size = fragment_raw.size_bytes_plus_size() as u32;
-
-            let span = span!(Level::TRACE, "fragment_selection_algorithm", kind="older_first", hash=%id.to_string());
-            let _enter = span.enter();
-            if fragment_size > ledger_params.block_content_max_size {
-                let reason = format!(
-                    "fragment size {} exceeds maximum block content size {}",
-                    fragment_size, ledger_params.block_content_max_size
-                );
-                tracing::debug!("{}", reason);
-                logs.modify(id, FragmentStatus::Rejected { reason }, date);
-                continue;
-            }
+    ) -> (Contents, ApplyBlockLedger, SelectionReport) {
+        let fragments = iter::from_fn(|| pool.remove_oldest());
+        let (contents, ledger, mut return_to_pool, report) = apply_fragments(
+            "older_first",
+            fragments,
+            ledger,
+            ledger_params,
+            logs,
+            soft_deadline_future,
+            hard_deadline_future,
+        )
+        .await;
 
-            let total_size = current_total_size + fragment_size;
+        return_to_pool.reverse();
+        pool.return_to_pool(return_to_pool, &ledger_params.fees);
 
-            if total_size > ledger_params.block_content_max_size {
-                // return a fragment to the pool later if does not fit the contents size limit
-                return_to_pool.push(fragment);
-                continue;
-            }
+        (contents, ledger, report)
+    }
+}
 
-            tracing::debug!("applying fragment in simulation");
-
-            let fragment1 = fragment.clone();
-            let ledger1 = ledger.clone();
-            let fragment_future =
-                tokio::task::spawn_blocking(move || ledger1.apply_fragment(&fragment1));
-
-            let result = match select(fragment_future, soft_deadline_future.clone()).await {
-                Either::Left((join_result, _)) => join_result.unwrap(),
-                Either::Right((_, fragment_future)) => {
-                    if current_total_size > 0 {
-                        tracing::debug!(
-                            "aborting processing of the current fragment to satisfy the soft deadline"
-                        );
-                        return_to_pool.push(fragment);
-                        break;
-                    }
+/// Selects the fragments paying the highest fee per byte first. The fee of each
+/// fragment is computed from the fee settings of the ledger parameters.
+pub struct FeePriority;
+
+impl FeePriority {
+    pub fn new() -> Self {
+        FeePriority
+    }
+}
+
+impl Default for FeePriority {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl FragmentSelectionAlgorithm for FeePriority {
+    async fn select(
+        &mut self,
+        ledger: ApplyBlockLedger,
+        ledger_params: &LedgerParameters,
+        logs: &mut Logs,
+        pool: &mut Pool,
+        soft_deadline_future: futures::channel::oneshot::Receiver<()>,
+        hard_deadline_future: futures::channel::oneshot::Receiver<()>,
+    ) -> (Contents, ApplyBlockLedger, SelectionReport) {
+        let fees = &ledger_params.fees;
+        let fragments = pool.remove_by_priority(|fragment| fee_per_byte(fragment, fees));
+        let (contents, ledger, mut return_to_pool, report) = apply_fragments(
+            "fee_priority",
+            fragments,
+            ledger,
+            ledger_params,
+            logs,
+            soft_deadline_future,
+            hard_deadline_future,
+        )
+        .await;
 
+        // the highest priority fragments are put back as the oldest ones
+        return_to_pool.reverse();
+        pool.return_to_pool(return_to_pool, &ledger_params.fees);
+
+        (contents, ledger, report)
+    }
+}
+
+/// Precision multiplier of the fee per byte ratio, so that fragments of the same fee
+/// but slightly different sizes are not considered as equal priority.
+const FEE_PER_BYTE_PRECISION: u128 = 1_000_000;
+
+pub(super) fn fee_per_byte(fragment: &Fragment, fees: &LinearFee) -> u128 {
+    let fragment_size = fragment.to_raw().size_bytes_plus_size() as u128;
+    let Value(fee) = fragment_fee(fragment, fees);
+    u128::from(fee) * FEE_PER_BYTE_PRECISION / fragment_size
+}
+
+fn fragment_fee(fragment: &Fragment, fees: &LinearFee) -> Value {
+    fn transaction_fee<P: Payload>(tx: &Transaction<P>, fees: &LinearFee) -> Value {
+        let tx = tx.as_slice();
+        fees.calculate(
+            tx.payload().into_certificate_slice(),
+            tx.nb_inputs(),
+            tx.nb_outputs(),
+        )
+    }
+
+    match fragment {
+        Fragment::Transaction(tx) => transaction_fee(tx, fees),
+        Fragment::StakeDelegation(tx) => transaction_fee(tx, fees),
+        Fragment::OwnerStakeDelegation(tx) => transaction_fee(tx, fees),
+        Fragment::PoolRegistration(tx) => transaction_fee(tx, fees),
+        Fragment::PoolRetirement(tx) => transaction_fee(tx, fees),
+        Fragment::PoolUpdate(tx) => transaction_fee(tx, fees),
+        Fragment::VotePlan(tx) => transaction_fee(tx, fees),
+        Fragment::VoteCast(tx) => transaction_fee(tx, fees),
+        Fragment::VoteTally(tx) => transaction_fee(tx, fees),
+        Fragment::EncryptedVoteTally(tx) => transaction_fee(tx, fees),
+        Fragment::Initial(_)
+        | Fragment::OldUtxoDeclaration(_)
+        | Fragment::UpdateProposal(_)
+        | Fragment::UpdateVote(_) => Value::zero(),
+    }
+}
+
+/// Applies the fragments in the given order to the ledger until the block contents
+/// are full or the deadlines are met. Returns the contents of the block, the resulting
+/// ledger, the fragments that should be returned to the pool, in the order they
+/// were taken from it, and the report of the selection.
+async fn apply_fragments(
+    kind: &'static str,
+    fragments: impl Iterator<Item = Fragment> + Send,
+    mut ledger: ApplyBlockLedger,
+    ledger_params: &LedgerParameters,
+    logs: &mut Logs,
+    soft_deadline_future: futures::channel::oneshot::Receiver<()>,
+    hard_deadline_future: futures::channel::oneshot::Receiver<()>,
+) -> (Contents, ApplyBlockLedger, Vec<Fragment>, SelectionReport) {
+    use futures::future::{select, Either};
+
+    let date: BlockDate = ledger.block_date().into();
+    let mut current_total_size = 0;
+    let mut contents_builder = ContentsBuilder::new();
+    let mut return_to_pool = Vec::new();
+    let mut report = SelectionReport::default();
+
+    let soft_deadline_future = soft_deadline_future.shared();
+    let hard_deadline_future = hard_deadline_future.shared();
+
+    for fragment in fragments {
+        report.considered += 1;
+        let id = fragment.id();
+        let fragment_raw = fragment.to_raw(); // TODO: replace everything to FragmentRaw in the node
+        let fragment_size = fragment_raw.size_bytes_plus_size() as u32;
+
+        let span = span!(Level::TRACE, "fragment_selection_algorithm", kind, hash=%id.to_string());
+        let _enter = span.enter();
+        if fragment_size > ledger_params.block_content_max_size {
+            let reason = format!(
+                "fragment size {} exceeds maximum block content size {}",
+                fragment_size, ledger_params.block_content_max_size
+            );
+            tracing::debug!("{}", reason);
+            report.rejected.push((id, reason.clone()));
+            logs.reject(fragment, reason, date);
+            continue;
+        }
+
+        let total_size = current_total_size + fragment_size;
+
+        if total_size > ledger_params.block_content_max_size {
+            // return a fragment to the pool later if does not fit the contents size limit
+            return_to_pool.push(fragment);
+            continue;
+        }
+
+        tracing::debug!("applying fragment in simulation");
+
+        let fragment1 = fragment.clone();
+        let ledger1 = ledger.clone();
+        let fragment_future =
+            tokio::task::spawn_blocking(move || ledger1.apply_fragment(&fragment1));
+
+        let result = match select(fragment_future, soft_deadline_future.clone()).await {
+            Either::Left((join_result, _)) => join_result.unwrap(),
+            Either::Right((_, fragment_future)) => {
+                if current_total_size > 0 {
                     tracing::debug!(
-                        "only one fragment in progress: continuing until meeting the hard deadline"
+                        "aborting processing of the current fragment to satisfy the soft deadline"
                     );
-
-                    match select(fragment_future, hard_deadline_future.clone()).await {
-                        Either::Left((join_result, _)) => join_result.unwrap(),
-                        Either::Right(_) => {
-                            let reason =
-                                "cannot process a single fragment within the given time bounds (hard deadline)";
-                            tracing::debug!("{}", reason);
-                            logs.modify(
-                                id,
-                                FragmentStatus::Rejected {
-                                    reason: reason.to_string(),
-                                },
-                                date,
-                            );
-                            break;
-                        }
-                    }
+                    return_to_pool.push(fragment);
+                    break;
                 }
-            };
 
-            match result {
-                Ok(ledger_new) => {
-                    contents_builder.push(fragment);
-                    ledger = ledger_new;
-                    tracing::debug!("successfully applied and committed the fragment");
-                }
-                Err(error) => {
-                    let mut msg = error.to_string();
-                    for e in iter::successors(error.source(), |&e| e.source()) {
-                        msg.push_str(": ");
-                        msg.push_str(&e.to_string());
+                tracing::debug!(
+                    "only one fragment in progress: continuing until meeting the hard deadline"
+                );
+
+                match select(fragment_future, hard_deadline_future.clone()).await {
+                    Either::Left((join_result, _)) => join_result.unwrap(),
+                    Either::Right(_) => {
+                        let reason =
+                            "cannot process a single fragment within the given time bounds (hard deadline)";
+                        tracing::debug!("{}", reason);
+                        report.rejected.push((id, reason.to_string()));
+                        logs.reject(fragment, reason.to_string(), date);
+                        break;
                     }
-                    tracing::debug!(?error, "fragment is rejected");
-                    logs.modify(id, FragmentStatus::Rejected { reason: msg }, date)
                 }
             }
+        };
 
-            current_total_size = total_size;
-
-            if total_size == ledger_params.block_content_max_size {
-                break;
+        match result {
+            Ok(ledger_new) => {
+                contents_builder.push(fragment);
+                ledger = ledger_new;
+                report.selected += 1;
+                tracing::debug!("successfully applied and committed the fragment");
+            }
+            Err(error) => {
+                let mut msg = error.to_string();
+                for e in iter::successors(error.source(), |&e| e.source()) {
+                    msg.push_str(": ");
+                    msg.push_str(&e.to_string());
+                }
+                tracing::debug!(?error, "fragment is rejected");
+                report.rejected.push((id, msg.clone()));
+                logs.reject(fragment, msg, date)
             }
         }
 
-        return_to_pool.reverse();
-        pool.return_to_pool(return_to_pool);
+        current_total_size = total_size;
 
-        (contents_builder.into(), ledger)
+        if total_size == ledger_params.block_content_max_size {
+            break;
+        }
     }
+
+    (contents_builder.into(), ledger, return_to_pool, report)
 }
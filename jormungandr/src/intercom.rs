@@ -2,9 +2,12 @@ use crate::blockcfg::{
     ApplyBlockLedger, Block, Fragment, FragmentId, Header, HeaderHash, LedgerParameters,
 };
 use crate::blockchain::{Checkpoints, LeadershipBlock, StorageError};
-use crate::fragment::selection::FragmentSelectionAlgorithmParams;
+use crate::fragment::selection::{FragmentSelectionAlgorithmParams, SelectionReport};
 use crate::network::p2p::{comm::PeerInfo, Address};
-use crate::topology::{Gossips, NodeId, Peer, PeerInfo as TopologyPeerInfo, View};
+use crate::topology::{
+    layers::PreferredListConfig, Gossips, NodeId, Peer, PeerInfo as TopologyPeerInfo,
+    PeerReputation, ReportReason, View,
+};
 use crate::utils::async_msg::{self, MessageBox, MessageQueue};
 use chain_impl_mockchain::fragment::Contents as FragmentContents;
 use chain_network::error as net_error;
@@ -18,7 +21,7 @@ use futures::prelude::*;
 use futures::ready;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     error,
     fmt::{self, Debug, Display},
     marker::PhantomData,
@@ -511,19 +514,36 @@ pub enum TransactionMsg {
     RemoveTransactions(Vec<FragmentId>, FragmentStatus),
     BranchSwitch(BlockDate),
     GetLogs(ReplyHandle<Vec<FragmentLog>>),
+    /// the logs of the given fragments, or of all the logged fragments, with
+    /// the contents of the ones pending or recently rejected
+    GetLogsWithContents(
+        Option<Vec<FragmentId>>,
+        ReplyHandle<Vec<(FragmentLog, Option<Fragment>)>>,
+    ),
     GetStatuses(
         Vec<FragmentId>,
         ReplyHandle<HashMap<FragmentId, FragmentStatus>>,
     ),
+    /// the fragments pending in the mempool with the given ids, the ones
+    /// not in the mempool are skipped, or all the pending fragments if no id
+    /// is given
+    GetFragments(Vec<FragmentId>, ReplyStreamHandle<Fragment>),
     SelectTransactions {
         pool_idx: usize,
         ledger: ApplyBlockLedger,
         ledger_params: LedgerParameters,
         selection_alg: FragmentSelectionAlgorithmParams,
-        reply_handle: ReplyHandle<(FragmentContents, ApplyBlockLedger)>,
+        reply_handle: ReplyHandle<(FragmentContents, ApplyBlockLedger, SelectionReport)>,
         soft_deadline_future: futures::channel::oneshot::Receiver<()>,
         hard_deadline_future: futures::channel::oneshot::Receiver<()>,
     },
+    /// change the size limits of the mempool and of the fragment logs,
+    /// the fragments already in the mempool are kept
+    SetLimits {
+        pool_max_entries: usize,
+        logs_max_entries: usize,
+        account_max_entries: Option<usize>,
+    },
 }
 
 /// Client messages, mainly requests from connected peers to our node.
@@ -614,12 +634,21 @@ pub enum NetworkMsg {
 /// Messages to the topology task
 pub enum TopologyMsg {
     AcceptGossip(Gossips),
-    DemotePeer(NodeId),
+    DemotePeer(NodeId, ReportReason),
     PromotePeer(NodeId),
+    /// report the peer at the given address for the given reason
+    ReportPeer(Address, ReportReason),
+    /// the peer at the given address delivered some useful data
+    RewardPeer(Address),
     View(Selection, ReplyHandle<View>),
     ListAvailable(ReplyHandle<Vec<TopologyPeerInfo>>),
     ListNonPublic(ReplyHandle<Vec<TopologyPeerInfo>>),
     ListQuarantined(ReplyHandle<Vec<TopologyPeerInfo>>),
+    ListReputation(ReplyHandle<Vec<PeerReputation>>),
+    SetQuarantineWhitelist(HashSet<Address>),
+    SetPreferredList(PreferredListConfig),
+    /// store the topology snapshot now, when the node shuts down
+    StoreSnapshot(ReplyHandle<()>),
 }
 
 /// Messages to the explorer task
@@ -1,5 +1,8 @@
+use crate::events::NodeEvents;
 pub use jormungandr_lib::interfaces::LeadershipLogStatus;
-use jormungandr_lib::interfaces::{LeadershipLog, LeadershipLogId};
+use jormungandr_lib::interfaces::{
+    LeadershipLog, LeadershipLogDiagnostics, LeadershipLogId, NodeEvent,
+};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -36,6 +39,14 @@ impl LeadershipLogHandle {
         self.logs.set_status(self.internal_id, status).await
     }
 
+    /// set the diagnostics of the block building. They are published with
+    /// the following status update, so they should be set first.
+    pub async fn set_diagnostics(&self, diagnostics: LeadershipLogDiagnostics) {
+        self.logs
+            .set_diagnostics(self.internal_id, diagnostics)
+            .await
+    }
+
     /// make a leadership event as finished.
     ///
     /// This should be called when the leadership event has finished its
@@ -55,8 +66,9 @@ impl Logs {
     /// create a Leadership Logs. Logs will be removed once the `Logs` passed
     /// beyond a certain number of entries.
     ///
-    pub fn new(cap: usize) -> Self {
-        Logs(Arc::new(RwLock::new(internal::Logs::new(cap))))
+    /// The new logs and their status updates are published to `events`.
+    pub fn new(cap: usize, events: NodeEvents) -> Self {
+        Logs(Arc::new(RwLock::new(internal::Logs::new(cap, events))))
     }
 
     pub async fn insert(&self, log: LeadershipLog) -> Result<LeadershipLogHandle, ()> {
@@ -78,6 +90,18 @@ impl Logs {
         inner.write().await.set_status(&leadership_log_id, status);
     }
 
+    async fn set_diagnostics(
+        &self,
+        leadership_log_id: LeadershipLogId,
+        diagnostics: LeadershipLogDiagnostics,
+    ) {
+        let inner = self.0.clone();
+        inner
+            .write()
+            .await
+            .set_diagnostics(&leadership_log_id, diagnostics);
+    }
+
     async fn mark_finished(&self, leadership_log_id: LeadershipLogId) {
         let inner = self.0.clone();
         inner.write().await.mark_finished(&leadership_log_id);
@@ -91,23 +115,30 @@ impl Logs {
 }
 
 pub(super) mod internal {
-    use super::{LeadershipLog, LeadershipLogId, LeadershipLogStatus};
+    use super::{
+        LeadershipLog, LeadershipLogDiagnostics, LeadershipLogId, LeadershipLogStatus, NodeEvent,
+        NodeEvents,
+    };
     use lru::LruCache;
 
     pub struct Logs {
         entries: LruCache<LeadershipLogId, LeadershipLog>,
+        events: NodeEvents,
     }
 
     impl Logs {
-        pub fn new(cap: usize) -> Self {
+        pub fn new(cap: usize, events: NodeEvents) -> Self {
             Logs {
                 entries: LruCache::new(cap),
+                events,
             }
         }
 
         pub fn insert(&mut self, log: LeadershipLog) -> LeadershipLogId {
             let id = log.leadership_log_id();
 
+            self.events
+                .publish(|| NodeEvent::LeadershipLog { log: log.clone() });
             self.entries.put(id, log);
             id
         }
@@ -125,6 +156,18 @@ pub(super) mod internal {
         ) {
             if let Some(ref mut log) = self.entries.get_mut(leadership_log_id) {
                 log.set_status(status);
+                self.events
+                    .publish(|| NodeEvent::LeadershipLog { log: log.clone() });
+            }
+        }
+
+        pub fn set_diagnostics(
+            &mut self,
+            leadership_log_id: &LeadershipLogId,
+            diagnostics: LeadershipLogDiagnostics,
+        ) {
+            if let Some(ref mut log) = self.entries.get_mut(leadership_log_id) {
+                log.set_diagnostics(diagnostics);
             }
         }
 
@@ -4,6 +4,7 @@ use crate::{
         Leadership, LedgerParameters,
     },
     blockchain::{new_epoch_leadership_from, EpochLeadership, LeadershipBlock, Ref, Tip},
+    fragment::selection::{FragmentSelectionAlgorithmParams, SelectionReport},
     intercom::{unary_reply, BlockMsg, Error as IntercomError, TransactionMsg},
     leadership::{
         enclave::{Enclave, EnclaveError, LeaderEvent, Schedule},
@@ -17,7 +18,10 @@ use chain_time::{
 };
 use futures::{future::TryFutureExt, sink::SinkExt};
 use jormungandr_lib::{
-    interfaces::{EnclaveLeaderId, LeadershipLog, LeadershipLogStatus},
+    interfaces::{
+        EnclaveLeaderId, LeadershipLog, LeadershipLogDiagnostics, LeadershipLogStatus,
+        LeadershipRejectedFragment,
+    },
     time::SystemTime,
 };
 use std::cmp::Ordering;
@@ -74,6 +78,7 @@ pub struct Module {
     rewards_report_all: bool,
     // the maximum number of slots we can allow the leader event to run for
     block_hard_deadline: u32,
+    selection_alg: FragmentSelectionAlgorithmParams,
 }
 
 pub struct ModuleConfig {
@@ -85,6 +90,7 @@ pub struct ModuleConfig {
     pub block_message: MessageBox<BlockMsg>,
     pub rewards_report_all: bool,
     pub block_hard_deadline: u32,
+    pub selection_alg: FragmentSelectionAlgorithmParams,
 }
 
 impl Module {
@@ -102,6 +108,7 @@ impl Module {
             block_message: config.block_message,
             rewards_report_all: config.rewards_report_all,
             block_hard_deadline: config.block_hard_deadline,
+            selection_alg: config.selection_alg,
         })
     }
 
@@ -350,6 +357,10 @@ impl Module {
                     "Eek... Too late, we missed an event schedule, system time might be off?"
                 );
 
+                entry
+                    .log
+                    .set_diagnostics(LeadershipLogDiagnostics::new(event_end, event_end_hard))
+                    .await;
                 entry
                     .log
                     .set_status(LeadershipLogStatus::Rejected {
@@ -420,6 +431,10 @@ impl Module {
         );
 
         async {
+            let Entry { event, log } = entry;
+            let mut diagnostics = LeadershipLogDiagnostics::new(event_end, event_end_hard);
+            let started = Instant::now();
+
             let (soft_deadline_tx, soft_deadline_rx) = futures::channel::oneshot::channel();
             let soft_deadline_future =
                 tokio::time::sleep_until(tokio::time::Instant::from_std(deadline));
@@ -428,23 +443,42 @@ impl Module {
             let hard_deadline_future =
                 tokio::time::sleep_until(tokio::time::Instant::from_std(hard_deadline));
 
-            let build_block_future =
-                self.action_run_entry_build_block(entry, soft_deadline_rx, hard_deadline_rx);
+            let mut soft_deadline_reached = false;
+            let mut hard_deadline_reached = false;
+
+            let build_block_future = self.action_run_entry_build_block(
+                event,
+                &mut diagnostics,
+                soft_deadline_rx,
+                hard_deadline_rx,
+            );
 
-            match select(Box::pin(build_block_future), Box::pin(soft_deadline_future)).await {
+            let status = match select(Box::pin(build_block_future), Box::pin(soft_deadline_future))
+                .await
+            {
                 Either::Left((result, _)) => result,
                 Either::Right((_, build_block_future)) => {
+                    soft_deadline_reached = true;
                     soft_deadline_tx.send(()).unwrap();
                     match select(Box::pin(build_block_future), Box::pin(hard_deadline_future)).await
                     {
                         Either::Left((result, _)) => result,
                         Either::Right((_, build_block_future)) => {
+                            hard_deadline_reached = true;
                             hard_deadline_tx.send(()).unwrap();
                             build_block_future.await
                         }
                     }
                 }
-            }
+            }?;
+
+            diagnostics.soft_deadline_reached = soft_deadline_reached;
+            diagnostics.hard_deadline_reached = hard_deadline_reached;
+            diagnostics.elapsed = Some(started.elapsed().into());
+            log.set_diagnostics(diagnostics).await;
+            log.set_status(status).await;
+
+            Ok::<(), LeadershipError>(())
         }
         .instrument(span)
         .await?;
@@ -452,15 +486,16 @@ impl Module {
         Ok(self)
     }
 
+    /// build the block of the leader event, returning the final status of
+    /// the leadership log. The diagnostics of the block building are filled
+    /// in as it goes.
     async fn action_run_entry_build_block(
         &self,
-        entry: Entry,
+        event: LeaderEvent,
+        diagnostics: &mut LeadershipLogDiagnostics,
         soft_deadline_future: futures::channel::oneshot::Receiver<()>,
         hard_deadline_future: futures::channel::oneshot::Receiver<()>,
-    ) -> Result<(), LeadershipError> {
-        let event = entry.event;
-        let event_logs = entry.log;
-
+    ) -> Result<LeadershipLogStatus, LeadershipError> {
         let enclave = self.enclave.clone();
         let mut sender = self.block_message.clone();
         let pool = self.pool.clone();
@@ -481,14 +516,15 @@ impl Module {
                 "It appears the node is running a bit behind schedule, system time might be off?"
             );
 
-            event_logs.set_status(
-                    LeadershipLogStatus::Rejected {
-                        reason: "Not computing this schedule because of invalid state against the network blockchain".to_owned()
-                    }
-                ).await;
-
-            return Ok(());
+            return Ok(LeadershipLogStatus::Rejected {
+                reason: format!(
+                    "Not computing this schedule because of invalid state against the network blockchain: the tip {} is at {}",
+                    self.tip_ref.hash(),
+                    self.tip_ref.block_date()
+                ),
+            });
         };
+        diagnostics.parent = Some(parent_id.into());
 
         let leadership = new_epoch_leadership_from(
             event.date.epoch,
@@ -502,17 +538,28 @@ impl Module {
             .begin_block((*ledger_parameters).clone(), chain_length, event.date)
             .map_err(Box::new)?;
 
-        let (contents, ledger) = prepare_block(
+        let (contents, ledger, report) = prepare_block(
             pool,
             event.id,
             ledger,
             ledger_parameters,
+            self.selection_alg,
             soft_deadline_future,
             hard_deadline_future,
         )
         .await?;
 
-        let event_logs_error = event_logs.clone();
+        diagnostics.fragments_considered = report.considered;
+        diagnostics.fragments_selected = report.selected;
+        diagnostics.fragments_rejected = report
+            .rejected
+            .into_iter()
+            .map(|(fragment_id, reason)| LeadershipRejectedFragment {
+                fragment_id: fragment_id.into(),
+                reason,
+            })
+            .collect();
+
         let signing = {
             let ver = match event.output {
                 LeaderOutput::None => BlockVersion::Genesis,
@@ -530,7 +577,7 @@ impl Module {
                         .into_unsigned_header()
                         .expect("Valid Header Builder")
                         .generalize();
-                    Ok(Some(Block { header, contents }))
+                    Ok(Block { header, contents })
                 }
                 LeaderOutput::Bft(leader_id) => {
                     let final_builder = hdr_builder
@@ -539,21 +586,11 @@ impl Module {
                         .set_consensus_data(&leader_id);
                     enclave
                         .query_header_bft_finalize(final_builder, event.id)
-                        .map_ok(|h| {
-                            Some(Block {
-                                header: h.generalize(),
-                                contents,
-                            })
-                        })
-                        .or_else(|e| async move {
-                            event_logs_error
-                                .set_status(LeadershipLogStatus::Rejected {
-                                    reason: format!("Cannot sign the block: {}", e),
-                                })
-                                .await;
-                            Ok(None)
-                        })
                         .await
+                        .map(|h| Block {
+                            header: h.generalize(),
+                            contents,
+                        })
                 }
                 LeaderOutput::GenesisPraos(node_id, vrfproof) => {
                     let final_builder = hdr_builder
@@ -562,52 +599,39 @@ impl Module {
                         .set_consensus_data(&node_id, &vrfproof.into());
                     enclave
                         .query_header_genesis_praos_finalize(final_builder, event.id)
-                        .map_ok(|h| {
-                            Some(Block {
-                                header: h.generalize(),
-                                contents,
-                            })
-                        })
-                        .or_else(|e| async move {
-                            event_logs_error
-                                .set_status(LeadershipLogStatus::Rejected {
-                                    reason: format!("Cannot sign the block: {}", e),
-                                })
-                                .await;
-                            Ok(None)
-                        })
                         .await
+                        .map(|h| Block {
+                            header: h.generalize(),
+                            contents,
+                        })
                 }
             }
         };
 
         match signing {
-            Ok(maybe_block) => {
-                if let Some(block) = maybe_block {
-                    let id = block.header.hash();
-                    let parent = block.header.block_parent_hash();
-                    let chain_length: u32 = block.header.chain_length().into();
-                    let ledger = ledger.finish(&block.header.get_consensus_eval_context());
-                    let leadership_block = LeadershipBlock {
-                        block,
-                        new_ledger: ledger,
-                        leadership,
-                    };
-                    sender
-                        .send(BlockMsg::LeadershipBlock(leadership_block))
-                        .map_err(|_send_error| LeadershipError::CannotSendLeadershipBlock)
-                        .await?;
-                    event_logs
-                        .set_status(LeadershipLogStatus::Block {
-                            block: id.into(),
-                            parent: parent.into(),
-                            chain_length,
-                        })
-                        .await;
+            Ok(block) => {
+                let id = block.header.hash();
+                let parent = block.header.block_parent_hash();
+                let chain_length: u32 = block.header.chain_length().into();
+                let ledger = ledger.finish(&block.header.get_consensus_eval_context());
+                let leadership_block = LeadershipBlock {
+                    block,
+                    new_ledger: ledger,
+                    leadership,
                 };
-                Ok(())
+                sender
+                    .send(BlockMsg::LeadershipBlock(leadership_block))
+                    .map_err(|_send_error| LeadershipError::CannotSendLeadershipBlock)
+                    .await?;
+                Ok(LeadershipLogStatus::Block {
+                    block: id.into(),
+                    parent: parent.into(),
+                    chain_length,
+                })
             }
-            Err(e) => Err(e),
+            Err(e) => Ok(LeadershipLogStatus::Rejected {
+                reason: format!("Cannot sign the block: {}", e),
+            }),
         }
     }
 
@@ -711,11 +735,10 @@ async fn prepare_block(
     leader_id: EnclaveLeaderId,
     ledger: ApplyBlockLedger,
     epoch_parameters: Arc<LedgerParameters>,
+    selection_alg: FragmentSelectionAlgorithmParams,
     soft_deadline_future: futures::channel::oneshot::Receiver<()>,
     hard_deadline_future: futures::channel::oneshot::Receiver<()>,
-) -> Result<(Contents, ApplyBlockLedger), LeadershipError> {
-    use crate::fragment::selection::FragmentSelectionAlgorithmParams;
-
+) -> Result<(Contents, ApplyBlockLedger, SelectionReport), LeadershipError> {
     let (reply_handle, reply_future) = unary_reply();
 
     let pool_idx: u32 = leader_id.into();
@@ -724,7 +747,7 @@ async fn prepare_block(
         pool_idx: pool_idx as usize,
         ledger,
         ledger_params: epoch_parameters.as_ref().clone(),
-        selection_alg: FragmentSelectionAlgorithmParams::OldestFirst,
+        selection_alg,
         reply_handle,
         soft_deadline_future,
         hard_deadline_future,
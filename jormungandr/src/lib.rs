@@ -0,0 +1,30 @@
+// Rustc default type_length_limit is too low for complex futures, which generate deeply nested
+// monomorphized structured with long signatures. This value is enough for current project.
+#![type_length_limit = "10000000"]
+
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate serde_derive;
+
+pub mod blockcfg;
+pub mod blockchain;
+pub mod client;
+pub mod diagnostic;
+pub mod events;
+pub mod explorer;
+pub mod fragment;
+pub mod intercom;
+pub mod leadership;
+pub mod log;
+pub mod network;
+pub mod reload;
+pub mod rest;
+pub mod secure;
+pub mod settings;
+pub mod start_up;
+pub mod state;
+pub mod stats_counter;
+pub mod stuck_notifier;
+pub mod topology;
+pub mod utils;
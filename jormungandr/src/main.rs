@@ -2,24 +2,27 @@
 // monomorphized structured with long signatures. This value is enough for current project.
 #![type_length_limit = "10000000"]
 
-#[macro_use]
-extern crate lazy_static;
-#[macro_use]
-extern crate serde_derive;
-
-use crate::{
-    blockcfg::{HeaderHash, Leader},
-    blockchain::Blockchain,
-    diagnostic::Diagnostic,
-    secure::enclave::Enclave,
-    settings::start::Settings,
-    utils::{async_msg, task::Services},
-};
 use chain_impl_mockchain::leadership::LeadershipConsensus;
 use futures::executor::block_on;
 use futures::prelude::*;
+use jormungandr::{
+    blockcfg::{self, HeaderHash, Leader},
+    blockchain::{self, Blockchain},
+    client,
+    diagnostic::Diagnostic,
+    events::NodeEvents,
+    explorer, fragment, leadership, network, reload, rest,
+    secure::{self, enclave::Enclave},
+    settings::{
+        start::{RawSettings, Settings},
+        CommandLine,
+    },
+    start_up,
+    stats_counter::StatsCounter,
+    stuck_notifier, topology,
+    utils::{async_msg, task::Services},
+};
 use jormungandr_lib::interfaces::NodeState;
-use settings::{start::RawSettings, CommandLine};
 use tokio::signal;
 use tokio_util::sync::CancellationToken;
 use tracing::{span, Level, Span};
@@ -27,27 +30,6 @@ use tracing::{span, Level, Span};
 use std::sync::Arc;
 use std::time::Duration;
 
-pub mod blockcfg;
-pub mod blockchain;
-pub mod client;
-pub mod diagnostic;
-pub mod explorer;
-pub mod fragment;
-pub mod intercom;
-pub mod leadership;
-pub mod log;
-pub mod network;
-pub mod rest;
-pub mod secure;
-pub mod settings;
-pub mod start_up;
-pub mod state;
-mod stats_counter;
-pub mod stuck_notifier;
-pub mod topology;
-pub mod utils;
-
-use stats_counter::StatsCounter;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_futures::Instrument;
 
@@ -68,6 +50,7 @@ pub struct BootstrappedNode {
     rest_context: Option<rest::ContextLock>,
     services: Services,
     initial_peers: Vec<topology::Peer>,
+    loaded_config: reload::LoadedConfig,
     _logger_guards: Vec<WorkerGuard>,
 }
 
@@ -78,6 +61,7 @@ const EXPLORER_TASK_QUEUE_LEN: usize = 32;
 const CLIENT_TASK_QUEUE_LEN: usize = 32;
 const TOPOLOGY_TASK_QUEUE_LEN: usize = 32;
 const BOOTSTRAP_RETRY_WAIT: Duration = Duration::from_secs(5);
+const BLOCKCHAIN_CACHE_CAPACITY: usize = 102_400;
 
 fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::Error> {
     if let Some(context) = bootstrapped_node.rest_context.as_ref() {
@@ -97,10 +81,14 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
     let (fragment_msgbox, fragment_queue) = async_msg::channel(FRAGMENT_TASK_QUEUE_LEN);
     let (client_msgbox, client_queue) = async_msg::channel(CLIENT_TASK_QUEUE_LEN);
     let (topology_msgbox, topology_queue) = async_msg::channel(TOPOLOGY_TASK_QUEUE_LEN);
+    let topology_shutdown_msgbox = topology_msgbox.clone();
     let blockchain_tip = bootstrapped_node.blockchain_tip;
     let blockchain = bootstrapped_node.blockchain;
-    let leadership_logs =
-        leadership::Logs::new(bootstrapped_node.settings.leadership.logs_capacity);
+    let events = NodeEvents::default();
+    let leadership_logs = leadership::Logs::new(
+        bootstrapped_node.settings.leadership.logs_capacity,
+        events.clone(),
+    );
 
     let stats_counter = StatsCounter::default();
 
@@ -135,11 +123,13 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
         // TODO: we should get this value from the configuration
         let block_cache_ttl: Duration = Duration::from_secs(120);
         let stats_counter = stats_counter.clone();
+        let events = events.clone();
         services.spawn_future("block", move |info| {
             let process = blockchain::Process {
                 blockchain,
                 blockchain_tip,
                 stats_counter,
+                events,
                 network_msgbox,
                 fragment_msgbox,
                 explorer_msgbox,
@@ -165,6 +155,7 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
         bootstrapped_node.block0_hash,
         bootstrapped_node.settings.network.clone(),
         stats_counter.clone(),
+        events.clone(),
         span!(Level::TRACE, "task", kind = "network"),
     ));
 
@@ -195,6 +186,11 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
             network_msgbox: network_msgbox.clone(),
             initial_peers: bootstrapped_node.initial_peers,
             topology_queue,
+            store: bootstrapped_node
+                .settings
+                .storage
+                .as_ref()
+                .map(topology::TopologyStore::new),
         };
 
         services.spawn_future("topology", move |_| topology::start(task_data));
@@ -250,6 +246,11 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
         let pool = fragment_msgbox.clone();
         let rewards_report_all = bootstrapped_node.settings.rewards_report_all;
         let block_hard_deadline = bootstrapped_node.settings.block_hard_deadline;
+        let selection_alg = bootstrapped_node
+            .settings
+            .mempool
+            .selection_algorithm
+            .into();
 
         services.spawn_try_future("leadership", move |service_info| {
             leadership::Module::new(leadership::ModuleConfig {
@@ -261,6 +262,7 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
                 block_message,
                 rewards_report_all,
                 block_hard_deadline,
+                selection_alg,
             })
             .and_then(|module| module.run())
         });
@@ -268,10 +270,17 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
 
     {
         let stats_counter = stats_counter.clone();
+        let events = events.clone();
+        let blockchain_tip = blockchain_tip.clone();
+        let mempool = &bootstrapped_node.settings.mempool;
         let process = fragment::Process::new(
-            bootstrapped_node.settings.mempool.pool_max_entries.into(),
-            bootstrapped_node.settings.mempool.log_max_entries.into(),
+            mempool.pool_max_entries.into(),
+            mempool.log_max_entries.into(),
+            mempool.eviction_policy,
+            mempool.account_max_entries,
+            mempool.fragment_ttl.map(Into::into),
             network_msgbox.clone(),
+            events,
         );
         let fragment_log_dir = bootstrapped_node
             .settings
@@ -284,15 +293,49 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
                 n_pools,
                 info,
                 stats_counter,
+                blockchain_tip,
                 fragment_queue,
                 fragment_log_dir,
             )
         });
     };
 
+    let address_index = if bootstrapped_node.settings.address_index {
+        let tip = block_on(blockchain_tip.get_ref());
+        let address_index = blockchain::AddressIndex::new(
+            *blockchain.block0(),
+            tip.ledger().get_static_parameters().discrimination,
+            tip.epoch_ledger_parameters().epoch_stability_depth,
+        );
+
+        let index = address_index.clone();
+        let storage = blockchain.storage().clone();
+        let blockchain_tip = blockchain_tip.clone();
+        // subscribe right away so the tips changed while the index
+        // is being built are not missed
+        let events = events.subscribe();
+        services.spawn_future("address_index", move |_| {
+            index.start(storage, blockchain_tip, events)
+        });
+        Some(address_index)
+    } else {
+        None
+    };
+
+    let reloader = reload::Reloader::new(
+        bootstrapped_node.loaded_config,
+        bootstrapped_node.settings.network.tls.clone(),
+        fragment_msgbox.clone(),
+        topology_msgbox.clone(),
+    );
+    #[cfg(unix)]
+    init_reload_signal_watcher(&mut services, reloader.clone());
+
     if let Some(rest_context) = bootstrapped_node.rest_context {
         let full_context = rest::FullContext {
             stats_counter,
+            events,
+            reloader,
             network_task: network_msgbox,
             transaction_task: fragment_msgbox,
             topology_task: topology_msgbox,
@@ -300,6 +343,7 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
             enclave,
             network_state,
             explorer: explorer.as_ref().map(|(_msg_box, context)| context.clone()),
+            address_index,
         };
         block_on(async {
             let mut rest_context = rest_context.write().await;
@@ -322,7 +366,15 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
         });
     }
 
-    match services.wait_any_finished() {
+    let result = services.wait_any_finished();
+
+    // the services are still running, the peers learnt since the last
+    // periodic snapshot of the topology are kept before they are dropped
+    services.block_on_task("topology_snapshot", |_| {
+        topology::store_snapshot(topology_shutdown_msgbox)
+    });
+
+    match result {
         Ok(()) => {
             tracing::info!("Shutting down node");
             Ok(())
@@ -355,6 +407,7 @@ fn bootstrap(initialized_node: InitializedNode) -> Result<BootstrappedNode, star
         rest_context,
         mut services,
         cancellation_token,
+        loaded_config,
         _logger_guards,
     } = initialized_node;
 
@@ -386,6 +439,7 @@ fn bootstrap(initialized_node: InitializedNode) -> Result<BootstrappedNode, star
         rest_context,
         services,
         initial_peers,
+        loaded_config,
         _logger_guards,
     })
 }
@@ -423,11 +477,16 @@ async fn bootstrap_internal(
 
     let block0_explorer = block0.clone();
 
-    let cache_capacity = 102_400;
+    let snapshots = start_up::prepare_snapshots(&settings)?;
 
-    let (blockchain, blockchain_tip) =
-        start_up::load_blockchain(block0, storage, cache_capacity, settings.rewards_report_all)
-            .await?;
+    let (blockchain, blockchain_tip) = start_up::load_blockchain(
+        block0,
+        storage,
+        snapshots,
+        BLOCKCHAIN_CACHE_CAPACITY,
+        settings.rewards_report_all,
+    )
+    .await?;
 
     if let Some(context) = &rest_context {
         let mut context = context.write().await;
@@ -476,8 +535,9 @@ async fn bootstrap_internal(
     };
 
     let explorer_db = if settings.explorer {
+        let explorer_store = start_up::prepare_explorer_store(&settings)?;
         futures::select! {
-            explorer_result = explorer::ExplorerDb::bootstrap(block0_explorer, &blockchain, blockchain_tip.clone()).fuse() => {
+            explorer_result = explorer::ExplorerDb::bootstrap(block0_explorer, &blockchain, blockchain_tip.clone(), explorer_store).fuse() => {
                 Some(explorer_result?)
             },
             _ = cancellation_token.cancelled().fuse() => return Err(start_up::Error::Interrupted),
@@ -511,6 +571,7 @@ pub struct InitializedNode {
     pub rest_context: Option<rest::ContextLock>,
     pub services: Services,
     pub cancellation_token: CancellationToken,
+    pub loaded_config: reload::LoadedConfig,
     pub _logger_guards: Vec<WorkerGuard>,
 }
 
@@ -547,6 +608,32 @@ fn init_os_signal_watchers(services: &mut Services, token: CancellationToken) {
     });
 }
 
+/// reload the node configuration file on SIGHUP
+#[cfg(unix)]
+fn init_reload_signal_watcher(services: &mut Services, reloader: reload::Reloader) {
+    use signal::unix::SignalKind;
+
+    services.spawn_future("sighup_watcher", move |_info| async move {
+        let mut signal = match signal::unix::signal(SignalKind::hangup()) {
+            Ok(signal) => signal,
+            Err(e) => {
+                tracing::warn!(reason = %e, "failed to install handler for SIGHUP");
+                return future::pending::<()>().await;
+            }
+        };
+
+        while let Some(()) = signal.recv().await {
+            tracing::info!("SIGHUP received, reloading the node configuration");
+            if let Err(e) = reloader.reload().await {
+                tracing::error!(reason = %e, "failed to reload the node configuration");
+            }
+        }
+
+        // the node stops when any of its services finishes
+        future::pending::<()>().await
+    });
+}
+
 #[cfg(not(unix))]
 fn init_os_signal_watchers(services: &mut Services, token: CancellationToken) {
     use signal::ctrl_c;
@@ -568,6 +655,7 @@ fn init_os_signal_watchers(services: &mut Services, token: CancellationToken) {
 fn initialize_node() -> Result<InitializedNode, start_up::Error> {
     let command_line = CommandLine::load();
     let exit_after_storage_setup = command_line.storage_check;
+    let check_snapshots_and_exit = command_line.storage_check_snapshots;
 
     if command_line.full_version {
         println!("{}", env!("FULL_VERSION"));
@@ -580,7 +668,8 @@ fn initialize_node() -> Result<InitializedNode, start_up::Error> {
     let raw_settings = RawSettings::load(command_line)?;
 
     let log_settings = raw_settings.log_settings();
-    let (_logger_guards, log_info_msgs) = log_settings.init_log()?;
+    let (_logger_guards, log_level, log_info_msgs) = log_settings.init_log()?;
+    let loaded_config = reload::LoadedConfig::new(&raw_settings, log_level);
 
     let init_span = span!(Level::TRACE, "task", kind = "init");
     let async_span = init_span.clone();
@@ -627,9 +716,24 @@ fn initialize_node() -> Result<InitializedNode, start_up::Error> {
             context.set_node_state(NodeState::PreparingStorage);
             let context = Arc::new(RwLock::new(context));
 
+            let admin_tokens = rest
+                .admin
+                .as_ref()
+                .map(|admin| rest::AdminTokens::load(&admin.tokens_file))
+                .transpose()
+                .map_err(|source| start_up::Error::Io {
+                    source,
+                    reason: start_up::ErrorKind::RestAdminTokens,
+                })?;
+
             let service_context = context.clone();
-            let explorer = settings.explorer;
-            let server_handler = rest::start_rest_server(rest, explorer, context.clone());
+            let explorer = if settings.explorer {
+                Some(settings.explorer_limits.clone())
+            } else {
+                None
+            };
+            let server_handler =
+                rest::start_rest_server(rest, admin_tokens, explorer, context.clone());
             services.spawn_future("rest", move |info| async move {
                 service_context.write().await.set_span(info.span().clone());
                 server_handler.await
@@ -677,6 +781,21 @@ fn initialize_node() -> Result<InitializedNode, start_up::Error> {
     }.instrument(async_span)
     })?;
 
+    if check_snapshots_and_exit {
+        let snapshots = start_up::prepare_snapshots(&settings)?;
+        services.block_on_task("check_snapshots", |_service_info| {
+            start_up::verify_ledger_snapshots(
+                block0,
+                storage,
+                snapshots,
+                BLOCKCHAIN_CACHE_CAPACITY,
+                settings.rewards_report_all,
+            )
+        })?;
+        tracing::info!("Exiting after successful ledger snapshots check");
+        std::process::exit(0);
+    }
+
     Ok(InitializedNode {
         settings,
         block0,
@@ -684,6 +803,7 @@ fn initialize_node() -> Result<InitializedNode, start_up::Error> {
         rest_context,
         services,
         cancellation_token,
+        loaded_config,
         _logger_guards,
     })
 }
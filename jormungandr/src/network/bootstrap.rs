@@ -1,18 +1,29 @@
 use super::grpc;
 use crate::blockcfg::{Block, HeaderDesc, HeaderHash};
 use crate::blockchain::{self, Blockchain, Error as BlockchainError, PreCheckedHeader, Ref, Tip};
+use crate::intercom::ExplorerMsg;
 use crate::network::convert::Decode;
 use crate::settings::start::network::Peer;
 use crate::topology;
+use crate::utils::async_msg::MessageBox;
 use chain_core::property::{Deserialize, HasHeader};
 use chain_network::data as net_data;
 use chain_network::error::Error as NetworkError;
-use futures::{prelude::*, stream, task::Poll};
+use futures::{
+    future::{BoxFuture, RemoteHandle},
+    prelude::*,
+    stream,
+    stream::FuturesOrdered,
+    task::Poll,
+};
 use tokio_util::sync::CancellationToken;
 
+use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -26,12 +37,30 @@ pub enum Error {
     PullRequestFailed(#[source] NetworkError),
     #[error("bootstrap pull stream failed")]
     PullStreamFailed(#[source] NetworkError),
+    #[error("block subscription request failed")]
+    SubscriptionFailed(#[source] NetworkError),
+    #[error("block subscription stream failed")]
+    SubscriptionStreamFailed(#[source] NetworkError),
     #[error("could not get the blockchain tip from a peer")]
     TipFailed(#[source] NetworkError),
     #[error("decoding of a peer failed")]
     PeerDecodingFailed(#[source] NetworkError),
+    #[error("failed to fetch the genesis block")]
+    Block0FetchFailed(#[source] grpc::FetchBlockError),
+    #[error("decoding of a block header failed")]
+    HeaderDecodingFailed(#[source] NetworkError),
     #[error("decoding of a block failed")]
     BlockDecodingFailed(#[source] <Block as Deserialize>::Error),
+    #[error("block batch request failed")]
+    GetBlocksFailed(#[source] NetworkError),
+    #[error("block batch stream failed")]
+    GetBlocksStreamFailed(#[source] NetworkError),
+    #[error("the peer did not send the requested blocks")]
+    UnexpectedBlocks,
+    #[error("the peer did not send the requested blocks in time")]
+    BlocksTimeout,
+    #[error("none of the peers could send a batch of blocks")]
+    BlockBatchFailed,
     #[error("block header check failed")]
     HeaderCheckFailed(#[source] BlockchainError),
     #[error(
@@ -55,6 +84,19 @@ pub enum Error {
 }
 
 const MAX_BOOTSTRAP_PEERS: u32 = 32;
+/// the maximum number of peers the blocks are downloaded from concurrently,
+/// including the peer the headers are pulled from
+const MAX_DOWNLOAD_PEERS: usize = 4;
+/// the number of blocks requested from a peer at once
+const BLOCK_BATCH_SIZE: usize = 256;
+/// the number of batches each peer may be downloading ahead of the blocks
+/// being applied
+const BATCHES_IN_FLIGHT_PER_PEER: usize = 2;
+/// a peer not delivering a batch within this time is considered too slow,
+/// the batch is requested from another peer
+const BATCH_TIMEOUT: Duration = Duration::from_secs(60);
+/// the number of blocks between two reports of the bootstrap progress
+const PROCESS_LOGGING_DISTANCE: u64 = 2500;
 
 pub async fn peers_from_trusted_peer(peer: &Peer) -> Result<Vec<topology::Peer>, Error> {
     tracing::info!("getting peers from bootstrap peer {}", peer.connection);
@@ -82,52 +124,227 @@ pub async fn bootstrap_from_peer(
     tip: Tip,
     cancellation_token: CancellationToken,
 ) -> Result<(), Error> {
-    use chain_network::data::BlockId;
-    use std::convert::TryFrom;
+    tracing::debug!("connecting to bootstrap peer {}", peer.connection);
+
+    let mut client = with_cancellation_token(grpc::connect(&peer).boxed(), &cancellation_token)
+        .await?
+        .map_err(Error::Connect)?;
+
+    pull_to_remote_tip(&mut client, &blockchain, &tip, None, &cancellation_token).await
+}
+
+/// Bootstrap from `peer` with the help of `download_peers`: the chain of
+/// headers is pulled from `peer` while the blocks are downloaded by batches
+/// from all the reachable peers concurrently, and applied in order.
+///
+/// A batch not delivered in time, or not matching the requested headers, is
+/// requested from the next peer, and the failing peer is no longer asked for
+/// blocks. Without any other reachable peer, the blocks are pulled from `peer`
+/// alone like in [`bootstrap_from_peer`].
+pub async fn bootstrap_from_peers(
+    peer: &Peer,
+    download_peers: &[Peer],
+    blockchain: Blockchain,
+    tip: Tip,
+    cancellation_token: CancellationToken,
+) -> Result<(), Error> {
+    tracing::debug!("connecting to bootstrap peer {}", peer.connection);
 
-    async fn with_cancellation_token<T>(
-        future: impl Future<Output = T> + Unpin,
-        token: &CancellationToken,
-    ) -> Result<T, Error> {
-        use futures::future::{select, Either};
+    let mut client = with_cancellation_token(grpc::connect(&peer).boxed(), &cancellation_token)
+        .await?
+        .map_err(Error::Connect)?;
 
-        match select(future, token.cancelled().boxed()).await {
-            Either::Left((result, _)) => Ok(result),
-            Either::Right(((), _)) => Err(Error::Interrupted),
+    let mut downloaders = vec![Downloader {
+        addr: peer.connection,
+        client: client.clone(),
+    }];
+    let connections = future::join_all(
+        download_peers
+            .iter()
+            .take(MAX_DOWNLOAD_PEERS - 1)
+            .map(|peer| async move { (peer.connection, grpc::connect(peer).await) }),
+    );
+    for (addr, result) in with_cancellation_token(connections.boxed(), &cancellation_token).await? {
+        match result {
+            Ok(client) => downloaders.push(Downloader { addr, client }),
+            Err(e) => {
+                tracing::debug!(peer = %addr, reason = %e, "unable to reach peer to download blocks from")
+            }
         }
     }
 
-    tracing::debug!("connecting to bootstrap peer {}", peer.connection);
+    if downloaders.len() == 1 {
+        return pull_to_remote_tip(&mut client, &blockchain, &tip, None, &cancellation_token).await;
+    }
+
+    tracing::info!("downloading blocks from {} peers", downloaders.len());
+
+    while let Some(request) =
+        next_pull_request(&mut client, &blockchain, &tip, &cancellation_token).await?
+    {
+        tracing::info!(
+            "pulling headers starting from checkpoints: {:?}; to tip {:?}",
+            request.from,
+            request.to,
+        );
+
+        let headers = with_cancellation_token(
+            client.pull_headers(request.from, request.to).boxed(),
+            &cancellation_token,
+        )
+        .await?
+        .map_err(Error::PullRequestFailed)?;
+
+        download_from_peers(
+            headers,
+            &mut downloaders,
+            blockchain.clone(),
+            tip.clone(),
+            &cancellation_token,
+        )
+        .await?;
+
+        // the blocks of the peer do not make a better chain, pulling them
+        // again would not change anything
+        if tip.get_ref().await.hash() == request.local_tip {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Keep the blockchain in sync with the peer: the blocks are pulled from the
+/// peer each time it announces a new block, and are passed to the explorer
+/// once applied.
+///
+/// This returns when the peer closes the block subscription, or on error.
+pub async fn follow_peer(
+    peer: &Peer,
+    blockchain: Blockchain,
+    tip: Tip,
+    explorer_msg_box: MessageBox<ExplorerMsg>,
+    cancellation_token: CancellationToken,
+) -> Result<(), Error> {
+    tracing::debug!("connecting to peer {}", peer.connection);
 
     let mut client = with_cancellation_token(grpc::connect(&peer).boxed(), &cancellation_token)
         .await?
         .map_err(Error::Connect)?;
 
+    // nothing is announced to the peer, the outbound stream only keeps the
+    // subscription open
+    let mut announcements = with_cancellation_token(
+        client
+            .block_subscription(stream::pending::<net_data::Header>())
+            .boxed(),
+        &cancellation_token,
+    )
+    .await?
+    .map_err(Error::SubscriptionFailed)?;
+
     loop {
-        let remote_tip = with_cancellation_token(client.tip().boxed(), &cancellation_token)
-            .await?
-            .and_then(|header| header.decode())
-            .map_err(Error::TipFailed)?
-            .id();
-
-        if remote_tip == tip.get_ref().await.hash() {
-            break Ok(());
+        pull_to_remote_tip(
+            &mut client,
+            &blockchain,
+            &tip,
+            Some(explorer_msg_box.clone()),
+            &cancellation_token,
+        )
+        .await?;
+
+        blockchain
+            .gc(tip.get_ref().await)
+            .await
+            .map_err(Error::GcFailed)?;
+
+        // the announced header itself does not matter, the blocks are pulled
+        // up to the tip of the peer anyway
+        match with_cancellation_token(announcements.next(), &cancellation_token).await? {
+            Some(Ok(_header)) => {}
+            Some(Err(e)) => return Err(Error::SubscriptionStreamFailed(e)),
+            None => return Ok(()),
         }
+    }
+}
+
+/// Fetch the genesis block from the peer
+pub async fn fetch_block0(peer: &Peer, block0_hash: HeaderHash) -> Result<Block, Error> {
+    grpc::fetch_block(peer, block0_hash)
+        .await
+        .map_err(Error::Block0FetchFailed)
+}
+
+async fn with_cancellation_token<T>(
+    future: impl Future<Output = T> + Unpin,
+    token: &CancellationToken,
+) -> Result<T, Error> {
+    use futures::future::{select, Either};
+
+    match select(future, token.cancelled().boxed()).await {
+        Either::Left((result, _)) => Ok(result),
+        Either::Right(((), _)) => Err(Error::Interrupted),
+    }
+}
+
+struct PullRequest {
+    from: net_data::BlockIds,
+    to: net_data::BlockId,
+    local_tip: HeaderHash,
+}
+
+/// the request to pull the blocks up to the tip of the peer, `None` if the
+/// local tip is already the tip of the peer
+async fn next_pull_request(
+    client: &mut grpc::Client,
+    blockchain: &Blockchain,
+    tip: &Tip,
+    cancellation_token: &CancellationToken,
+) -> Result<Option<PullRequest>, Error> {
+    use chain_network::data::BlockId;
+    use std::convert::TryFrom;
+
+    let remote_tip = with_cancellation_token(client.tip().boxed(), cancellation_token)
+        .await?
+        .and_then(|header| header.decode())
+        .map_err(Error::TipFailed)?
+        .id();
+
+    let local_tip = tip.get_ref().await.hash();
+    if remote_tip == local_tip {
+        return Ok(None);
+    }
+
+    let checkpoints = blockchain.get_checkpoints(tip.branch()).await;
+    let checkpoints = net_data::block::try_ids_from_iter(checkpoints).unwrap();
 
-        let checkpoints = blockchain.get_checkpoints(tip.branch()).await;
-        let checkpoints = net_data::block::try_ids_from_iter(checkpoints).unwrap();
+    let remote_tip = BlockId::try_from(remote_tip.as_ref()).unwrap();
 
-        let remote_tip = BlockId::try_from(remote_tip.as_ref()).unwrap();
+    Ok(Some(PullRequest {
+        from: checkpoints,
+        to: remote_tip,
+        local_tip,
+    }))
+}
 
+async fn pull_to_remote_tip(
+    client: &mut grpc::Client,
+    blockchain: &Blockchain,
+    tip: &Tip,
+    explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
+    cancellation_token: &CancellationToken,
+) -> Result<(), Error> {
+    while let Some(request) = next_pull_request(client, blockchain, tip, cancellation_token).await?
+    {
         tracing::info!(
             "pulling blocks starting from checkpoints: {:?}; to tip {:?}",
-            checkpoints,
-            remote_tip,
+            request.from,
+            request.to,
         );
 
         let stream = with_cancellation_token(
-            client.pull_blocks(checkpoints, remote_tip).boxed(),
-            &cancellation_token,
+            client.pull_blocks(request.from, request.to).boxed(),
+            cancellation_token,
         )
         .await?
         .map_err(Error::PullRequestFailed)?;
@@ -136,10 +353,241 @@ pub async fn bootstrap_from_peer(
             blockchain.clone(),
             tip.clone(),
             stream,
+            explorer_msg_box.clone(),
             cancellation_token.clone(),
         )
         .await?;
+
+        // the blocks of the peer do not make a better chain, pulling them
+        // again would not change anything
+        if tip.get_ref().await.hash() == request.local_tip {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+struct Downloader<C = grpc::Client> {
+    addr: SocketAddr,
+    client: C,
+}
+
+/// a peer the blocks can be requested from
+trait BlockFetcher: Clone + Send + 'static {
+    fn fetch_blocks(&self, ids: Vec<HeaderHash>) -> BoxFuture<'static, Result<Vec<Block>, Error>>;
+}
+
+impl BlockFetcher for grpc::Client {
+    fn fetch_blocks(&self, ids: Vec<HeaderHash>) -> BoxFuture<'static, Result<Vec<Block>, Error>> {
+        fetch_blocks(self.clone(), ids).boxed()
+    }
+}
+
+struct DownloadedBatch {
+    peer: SocketAddr,
+    blocks: Vec<Block>,
+    /// the peers which failed to deliver the batch before `peer`
+    failed_peers: Vec<SocketAddr>,
+}
+
+/// download the blocks of the headers from the downloaders, applying them
+/// in the order of the headers
+async fn download_from_peers<S>(
+    headers: S,
+    downloaders: &mut Vec<Downloader>,
+    blockchain: Blockchain,
+    tip: Tip,
+    cancellation_token: &CancellationToken,
+) -> Result<(), Error>
+where
+    S: Stream<Item = Result<net_data::Header, NetworkError>> + Send + Unpin,
+{
+    let headers = headers.map_err(Error::PullStreamFailed).and_then(|header| {
+        future::ready(
+            header
+                .decode()
+                .map(|header| header.hash())
+                .map_err(Error::HeaderDecodingFailed),
+        )
+    });
+    let mut batches = BatchDownloads::new(headers, downloaders, BLOCK_BATCH_SIZE, BATCH_TIMEOUT);
+    let mut applier = BlockApplier::new(blockchain, tip, None);
+
+    let result: Result<(), Error> = async {
+        while let Some(batch) =
+            with_cancellation_token(batches.next_batch().boxed(), cancellation_token).await??
+        {
+            for _ in &batch.failed_peers {
+                applier.bootstrap_info.record_reassigned_batch();
+            }
+            applier
+                .bootstrap_info
+                .record_batch(batch.peer, batch.blocks.len() as u64);
+            for block in batch.blocks {
+                applier.apply(block).await?;
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => applier.finish().await,
+        Err(err) => {
+            applier.abort().await;
+            Err(err)
+        }
+    }
+}
+
+/// the batches of blocks downloaded concurrently from the downloaders, given
+/// in the order of the block ids. The peers failing to deliver a batch are
+/// removed from the downloaders, unless they are the last one
+struct BatchDownloads<'a, S, C> {
+    ids: S,
+    downloaders: &'a mut Vec<Downloader<C>>,
+    batch_size: usize,
+    batch_timeout: Duration,
+    in_flight: FuturesOrdered<RemoteHandle<Result<DownloadedBatch, Error>>>,
+    ids_exhausted: bool,
+    next_downloader: usize,
+}
+
+impl<'a, S, C> BatchDownloads<'a, S, C>
+where
+    S: Stream<Item = Result<HeaderHash, Error>> + Unpin,
+    C: BlockFetcher,
+{
+    fn new(
+        ids: S,
+        downloaders: &'a mut Vec<Downloader<C>>,
+        batch_size: usize,
+        batch_timeout: Duration,
+    ) -> Self {
+        BatchDownloads {
+            ids,
+            downloaders,
+            batch_size,
+            batch_timeout,
+            in_flight: FuturesOrdered::new(),
+            ids_exhausted: false,
+            next_downloader: 0,
+        }
+    }
+
+    /// the next batch of blocks, `None` once all the blocks are downloaded
+    async fn next_batch(&mut self) -> Result<Option<DownloadedBatch>, Error> {
+        while !self.ids_exhausted
+            && self.in_flight.len() < self.downloaders.len() * BATCHES_IN_FLIGHT_PER_PEER
+        {
+            let mut ids = Vec::with_capacity(self.batch_size);
+            while ids.len() < self.batch_size {
+                match self.ids.next().await {
+                    Some(id) => ids.push(id?),
+                    None => {
+                        self.ids_exhausted = true;
+                        break;
+                    }
+                }
+            }
+            if ids.is_empty() {
+                break;
+            }
+
+            // each batch starts with the next peer, the others are asked in
+            // turn if it fails
+            let mut candidates = self.downloaders.clone();
+            candidates.rotate_left(self.next_downloader % candidates.len());
+            self.next_downloader += 1;
+
+            // dropping the handle cancels the download
+            let (download, handle) =
+                download_batch(ids, candidates, self.batch_timeout).remote_handle();
+            tokio::spawn(download);
+            self.in_flight.push(handle);
+        }
+
+        let batch = match self.in_flight.next().await {
+            Some(batch) => batch?,
+            None => return Ok(None),
+        };
+
+        for peer in &batch.failed_peers {
+            if self.downloaders.len() > 1 && self.downloaders.iter().any(|d| d.addr == *peer) {
+                tracing::warn!(peer = %peer, "no longer downloading blocks from the peer");
+                self.downloaders.retain(|d| d.addr != *peer);
+            }
+        }
+
+        Ok(Some(batch))
+    }
+}
+
+/// download a batch of blocks from the first of the downloaders able to
+/// send them in time
+async fn download_batch<C: BlockFetcher>(
+    ids: Vec<HeaderHash>,
+    downloaders: Vec<Downloader<C>>,
+    timeout: Duration,
+) -> Result<DownloadedBatch, Error> {
+    let mut failed_peers = Vec::new();
+
+    for downloader in downloaders {
+        let result = tokio::time::timeout(timeout, downloader.client.fetch_blocks(ids.clone()))
+            .await
+            .unwrap_or(Err(Error::BlocksTimeout))
+            .and_then(|blocks| check_blocks(blocks, &ids));
+        match result {
+            Ok(blocks) => {
+                return Ok(DownloadedBatch {
+                    peer: downloader.addr,
+                    blocks,
+                    failed_peers,
+                })
+            }
+            Err(e) => {
+                tracing::warn!(
+                    peer = %downloader.addr,
+                    error = ?e,
+                    "failed to download a batch of blocks, requesting it from another peer"
+                );
+                failed_peers.push(downloader.addr);
+            }
+        }
+    }
+
+    Err(Error::BlockBatchFailed)
+}
+
+async fn fetch_blocks(mut client: grpc::Client, ids: Vec<HeaderHash>) -> Result<Vec<Block>, Error> {
+    let block_ids = net_data::block::try_ids_from_iter(ids.iter()).unwrap();
+
+    client
+        .get_blocks(block_ids)
+        .await
+        .map_err(Error::GetBlocksFailed)?
+        .map_err(Error::GetBlocksStreamFailed)
+        .and_then(|block| {
+            future::ready(Block::deserialize(block.as_bytes()).map_err(Error::BlockDecodingFailed))
+        })
+        .try_collect()
+        .await
+}
+
+/// check that the peer sent the requested blocks
+fn check_blocks(blocks: Vec<Block>, ids: &[HeaderHash]) -> Result<Vec<Block>, Error> {
+    if blocks.len() != ids.len()
+        || blocks
+            .iter()
+            .zip(ids)
+            .any(|(block, id)| block.header.hash() != *id)
+    {
+        return Err(Error::UnexpectedBlocks);
     }
+
+    Ok(blocks)
 }
 
 struct BootstrapInfo {
@@ -148,6 +596,8 @@ struct BootstrapInfo {
     bytes_received: u64,
     block_received: u64,
     last_block_description: Option<HeaderDesc>,
+    blocks_by_peer: BTreeMap<SocketAddr, u64>,
+    reassigned_batches: u64,
 }
 
 impl BootstrapInfo {
@@ -160,9 +610,27 @@ impl BootstrapInfo {
             bytes_received: 0,
             block_received: 0,
             last_block_description: lbd,
+            blocks_by_peer: BTreeMap::new(),
+            reassigned_batches: 0,
         }
     }
 
+    pub fn record_batch(&mut self, peer: SocketAddr, blocks: u64) {
+        *self.blocks_by_peer.entry(peer).or_default() += blocks;
+    }
+
+    pub fn record_reassigned_batch(&mut self) {
+        self.reassigned_batches += 1;
+    }
+
+    fn peers_summary(&self) -> String {
+        self.blocks_by_peer
+            .iter()
+            .map(|(peer, blocks)| format!("{}: {} blocks", peer, blocks))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     pub fn append_block(&mut self, b: &Block) {
         use chain_core::property::Serialize;
         self.bytes_received += b.serialize_as_vec().unwrap().len() as u64; // TODO sad serialization back
@@ -202,24 +670,126 @@ impl BootstrapInfo {
                 .as_ref()
                 .map(|lbd| lbd.to_string())
                 .expect("append_block should always be called before report")
-        )
+        );
+        if !self.blocks_by_peer.is_empty() {
+            tracing::info!(
+                "downloaded from peers: {}; reassigned batches: {}",
+                self.peers_summary(),
+                self.reassigned_batches,
+            );
+        }
+    }
+
+    pub fn report_peers(&self) {
+        if !self.blocks_by_peer.is_empty() {
+            tracing::info!(
+                "bootstrap downloaded {} blocks from peers: {}; reassigned batches: {}",
+                self.block_received,
+                self.peers_summary(),
+                self.reassigned_batches,
+            );
+        }
+    }
+}
+
+/// apply the blocks received during the bootstrap in order, and select the
+/// last applied block as the new tip once done
+struct BlockApplier {
+    blockchain: Blockchain,
+    branch: Tip,
+    explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
+    block0: HeaderHash,
+    bootstrap_info: BootstrapInfo,
+    maybe_parent_tip: Option<Arc<Ref>>,
+}
+
+impl BlockApplier {
+    fn new(
+        blockchain: Blockchain,
+        branch: Tip,
+        explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
+    ) -> Self {
+        let block0 = *blockchain.block0();
+        BlockApplier {
+            blockchain,
+            branch,
+            explorer_msg_box,
+            block0,
+            bootstrap_info: BootstrapInfo::new(),
+            maybe_parent_tip: None,
+        }
+    }
+
+    async fn apply(&mut self, block: Block) -> Result<(), Error> {
+        if block.header.hash() == self.block0 {
+            return Ok(());
+        }
+
+        self.bootstrap_info.append_block(&block);
+
+        if self.bootstrap_info.block_received % PROCESS_LOGGING_DISTANCE == 0 {
+            self.bootstrap_info.report();
+        }
+
+        let parent_tip =
+            handle_block(&self.blockchain, block, self.explorer_msg_box.as_mut()).await?;
+        self.maybe_parent_tip = Some(parent_tip);
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<(), Error> {
+        self.bootstrap_info.report_peers();
+
+        if let Some(parent_tip) = self.maybe_parent_tip {
+            blockchain::process_new_ref(
+                &mut self.blockchain,
+                self.branch,
+                parent_tip,
+                self.explorer_msg_box,
+                None,
+                None,
+                None,
+            )
+            .await
+            .map_err(Error::ChainSelectionFailed)
+        } else {
+            tracing::info!("no new blocks in bootstrap stream");
+            Ok(())
+        }
+    }
+
+    /// select the last applied block as the new tip after a failure, so the
+    /// blocks applied so far are not lost
+    async fn abort(mut self) {
+        if let Some(parent_tip) = self.maybe_parent_tip {
+            if let Err(err) = blockchain::process_new_ref(
+                &mut self.blockchain,
+                self.branch,
+                parent_tip,
+                self.explorer_msg_box,
+                None,
+                None,
+                None,
+            )
+            .await
+            {
+                tracing::warn!(error = ?err, "couldn't gracefully exit from failed netboot");
+            }
+        }
     }
 }
 
 async fn bootstrap_from_stream<S>(
-    mut blockchain: Blockchain,
+    blockchain: Blockchain,
     branch: Tip,
     stream: S,
+    explorer_msg_box: Option<MessageBox<ExplorerMsg>>,
     cancellation_token: CancellationToken,
 ) -> Result<(), Error>
 where
     S: Stream<Item = Result<net_data::Block, NetworkError>> + Unpin,
 {
-    const PROCESS_LOGGING_DISTANCE: u64 = 2500;
-    let block0 = *blockchain.block0();
-
-    let mut bootstrap_info = BootstrapInfo::new();
-    let mut maybe_parent_tip = None;
+    let mut applier = BlockApplier::new(blockchain, branch, explorer_msg_box);
 
     let mut stream = stream.map_err(Error::PullStreamFailed);
     let mut cancel = cancellation_token.cancelled().boxed();
@@ -241,59 +811,27 @@ where
 
     while let Some(block_result) = stream.next().await {
         let result = match block_result {
-            Ok(block) => {
-                let block =
-                    Block::deserialize(block.as_bytes()).map_err(Error::BlockDecodingFailed)?;
-
-                if block.header.hash() == block0 {
-                    continue;
-                }
-
-                bootstrap_info.append_block(&block);
-
-                if bootstrap_info.block_received % PROCESS_LOGGING_DISTANCE == 0 {
-                    bootstrap_info.report();
-                }
-
-                handle_block(&blockchain, block).await
-            }
+            Ok(block) => match Block::deserialize(block.as_bytes()) {
+                Ok(block) => applier.apply(block).await,
+                Err(err) => Err(Error::BlockDecodingFailed(err)),
+            },
             Err(err) => Err(err),
         };
 
-        match result {
-            Ok(parent_tip) => {
-                maybe_parent_tip = Some(parent_tip);
-            }
-            Err(err) => {
-                if let Some(parent_tip) = maybe_parent_tip {
-                    if let Err(err) = blockchain::process_new_ref(
-                        &mut blockchain,
-                        branch.clone(),
-                        parent_tip.clone(),
-                        None,
-                        None,
-                    )
-                    .await
-                    {
-                        tracing::warn!(error = ?err, "couldn't gracefully exit from failed netboot");
-                    }
-                }
-                return Err(err);
-            }
+        if let Err(err) = result {
+            applier.abort().await;
+            return Err(err);
         }
     }
 
-    if let Some(parent_tip) = maybe_parent_tip {
-        blockchain::process_new_ref(&mut blockchain, branch, parent_tip, None, None)
-            .await
-            .map_err(Error::ChainSelectionFailed)
-    } else {
-        tracing::info!("no new blocks in bootstrap stream");
-        Ok(())
-    }
+    applier.finish().await
 }
 
-async fn handle_block(blockchain: &Blockchain, block: Block) -> Result<Arc<Ref>, Error> {
+async fn handle_block(
+    blockchain: &Blockchain,
+    block: Block,
+    explorer_msg_box: Option<&mut MessageBox<ExplorerMsg>>,
+) -> Result<Arc<Ref>, Error> {
     let header = block.header();
     let pre_checked = blockchain
         .pre_check_header(header, true)
@@ -322,11 +860,195 @@ async fn handle_block(blockchain: &Blockchain, block: Block) -> Result<Arc<Ref>,
                 block_date = %post_checked.header().block_date(),
                 "validated block"
             );
+            let block_for_explorer = explorer_msg_box.as_ref().map(|_| block.clone());
             let applied = blockchain
                 .apply_and_store_block(post_checked, block)
                 .await
                 .map_err(Error::ApplyBlockFailed)?;
+            if let (Some(msg_box), Some(block)) = (explorer_msg_box, block_for_explorer) {
+                msg_box
+                    .send(ExplorerMsg::NewBlock(block))
+                    .await
+                    .unwrap_or_else(|err| {
+                        tracing::error!("cannot send new block to explorer: {}", err)
+                    });
+            }
             Ok(applied.cached_ref())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_addr::Discrimination;
+    use chain_crypto::{Ed25519, SecretKey};
+    use chain_impl_mockchain::{chaintypes::ConsensusVersion, fee::LinearFee};
+    use jormungandr_lib::{
+        interfaces::{Block0Configuration, BlockchainConfiguration},
+        time::SecondsSinceUnixEpoch,
+    };
+    use std::collections::HashMap;
+
+    /// a peer sending the blocks it knows after a delay
+    #[derive(Clone)]
+    struct TestPeer {
+        blocks: Arc<HashMap<HeaderHash, Block>>,
+        delay: Duration,
+    }
+
+    impl BlockFetcher for TestPeer {
+        fn fetch_blocks(
+            &self,
+            ids: Vec<HeaderHash>,
+        ) -> BoxFuture<'static, Result<Vec<Block>, Error>> {
+            let peer = self.clone();
+            async move {
+                tokio::time::sleep(peer.delay).await;
+                Ok(ids.iter().map(|id| peer.blocks[id].clone()).collect())
+            }
+            .boxed()
+        }
+    }
+
+    /// distinct blocks, told apart by their creation date
+    fn test_blocks(count: u64) -> Vec<Block> {
+        let leader = SecretKey::<Ed25519>::generate(&mut rand::rngs::OsRng).to_public();
+        (0..count)
+            .map(|i| {
+                let mut blockchain_configuration = BlockchainConfiguration::new(
+                    Discrimination::Test,
+                    ConsensusVersion::Bft,
+                    LinearFee::new(0, 0, 0),
+                );
+                blockchain_configuration.consensus_leader_ids = vec![leader.clone().into()];
+                blockchain_configuration.block0_date = SecondsSinceUnixEpoch::from_secs(i);
+                Block0Configuration {
+                    blockchain_configuration,
+                    initial: Vec::new(),
+                }
+                .to_block()
+            })
+            .collect()
+    }
+
+    fn downloader(port: u16, blocks: &[Block], delay: Duration) -> Downloader<TestPeer> {
+        Downloader {
+            addr: SocketAddr::from(([127, 0, 0, 1], port)),
+            client: TestPeer {
+                blocks: Arc::new(
+                    blocks
+                        .iter()
+                        .map(|block| (block.header.hash(), block.clone()))
+                        .collect(),
+                ),
+                delay,
+            },
+        }
+    }
+
+    fn ids(blocks: &[Block]) -> impl Stream<Item = Result<HeaderHash, Error>> + Unpin {
+        stream::iter(
+            blocks
+                .iter()
+                .map(|block| Ok(block.header.hash()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    async fn download_all(
+        batches: &mut BatchDownloads<
+            '_,
+            impl Stream<Item = Result<HeaderHash, Error>> + Unpin,
+            TestPeer,
+        >,
+    ) -> Vec<DownloadedBatch> {
+        let mut downloaded = Vec::new();
+        while let Some(batch) = batches.next_batch().await.unwrap() {
+            downloaded.push(batch);
+        }
+        downloaded
+    }
+
+    fn hashes(blocks: &[Block]) -> Vec<HeaderHash> {
+        blocks.iter().map(|block| block.header.hash()).collect()
+    }
+
+    fn block_ids(batches: &[DownloadedBatch]) -> Vec<HeaderHash> {
+        batches
+            .iter()
+            .flat_map(|batch| batch.blocks.iter().map(|block| block.header.hash()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn batches_are_split_across_peers() {
+        let blocks = test_blocks(6);
+        let mut downloaders = vec![
+            downloader(1, &blocks, Duration::from_millis(0)),
+            downloader(2, &blocks, Duration::from_millis(0)),
+            downloader(3, &blocks, Duration::from_millis(0)),
+        ];
+        let peers: Vec<_> = downloaders.iter().map(|d| d.addr).collect();
+
+        let mut batches =
+            BatchDownloads::new(ids(&blocks), &mut downloaders, 2, Duration::from_secs(10));
+        let downloaded = download_all(&mut batches).await;
+
+        assert_eq!(
+            downloaded
+                .iter()
+                .map(|batch| batch.peer)
+                .collect::<Vec<_>>(),
+            peers
+        );
+        assert!(downloaded.iter().all(|batch| batch.failed_peers.is_empty()));
+        assert_eq!(block_ids(&downloaded), hashes(&blocks));
+        assert_eq!(downloaders.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn batch_is_reassigned_after_timeout() {
+        let blocks = test_blocks(4);
+        let slow = downloader(1, &blocks, Duration::from_secs(10));
+        let fast = downloader(2, &blocks, Duration::from_millis(0));
+        let (slow_addr, fast_addr) = (slow.addr, fast.addr);
+        let mut downloaders = vec![slow, fast];
+
+        let mut batches = BatchDownloads::new(
+            ids(&blocks),
+            &mut downloaders,
+            2,
+            Duration::from_millis(100),
+        );
+        let downloaded = download_all(&mut batches).await;
+
+        assert_eq!(downloaded.len(), 2);
+        assert_eq!(downloaded[0].peer, fast_addr);
+        assert_eq!(downloaded[0].failed_peers, vec![slow_addr]);
+        assert_eq!(block_ids(&downloaded), hashes(&blocks));
+        // the slow peer is no longer asked for blocks
+        assert_eq!(
+            downloaders.iter().map(|d| d.addr).collect::<Vec<_>>(),
+            vec![fast_addr]
+        );
+    }
+
+    #[tokio::test]
+    async fn batches_are_given_in_order() {
+        let blocks = test_blocks(4);
+        let mut downloaders = vec![
+            downloader(1, &blocks, Duration::from_millis(200)),
+            downloader(2, &blocks, Duration::from_millis(0)),
+        ];
+
+        // the batches of the second peer are downloaded first
+        let mut batches =
+            BatchDownloads::new(ids(&blocks), &mut downloaders, 1, Duration::from_secs(10));
+        let downloaded = download_all(&mut batches).await;
+
+        assert_eq!(downloaded.len(), 4);
+        assert!(downloaded.iter().all(|batch| batch.failed_peers.is_empty()));
+        assert_eq!(block_ids(&downloaded), hashes(&blocks));
+    }
+}
@@ -3,6 +3,7 @@ use crate::blockcfg::HeaderHash;
 use crate::network::{
     grpc, p2p::comm::PeerComms, security_params::NONCE_LEN, Channels, ConnectionState,
 };
+use crate::settings::start::network::Peer;
 use chain_core::mempack::{self, ReadBuf, Readable};
 use chain_network::data::{AuthenticatedNodeId, NodeId};
 use chain_network::error::{self as net_error, HandshakeError};
@@ -13,6 +14,8 @@ use futures::prelude::*;
 use futures::ready;
 use rand::Rng;
 
+use std::error;
+use std::io;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use tracing_futures::Instrument;
@@ -32,11 +35,18 @@ pub fn connect(state: ConnectionState, channels: Channels) -> (ConnectHandle, Co
     let async_span = span.clone();
     let _enter = span.enter();
     let cf = async move {
+        let endpoint = grpc::destination_endpoint(&peer).map_err(ConnectError::TlsConfig)?;
         let mut grpc_client = {
             tracing::debug!("connecting");
-            grpc::connect(&peer).await
+            grpc::connect_endpoint(&peer, endpoint).await
         }
-        .map_err(ConnectError::Transport)?;
+        .map_err(|e| {
+            if peer.tls.is_some() && is_tls_error(&e) {
+                ConnectError::TlsHandshake(e)
+            } else {
+                ConnectError::Transport(e)
+            }
+        })?;
 
         let mut nonce = [0u8; NONCE_LEN];
         rand::thread_rng().fill(&mut nonce);
@@ -52,6 +62,7 @@ pub fn connect(state: ConnectionState, channels: Channels) -> (ConnectHandle, Co
 
         // Validate the server's node ID
         let peer_id = validate_peer_auth(hr.auth, &nonce)?;
+        validate_pinned_node_id(&peer, &peer_id)?;
 
         tracing::debug!(node_id = ?peer_id, "authenticated server peer node");
 
@@ -105,6 +116,22 @@ pub fn connect(state: ConnectionState, channels: Channels) -> (ConnectHandle, Co
     (handle, future)
 }
 
+// The failures of the TLS handshake are reported by rustls as I/O errors
+// of the `InvalidData` kind, tonic does not tell them apart from the other
+// transport errors
+fn is_tls_error(error: &tonic::transport::Error) -> bool {
+    let mut source = error::Error::source(error);
+    while let Some(e) = source {
+        if let Some(e) = e.downcast_ref::<io::Error>() {
+            if e.kind() == io::ErrorKind::InvalidData {
+                return true;
+            }
+        }
+        source = e.source();
+    }
+    false
+}
+
 // Validate the server peer's node ID
 fn validate_peer_auth(auth: AuthenticatedNodeId, nonce: &[u8]) -> Result<NodeId, ConnectError> {
     auth.verify(&nonce)
@@ -112,6 +139,22 @@ fn validate_peer_auth(auth: AuthenticatedNodeId, nonce: &[u8]) -> Result<NodeId,
     Ok(auth.into())
 }
 
+// With the TLS identity pinned to the node ID, the certificate of the server
+// has been verified for the expected node ID, the server shall authenticate
+// with this node ID as well
+fn validate_pinned_node_id(peer: &Peer, peer_id: &NodeId) -> Result<(), ConnectError> {
+    match (&peer.tls, &peer.id) {
+        (Some(tls), Some(expected)) if tls.pin_node_id => {
+            if peer_id.as_bytes() == expected.as_ref().as_ref() {
+                Ok(())
+            } else {
+                Err(ConnectError::PinnedNodeIdMismatch)
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Handle used to monitor the P2P client in process of
 /// establishing a connection and subscription streams.
 ///
@@ -150,6 +193,10 @@ pub enum ConnectError {
     Canceled,
     #[error(transparent)]
     Transport(tonic::transport::Error),
+    #[error("the TLS settings of the node cannot be used")]
+    TlsConfig(#[source] tonic::transport::Error),
+    #[error("TLS handshake failed")]
+    TlsHandshake(#[source] tonic::transport::Error),
     #[error("protocol handshake failed: {0}")]
     Handshake(#[source] HandshakeError),
     #[error("failed to decode genesis block in response")]
@@ -167,6 +214,8 @@ pub enum ConnectError {
     InvalidNodeSignature(#[source] chain_crypto::SignatureError),
     #[error("signature verification failed for peer node ID")]
     PeerSignatureVerificationFailed(#[source] net_error::Error),
+    #[error("peer node ID is not the one its TLS certificate is issued for")]
+    PinnedNodeIdMismatch,
     #[error("client authentication failed")]
     ClientAuth(#[source] net_error::Error),
     #[error("subscription request failed")]
@@ -206,3 +255,54 @@ fn match_block0(expected: HeaderHash, peer_responded: HeaderHash) -> Result<(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::start::network::Tls;
+    use chain_network::data::NodeKeyPair;
+    use rand::SeedableRng;
+
+    fn tls(pin_node_id: bool) -> Tls {
+        Tls {
+            cert: Vec::new(),
+            priv_key: Vec::new(),
+            ca_cert: Vec::new(),
+            require_client_auth: true,
+            pin_node_id,
+        }
+    }
+
+    // the node ID a server authenticates with in the handshake
+    fn server_node_id(seed: u8) -> NodeId {
+        let keypair = NodeKeyPair::generate(&mut rand_chacha::ChaChaRng::from_seed([seed; 32]));
+        let nonce = [seed; NONCE_LEN];
+        validate_peer_auth(keypair.sign(&nonce[..]), &nonce[..]).unwrap()
+    }
+
+    fn topology_node_id(id: &NodeId) -> crate::topology::NodeId {
+        jormungandr_lib::interfaces::NodeId::from_hex(&hex::encode(id.as_bytes()))
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn connect_rejects_mismatched_pinned_node_id() {
+        let server_id = server_node_id(1);
+        let other_id = server_node_id(2);
+        let address = "127.0.0.1:3000".parse().unwrap();
+
+        let peer = Peer::new(address).with_tls(Some(tls(true)), Some(topology_node_id(&other_id)));
+        assert!(matches!(
+            validate_pinned_node_id(&peer, &server_id),
+            Err(ConnectError::PinnedNodeIdMismatch)
+        ));
+
+        let peer = Peer::new(address).with_tls(Some(tls(true)), Some(topology_node_id(&server_id)));
+        assert!(validate_pinned_node_id(&peer, &server_id).is_ok());
+
+        // the certificate is not verified for the node ID without pinning
+        let peer = Peer::new(address).with_tls(Some(tls(false)), Some(topology_node_id(&other_id)));
+        assert!(validate_pinned_node_id(&peer, &server_id).is_ok());
+    }
+}
@@ -11,15 +11,19 @@ use super::{
         comm::{OutboundSubscription, PeerComms},
         Address,
     },
-    subscription::{BlockAnnouncementProcessor, FragmentProcessor, GossipProcessor},
+    subscription::{self, BlockAnnouncementProcessor, FragmentProcessor, GossipProcessor},
     Channels, GlobalStateR,
 };
 use crate::{
-    intercom::{self, BlockMsg, ClientMsg},
+    intercom::{self, BlockMsg, ClientMsg, ReplyFuture, TopologyMsg, TransactionMsg},
+    topology::ReportReason,
     utils::async_msg::MessageBox,
 };
 use chain_network::data as net_data;
 use chain_network::data::block::{BlockEvent, BlockIds, ChainPullRequest};
+use chain_network::data::FragmentIds;
+use chain_network::error::Code;
+use jormungandr_lib::interfaces::FragmentOrigin;
 
 use futures::prelude::*;
 use futures::ready;
@@ -38,6 +42,7 @@ pub struct Client {
     inbound: InboundSubscriptions,
     block_solicitations: OutboundSubscription<BlockIds>,
     chain_pulls: OutboundSubscription<ChainPullRequest>,
+    fragment_fetches: OutboundSubscription<FragmentIds>,
     block_sink: BlockAnnouncementProcessor,
     fragment_sink: FragmentProcessor,
     gossip_sink: GossipProcessor,
@@ -83,6 +88,7 @@ impl Client {
         );
         let fragment_sink = FragmentProcessor::new(
             builder.channels.transaction_box,
+            builder.channels.topology_box.clone(),
             inbound.peer_address,
             global_state.clone(),
             span!(
@@ -106,12 +112,13 @@ impl Client {
             ),
         );
 
-        Client {
+        let mut client = Client {
             inner,
             global_state,
             inbound,
             block_solicitations: comms.subscribe_to_block_solicitations(),
             chain_pulls: comms.subscribe_to_chain_pulls(),
+            fragment_fetches: comms.subscribe_to_fragment_fetches(),
             block_sink,
             fragment_sink,
             gossip_sink,
@@ -120,10 +127,40 @@ impl Client {
             incoming_solicitation: None,
             shutting_down: false,
             span: parent_span,
-        }
+        };
+        // the fragments pushed by the peer while the nodes were not
+        // connected are missed by the fragment subscription, so the mempool
+        // is resynchronized with all the fragments pending at the peer
+        client.fetch_fragments(Vec::new().into());
+        client
     }
 }
 
+/// update the reputation of the peer with the outcome of the processing of
+/// the blocks or headers it sent: data failing the verification gets the
+/// peer reported for the given reason
+async fn report_block_processing(
+    reply: ReplyFuture<()>,
+    mut topology_box: MessageBox<TopologyMsg>,
+    node_id: Address,
+    reason: ReportReason,
+) {
+    let msg = match reply.await {
+        Ok(()) => TopologyMsg::RewardPeer(node_id),
+        Err(e) if e.code() == Code::InvalidArgument => {
+            tracing::info!(reason = %e, "peer sent invalid data");
+            TopologyMsg::ReportPeer(node_id, reason)
+        }
+        Err(e) => {
+            tracing::debug!(reason = %e, "processing of the response failed");
+            return;
+        }
+    };
+    topology_box.send(msg).await.unwrap_or_else(
+        |e| tracing::error!(reason = %e, "cannot send message to the topology task"),
+    );
+}
+
 struct InboundSubscriptions {
     pub peer_address: Address,
     pub block_events: BlockSubscription,
@@ -392,7 +429,9 @@ impl Client {
         );
         let _enter = span.enter();
 
-        let (handle, sink, _) = intercom::stream_request(buffer_sizes::inbound::HEADERS);
+        let (handle, sink, reply) = intercom::stream_request(buffer_sizes::inbound::HEADERS);
+        let topology_box = self.fragment_sink.topology_box();
+        let node_id = self.inbound.peer_address;
         // TODO: make sure that back pressure on the number of requests
         // in flight prevents unlimited spawning of these tasks.
         // https://github.com/input-output-hk/jormungandr/issues/1034
@@ -404,7 +443,10 @@ impl Client {
                         reason = %e,
                         "failed to enqueue request for processing"
                     );
+                    return;
                 }
+                report_block_processing(reply, topology_box, node_id, ReportReason::InvalidHeader)
+                    .await;
             }
             .instrument(span.clone()),
         );
@@ -442,7 +484,9 @@ impl Client {
             "request",
             kind = "GetBlocks"
         );
-        let (handle, sink, _) = intercom::stream_request(buffer_sizes::inbound::BLOCKS);
+        let (handle, sink, reply) = intercom::stream_request(buffer_sizes::inbound::BLOCKS);
+        let topology_box = self.fragment_sink.topology_box();
+        let node_id = self.inbound.peer_address;
         // TODO: make sure that back pressure on the number of requests
         // in flight prevents unlimited spawning of these tasks.
         // https://github.com/input-output-hk/jormungandr/issues/1034
@@ -454,7 +498,10 @@ impl Client {
                         reason = %e,
                         "failed to enqueue request for processing"
                     );
+                    return;
                 }
+                report_block_processing(reply, topology_box, node_id, ReportReason::InvalidBlock)
+                    .await;
             }
             .instrument(span.clone()),
         );
@@ -484,6 +531,70 @@ impl Client {
         );
     }
 
+    fn fetch_fragments(&mut self, fragment_ids: FragmentIds) {
+        let mut transaction_box = self.fragment_sink.message_box();
+        let topology_box = self.fragment_sink.topology_box();
+        let node_id = self.inbound.peer_address;
+        let span = span!(
+            parent: self.span(),
+            Level::TRACE,
+            "request",
+            kind = "GetFragments"
+        );
+        let mut client = self.inner.clone();
+        self.global_state.spawn(
+            async move {
+                let stream = match client.get_fragments(fragment_ids).await {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        tracing::info!(
+                            reason = %e,
+                            "request failed"
+                        );
+                        return;
+                    }
+                };
+                let fragments = match stream
+                    .map_err(|e| e.to_string())
+                    .and_then(|fragment| async { fragment.decode().map_err(|e| e.to_string()) })
+                    .try_collect::<Vec<_>>()
+                    .await
+                {
+                    Ok(fragments) => fragments,
+                    Err(reason) => {
+                        tracing::info!(
+                            %reason,
+                            "response stream failed"
+                        );
+                        return;
+                    }
+                };
+                if fragments.is_empty() {
+                    return;
+                }
+                let (reply_handle, reply_future) = intercom::unary_reply();
+                let res = transaction_box
+                    .send(TransactionMsg::SendTransactions {
+                        origin: FragmentOrigin::Network,
+                        fragments,
+                        fail_fast: false,
+                        reply_handle,
+                    })
+                    .await;
+                if let Err(e) = res {
+                    tracing::error!(
+                        reason = %e,
+                        "failed to enqueue fragments for processing"
+                    );
+                    return;
+                }
+                subscription::report_fragments_processing(reply_future, topology_box, node_id)
+                    .await;
+            }
+            .instrument(span),
+        );
+    }
+
     fn process_fragments(&mut self, cx: &mut Context<'_>) -> Poll<Result<ProcessingOutcome, ()>> {
         use self::ProcessingOutcome::*;
         let span = self.span().clone();
@@ -614,6 +725,20 @@ impl Future for Client {
                         }
                     })
             });
+            progress.and_proceed_with(|| {
+                Pin::new(&mut self.fragment_fetches).poll_next(cx).map(
+                    |maybe_item| match maybe_item {
+                        Some(fragment_ids) => {
+                            self.fetch_fragments(fragment_ids);
+                            Ok(Continue)
+                        }
+                        None => {
+                            tracing::debug!("outbound fragment fetch stream closed");
+                            Ok(Disconnect)
+                        }
+                    },
+                )
+            });
 
             match progress {
                 Progress(Poll::Pending) => return Poll::Pending,
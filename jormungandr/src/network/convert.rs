@@ -1,4 +1,4 @@
-use crate::blockcfg::{Block, Fragment, Header, HeaderId};
+use crate::blockcfg::{Block, Fragment, FragmentId, Header, HeaderId};
 use crate::intercom;
 use crate::topology::{Gossip, Gossips};
 use chain_core::mempack::{ReadBuf, Readable};
@@ -87,6 +87,14 @@ impl Decode for net_data::Fragment {
     }
 }
 
+impl Decode for net_data::FragmentId {
+    type Object = FragmentId;
+
+    fn decode(self) -> Result<Self::Object, Error> {
+        read(&self)
+    }
+}
+
 impl Decode for net_data::gossip::Node {
     type Object = Gossip;
     fn decode(self) -> Result<Self::Object, Error> {
@@ -1,3 +1,4 @@
+use super::tls;
 use crate::{
     blockcfg::{Block, HeaderHash},
     network::convert::Decode,
@@ -35,12 +36,25 @@ pub type ConnectError = transport::Error;
 pub type Client = chain_network::grpc::Client<tonic::transport::Channel>;
 
 pub async fn connect(peer: &Peer) -> Result<Client, ConnectError> {
-    connect_internal(peer, Builder::new()).await
+    let endpoint = destination_endpoint(peer)?;
+    connect_endpoint(peer, endpoint).await
 }
 
-async fn connect_internal(peer: &Peer, builder: Builder) -> Result<Client, ConnectError> {
+/// connect to the peer through the endpoint given by `destination_endpoint`
+pub async fn connect_endpoint(
+    peer: &Peer,
+    endpoint: transport::Endpoint,
+) -> Result<Client, ConnectError> {
+    connect_internal(peer, endpoint, Builder::new()).await
+}
+
+async fn connect_internal(
+    peer: &Peer,
+    endpoint: transport::Endpoint,
+    builder: Builder,
+) -> Result<Client, ConnectError> {
     assert!(peer.protocol == Protocol::Grpc);
-    let endpoint = destination_endpoint(peer.connection)
+    let endpoint = endpoint
         .concurrency_limit(concurrency_limits::CLIENT_REQUESTS)
         .tcp_keepalive(Some(keepalive_durations::TCP))
         .http2_keep_alive_interval(keepalive_durations::HTTP2)
@@ -48,8 +62,18 @@ async fn connect_internal(peer: &Peer, builder: Builder) -> Result<Client, Conne
     builder.connect(endpoint).await
 }
 
-fn destination_endpoint(addr: SocketAddr) -> transport::Endpoint {
-    let uri = format!("http://{}", addr);
+/// the endpoint of the peer, this fails when the TLS settings of the node
+/// cannot be used
+pub fn destination_endpoint(peer: &Peer) -> Result<transport::Endpoint, ConnectError> {
+    match &peer.tls {
+        None => Ok(endpoint_uri("http", peer.connection)),
+        Some(tls_config) => endpoint_uri("https", peer.connection)
+            .tls_config(tls::client_config(tls_config, peer.id.as_ref())),
+    }
+}
+
+fn endpoint_uri(scheme: &str, addr: SocketAddr) -> transport::Endpoint {
+    let uri = format!("{}://{}", scheme, addr);
     transport::Endpoint::try_from(uri).unwrap()
 }
 
@@ -1,5 +1,10 @@
 pub(super) mod client;
 mod server;
+mod tls;
 
-pub use self::client::{connect, fetch_block, Client, ConnectError, FetchBlockError};
+pub use self::client::{
+    connect, connect_endpoint, destination_endpoint, fetch_block, Client, ConnectError,
+    FetchBlockError,
+};
 pub use self::server::run_listen_socket;
+pub use self::tls::{is_issued_for, PeerCertificates};
@@ -2,9 +2,11 @@ use super::super::{
     concurrency_limits, keepalive_durations, service::NodeService, Channels, GlobalStateR,
     ListenError,
 };
+use super::tls;
 use crate::settings::start::network::Listen;
 use chain_network::grpc;
 
+use tonic::service::interceptor::InterceptedService;
 use tonic::transport::Server;
 use tracing::{span, Level};
 
@@ -19,9 +21,35 @@ pub async fn run_listen_socket(
     let span = span!(parent: &state.span, Level::TRACE, "listen_socket", local_addr = %sockaddr.to_string());
     async {
         tracing::info!("listening and accepting gRPC connections");
-        let service = grpc::server::Builder::new().build(NodeService::new(channels, state));
+        let mut server = Server::builder();
+        if let Some(tls_config) = &state.config.tls {
+            tracing::info!("securing the gRPC connections with TLS");
+            server = server
+                .tls_config(tls::server_config(tls_config))
+                .map_err(|cause| ListenError { cause, sockaddr })?;
+        }
+        // the node ID the clients authenticate with is checked against
+        // their certificate when the TLS identity is pinned to the node ID
+        let pin_node_id = state
+            .config
+            .tls
+            .as_ref()
+            .map_or(false, |tls| tls.require_client_auth && tls.pin_node_id);
+        let peer_certs = state.peer_certs.clone();
+        let service = InterceptedService::new(
+            grpc::server::Builder::new().build(NodeService::new(channels, state)),
+            move |request: tonic::Request<()>| {
+                if pin_node_id {
+                    if let (Some(addr), Some(certs)) = (request.remote_addr(), request.peer_certs())
+                    {
+                        peer_certs.record(addr, certs);
+                    }
+                }
+                Ok(request)
+            },
+        );
 
-        Server::builder()
+        server
             .concurrency_limit_per_connection(concurrency_limits::SERVER_REQUESTS)
             .tcp_keepalive(Some(keepalive_durations::TCP))
             .add_service(service)
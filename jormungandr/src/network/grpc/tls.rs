@@ -0,0 +1,261 @@
+//! TLS settings of the gRPC connections between the nodes.
+//!
+//! The certificates of the nodes are verified against the certificates of
+//! the authorities configured with `p2p.tls.ca_file`. As the peers are
+//! reached by their IP address, a node certificate is issued for the DNS
+//! name `NODE_DOMAIN_NAME` rather than for the address of the node.
+//!
+//! When the TLS identity is pinned to the node ID, the certificate of a node
+//! is also issued for the name derived from its node ID by
+//! `node_domain_name`. The certificate of a peer whose node ID is known is
+//! verified for this name, and the node ID the peer authenticates with in
+//! the handshake shall be the one the certificate has been verified for.
+//! The server checks in the same way that the client certificate is issued
+//! for the node ID the client authenticates with.
+use crate::network::p2p::Address;
+use crate::settings::start::network::Tls;
+use crate::topology::NodeId;
+use lru::LruCache;
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
+
+use std::sync::{Arc, Mutex};
+
+/// the name the certificates of the nodes are issued for
+pub const NODE_DOMAIN_NAME: &str = "node.jormungandr";
+
+/// the name the certificate of the node with the given ID is issued for
+/// when the TLS identity is pinned to the node ID.
+///
+/// The hex encoding of the node ID does not fit in a single DNS label, so it
+/// is split in two labels, e.g.
+/// `0123...ef.0123...ef.node.jormungandr`
+pub fn node_domain_name(id: &NodeId) -> String {
+    hex_node_domain_name(&id.as_ref().to_string())
+}
+
+fn hex_node_domain_name(id: &str) -> String {
+    let (high, low) = id.split_at(id.len() / 2);
+    format!("{}.{}.{}", high, low, NODE_DOMAIN_NAME)
+}
+
+/// whether the certificate of a peer, the first one of the chain it
+/// presented, is issued for the name derived from the node ID `id`
+pub fn is_issued_for(certs: &[Certificate], id: &[u8]) -> bool {
+    let name = hex_node_domain_name(&hex::encode(id));
+    let dns_name = match webpki::DNSNameRef::try_from_ascii_str(&name) {
+        Ok(dns_name) => dns_name,
+        Err(_) => return false,
+    };
+    certs.first().map_or(false, |cert| {
+        webpki::EndEntityCert::from(cert.get_ref())
+            .and_then(|cert| cert.verify_is_valid_for_dns_name(dns_name))
+            .is_ok()
+    })
+}
+
+/// The certificates the clients connected to the server presented, by
+/// remote address. The node IDs the clients authenticate with are checked
+/// against them.
+#[derive(Clone)]
+pub struct PeerCertificates(Arc<Mutex<LruCache<Address, Arc<Vec<Certificate>>>>>);
+
+impl PeerCertificates {
+    pub fn new(capacity: usize) -> Self {
+        PeerCertificates(Arc::new(Mutex::new(LruCache::new(capacity.max(1)))))
+    }
+
+    pub fn record(&self, peer: Address, certs: Arc<Vec<Certificate>>) {
+        self.0.lock().unwrap().put(peer, certs);
+    }
+
+    /// the certificates presented by the client at the given address, they
+    /// are forgotten afterwards
+    pub fn take(&self, peer: Address) -> Option<Arc<Vec<Certificate>>> {
+        self.0.lock().unwrap().pop(&peer)
+    }
+}
+
+pub fn server_config(tls: &Tls) -> ServerTlsConfig {
+    let config = ServerTlsConfig::new().identity(Identity::from_pem(&tls.cert, &tls.priv_key));
+    if tls.require_client_auth {
+        config.client_ca_root(Certificate::from_pem(&tls.ca_cert))
+    } else {
+        config
+    }
+}
+
+/// the settings of a connection to the peer with the node ID `id`, the
+/// certificate of a peer whose node ID is not known can only be verified
+/// against the authorities.
+pub fn client_config(tls: &Tls, id: Option<&NodeId>) -> ClientTlsConfig {
+    let domain_name = match id {
+        Some(id) if tls.pin_node_id => node_domain_name(id),
+        _ => NODE_DOMAIN_NAME.to_owned(),
+    };
+    ClientTlsConfig::new()
+        .domain_name(domain_name)
+        .ca_certificate(Certificate::from_pem(&tls.ca_cert))
+        .identity(Identity::from_pem(&tls.cert, &tls.priv_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::grpc::destination_endpoint;
+    use crate::settings::start::network::Peer;
+    use futures::future::{ready, Ready};
+    use rand::SeedableRng;
+    use tonic::body::BoxBody;
+    use tonic::codegen::{http, Service};
+    use tonic::transport::{self, Body, NamedService, Server};
+
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    // generated by resources/tls/generate.sh, the certificate of each node is
+    // issued for the name derived from the node ID next to it
+    const CA_CERT: &[u8] = include_bytes!("../../../resources/tls/ca.crt");
+    const NODE1_CERT: &[u8] = include_bytes!("../../../resources/tls/node1.crt");
+    const NODE1_KEY: &[u8] = include_bytes!("../../../resources/tls/node1.key");
+    const NODE1_ID: &str = include_str!("../../../resources/tls/node1.id");
+    const NODE2_CERT: &[u8] = include_bytes!("../../../resources/tls/node2.crt");
+    const NODE2_KEY: &[u8] = include_bytes!("../../../resources/tls/node2.key");
+    const NODE2_ID: &str = include_str!("../../../resources/tls/node2.id");
+
+    fn node_tls(cert: &[u8], priv_key: &[u8]) -> Tls {
+        Tls {
+            cert: cert.to_vec(),
+            priv_key: priv_key.to_vec(),
+            ca_cert: CA_CERT.to_vec(),
+            require_client_auth: true,
+            pin_node_id: true,
+        }
+    }
+
+    fn node_id(hex_id: &str) -> NodeId {
+        jormungandr_lib::interfaces::NodeId::from_hex(hex_id.trim())
+            .unwrap()
+            .into()
+    }
+
+    // the DER-encoded certificate, as presented by a peer
+    fn der_certificate(pem: &[u8]) -> Certificate {
+        let encoded: String = std::str::from_utf8(pem)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("-----"))
+            .collect();
+        Certificate::from_pem(base64::decode(encoded).unwrap())
+    }
+
+    // the handshake is all that is tested, the requests are not served
+    #[derive(Clone)]
+    struct Unimplemented;
+
+    impl NamedService for Unimplemented {
+        const NAME: &'static str = "jormungandr.test.Unimplemented";
+    }
+
+    impl Service<http::Request<Body>> for Unimplemented {
+        type Response = http::Response<BoxBody>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: http::Request<Body>) -> Self::Future {
+            ready(Ok(tonic::Status::unimplemented("").to_http()))
+        }
+    }
+
+    async fn listen(tls: &Tls) -> SocketAddr {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let server = Server::builder()
+            .tls_config(server_config(tls))
+            .unwrap()
+            .add_service(Unimplemented)
+            .serve(addr);
+        tokio::spawn(server);
+        while tokio::net::TcpStream::connect(addr).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        addr
+    }
+
+    async fn connect(addr: SocketAddr, tls: &Tls, id: NodeId) -> Result<(), transport::Error> {
+        let peer = Peer::new(addr).with_tls(Some(tls.clone()), Some(id));
+        destination_endpoint(&peer)?.connect().await.map(|_| ())
+    }
+
+    #[test]
+    fn node_domain_name_splits_the_node_id_in_two_labels() {
+        let key =
+            keynesis::key::ed25519::SecretKey::new(rand_chacha::ChaChaRng::from_seed([1; 32]));
+        let id: NodeId =
+            jormungandr_lib::interfaces::NodeId::from_hex(&key.public_key().to_string())
+                .unwrap()
+                .into();
+        let hex_id = id.as_ref().to_string();
+
+        let name = node_domain_name(&id);
+        let labels: Vec<&str> = name.split('.').collect();
+        assert_eq!(labels.len(), 4);
+        assert_eq!(labels[0].len(), 32);
+        assert_eq!(labels[1].len(), 32);
+        assert_eq!(format!("{}{}", labels[0], labels[1]), hex_id);
+        assert_eq!(labels[2..].join("."), NODE_DOMAIN_NAME);
+        // a DNS label is at most 63 characters long
+        assert!(webpki::DNSNameRef::try_from_ascii_str(&name).is_ok());
+    }
+
+    #[test]
+    fn certificates_are_required_for_the_node_id() {
+        assert!(!is_issued_for(&[], &[0; 32]));
+        let not_der = Certificate::from_pem("not a certificate");
+        assert!(!is_issued_for(&[not_der], &[0; 32]));
+    }
+
+    #[test]
+    fn certificates_are_checked_for_the_node_id() {
+        let certs = [der_certificate(NODE1_CERT)];
+        assert!(is_issued_for(
+            &certs,
+            &hex::decode(NODE1_ID.trim()).unwrap()
+        ));
+        assert!(!is_issued_for(
+            &certs,
+            &hex::decode(NODE2_ID.trim()).unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    async fn handshake_succeeds_with_the_pinned_node_id() {
+        let addr = listen(&node_tls(NODE1_CERT, NODE1_KEY)).await;
+        let client_tls = node_tls(NODE2_CERT, NODE2_KEY);
+
+        connect(addr, &client_tls, node_id(NODE1_ID)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn handshake_fails_with_a_mismatched_node_id() {
+        let addr = listen(&node_tls(NODE1_CERT, NODE1_KEY)).await;
+        let client_tls = node_tls(NODE2_CERT, NODE2_KEY);
+
+        assert!(connect(addr, &client_tls, node_id(NODE2_ID)).await.is_err());
+        // the certificate is still valid for the name shared by all the nodes
+        let unpinned_tls = Tls {
+            pin_node_id: false,
+            ..client_tls
+        };
+        connect(addr, &unpinned_tls, node_id(NODE2_ID))
+            .await
+            .unwrap();
+    }
+}
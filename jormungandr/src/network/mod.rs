@@ -46,6 +46,11 @@ mod buffer_sizes {
         // (GetBlocks response or an UploadBlocks request)
         // before the client request task producing them gets preempted.
         pub const BLOCKS: usize = 8;
+
+        // The maximum number of fragments to buffer for an outbound stream
+        // (GetFragments response) before the mempool task producing them
+        // gets preempted.
+        pub const FRAGMENTS: usize = 128;
     }
 }
 
@@ -77,7 +82,7 @@ use crate::blockcfg::{Block, HeaderHash};
 use crate::blockchain::{Blockchain as NewBlockchain, Tip};
 use crate::intercom::{BlockMsg, ClientMsg, NetworkMsg, PropagateMsg, TopologyMsg, TransactionMsg};
 use crate::settings::start::network::{Configuration, Peer, Protocol};
-use crate::topology::{self, NodeId};
+use crate::topology::{self, NodeId, ReportReason};
 use crate::utils::async_msg::{MessageBox, MessageQueue};
 use chain_network::data::NodeKeyPair;
 use rand::seq::SliceRandom;
@@ -91,9 +96,9 @@ use std::fmt;
 use std::iter::FromIterator;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::Duration;
 
 pub use self::bootstrap::Error as BootstrapError;
+use crate::events::NodeEvents;
 use crate::stats_counter::StatsCounter;
 
 #[derive(Debug)]
@@ -126,8 +131,6 @@ enum PropagateError {
     InternalCommRecv(#[from] crate::intercom::Error),
 }
 
-type Connection = SocketAddr;
-
 /// all the different channels the network may need to talk to
 pub struct Channels {
     pub client_box: MessageBox<ClientMsg>,
@@ -153,6 +156,7 @@ pub struct GlobalState {
     config: Configuration,
     stats_counter: StatsCounter,
     peers: Peers,
+    peer_certs: grpc::PeerCertificates,
     keypair: NodeKeyPair,
     span: Span,
 }
@@ -165,10 +169,12 @@ impl GlobalState {
         block0_hash: HeaderHash,
         config: Configuration,
         stats_counter: StatsCounter,
+        events: NodeEvents,
         span: Span,
     ) -> Self {
         let peers = Peers::new(
             config.max_connections,
+            events,
             span!(parent: &span, Level::TRACE, "peers"),
         );
 
@@ -177,12 +183,14 @@ impl GlobalState {
         let mut prng = ChaChaRng::from_seed(rng_seed);
 
         let keypair = NodeKeyPair::generate(&mut prng);
+        let peer_certs = grpc::PeerCertificates::new(config.max_inbound_connections);
 
         GlobalState {
             block0_hash,
             config,
             stats_counter,
             peers,
+            peer_certs,
             keypair,
             span,
         }
@@ -232,11 +240,8 @@ pub struct ConnectionState {
     /// The global state shared between all connections
     pub global: GlobalStateR,
 
-    /// the timeout to wait for unbefore the connection replies
-    pub timeout: Duration,
-
     /// the local (to the task) connection details
-    pub connection: Connection,
+    pub peer: Peer,
 
     pub span: Span,
 }
@@ -244,15 +249,14 @@ pub struct ConnectionState {
 impl ConnectionState {
     fn new(global: GlobalStateR, peer: &Peer, span: Span) -> Self {
         ConnectionState {
-            timeout: peer.timeout,
-            connection: peer.connection,
+            peer: peer.clone(),
             span,
             global,
         }
     }
 
     fn peer(&self) -> Peer {
-        Peer::with_timeout(self.connection, self.timeout)
+        self.peer.clone()
     }
 
     fn span(&self) -> &Span {
@@ -267,9 +271,6 @@ pub struct TaskParams {
 }
 
 pub async fn start(params: TaskParams) {
-    // TODO: the node needs to be saved/loaded
-    //
-    // * the ID needs to be consistent between restart;
     let input = params.input;
     let channels = params.channels;
     let global_state = params.global_state;
@@ -461,7 +462,7 @@ fn connect_and_propagate(
         }
     }
     drop(_enter);
-    let peer = Peer::new(node_addr);
+    let peer = state.config.peer(node_addr, Some(node_id.clone()));
     let conn_span = span!(parent: &state.span, Level::TRACE, "peer", node = %node_addr);
     let _enter = conn_span.enter();
     let conn_state = ConnectionState::new(state.clone(), &peer, conn_span.clone());
@@ -472,28 +473,60 @@ fn connect_and_propagate(
         state.peers.add_connecting(node_addr, handle, options).await;
         match connecting.await {
             Err(e) => {
-                let benign = match e {
+                let report_reason = match e {
                     ConnectError::Transport(e) => {
                         tracing::info!(reason = %e, "gRPC connection to peer failed");
-                        false
+                        Some(ReportReason::Connectivity)
+                    }
+                    // the TLS settings of this node may be at fault, the
+                    // peer is not penalised for those
+                    ConnectError::TlsConfig(e) => {
+                        tracing::error!(
+                            reason = %e,
+                            "cannot use the TLS settings to connect to peer"
+                        );
+                        None
+                    }
+                    ConnectError::TlsHandshake(e) => {
+                        tracing::warn!(reason = %e, "TLS handshake with peer failed");
+                        None
+                    }
+                    ConnectError::ClientAuth(e) => {
+                        tracing::warn!(
+                            reason = %e,
+                            "peer rejected the authentication of this node"
+                        );
+                        None
                     }
                     ConnectError::Handshake(e) => {
                         tracing::info!(reason = %e, "protocol handshake with peer failed");
-                        false
+                        Some(ReportReason::Connectivity)
                     }
                     ConnectError::Canceled => {
                         tracing::debug!("connection to peer has been canceled");
-                        true
+                        None
+                    }
+                    ConnectError::Block0Mismatch { .. } => {
+                        tracing::info!(error = ?e, "peer follows another blockchain");
+                        Some(ReportReason::Block0Mismatch)
+                    }
+                    ConnectError::DecodeBlock0(_)
+                    | ConnectError::InvalidNodeId(_)
+                    | ConnectError::InvalidNodeSignature(_)
+                    | ConnectError::PeerSignatureVerificationFailed(_)
+                    | ConnectError::PinnedNodeIdMismatch => {
+                        tracing::info!(error = ?e, "peer violated the protocol");
+                        Some(ReportReason::ProtocolViolation)
                     }
                     _ => {
                         tracing::info!(error = ?e, "connection to peer failed");
-                        false
+                        Some(ReportReason::Connectivity)
                     }
                 };
-                if !benign {
+                if let Some(reason) = report_reason {
                     channels
                         .topology_box
-                        .send(TopologyMsg::DemotePeer(node_id))
+                        .send(TopologyMsg::DemotePeer(node_id, reason))
                         .await
                         .unwrap_or_else(|e| {
                             tracing::error!("Error sending message to topology task: {}", e)
@@ -525,11 +558,11 @@ fn connect_and_propagate(
     spawn_state.spawn(cf);
 }
 
-fn trusted_peers_shuffled(config: &Configuration) -> Vec<SocketAddr> {
+fn trusted_peers_shuffled(config: &Configuration) -> Vec<Peer> {
     let mut peers = config
         .trusted_peers
         .iter()
-        .map(|peer| peer.addr)
+        .map(|peer| config.peer(peer.addr, peer.id.clone()))
         .collect::<Vec<_>>();
     let mut rng = rand::thread_rng();
     peers.shuffle(&mut rng);
@@ -553,23 +586,24 @@ async fn netboot_peers(config: &Configuration, parent_span: &Span) -> BootstrapP
         .map(|peer| peer.addr)
         .collect::<Vec<_>>();
     let mut peers = HashSet::new();
-    for tpeer in &trusted_peers_addrs {
+    for tpeer in &config.trusted_peers {
         let span = span!(
             parent: parent_span,
             Level::TRACE,
             "netboot_peers",
-            peer_addr = %tpeer.to_string()
+            peer_addr = %tpeer.addr.to_string()
         );
         let received_peers = async move {
-            let res = bootstrap::peers_from_trusted_peer(&Peer::new(*tpeer))
-                .await
-                .unwrap_or_else(|e| {
-                    tracing::warn!(
-                        reason = %e,
-                        "failed to retrieve the list of bootstrap peers from trusted peer"
-                    );
-                    Vec::new()
-                });
+            let res =
+                bootstrap::peers_from_trusted_peer(&config.peer(tpeer.addr, tpeer.id.clone()))
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::warn!(
+                            reason = %e,
+                            "failed to retrieve the list of bootstrap peers from trusted peer"
+                        );
+                        Vec::new()
+                    });
             tracing::info!("adding {} peers from peer", res.len());
             res
         }
@@ -640,11 +674,24 @@ pub async fn bootstrap(
     let mut rng = rand::thread_rng();
     bootstrap_peers.shuffle(&mut rng);
 
-    for peer in &bootstrap_peers {
+    let peers: Vec<Peer> = bootstrap_peers
+        .iter()
+        .map(|peer| config.peer(peer.address(), Some(peer.id())))
+        .collect();
+
+    for (i, peer) in peers.iter().enumerate() {
         let span =
             span!(parent: span, Level::TRACE, "bootstrap", peer_addr = %peer.address().to_string());
-        let res = bootstrap::bootstrap_from_peer(
-            &Peer::new(peer.address()),
+        // the blocks are downloaded from the other peers as well, the
+        // headers are pulled from this one
+        let download_peers: Vec<Peer> = peers[i + 1..]
+            .iter()
+            .chain(peers[..i].iter())
+            .cloned()
+            .collect();
+        let res = bootstrap::bootstrap_from_peers(
+            peer,
+            &download_peers,
             blockchain.clone(),
             branch.clone(),
             cancellation_token.clone(),
@@ -721,9 +768,9 @@ pub async fn fetch_block(
 
     let span = span!(Level::TRACE, "fetch_block", block = %hash.to_string());
     async {
-        for address in trusted_peers_shuffled(&config) {
-            let peer_span = span!(Level::TRACE, "peer_address", address = %address.to_string());
-            let peer = Peer::new(address);
+        for peer in trusted_peers_shuffled(&config) {
+            let peer_span =
+                span!(Level::TRACE, "peer_address", address = %peer.address().to_string());
             match grpc::fetch_block(&peer, hash)
                 .instrument(peer_span.clone())
                 .await
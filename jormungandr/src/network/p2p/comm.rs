@@ -1,8 +1,9 @@
 mod peer_map;
 use super::Address;
+use crate::events::NodeEvents;
 use crate::network::{client::ConnectHandle, security_params::NONCE_LEN};
 use chain_network::data::block::{BlockEvent, ChainPullRequest};
-use chain_network::data::{BlockId, BlockIds, Fragment, Gossip, Header, NodeId};
+use chain_network::data::{BlockId, BlockIds, Fragment, FragmentIds, Gossip, Header, NodeId};
 use futures::channel::mpsc;
 use futures::lock::{Mutex, MutexLockFuture};
 use futures::prelude::*;
@@ -246,6 +247,7 @@ pub struct PeerComms {
     block_solicitations: CommHandle<BlockIds>,
     chain_pulls: CommHandle<ChainPullRequest>,
     fragments: CommHandle<Fragment>,
+    fragment_fetches: CommHandle<FragmentIds>,
     gossip: CommHandle<Gossip>,
     auth: PeerAuth,
 }
@@ -295,6 +297,7 @@ impl PeerComms {
         self.gossip.update(newer.gossip);
         self.block_solicitations.update(newer.block_solicitations);
         self.chain_pulls.update(newer.chain_pulls);
+        self.fragment_fetches.update(newer.fragment_fetches);
         self.auth = newer.auth;
     }
 
@@ -304,6 +307,7 @@ impl PeerComms {
         self.gossip.clear_pending();
         self.block_solicitations.clear_pending();
         self.chain_pulls.clear_pending();
+        self.fragment_fetches.clear_pending();
     }
 
     pub fn set_pending_block_announcement(&mut self, header: Header) {
@@ -348,6 +352,10 @@ impl PeerComms {
         self.chain_pulls.subscribe()
     }
 
+    pub fn subscribe_to_fragment_fetches(&mut self) -> OutboundSubscription<FragmentIds> {
+        self.fragment_fetches.subscribe()
+    }
+
     pub fn subscribe_to_block_events(&mut self) -> BlockEventSubscription {
         let announce_events: BlockEventAnnounceStream = self
             .block_announcements
@@ -384,6 +392,18 @@ impl PeerComms {
     pub fn gossip_subscribed(&self) -> bool {
         self.gossip.is_subscribed()
     }
+
+    /// number of the subscriptions currently established with the peer
+    pub fn subscriptions_count(&self) -> usize {
+        [
+            self.block_announcements_subscribed(),
+            self.fragments_subscribed(),
+            self.gossip_subscribed(),
+        ]
+        .iter()
+        .filter(|subscribed| **subscribed)
+        .count()
+    }
 }
 
 /// Options for Peers::add_connecting
@@ -478,6 +498,7 @@ fn update_last_timestamp(field: &mut Option<SystemTime>, timestamp: SystemTime)
 pub struct PeerInfo {
     pub addr: Option<SocketAddr>,
     pub stats: PeerStats,
+    pub subscriptions: usize,
 }
 
 /// The collection of currently connected peer nodes.
@@ -490,9 +511,9 @@ pub struct Peers {
 }
 
 impl Peers {
-    pub fn new(capacity: usize, span: Span) -> Self {
+    pub fn new(capacity: usize, events: NodeEvents, span: Span) -> Self {
         Peers {
-            mutex: Mutex::new(PeerMap::new(capacity)),
+            mutex: Mutex::new(PeerMap::new(capacity, events)),
             span,
         }
     }
@@ -799,6 +820,38 @@ impl Peers {
         .await
     }
 
+    pub async fn fetch_fragments(&self, peer: Address, ids: FragmentIds) {
+        async move {
+            let mut map = self.inner().await;
+            match map.peer_comms(&peer) {
+                Some(comms) => {
+                    tracing::debug!(
+                        peer = %peer,
+                        count = ids.len(),
+                        "fetching fragments"
+                    );
+                    // only the connections established by this node can
+                    // fetch fragments, the peer is not at fault otherwise
+                    comms.fragment_fetches.try_send(ids).unwrap_or_else(|e| {
+                        tracing::debug!(
+                            peer = %peer,
+                            error = ?e,
+                            "cannot fetch fragments from the peer"
+                        );
+                    });
+                }
+                None => {
+                    tracing::info!(
+                        peer = %peer,
+                        "peer not available to fetch fragments from"
+                    );
+                }
+            }
+        }
+        .instrument(self.span.clone())
+        .await
+    }
+
     pub async fn pull_headers(&self, peer: Address, from: BlockIds, to: BlockId) {
         async move {
             let mut map = self.inner().await;
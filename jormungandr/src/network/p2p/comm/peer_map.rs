@@ -1,20 +1,32 @@
-use crate::network::{
-    client::ConnectHandle,
-    p2p::{
-        comm::{PeerComms, PeerInfo, PeerStats},
-        Address,
+use crate::{
+    events::NodeEvents,
+    network::{
+        client::ConnectHandle,
+        p2p::{
+            comm::{PeerComms, PeerInfo, PeerStats},
+            Address,
+        },
     },
 };
 use chain_network::data::NodeId;
+use jormungandr_lib::interfaces::NodeEvent;
 use linked_hash_map::LinkedHashMap;
+use std::mem;
 
 pub struct PeerMap {
     map: LinkedHashMap<Address, PeerData>,
     capacity: usize,
+    events: NodeEvents,
 }
 
-#[derive(Default)]
+/// The connection events of a peer are published when its comms are
+/// established, either by the handshake of the peer with this node's server
+/// or by the completion of the client connection, and when its data is dropped
+/// as the peer is removed from the map.
 struct PeerData {
+    addr: Address,
+    events: NodeEvents,
+    connected: bool,
     comms: PeerComms,
     stats: PeerStats,
     connecting: Option<ConnectHandle>,
@@ -26,6 +38,25 @@ pub enum CommStatus<'a> {
 }
 
 impl PeerData {
+    fn new(addr: Address, events: NodeEvents) -> Self {
+        PeerData {
+            addr,
+            events,
+            connected: false,
+            comms: Default::default(),
+            stats: Default::default(),
+            connecting: None,
+        }
+    }
+
+    fn set_connected(&mut self) {
+        if !self.connected {
+            self.connected = true;
+            let addr = self.addr;
+            self.events.publish(|| NodeEvent::PeerConnected { addr });
+        }
+    }
+
     fn update_comm_status(&mut self) -> CommStatus<'_> {
         if let Some(ref mut handle) = self.connecting {
             match handle.try_complete() {
@@ -33,6 +64,7 @@ impl PeerData {
                 Ok(Some(comms)) => {
                     self.connecting = None;
                     self.comms.update(comms);
+                    self.set_connected();
                 }
                 Err(_) => {
                     self.connecting = None;
@@ -51,10 +83,20 @@ impl PeerData {
         // since we tabulate peer entries per address rather than node ID.
         self.connecting = None;
         self.comms.clear_pending();
+        self.set_connected();
         &mut self.comms
     }
 }
 
+impl Drop for PeerData {
+    fn drop(&mut self) {
+        if self.connected {
+            let addr = self.addr;
+            self.events.publish(|| NodeEvent::PeerDisconnected { addr });
+        }
+    }
+}
+
 impl<'a> CommStatus<'a> {
     #[allow(dead_code)]
     pub fn node_id(&self) -> Option<NodeId> {
@@ -73,10 +115,11 @@ impl<'a> CommStatus<'a> {
 }
 
 impl PeerMap {
-    pub fn new(capacity: usize) -> Self {
+    pub fn new(capacity: usize, events: NodeEvents) -> Self {
         PeerMap {
             map: LinkedHashMap::new(),
             capacity,
+            events,
         }
     }
 
@@ -108,7 +151,10 @@ impl PeerMap {
         if !self.map.contains_key(&id) {
             self.evict_if_full();
         }
-        self.map.entry(id).or_insert_with(Default::default)
+        let events = &self.events;
+        self.map
+            .entry(id)
+            .or_insert_with(|| PeerData::new(id, events.clone()))
     }
 
     pub fn server_comms(&mut self, id: Address) -> &mut PeerComms {
@@ -126,7 +172,7 @@ impl PeerMap {
             // A bit tricky here: use PeerData::update_comm_status for the
             // side effect, then return the up-to-date member.
             data.update_comm_status();
-            data.comms
+            mem::take(&mut data.comms)
         })
     }
 
@@ -147,6 +193,7 @@ impl PeerMap {
             .map(|(addr, data)| PeerInfo {
                 addr: Some(*addr),
                 stats: data.stats.clone(),
+                subscriptions: data.comms.subscriptions_count(),
             })
             .collect()
     }
@@ -186,3 +233,73 @@ impl<'a> Entry<'a> {
         self.inner.remove();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use tokio::sync::broadcast::Receiver;
+
+    fn addr(port: u16) -> Address {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    fn next_event(events: &mut Receiver<NodeEvent>) -> Option<NodeEvent> {
+        events.try_recv().ok()
+    }
+
+    #[test]
+    fn connection_events_are_published_for_inbound_peers() {
+        let events = NodeEvents::default();
+        let mut receiver = events.subscribe();
+        let mut map = PeerMap::new(2, events);
+
+        map.server_comms(addr(3000));
+        map.server_comms(addr(3000));
+        assert!(matches!(
+            next_event(&mut receiver),
+            Some(NodeEvent::PeerConnected { addr: a }) if a == addr(3000)
+        ));
+        assert!(next_event(&mut receiver).is_none());
+
+        map.remove_peer(addr(3000));
+        assert!(matches!(
+            next_event(&mut receiver),
+            Some(NodeEvent::PeerDisconnected { addr: a }) if a == addr(3000)
+        ));
+        assert!(map.remove_peer(addr(3000)).is_none());
+        assert!(next_event(&mut receiver).is_none());
+    }
+
+    #[test]
+    fn disconnection_is_published_for_evicted_peers() {
+        let events = NodeEvents::default();
+        let mut receiver = events.subscribe();
+        let mut map = PeerMap::new(2, events);
+
+        map.server_comms(addr(3000));
+        map.server_comms(addr(3001));
+        map.server_comms(addr(3002));
+        for port in 3000..3003 {
+            assert!(matches!(
+                next_event(&mut receiver),
+                Some(NodeEvent::PeerConnected { addr: a }) if a == addr(port)
+            ));
+            if port == 3001 {
+                // the oldest peer makes room for the third one
+                assert!(matches!(
+                    next_event(&mut receiver),
+                    Some(NodeEvent::PeerDisconnected { addr: a }) if a == addr(3000)
+                ));
+            }
+        }
+
+        map.clear();
+        for port in 3001..3003 {
+            assert!(matches!(
+                next_event(&mut receiver),
+                Some(NodeEvent::PeerDisconnected { addr: a }) if a == addr(port)
+            ));
+        }
+    }
+}
@@ -1,17 +1,18 @@
 use super::{
     buffer_sizes,
     convert::{self, Decode, Encode, ResponseStream},
+    grpc,
     p2p::comm::{BlockEventSubscription, FragmentSubscription, GossipSubscription},
     subscription, Channels, GlobalStateR,
 };
 use crate::blockcfg as app_data;
-use crate::intercom::{self, BlockMsg, ClientMsg, RequestSink, TopologyMsg};
+use crate::intercom::{self, BlockMsg, ClientMsg, RequestSink, TopologyMsg, TransactionMsg};
 use crate::topology::{self, Gossips};
 use crate::utils::async_msg::MessageBox;
 use chain_network::core::server::{BlockService, FragmentService, GossipService, Node, PushStream};
 use chain_network::data::p2p::{AuthenticatedNodeId, Peer};
 use chain_network::data::{
-    Block, BlockId, BlockIds, Fragment, FragmentIds, Gossip, HandshakeResponse, Header,
+    Block, BlockId, BlockIds, Fragment, FragmentIds, Gossip, HandshakeResponse, Header, NodeId,
 };
 use chain_network::error::{Code as ErrorCode, Error};
 
@@ -22,6 +23,7 @@ use tracing::{span, Level, Span};
 use tracing_futures::Instrument;
 
 use std::convert::TryFrom;
+use std::net::SocketAddr;
 
 #[derive(Clone)]
 pub struct NodeService {
@@ -49,6 +51,35 @@ impl NodeService {
     fn subscription_span(&self, subscriber: Peer, stream_name: &'static str) -> Span {
         span!(parent: self.span(), Level::TRACE, "NodeService", peer = %subscriber.to_string(), stream = %stream_name)
     }
+
+    // With the TLS identity pinned to the node ID, the client shall
+    // authenticate with the node ID its certificate is issued for
+    fn check_pinned_node_id(&self, addr: SocketAddr, node_id: &NodeId) -> Result<(), Error> {
+        let pinned = self
+            .global_state
+            .config
+            .tls
+            .as_ref()
+            .map_or(false, |tls| tls.require_client_auth && tls.pin_node_id);
+        if !pinned {
+            return Ok(());
+        }
+        let certs = self.global_state.peer_certs.take(addr);
+        if certs.map_or(false, |certs| {
+            grpc::is_issued_for(&certs, node_id.as_bytes())
+        }) {
+            Ok(())
+        } else {
+            tracing::info!(
+                peer = %addr,
+                "client node ID is not the one its TLS certificate is issued for"
+            );
+            Err(Error::new(
+                ErrorCode::Unauthenticated,
+                "node ID is not the one the TLS certificate is issued for",
+            ))
+        }
+    }
 }
 
 #[async_trait]
@@ -82,7 +113,9 @@ impl Node for NodeService {
             )
         })?;
         auth.verify(&nonce[..])?;
-        self.global_state.peers.set_node_id(addr, auth.into()).await;
+        let node_id: NodeId = auth.into();
+        self.check_pinned_node_id(addr, &node_id)?;
+        self.global_state.peers.set_node_id(addr, node_id).await;
         Ok(())
     }
 
@@ -289,8 +322,17 @@ impl FragmentService for NodeService {
     type GetFragmentsStream = ResponseStream<app_data::Fragment>;
     type SubscriptionStream = SubscriptionStream<FragmentSubscription>;
 
-    async fn get_fragments(&self, _ids: FragmentIds) -> Result<Self::GetFragmentsStream, Error> {
-        Err(Error::unimplemented())
+    async fn get_fragments(&self, ids: FragmentIds) -> Result<Self::GetFragmentsStream, Error> {
+        let ids = ids.decode()?;
+        let span = span!(Level::TRACE, "request", kind = "GetFragments");
+        let (handle, future) = intercom::stream_reply(buffer_sizes::outbound::FRAGMENTS);
+        let future = future.instrument(span.clone());
+        let transaction_box = self.channels.transaction_box.clone();
+        send_message(transaction_box, TransactionMsg::GetFragments(ids, handle))
+            .instrument(span)
+            .await?;
+        let stream = future.await?;
+        Ok(convert::response_stream(stream))
     }
 
     async fn fragment_subscription(
@@ -306,6 +348,7 @@ impl FragmentService for NodeService {
                 subscription::process_fragments(
                     stream,
                     self.channels.transaction_box.clone(),
+                    self.channels.topology_box.clone(),
                     addr,
                     self.global_state.clone(),
                     span.clone(),
@@ -1,14 +1,16 @@
 use super::{buffer_sizes, convert::Decode, p2p::Address, GlobalStateR};
 use crate::{
     blockcfg::Fragment,
-    intercom::{self, BlockMsg, TopologyMsg, TransactionMsg},
+    intercom::{self, BlockMsg, ReplyFuture, TopologyMsg, TransactionMsg},
     settings::start::network::Configuration,
-    topology::Gossip,
+    topology::{Gossip, ReportReason},
     utils::async_msg::{self, MessageBox},
 };
 use chain_network::data as net_data;
 use chain_network::error::{Code, Error};
-use jormungandr_lib::interfaces::FragmentOrigin;
+use jormungandr_lib::interfaces::{
+    FragmentOrigin, FragmentRejectionReason, FragmentsProcessingSummary,
+};
 
 use futures::future::BoxFuture;
 use futures::prelude::*;
@@ -81,13 +83,14 @@ pub async fn process_gossip<S>(
 pub async fn process_fragments<S>(
     stream: S,
     mbox: MessageBox<TransactionMsg>,
+    topology_box: MessageBox<TopologyMsg>,
     node_id: Address,
     global_state: GlobalStateR,
     span: Span,
 ) where
     S: TryStream<Ok = net_data::Fragment, Error = Error>,
 {
-    let sink = FragmentProcessor::new(mbox, node_id, global_state, span);
+    let sink = FragmentProcessor::new(mbox, topology_box, node_id, global_state, span);
     stream
         .into_stream()
         .forward(sink)
@@ -97,6 +100,37 @@ pub async fn process_fragments<S>(
         });
 }
 
+/// update the reputation of the peer with the outcome of the processing of
+/// the fragments it sent: the peer is reported if the mempool found some of
+/// them invalid, and rewarded for the fragments accepted otherwise
+pub(super) async fn report_fragments_processing(
+    reply_future: ReplyFuture<FragmentsProcessingSummary>,
+    mut topology_box: MessageBox<TopologyMsg>,
+    node_id: Address,
+) {
+    let summary = match reply_future.await {
+        Ok(summary) => summary,
+        Err(e) => {
+            tracing::debug!(reason = %e, "fragments have not been processed");
+            return;
+        }
+    };
+    let invalid = summary
+        .rejected
+        .iter()
+        .any(|info| info.reason == FragmentRejectionReason::FragmentInvalid);
+    let msg = if invalid {
+        TopologyMsg::ReportPeer(node_id, ReportReason::InvalidFragment)
+    } else if !summary.accepted.is_empty() {
+        TopologyMsg::RewardPeer(node_id)
+    } else {
+        return;
+    };
+    topology_box.send(msg).await.unwrap_or_else(
+        |e| tracing::error!(reason = %e, "cannot send message to the topology task"),
+    );
+}
+
 #[must_use = "sinks do nothing unless polled"]
 pub struct BlockAnnouncementProcessor {
     mbox: MessageBox<BlockMsg>,
@@ -151,6 +185,7 @@ impl BlockAnnouncementProcessor {
 #[must_use = "sinks do nothing unless polled"]
 pub struct FragmentProcessor {
     mbox: MessageBox<TransactionMsg>,
+    topology_box: MessageBox<TopologyMsg>,
     node_id: Address,
     global_state: GlobalStateR,
     buffered_fragments: Vec<Fragment>,
@@ -161,12 +196,14 @@ pub struct FragmentProcessor {
 impl FragmentProcessor {
     pub(super) fn new(
         mbox: MessageBox<TransactionMsg>,
+        topology_box: MessageBox<TopologyMsg>,
         node_id: Address,
         global_state: GlobalStateR,
         span: Span,
     ) -> Self {
         FragmentProcessor {
             mbox,
+            topology_box,
             node_id,
             global_state,
             buffered_fragments: Vec::with_capacity(buffer_sizes::inbound::FRAGMENTS),
@@ -175,6 +212,14 @@ impl FragmentProcessor {
         }
     }
 
+    pub fn message_box(&self) -> MessageBox<TransactionMsg> {
+        self.mbox.clone()
+    }
+
+    pub fn topology_box(&self) -> MessageBox<TopologyMsg> {
+        self.topology_box.clone()
+    }
+
     fn refresh_stat(&mut self) {
         let refresh_span = self.span.clone();
         let state = self.global_state.clone();
@@ -347,7 +392,7 @@ impl FragmentProcessor {
             &mut self.buffered_fragments,
             Vec::with_capacity(buffer_sizes::inbound::FRAGMENTS),
         );
-        let (reply_handle, _reply_future) = intercom::unary_reply();
+        let (reply_handle, reply_future) = intercom::unary_reply();
         self.mbox
             .start_send(TransactionMsg::SendTransactions {
                 origin: FragmentOrigin::Network,
@@ -362,6 +407,10 @@ impl FragmentProcessor {
                 );
                 Error::new(Code::Internal, e)
             })?;
+        self.global_state.spawn(
+            report_fragments_processing(reply_future, self.topology_box.clone(), self.node_id)
+                .instrument(span.clone()),
+        );
         self.refresh_stat();
         Poll::Ready(Ok(()))
     }
@@ -0,0 +1,332 @@
+//! Reloading of the node configuration file while the node is running.
+//!
+//! The configuration file is read again and compared with the one that was
+//! last loaded. The entries that can be changed on a running node are sent
+//! to the services using them, the other changed entries are reported as
+//! requiring a restart of the node to be taken into account.
+
+use crate::{
+    intercom::{TopologyMsg, TransactionMsg},
+    network::bootstrap,
+    settings::{
+        self,
+        logging::{self, LogLevelHandle},
+        start::{
+            network::{Peer, Tls},
+            RawSettings, ReloadableSettings,
+        },
+        CommandLine,
+    },
+    topology::Gossips,
+    utils::async_msg::MessageBox,
+};
+use futures::{channel::mpsc::SendError, prelude::*};
+use serde_yaml::{Mapping, Value};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// entries of the configuration file applied without restarting the node
+const RELOADABLE_ENTRIES: &[&str] = &[
+    "log.level",
+    "mempool.pool_max_entries",
+    "mempool.log_max_entries",
+    "mempool.account_max_entries",
+    "p2p.trusted_peers",
+    "p2p.policy.quarantine_whitelist",
+    "p2p.layers.preferred_list",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("the node was started without a configuration file")]
+    NoConfigFile,
+    #[error(transparent)]
+    Settings(#[from] settings::Error),
+    #[error(transparent)]
+    Logging(#[from] logging::Error),
+    #[error("cannot send the new settings to the node services")]
+    MsgSend(#[from] SendError),
+    #[error("the task loading the configuration file failed")]
+    LoadTask(#[from] tokio::task::JoinError),
+}
+
+/// The changed entries of the configuration file, as dotted paths
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadReport {
+    /// entries applied to the running node
+    pub applied: Vec<String>,
+    /// entries that are only taken into account when the node is restarted
+    pub restart_required: Vec<String>,
+}
+
+/// The configuration the node was started with
+pub struct LoadedConfig {
+    command_line: CommandLine,
+    config: Option<Value>,
+    log_level: LogLevelHandle,
+}
+
+impl LoadedConfig {
+    pub fn new(raw_settings: &RawSettings, log_level: LogLevelHandle) -> Self {
+        LoadedConfig {
+            command_line: raw_settings.command_line().clone(),
+            config: raw_settings.raw_config().cloned(),
+            log_level,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Reloader {
+    loaded: Arc<Mutex<LoadedConfig>>,
+    /// the TLS settings of the connections to the peers, changing them
+    /// requires a restart
+    tls: Option<Tls>,
+    transaction_task: MessageBox<TransactionMsg>,
+    topology_task: MessageBox<TopologyMsg>,
+}
+
+impl Reloader {
+    pub fn new(
+        loaded: LoadedConfig,
+        tls: Option<Tls>,
+        transaction_task: MessageBox<TransactionMsg>,
+        topology_task: MessageBox<TopologyMsg>,
+    ) -> Self {
+        Reloader {
+            loaded: Arc::new(Mutex::new(loaded)),
+            tls,
+            transaction_task,
+            topology_task,
+        }
+    }
+
+    /// read the configuration file again and apply the entries that changed
+    /// and can be changed on the running node
+    pub async fn reload(&self) -> Result<ReloadReport, Error> {
+        let mut loaded = self.loaded.lock().await;
+
+        if loaded.command_line.start_arguments.node_config.is_none() {
+            return Err(Error::NoConfigFile);
+        }
+
+        // resolving the DNS names of the configured peers blocks, the
+        // configuration is rejected as a whole if any of them is invalid
+        let command_line = loaded.command_line.clone();
+        let (raw_settings, settings) =
+            tokio::task::spawn_blocking(move || -> Result<_, settings::Error> {
+                let raw_settings = RawSettings::load(command_line)?;
+                let settings = raw_settings.reloadable_settings()?;
+                Ok((raw_settings, settings))
+            })
+            .await??;
+
+        let mut changed = Vec::new();
+        changed_entries(
+            "",
+            loaded.config.as_ref().unwrap_or(&Value::Null),
+            raw_settings.raw_config().unwrap_or(&Value::Null),
+            &mut changed,
+        );
+
+        let (applied, restart_required): (Vec<String>, Vec<String>) = changed
+            .into_iter()
+            .partition(|entry| RELOADABLE_ENTRIES.iter().any(|e| is_under(entry, e)));
+
+        if !applied.is_empty() {
+            self.apply(&loaded.log_level, &applied, settings).await?;
+        }
+        loaded.config = raw_settings.raw_config().cloned();
+
+        if restart_required.is_empty() {
+            tracing::info!(?applied, "node configuration reloaded");
+        } else {
+            tracing::warn!(
+                ?applied,
+                ?restart_required,
+                "node configuration reloaded, some changes require a restart of the node"
+            );
+        }
+
+        Ok(ReloadReport {
+            applied,
+            restart_required,
+        })
+    }
+
+    async fn apply(
+        &self,
+        log_level: &LogLevelHandle,
+        applied: &[String],
+        settings: ReloadableSettings,
+    ) -> Result<(), Error> {
+        let changed = |entry: &str| applied.iter().any(|applied| is_under(applied, entry));
+
+        if changed("log.level") {
+            log_level.set_level(settings.log_level)?;
+        }
+
+        if changed("mempool") {
+            let mempool = settings.mempool;
+            self.transaction_task
+                .clone()
+                .send(TransactionMsg::SetLimits {
+                    pool_max_entries: mempool.pool_max_entries.into(),
+                    logs_max_entries: mempool.log_max_entries.into(),
+                    account_max_entries: mempool.account_max_entries,
+                })
+                .await?;
+        }
+
+        let mut topology_task = self.topology_task.clone();
+        if changed("p2p.policy.quarantine_whitelist") {
+            topology_task
+                .send(TopologyMsg::SetQuarantineWhitelist(
+                    settings.quarantine_whitelist,
+                ))
+                .await?;
+        }
+        if changed("p2p.layers.preferred_list") {
+            topology_task
+                .send(TopologyMsg::SetPreferredList(settings.preferred_list))
+                .await?;
+        }
+        if changed("p2p.trusted_peers") {
+            let peers = settings
+                .trusted_peers
+                .iter()
+                .map(|peer| Peer::new(peer.addr).with_tls(self.tls.clone(), peer.id.clone()))
+                .collect();
+            tokio::spawn(gossip_from_trusted_peers(peers, topology_task));
+        }
+
+        Ok(())
+    }
+}
+
+/// the trusted peers are only contacted when bootstrapping, so the peers
+/// they know are fetched to give the topology a chance to reach them
+async fn gossip_from_trusted_peers(peers: Vec<Peer>, mut topology_task: MessageBox<TopologyMsg>) {
+    for peer in peers {
+        match bootstrap::peers_from_trusted_peer(&peer).await {
+            Ok(peers) => {
+                if let Err(e) = topology_task
+                    .send(TopologyMsg::AcceptGossip(Gossips::from(peers)))
+                    .await
+                {
+                    tracing::error!(reason = %e, "cannot send the trusted peers gossip to the topology");
+                    return;
+                }
+            }
+            Err(e) => {
+                tracing::warn!(
+                    addr = %peer.address(),
+                    reason = %e,
+                    "failed to retrieve the list of peers from trusted peer"
+                );
+            }
+        }
+    }
+}
+
+fn is_under(entry: &str, parent: &str) -> bool {
+    entry == parent
+        || entry
+            .strip_prefix(parent)
+            .map_or(false, |rest| rest.starts_with('.'))
+}
+
+/// list the entries that differ between the two configurations, going down
+/// into the mappings so only the innermost changed entries are listed
+fn changed_entries(path: &str, old: &Value, new: &Value, changed: &mut Vec<String>) {
+    let empty = Mapping::new();
+    let (old, new) = match (old, new) {
+        (Value::Mapping(old), Value::Mapping(new)) => (old, new),
+        (Value::Mapping(old), Value::Null) => (old, &empty),
+        (Value::Null, Value::Mapping(new)) => (&empty, new),
+        (old, new) => {
+            if old != new {
+                changed.push(path.to_owned());
+            }
+            return;
+        }
+    };
+
+    let entry_path = |key: &Value| {
+        let key = key.as_str().unwrap_or_default();
+        if path.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+
+    for (key, old_value) in old {
+        changed_entries(
+            &entry_path(key),
+            old_value,
+            new.get(key).unwrap_or(&Value::Null),
+            changed,
+        );
+    }
+    for (key, new_value) in new {
+        if !old.contains_key(key) {
+            changed_entries(&entry_path(key), &Value::Null, new_value, changed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(old: &str, new: &str) -> Vec<String> {
+        let mut changed = Vec::new();
+        changed_entries(
+            "",
+            &serde_yaml::from_str(old).unwrap(),
+            &serde_yaml::from_str(new).unwrap(),
+            &mut changed,
+        );
+        changed.sort();
+        changed
+    }
+
+    #[test]
+    fn changed_entries_lists_innermost_entries() {
+        let old = "log:\n  level: info\n  format: plain\nrest:\n  listen: 127.0.0.1:8443\n";
+        assert!(changed(old, old).is_empty());
+        assert_eq!(
+            changed(
+                old,
+                "log:\n  level: debug\n  format: plain\nrest:\n  listen: 127.0.0.1:8444\n"
+            ),
+            vec!["log.level", "rest.listen"]
+        );
+        assert_eq!(
+            changed(
+                old,
+                "log:\n  level: info\n  format: plain\nmempool:\n  pool_max_entries: 10\n"
+            ),
+            vec!["mempool.pool_max_entries", "rest.listen"]
+        );
+    }
+
+    #[test]
+    fn reloadable_entries_include_their_sub_entries() {
+        assert!(is_under(
+            "p2p.layers.preferred_list",
+            "p2p.layers.preferred_list"
+        ));
+        assert!(is_under(
+            "p2p.layers.preferred_list.view_max",
+            "p2p.layers.preferred_list"
+        ));
+        assert!(!is_under(
+            "p2p.layers.preferred_lists",
+            "p2p.layers.preferred_list"
+        ));
+        assert!(!is_under("p2p.layers", "p2p.layers.preferred_list"));
+    }
+}
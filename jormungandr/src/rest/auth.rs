@@ -0,0 +1,132 @@
+//! Authentication of the clients of the admin endpoints
+//!
+//! The admin endpoints (node shutdown, configuration reload, leaders
+//! management, peers listing and events stream) are restricted to the clients
+//! presenting one of the configured tokens in the `Authorization: Bearer <token>`
+//! header. When no tokens are configured the admin endpoints stay available to
+//! every client.
+//!
+//! The metrics only expose counters and are left public so they can be scraped
+//! without credentials.
+
+use std::{fs, io, path::Path, sync::Arc};
+use warp::{
+    http::{header, StatusCode},
+    reject::Reject,
+    Filter, Rejection, Reply,
+};
+
+#[derive(Clone)]
+pub struct AdminTokens(Arc<Vec<String>>);
+
+#[derive(Debug)]
+pub struct Unauthorized;
+
+impl Reject for Unauthorized {}
+
+impl AdminTokens {
+    /// load the tokens from a file listing one token per line, empty lines
+    /// and lines starting with `#` are ignored
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let tokens: Vec<String> = fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect();
+
+        if tokens.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "no token found in the admin tokens file",
+            ));
+        }
+
+        Ok(AdminTokens(Arc::new(tokens)))
+    }
+
+    fn authorize(&self, token: &str) -> bool {
+        // all the tokens are compared in full so the response time does not
+        // tell how close a guess is to one of them
+        self.0.iter().fold(false, |found, expected| {
+            found | constant_time_eq(expected, token)
+        })
+    }
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// filter rejecting the requests without a valid admin token, if any tokens
+/// are configured
+pub fn admin(tokens: Option<AdminTokens>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and_then(move |authorization: Option<String>| {
+            let tokens = tokens.clone();
+            async move {
+                let tokens = match tokens {
+                    Some(tokens) => tokens,
+                    None => return Ok(()),
+                };
+                match authorization
+                    .as_deref()
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                {
+                    Some(token) if tokens.authorize(token.trim()) => Ok(()),
+                    _ => Err(warp::reject::custom(Unauthorized)),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Convert the authentication rejections to HTTP errors
+pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
+    if err.find::<Unauthorized>().is_some() {
+        return Ok(warp::reply::with_header(
+            warp::reply::with_status("Unauthorized", StatusCode::UNAUTHORIZED),
+            header::WWW_AUTHENTICATE,
+            "Bearer",
+        ));
+    }
+
+    Err(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn admin_filter_checks_bearer_token() {
+        let tokens = AdminTokens(Arc::new(vec!["secret".to_string()]));
+        let filter = admin(Some(tokens));
+
+        assert!(warp::test::request()
+            .header("authorization", "Bearer secret")
+            .filter(&filter)
+            .await
+            .is_ok());
+        assert!(warp::test::request()
+            .header("authorization", "Bearer secrets")
+            .filter(&filter)
+            .await
+            .is_err());
+        assert!(warp::test::request()
+            .header("authorization", "secret")
+            .filter(&filter)
+            .await
+            .is_err());
+        assert!(warp::test::request().filter(&filter).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn admin_filter_without_tokens_lets_everything_through() {
+        assert!(warp::test::request().filter(&admin(None)).await.is_ok());
+    }
+}
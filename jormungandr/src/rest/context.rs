@@ -1,12 +1,14 @@
 use std::sync::Arc;
 
 use crate::{
-    blockchain::{Blockchain, Tip},
+    blockchain::{AddressIndex, Blockchain, Tip},
     diagnostic::Diagnostic,
+    events::NodeEvents,
     intercom::{NetworkMsg, TopologyMsg, TransactionMsg},
     leadership::Logs as LeadershipLogs,
     network::GlobalStateR as NetworkStateR,
-    rest::ServerStopper,
+    reload::Reloader,
+    rest::{v0::logic::LeadersScheduleCache, ServerStopper},
     secure::enclave::Enclave,
     stats_counter::StatsCounter,
     utils::async_msg::MessageBox,
@@ -28,6 +30,7 @@ pub struct Context {
     blockchain: Option<Blockchain>,
     blockchain_tip: Option<Tip>,
     bootstrap_stopper: Option<CancellationToken>,
+    leaders_schedule: LeadersScheduleCache,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -63,6 +66,7 @@ impl Context {
             blockchain: Default::default(),
             blockchain_tip: Default::default(),
             bootstrap_stopper: Default::default(),
+            leaders_schedule: Default::default(),
         }
     }
 
@@ -130,6 +134,10 @@ impl Context {
         self.bootstrap_stopper = None;
     }
 
+    pub fn leaders_schedule(&self) -> &LeadersScheduleCache {
+        &self.leaders_schedule
+    }
+
     pub fn stop_bootstrap(&mut self) {
         if let Some(cancellation_token) = self.bootstrap_stopper.take() {
             cancellation_token.cancel();
@@ -139,6 +147,8 @@ impl Context {
 
 pub struct FullContext {
     pub stats_counter: StatsCounter,
+    pub events: NodeEvents,
+    pub reloader: Reloader,
     pub network_task: MessageBox<NetworkMsg>,
     pub topology_task: MessageBox<TopologyMsg>,
     pub transaction_task: MessageBox<TransactionMsg>,
@@ -146,4 +156,5 @@ pub struct FullContext {
     pub enclave: Enclave,
     pub network_state: NetworkStateR,
     pub explorer: Option<crate::explorer::Explorer>,
+    pub address_index: Option<AddressIndex>,
 }
@@ -1,4 +1,9 @@
+mod rate_limit;
+
+use self::rate_limit::{RateLimiter, TooManyRequests};
+use crate::explorer::{graphql::MaxPageSize, Explorer};
 use crate::rest::{context, display_internal_server_error, ContextLock};
+use crate::settings::start::config::ExplorerLimits;
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use thiserror::Error;
 use warp::reject::Reject;
@@ -15,31 +20,56 @@ impl Reject for ExplorerGraphQlError {}
 
 pub fn filter(
     context: ContextLock,
+    limits: ExplorerLimits,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    explorer_filter(EContext::Node(context), limits)
+}
+
+/// the explorer API of an explorer running in its own process, without a
+/// node
+pub fn standalone_filter(
+    explorer: Explorer,
+    limits: ExplorerLimits,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let context = explorer.context(None, None);
+    explorer_filter(EContext::Standalone(context), limits)
+}
+
+fn explorer_filter(
+    context: EContext,
+    limits: ExplorerLimits,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let root = warp::path!("explorer" / ..);
 
-    let context_filter_check = std::sync::Arc::clone(&context);
+    let context_filter_check = context.clone();
 
     let with_full_context = warp::any()
         .map(move || context_filter_check.clone())
-        .and_then(|ctx: ContextLock| async move {
-            ctx.read()
-                .await
-                .try_full()
-                .map_err(ExplorerGraphQlError::Context)
-                .map_err(warp::reject::custom)
-                .map(|_| ())
+        .and_then(|ctx: EContext| async move {
+            ctx.get().await.map_err(warp::reject::custom).map(|_| ())
         });
 
-    let schema = async_graphql::Schema::build(
+    let mut schema = async_graphql::Schema::build(
         crate::explorer::graphql::Query {},
         async_graphql::EmptyMutation,
         crate::explorer::graphql::Subscription {},
     )
-    .data(EContext { context })
-    .finish();
+    .data(context);
+    if let Some(max_depth) = limits.max_query_depth {
+        schema = schema.limit_depth(max_depth);
+    }
+    if let Some(max_complexity) = limits.max_query_complexity {
+        schema = schema.limit_complexity(max_complexity);
+    }
+    if let Some(max_page_size) = limits.max_page_size {
+        schema = schema.data(MaxPageSize(max_page_size));
+    }
+    let schema = schema.finish();
+
+    let rate_limiter = limits.max_requests_per_second.map(RateLimiter::new);
 
-    let graphql_post = with_full_context
+    let graphql_post = rate_limit::filter(rate_limiter.clone())
+        .and(with_full_context)
         .and(async_graphql_warp::graphql(schema.clone()))
         .and_then(|_, (schema, request)| handler(schema, request));
 
@@ -54,8 +84,10 @@ pub fn filter(
             ))
     });
 
-    let subscription =
-        warp::path!("subscription").and(async_graphql_warp::graphql_subscription(schema));
+    // opening a subscription counts as a request
+    let subscription = warp::path!("subscription")
+        .and(rate_limit::filter(rate_limiter))
+        .and(async_graphql_warp::graphql_subscription(schema));
 
     let playground = warp::path!("playground").and(graphql_playground).boxed();
 
@@ -80,25 +112,46 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
             StatusCode::INTERNAL_SERVER_ERROR,
         );
 
-        return Ok(warp::reply::with_status(body, code));
+        return Ok(warp::reply::with_status(body, code).into_response());
+    }
+
+    if err.find::<TooManyRequests>().is_some() {
+        // the clients expect the errors in the GraphQL response format
+        let body = warp::reply::json(&serde_json::json!({
+            "errors": [{ "message": "too many requests, try again later" }]
+        }));
+
+        return Ok(warp::reply::with_status(body, StatusCode::TOO_MANY_REQUESTS).into_response());
     }
 
     Err(err)
 }
 
-pub(crate) struct EContext {
-    context: ContextLock,
+#[derive(Clone)]
+pub(crate) enum EContext {
+    /// the explorer runs in the node, it is only available once the node
+    /// is bootstrapped
+    Node(ContextLock),
+    Standalone(crate::explorer::graphql::EContext),
 }
 
 impl EContext {
     pub(crate) async fn get(
         &self,
     ) -> Result<crate::explorer::graphql::EContext, ExplorerGraphQlError> {
-        self.context
-            .read()
-            .await
-            .try_full()
-            .map_err(ExplorerGraphQlError::Context)
-            .map(|ctx| ctx.explorer.clone().unwrap().context())
+        match self {
+            EContext::Node(context) => context
+                .read()
+                .await
+                .try_full()
+                .map_err(ExplorerGraphQlError::Context)
+                .map(|ctx| {
+                    ctx.explorer
+                        .clone()
+                        .unwrap()
+                        .context(Some(ctx.transaction_task.clone()), Some(ctx.events.clone()))
+                }),
+            EContext::Standalone(context) => Ok(context.clone()),
+        }
     }
 }
@@ -0,0 +1,140 @@
+//! Rate limiting of the explorer GraphQL requests of each client
+//!
+//! Each client IP address gets a bucket holding up to a second worth of
+//! requests, refilled at the configured rate. A request takes one token from
+//! the bucket of its client and is rejected when the bucket is empty.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    num::NonZeroU32,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use warp::{reject::Reject, Filter, Rejection};
+
+/// the buckets of the clients idle for longer than this are full again, so
+/// they are dropped instead of being kept forever
+const IDLE_CLIENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    requests_per_second: NonZeroU32,
+    clients: Arc<Mutex<Clients>>,
+}
+
+struct Clients {
+    buckets: HashMap<IpAddr, Bucket>,
+    last_cleanup: Instant,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_update: Instant,
+}
+
+#[derive(Debug)]
+pub struct TooManyRequests;
+
+impl Reject for TooManyRequests {}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: NonZeroU32) -> Self {
+        RateLimiter {
+            requests_per_second,
+            clients: Arc::new(Mutex::new(Clients {
+                buckets: HashMap::new(),
+                last_cleanup: Instant::now(),
+            })),
+        }
+    }
+
+    /// take a token from the bucket of the client, `false` if there is none
+    /// left
+    fn check(&self, client: IpAddr, now: Instant) -> bool {
+        let mut clients = self.clients.lock().unwrap();
+
+        if now.duration_since(clients.last_cleanup) > IDLE_CLIENT_TIMEOUT {
+            clients
+                .buckets
+                .retain(|_, bucket| now.duration_since(bucket.last_update) <= IDLE_CLIENT_TIMEOUT);
+            clients.last_cleanup = now;
+        }
+
+        let rate = f64::from(self.requests_per_second.get());
+        let bucket = clients.buckets.entry(client).or_insert(Bucket {
+            tokens: rate,
+            last_update: now,
+        });
+        let elapsed = now.duration_since(bucket.last_update).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+        bucket.last_update = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// filter rejecting the requests of the clients going over the rate limit,
+/// if a rate limit is configured
+pub fn filter(
+    limiter: Option<RateLimiter>,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::addr::remote()
+        .and_then(move |addr: Option<SocketAddr>| {
+            let limiter = limiter.clone();
+            async move {
+                match (limiter, addr) {
+                    (Some(limiter), Some(addr)) if !limiter.check(addr.ip(), Instant::now()) => {
+                        Err(warp::reject::custom(TooManyRequests))
+                    }
+                    _ => Ok(()),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn bucket_is_refilled_at_the_configured_rate() {
+        let limiter = RateLimiter::new(NonZeroU32::new(2).unwrap());
+        let client = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let other_client = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let now = Instant::now();
+
+        assert!(limiter.check(client, now));
+        assert!(limiter.check(client, now));
+        assert!(!limiter.check(client, now));
+        assert!(limiter.check(other_client, now));
+
+        let later = now + Duration::from_millis(500);
+        assert!(limiter.check(client, later));
+        assert!(!limiter.check(client, later));
+    }
+
+    #[test]
+    fn idle_clients_are_forgotten() {
+        let limiter = RateLimiter::new(NonZeroU32::new(1).unwrap());
+        let client = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let now = Instant::now();
+
+        assert!(limiter.check(client, now));
+        let later = now + IDLE_CLIENT_TIMEOUT * 2;
+        assert!(limiter.check(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), later));
+        assert!(!limiter
+            .clients
+            .lock()
+            .unwrap()
+            .buckets
+            .contains_key(&client));
+    }
+}
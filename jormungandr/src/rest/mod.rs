@@ -1,12 +1,17 @@
 //! REST API of the node
 
+mod auth;
 pub mod context;
 pub mod explorer;
+mod prometheus;
 pub mod v0;
 mod v1;
 
+pub use self::auth::AdminTokens;
 pub use self::context::{Context, ContextLock, FullContext};
 
+use crate::explorer::Explorer;
+use crate::settings::start::config::ExplorerLimits;
 use jormungandr_lib::interfaces::{Rest, Tls};
 
 use futures::{channel::mpsc, prelude::*};
@@ -22,7 +27,12 @@ impl ServerStopper {
     }
 }
 
-pub async fn start_rest_server(config: Rest, explorer_enabled: bool, context: ContextLock) {
+pub async fn start_rest_server(
+    config: Rest,
+    admin_tokens: Option<AdminTokens>,
+    explorer_limits: Option<ExplorerLimits>,
+    context: ContextLock,
+) {
     let (stopper_tx, stopper_rx) = mpsc::channel::<()>(0);
     let stopper_rx = stopper_rx.into_future().map(|_| ());
     context
@@ -30,8 +40,11 @@ pub async fn start_rest_server(config: Rest, explorer_enabled: bool, context: Co
         .await
         .set_server_stopper(ServerStopper(stopper_tx));
 
+    let admin = auth::admin(admin_tokens);
     let api = warp::path!("api" / ..)
-        .and(v0::filter(context.clone()).or(v1::filter(context.clone())))
+        .and(v0::filter(context.clone(), admin.clone()).or(v1::filter(context.clone(), admin)))
+        .or(prometheus::filter(context.clone()))
+        .recover(auth::handle_rejection)
         .with(warp::filters::trace::trace(|info| {
             use http_zipkin::get_trace_context;
             use tracing::field::Empty;
@@ -58,14 +71,26 @@ pub async fn start_rest_server(config: Rest, explorer_enabled: bool, context: Co
             }
             span
         }));
-    if explorer_enabled {
-        let explorer = explorer::filter(context);
+    if let Some(explorer_limits) = explorer_limits {
+        let explorer = explorer::filter(context, explorer_limits);
         setup_cors(api.or(explorer), config, stopper_rx).await;
     } else {
         setup_cors(api, config, stopper_rx).await;
     }
 }
 
+/// Serve the explorer API of an explorer running in its own process, until
+/// the shutdown signal
+pub async fn start_explorer_server(
+    config: Rest,
+    explorer: Explorer,
+    explorer_limits: ExplorerLimits,
+    shutdown_signal: impl Future<Output = ()> + Send + 'static,
+) {
+    let explorer = explorer::standalone_filter(explorer, explorer_limits);
+    setup_cors(explorer, config, shutdown_signal).await;
+}
+
 async fn setup_cors<App>(
     app: App,
     config: Rest,
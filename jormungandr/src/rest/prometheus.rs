@@ -0,0 +1,216 @@
+//! Node metrics in the Prometheus text exposition format
+
+use crate::{
+    intercom::{self, NetworkMsg},
+    rest::{context, display_internal_server_error, Context, ContextLock},
+};
+use jormungandr_lib::interfaces::LeadershipLogStatus;
+
+use futures::{channel::mpsc::SendError, prelude::*};
+use std::fmt::{self, Write};
+use warp::{http::StatusCode, reject::Reject, Filter, Rejection, Reply};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Context(#[from] context::Error),
+    #[error(transparent)]
+    Intercom(#[from] intercom::Error),
+    #[error(transparent)]
+    MsgSend(#[from] SendError),
+    #[error("cannot format the metrics")]
+    Format(#[from] fmt::Error),
+}
+
+impl Reject for Error {}
+
+pub fn filter(
+    context: ContextLock,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let with_context = warp::any().map(move || context.clone());
+
+    warp::path!("metrics")
+        .and(warp::get())
+        .and(with_context)
+        .and_then(get_metrics)
+        .recover(handle_rejection)
+        .boxed()
+}
+
+async fn get_metrics(context: ContextLock) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    let body = render_metrics(&context)
+        .await
+        .map_err(warp::reject::custom)?;
+    Ok(warp::reply::with_header(body, "content-type", CONTENT_TYPE))
+}
+
+/// Convert rejections to actual HTTP errors
+async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
+    if let Some(err) = err.find::<Error>() {
+        let code = match err {
+            Error::Context(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        return Ok(warp::reply::with_status(
+            display_internal_server_error(err),
+            code,
+        ));
+    }
+
+    Err(err)
+}
+
+struct Metrics(String);
+
+impl Metrics {
+    fn header(&mut self, name: &str, kind: &str, help: &str) -> fmt::Result {
+        writeln!(self.0, "# HELP jormungandr_{} {}", name, help)?;
+        writeln!(self.0, "# TYPE jormungandr_{} {}", name, kind)
+    }
+
+    fn sample(&mut self, name: &str, labels: &str, value: impl fmt::Display) -> fmt::Result {
+        if labels.is_empty() {
+            writeln!(self.0, "jormungandr_{} {}", name, value)
+        } else {
+            writeln!(self.0, "jormungandr_{}{{{}}} {}", name, labels, value)
+        }
+    }
+
+    fn single(
+        &mut self,
+        name: &str,
+        kind: &str,
+        help: &str,
+        value: impl fmt::Display,
+    ) -> fmt::Result {
+        self.header(name, kind, help)?;
+        self.sample(name, "", value)
+    }
+}
+
+async fn render_metrics(context: &Context) -> Result<String, Error> {
+    let full_context = context.try_full()?;
+    let stats = &full_context.stats_counter;
+    let mut metrics = Metrics(String::new());
+
+    metrics.single(
+        "uptime_seconds",
+        "gauge",
+        "Time elapsed since the node started.",
+        stats.uptime_sec(),
+    )?;
+    metrics.single(
+        "tx_received_total",
+        "counter",
+        "Number of fragments accepted in the mempool.",
+        stats.tx_recv_cnt(),
+    )?;
+    metrics.single(
+        "block_received_total",
+        "counter",
+        "Number of blocks received from the network.",
+        stats.block_recv_cnt(),
+    )?;
+    metrics.single(
+        "rollback_refused_total",
+        "counter",
+        "Number of branches refused for rolling back deeper than the epoch stability depth.",
+        stats.rollback_refused_cnt(),
+    )?;
+    metrics.single(
+        "peers_connected",
+        "gauge",
+        "Number of peers currently connected.",
+        stats.peer_connected_cnt(),
+    )?;
+    metrics.single(
+        "mempool_fragments",
+        "gauge",
+        "Number of fragments pending in the mempool.",
+        stats.mempool_usage_cnt(),
+    )?;
+    metrics.single(
+        "fragment_logs_entries",
+        "gauge",
+        "Number of entries in the fragment logs.",
+        stats.fragment_logs_cnt(),
+    )?;
+
+    metrics.header(
+        "block_apply_duration_seconds",
+        "summary",
+        "Time spent checking and applying the blocks received from the network.",
+    )?;
+    metrics.sample(
+        "block_apply_duration_seconds_sum",
+        "",
+        stats.block_apply_time().as_secs_f64(),
+    )?;
+    metrics.sample(
+        "block_apply_duration_seconds_count",
+        "",
+        stats.block_apply_cnt(),
+    )?;
+
+    let (mut pending, mut rejected, mut created) = (0, 0, 0);
+    for log in full_context.leadership_logs.logs().await {
+        match log.status() {
+            LeadershipLogStatus::Pending => pending += 1,
+            LeadershipLogStatus::Rejected { .. } => rejected += 1,
+            LeadershipLogStatus::Block { .. } => created += 1,
+        }
+    }
+    metrics.header(
+        "leadership_logs",
+        "gauge",
+        "Number of leadership events by outcome.",
+    )?;
+    metrics.sample("leadership_logs", "status=\"pending\"", pending)?;
+    metrics.sample("leadership_logs", "status=\"rejected\"", rejected)?;
+    metrics.sample("leadership_logs", "status=\"block\"", created)?;
+
+    let (reply_handle, reply_future) = intercom::unary_reply();
+    let mut mbox = full_context.network_task.clone();
+    mbox.send(NetworkMsg::PeerInfo(reply_handle)).await?;
+    let peers = reply_future.await?;
+    metrics.header(
+        "peer_subscriptions",
+        "gauge",
+        "Number of subscriptions established with each connected peer.",
+    )?;
+    for peer in peers {
+        if let Some(addr) = peer.addr {
+            metrics.sample(
+                "peer_subscriptions",
+                &format!("peer=\"{}\"", addr),
+                peer.subscriptions,
+            )?;
+        }
+    }
+
+    if let Ok(tip) = context.blockchain_tip() {
+        let tip_chain_length = u32::from(tip.get_ref().await.chain_length());
+        metrics.single(
+            "tip_chain_length",
+            "gauge",
+            "Chain length of the current tip.",
+            tip_chain_length,
+        )?;
+
+        if let Some(explorer) = &full_context.explorer {
+            if let Some(indexed_chain_length) = explorer.db.tip_chain_length().await {
+                metrics.single(
+                    "explorer_indexing_lag_blocks",
+                    "gauge",
+                    "Number of blocks the explorer tip is behind the node tip.",
+                    tip_chain_length.saturating_sub(u32::from(indexed_chain_length)),
+                )?;
+            }
+        }
+    }
+
+    Ok(metrics.0)
+}
@@ -115,6 +115,14 @@ pub async fn shutdown(context: ContextLock) -> Result<impl Reply, Rejection> {
         .map_err(warp::reject::custom)
 }
 
+pub async fn reload_settings(context: ContextLock) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::reload_settings(&context)
+        .await
+        .map(|r| warp::reply::json(&r))
+        .map_err(warp::reject::custom)
+}
+
 pub async fn get_leaders(context: ContextLock) -> Result<impl Reply, Rejection> {
     let context = context.read().await;
     logic::get_leader_ids(&context)
@@ -151,6 +159,14 @@ pub async fn get_leaders_logs(context: ContextLock) -> Result<impl Reply, Reject
         .map_err(warp::reject::custom)
 }
 
+pub async fn get_leaders_schedule(context: ContextLock) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::get_leaders_schedule(&context)
+        .await
+        .map(|r| warp::reply::json(&r))
+        .map_err(warp::reject::custom)
+}
+
 pub async fn get_stake_pools(context: ContextLock) -> Result<impl Reply, Rejection> {
     let context = context.read().await;
     logic::get_stake_pools(&context)
@@ -231,6 +247,14 @@ pub async fn get_network_p2p_quarantined(context: ContextLock) -> Result<impl Re
         .map_err(warp::reject::custom)
 }
 
+pub async fn get_network_p2p_reputation(context: ContextLock) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::get_network_p2p_reputation(&context)
+        .await
+        .map(|r| warp::reply::json(&r))
+        .map_err(warp::reject::custom)
+}
+
 pub async fn get_network_p2p_non_public(context: ContextLock) -> Result<impl Reply, Rejection> {
     let context = context.read().await;
     logic::get_network_p2p_non_public(&context)
@@ -8,12 +8,14 @@
 use std::net::SocketAddr;
 
 use crate::{
-    blockchain::StorageError,
+    blockchain::{next_epoch_leadership_preview, StorageError},
     diagnostic::Diagnostic,
     intercom::{self, NetworkMsg, TopologyMsg, TransactionMsg},
+    leadership::{Enclave, EnclaveError},
+    reload::{self, ReloadReport},
     rest::Context,
     secure::NodeSecret,
-    topology::PeerInfo,
+    topology::{PeerInfo, PeerReputation},
     utils::async_msg::MessageBox,
 };
 use chain_core::property::{Block as _, Deserialize, FromStr, Serialize};
@@ -26,16 +28,21 @@ use chain_impl_mockchain::{
     block::Block as ChainBlock,
     fragment::{Fragment, FragmentId},
     key::Hash,
-    leadership::{Leader, LeadershipConsensus},
+    leadership::{Leader, Leadership, LeadershipConsensus},
     transaction::Transaction,
     value::{Value, ValueError},
 };
+use chain_time::{
+    era::{EpochPosition, EpochSlotOffset},
+    TimeFrame,
+};
 use jormungandr_lib::{
     interfaces::{
         AccountState, EnclaveLeaderId, EpochRewardsInfo, FragmentLog, FragmentOrigin,
-        FragmentsProcessingSummary, LeadershipLog, NodeStats, NodeStatsDto, PeerStats,
-        Rewards as StakePoolRewards, SettingsDto, StakeDistribution, StakeDistributionDto,
-        StakePoolStats, TaxTypeSerde, TransactionOutput, VotePlanStatus,
+        FragmentsProcessingSummary, LeadershipLog, LeadershipSchedule, LeadershipScheduleEntry,
+        NodeStats, NodeStatsDto, PeerStats, Rewards as StakePoolRewards, SettingsDto,
+        StakeDistribution, StakeDistributionDto, StakePoolStats, TaxTypeSerde, TransactionOutput,
+        VotePlanStatus,
     },
     time::SystemTime,
 };
@@ -79,6 +86,10 @@ pub enum Error {
     Hex(#[from] hex::FromHexError),
     #[error("Could not process fragment")]
     Fragment(FragmentsProcessingSummary),
+    #[error(transparent)]
+    Reload(#[from] reload::Error),
+    #[error(transparent)]
+    Enclave(#[from] EnclaveError),
 }
 
 fn parse_account_id(id_hex: &str) -> Result<Identifier, Error> {
@@ -252,6 +263,7 @@ async fn create_stats(context: &Context) -> Result<Option<NodeStats>, Error> {
         peer_quarantined_cnt,
         peer_total_cnt,
         peer_unreachable_cnt: 0, // FIXME
+        rollback_refused_cnt: stats.rollback_refused_cnt(),
         tx_recv_cnt: stats.tx_recv_cnt(),
         uptime: stats.uptime_sec().into(),
     };
@@ -409,6 +421,10 @@ pub async fn shutdown(context: &mut Context) -> Result<(), Error> {
     Ok(())
 }
 
+pub async fn reload_settings(context: &Context) -> Result<ReloadReport, Error> {
+    Ok(context.try_full()?.reloader.reload().await?)
+}
+
 pub async fn get_leader_ids(context: &Context) -> Result<Vec<EnclaveLeaderId>, Error> {
     Ok(context.try_full()?.enclave.get_leader_ids().await)
 }
@@ -439,6 +455,102 @@ pub async fn get_leaders_logs(context: &Context) -> Result<Vec<LeadershipLog>, E
     Ok(context.try_full()?.leadership_logs.logs().await)
 }
 
+/// the leadership schedules given by the last request, they are only
+/// computed again when the epoch of the tip or the leaders of the enclave
+/// change
+#[derive(Default)]
+pub struct LeadersScheduleCache(tokio::sync::Mutex<Option<CachedLeadersSchedule>>);
+
+struct CachedLeadersSchedule {
+    leadership: Arc<Leadership>,
+    leader_ids: Vec<EnclaveLeaderId>,
+    current: LeadershipSchedule,
+    next: Option<LeadershipSchedule>,
+}
+
+/// the leadership schedule of the leaders of the enclave for the epoch of the
+/// tip, and for the following epoch if its leadership is already fixed.
+///
+/// The leader election of every slot of the epochs is evaluated, this may
+/// take a while with large epochs or many leaders.
+pub async fn get_leaders_schedule(context: &Context) -> Result<Vec<LeadershipSchedule>, Error> {
+    let secure_enclave = &context.try_full()?.enclave;
+    let enclave = Enclave::new(secure_enclave.clone());
+    let tip = context.blockchain_tip()?.get_ref().await;
+    let time_frame = tip.time_frame();
+
+    // the requests wait for the schedule computed by the first one
+    let mut cache = context.leaders_schedule().0.lock().await;
+    let mut leader_ids = secure_enclave.get_leader_ids().await;
+    leader_ids.sort_unstable();
+    let leadership = tip.epoch_leadership_schedule();
+    let cached = cache.take().filter(|cached| {
+        Arc::ptr_eq(&cached.leadership, leadership) && cached.leader_ids == leader_ids
+    });
+    let mut cached = match cached {
+        Some(cached) => cached,
+        None => CachedLeadersSchedule {
+            current: epoch_leaders_schedule(
+                &enclave,
+                Arc::clone(leadership),
+                tip.block_date().epoch,
+                time_frame,
+            )
+            .await?,
+            leadership: Arc::clone(leadership),
+            leader_ids,
+            next: None,
+        },
+    };
+    if cached.next.is_none() {
+        if let Some(leadership) = next_epoch_leadership_preview(&tip) {
+            let epoch = tip.block_date().epoch + 1;
+            cached.next =
+                Some(epoch_leaders_schedule(&enclave, leadership, epoch, time_frame).await?);
+        }
+    }
+
+    let schedules = std::iter::once(cached.current.clone())
+        .chain(cached.next.clone())
+        .collect();
+    *cache = Some(cached);
+    Ok(schedules)
+}
+
+async fn epoch_leaders_schedule(
+    enclave: &Enclave,
+    leadership: Arc<Leadership>,
+    epoch: u32,
+    time_frame: &TimeFrame,
+) -> Result<LeadershipSchedule, Error> {
+    let era = leadership.era().clone();
+    let nb_slots = era.slots_per_epoch();
+    let mut schedule = enclave.query_schedules(leadership, 0, nb_slots).await?;
+
+    let mut entries = Vec::new();
+    while let Some(event) = schedule.next().await {
+        let slot = era.from_era_to_slot(EpochPosition {
+            epoch: chain_time::Epoch(event.date.epoch),
+            slot: EpochSlotOffset(event.date.slot_id),
+        });
+        // the slots of the epochs are always after the start of the
+        // time frame
+        let scheduled_at_time = match time_frame.slot_to_systemtime(slot) {
+            Some(time) => SystemTime::from(time),
+            None => continue,
+        };
+        entries.push(LeadershipScheduleEntry {
+            enclave_leader_id: event.id,
+            scheduled_at_date: event.date.into(),
+            scheduled_at_time,
+        });
+    }
+    // the events of a slot are not given in order
+    entries.sort_by_key(|entry| (entry.scheduled_at_date, entry.enclave_leader_id));
+
+    Ok(LeadershipSchedule { epoch, entries })
+}
+
 pub async fn get_stake_pools(context: &Context) -> Result<Vec<String>, Error> {
     Ok(context
         .blockchain_tip()?
@@ -602,6 +714,18 @@ pub async fn get_network_p2p_quarantined(context: &Context) -> Result<Vec<PeerIn
     reply_future.await.map_err(Into::into)
 }
 
+pub async fn get_network_p2p_reputation(context: &Context) -> Result<Vec<PeerReputation>, Error> {
+    let (reply_handle, reply_future) = intercom::unary_reply();
+    let mut mbox = context.try_full()?.topology_task.clone();
+    mbox.send(TopologyMsg::ListReputation(reply_handle))
+        .await
+        .map_err(|e| {
+            tracing::debug!(reason = %e, "error getting the reputation of the peers");
+            Error::MsgSendError(e)
+        })?;
+    reply_future.await.map_err(Into::into)
+}
+
 pub async fn get_network_p2p_non_public(context: &Context) -> Result<Vec<PeerInfo>, Error> {
     let (reply_handle, reply_future) = intercom::unary_reply();
     let mut mbox = context.try_full()?.topology_task.clone();
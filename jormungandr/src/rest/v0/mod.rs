@@ -1,18 +1,28 @@
 mod handlers;
 pub mod logic;
 
-use crate::rest::{display_internal_server_error, ContextLock};
+use crate::{
+    reload,
+    rest::{display_internal_server_error, ContextLock},
+};
 
 use warp::{http::StatusCode, Filter, Rejection, Reply};
 
-pub fn filter(
+/// `admin` guards the endpoints managing the node or exposing its peers, it
+/// rejects the requests that are not allowed to use them
+pub fn filter<A>(
     context: ContextLock,
-) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    admin: A,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone
+where
+    A: Filter<Extract = (), Error = Rejection> + Clone + Send + Sync + 'static,
+{
     let with_context = warp::any().map(move || context.clone());
     let root = warp::path!("v0" / ..);
 
     let shutdown = warp::path!("shutdown")
         .and(warp::get().or(warp::post()))
+        .and(admin.clone())
         .and(with_context.clone())
         .and_then(|_, context| handlers::shutdown(context))
         .boxed();
@@ -65,6 +75,7 @@ pub fn filter(
 
         let post = warp::path::end()
             .and(warp::post())
+            .and(admin.clone())
             .and(warp::body::json())
             .and(with_context.clone())
             .and_then(handlers::post_leaders)
@@ -76,17 +87,27 @@ pub fn filter(
             .and_then(handlers::get_leaders_logs)
             .boxed();
 
+        let schedule = warp::path!("schedule")
+            .and(warp::get())
+            .and(admin.clone())
+            .and(with_context.clone())
+            .and_then(handlers::get_leaders_schedule)
+            .boxed();
+
         let delete = warp::path!(u32)
             .and(warp::delete())
+            .and(admin.clone())
             .and(with_context.clone())
             .and_then(handlers::delete_leaders)
             .boxed();
 
-        root.and(get.or(post).or(logs).or(delete)).boxed()
+        root.and(get.or(post).or(logs).or(schedule).or(delete))
+            .boxed()
     };
 
+    // the peers known by the node are only listed to the admin clients
     let p2p = {
-        let root = warp::path!("p2p" / ..);
+        let root = warp::path!("p2p" / ..).and(admin.clone());
 
         let quarantined = warp::path!("quarantined")
             .and(warp::get())
@@ -94,6 +115,12 @@ pub fn filter(
             .and_then(handlers::get_network_p2p_quarantined)
             .boxed();
 
+        let reputation = warp::path!("reputation")
+            .and(warp::get())
+            .and(with_context.clone())
+            .and_then(handlers::get_network_p2p_reputation)
+            .boxed();
+
         let non_public = warp::path!("non_public")
             .and(warp::get())
             .and(with_context.clone())
@@ -124,8 +151,14 @@ pub fn filter(
             root.and(view.or(view_topic)).boxed()
         };
 
-        root.and(quarantined.or(non_public).or(available).or(view))
-            .boxed()
+        root.and(
+            quarantined
+                .or(reputation)
+                .or(non_public)
+                .or(available)
+                .or(view),
+        )
+        .boxed()
     };
 
     let network = {
@@ -189,6 +222,13 @@ pub fn filter(
         .and_then(handlers::get_stats_counter)
         .boxed();
 
+    let node_reload = warp::path!("node" / "reload")
+        .and(warp::post())
+        .and(admin)
+        .and(with_context.clone())
+        .and_then(handlers::reload_settings)
+        .boxed();
+
     let tip = warp::path!("tip")
         .and(warp::get())
         .and(with_context.clone())
@@ -253,6 +293,7 @@ pub fn filter(
         .or(stake_pool)
         .or(message)
         .or(node_stats)
+        .or(node_reload)
         .or(tip)
         .or(rewards)
         .or(utxo)
@@ -270,6 +311,10 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
             logic::Error::PublicKey(_) | logic::Error::Hash(_) | logic::Error::Hex(_) => {
                 (err.to_string(), StatusCode::BAD_REQUEST)
             }
+            logic::Error::Reload(reload::Error::NoConfigFile)
+            | logic::Error::Reload(reload::Error::Settings(_)) => {
+                (err.to_string(), StatusCode::BAD_REQUEST)
+            }
             logic::Error::Fragment(summary) => (
                 serde_json::to_string(&summary).unwrap(),
                 StatusCode::BAD_REQUEST,
@@ -1,6 +1,8 @@
 use crate::rest::{v1::logic, ContextLock};
+use futures::prelude::*;
 use jormungandr_lib::interfaces::FragmentsBatch;
-use warp::{reject::Reject, Rejection, Reply};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use warp::{reject::Reject, sse, Rejection, Reply};
 
 impl Reject for logic::Error {}
 
@@ -39,3 +41,35 @@ pub async fn get_fragment_logs(context: ContextLock) -> Result<impl Reply, Rejec
         .map_err(warp::reject::custom)
         .map(|r| warp::reply::json(&r))
 }
+
+#[derive(Deserialize)]
+pub struct GetAddressTransactionsQuery {
+    cursor: Option<u64>,
+    limit: Option<usize>,
+}
+
+pub async fn get_address_transactions(
+    address: String,
+    query: GetAddressTransactionsQuery,
+    context: ContextLock,
+) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    logic::get_address_transactions(&context, &address, query.cursor, query.limit)
+        .await
+        .map_err(warp::reject::custom)
+        .map(|r| warp::reply::json(&r))
+}
+
+pub async fn get_events(context: ContextLock) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    let events = logic::subscribe_events(&context).map_err(warp::reject::custom)?;
+    let stream = BroadcastStream::new(events).map(|event| match event {
+        Ok(event) => sse::Event::default().json_data(event),
+        // let the client know it missed some events, so it can fetch the
+        // current state again if needed
+        Err(BroadcastStreamRecvError::Lagged(missed)) => Ok(sse::Event::default()
+            .event("lagged")
+            .data(missed.to_string())),
+    });
+    Ok(sse::reply(sse::keep_alive().stream(stream)))
+}
@@ -7,12 +7,19 @@ use chain_crypto::{digest::Error as DigestError, hash::Error as HashError, Publi
 use chain_impl_mockchain::{fragment::FragmentId, value::ValueError};
 use futures::{channel::mpsc::SendError, channel::mpsc::TrySendError, prelude::*};
 use jormungandr_lib::interfaces::{
-    FragmentLog, FragmentOrigin, FragmentStatus, FragmentsBatch, FragmentsProcessingSummary,
+    Address, AddressTransactions, FragmentLog, FragmentOrigin, FragmentStatus, FragmentsBatch,
+    FragmentsProcessingSummary, NodeEvent,
 };
 use std::{collections::HashMap, str::FromStr};
+use tokio::sync::broadcast;
 use tracing::{span, Level};
 use tracing_futures::Instrument;
 
+/// number of transactions returned when the request does not give a limit
+const DEFAULT_TRANSACTIONS_LIMIT: usize = 100;
+/// maximum number of transactions returned by a request
+const MAX_TRANSACTIONS_LIMIT: usize = 1000;
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -36,6 +43,10 @@ pub enum Error {
     Storage(#[from] StorageError),
     #[error(transparent)]
     Hex(#[from] hex::FromHexError),
+    #[error(transparent)]
+    Address(#[from] chain_addr::Error),
+    #[error("the address index is not enabled on this node")]
+    AddressIndexDisabled,
     #[error("Could not process all fragments")]
     Fragments(FragmentsProcessingSummary),
 }
@@ -111,3 +122,27 @@ pub async fn get_fragment_logs(context: &Context) -> Result<Vec<FragmentLog>, Er
     .instrument(span)
     .await
 }
+
+pub fn subscribe_events(context: &Context) -> Result<broadcast::Receiver<NodeEvent>, Error> {
+    Ok(context.try_full()?.events.subscribe())
+}
+
+pub async fn get_address_transactions(
+    context: &Context,
+    address: &str,
+    cursor: Option<u64>,
+    limit: Option<usize>,
+) -> Result<AddressTransactions, Error> {
+    let address: Address = address.parse()?;
+    let address_index = context
+        .try_full()?
+        .address_index
+        .as_ref()
+        .ok_or(Error::AddressIndexDisabled)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_TRANSACTIONS_LIMIT)
+        .min(MAX_TRANSACTIONS_LIMIT);
+    Ok(address_index
+        .transactions(address.as_ref(), cursor, limit)
+        .await)
+}
@@ -5,9 +5,15 @@ use crate::rest::{display_internal_server_error, ContextLock};
 
 use warp::{http::StatusCode, Filter, Rejection, Reply};
 
-pub fn filter(
+/// `admin` guards the events stream, it rejects the requests that are not
+/// allowed to use it
+pub fn filter<A>(
     context: ContextLock,
-) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    admin: A,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone
+where
+    A: Filter<Extract = (), Error = Rejection> + Clone + Send + Sync + 'static,
+{
     let with_context = warp::any().map(move || context.clone());
     let root = warp::path!("v1" / ..);
 
@@ -30,14 +36,29 @@ pub fn filter(
 
         let logs = warp::path!("logs")
             .and(warp::get())
-            .and(with_context)
+            .and(with_context.clone())
             .and_then(handlers::get_fragment_logs)
             .boxed();
 
         root.and(post.or(status).or(logs)).boxed()
     };
 
-    let routes = fragments;
+    let address_transactions = warp::path!("address" / String / "transactions")
+        .and(warp::get())
+        .and(warp::query())
+        .and(with_context.clone())
+        .and_then(handlers::get_address_transactions)
+        .boxed();
+
+    // the events tell the peers of the node and the status of its leaders
+    let events = warp::path!("events")
+        .and(warp::get())
+        .and(admin)
+        .and(with_context)
+        .and_then(handlers::get_events)
+        .boxed();
+
+    let routes = fragments.or(address_transactions).or(events);
 
     root.and(routes).recover(handle_rejection).boxed()
 }
@@ -46,9 +67,11 @@ pub fn filter(
 async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
     if let Some(err) = err.find::<logic::Error>() {
         let (body, code) = match err {
-            logic::Error::PublicKey(_) | logic::Error::Hash(_) | logic::Error::Hex(_) => {
-                (err.to_string(), StatusCode::BAD_REQUEST)
-            }
+            logic::Error::PublicKey(_)
+            | logic::Error::Hash(_)
+            | logic::Error::Hex(_)
+            | logic::Error::Address(_) => (err.to_string(), StatusCode::BAD_REQUEST),
+            logic::Error::AddressIndexDisabled => (err.to_string(), StatusCode::NOT_FOUND),
             logic::Error::Fragments(summary) => (
                 serde_json::to_string(&summary).unwrap(),
                 StatusCode::BAD_REQUEST,
@@ -14,7 +14,7 @@ fn trusted_peer_from_json(json: &str) -> Result<TrustedPeer, serde_json::Error>
     serde_json::from_str(json)
 }
 
-#[derive(StructOpt, Debug)]
+#[derive(StructOpt, Debug, Clone)]
 pub struct StartArguments {
     /// Path to the blockchain pool storage directory
     #[structopt(long = "storage", parse(from_os_str))]
@@ -62,7 +62,7 @@ pub struct StartArguments {
     pub listen_address: Option<SocketAddr>,
 }
 
-#[derive(StructOpt, Debug)]
+#[derive(StructOpt, Debug, Clone)]
 pub struct RestArguments {
     /// REST API listening address.
     /// If not configured anywhere, defaults to REST API being disabled
@@ -70,7 +70,7 @@ pub struct RestArguments {
     pub listen: Option<SocketAddr>,
 }
 
-#[derive(StructOpt, Debug)]
+#[derive(StructOpt, Debug, Clone)]
 #[structopt(
     name = "jormungandr",
     setting = structopt::clap::AppSettings::ColoredHelp
@@ -99,7 +99,9 @@ pub struct CommandLine {
     /// report all the rewards in the reward distribution history
     ///
     /// NOTE: this will slowdown the epoch transition computation and will add
-    /// add a lot of items for in-memory operations, this is not recommended to set
+    /// add a lot of items for in-memory operations, this is not recommended to set.
+    /// It is always set when the explorer is enabled, to index the rewards of
+    /// every account
     #[structopt(long = "rewards-report-all")]
     pub rewards_report_all: bool,
 
@@ -121,6 +123,11 @@ pub struct CommandLine {
     /// Initialize the storage and exit, useful to check that the storage has been set up correctly.
     #[structopt(long = "storage-check")]
     pub storage_check: bool,
+
+    /// Replay the whole blockchain from the storage, check the ledger snapshots
+    /// against the replayed ledger states and exit.
+    #[structopt(long = "storage-check-snapshots")]
+    pub storage_check_snapshots: bool,
 }
 
 impl CommandLine {
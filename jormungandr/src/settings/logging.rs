@@ -12,12 +12,17 @@ use tracing_appender::non_blocking::WorkerGuard;
 use tracing::subscriber::SetGlobalDefaultError;
 #[allow(unused_imports)]
 use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{reload, Registry};
 
 pub struct LogSettings {
     pub config: LogSettingsEntry,
     pub msgs: LogInfoMsg,
 }
 
+/// Handle allowing to change the level of the logger once initialized
+#[derive(Clone)]
+pub struct LogLevelHandle(reload::Handle<LevelFilter, Registry>);
+
 /// A wrapper to return an optional string message that we
 /// have to manually log with `info!`, we need this because
 /// some code executes before the logs are initialized.
@@ -100,12 +105,16 @@ impl FromStr for LogOutput {
 }
 
 impl LogSettings {
-    pub fn init_log(self) -> Result<(Vec<WorkerGuard>, LogInfoMsg), Error> {
+    pub fn init_log(self) -> Result<(Vec<WorkerGuard>, LogLevelHandle, LogInfoMsg), Error> {
         use tracing_subscriber::prelude::*;
 
         // Worker guards that need to be held on to.
         let mut guards = Vec::new();
 
+        // the level filter is wrapped so it can be changed when the node
+        // configuration is reloaded
+        let (level, level_handle) = reload::Layer::new(self.config.level);
+
         // configure the registry subscriber as the global default,
         // panics if something goes wrong.
         match self.config.output {
@@ -118,7 +127,7 @@ impl LogSettings {
                             .with_level(true)
                             .with_writer(non_blocking);
                         tracing_subscriber::registry()
-                            .with(self.config.level)
+                            .with(level)
                             .with(layer)
                             .init();
                     }
@@ -128,7 +137,7 @@ impl LogSettings {
                             .with_level(true)
                             .with_writer(non_blocking);
                         tracing_subscriber::registry()
-                            .with(self.config.level)
+                            .with(level)
                             .with(layer)
                             .init();
                     }
@@ -143,7 +152,7 @@ impl LogSettings {
                             .with_level(true)
                             .with_writer(non_blocking);
                         tracing_subscriber::registry()
-                            .with(self.config.level)
+                            .with(level)
                             .with(layer)
                             .init();
                     }
@@ -153,7 +162,7 @@ impl LogSettings {
                             .with_level(true)
                             .with_writer(non_blocking);
                         tracing_subscriber::registry()
-                            .with(self.config.level)
+                            .with(level)
                             .with(layer)
                             .init();
                     }
@@ -178,7 +187,7 @@ impl LogSettings {
                             .with_level(true)
                             .with_writer(non_blocking);
                         tracing_subscriber::registry()
-                            .with(self.config.level)
+                            .with(level)
                             .with(layer)
                             .init();
                     }
@@ -188,7 +197,7 @@ impl LogSettings {
                             .with_level(true)
                             .with_writer(non_blocking);
                         tracing_subscriber::registry()
-                            .with(self.config.level)
+                            .with(level)
                             .with(layer)
                             .init();
                     }
@@ -199,7 +208,7 @@ impl LogSettings {
                 self.config.format.require_default()?;
                 let layer = tracing_journald::layer().map_err(Error::Journald)?;
                 tracing_subscriber::registry()
-                    .with(self.config.level)
+                    .with(level)
                     .with(layer)
                     .init();
             }
@@ -210,13 +219,19 @@ impl LogSettings {
                     .map_err(Error::Gelf)?;
                 tokio::spawn(task);
                 tracing_subscriber::registry()
-                    .with(self.config.level)
+                    .with(level)
                     .with(layer)
                     .init();
             }
         }
 
-        Ok((guards, self.msgs))
+        Ok((guards, LogLevelHandle(level_handle), self.msgs))
+    }
+}
+
+impl LogLevelHandle {
+    pub fn set_level(&self, level: LevelFilter) -> Result<(), Error> {
+        self.0.reload(level).map_err(Error::LevelReload)
     }
 }
 
@@ -246,6 +261,8 @@ pub enum Error {
     #[cfg(feature = "gelf")]
     #[error("GELF connection failed")]
     Gelf(tracing_gelf::BuilderError),
+    #[error("failed to change the log level")]
+    LevelReload(#[source] reload::Error),
     #[error("failed to set global subscriber")]
     SetGlobalSubscriberError(#[source] SetGlobalDefaultError),
 }
@@ -11,7 +11,7 @@ use multiaddr::Multiaddr;
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use tracing::level_filters::LevelFilter;
 
-use std::path::PathBuf;
+use std::{num::NonZeroU32, path::PathBuf};
 
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -38,6 +38,9 @@ pub struct Config {
 
     pub explorer: Option<Explorer>,
 
+    /// index of the transactions of each address, queried with the REST API
+    pub address_index: Option<AddressIndex>,
+
     /// the time interval with no blockchain updates after which alerts are thrown
     #[serde(default)]
     pub no_blockchain_updates_warning_interval: Option<Duration>,
@@ -140,6 +143,36 @@ pub struct P2pConfig {
     /// gossip with the trusted peers if any are defined.
     #[serde(default)]
     pub max_bootstrap_attempts: Option<usize>,
+
+    /// TLS settings of the connections to and from the peers, the
+    /// connections are not encrypted if not set
+    #[serde(default)]
+    pub tls: Option<P2pTls>,
+}
+
+/// TLS settings of the node to node connections. All the nodes of the
+/// network shall use TLS as a node only accepts TLS connections once it is
+/// configured.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct P2pTls {
+    /// Path to the X.509 certificate chain file presented to the peers,
+    /// must be PEM-encoded and contain at least 1 item
+    pub cert_file: PathBuf,
+    /// Path to the private key file of the certificate, must be PKCS8 with
+    /// single PEM-encoded, unencrypted key
+    pub priv_key_file: PathBuf,
+    /// Path to the PEM-encoded certificates of the authorities the
+    /// certificates of the peers are verified against
+    pub ca_file: PathBuf,
+    /// Whether to reject the connections of the peers not presenting a
+    /// certificate issued by the authorities of `ca_file`
+    #[serde(default)]
+    pub require_client_auth: bool,
+    /// Whether the certificate of a peer must be issued for the node ID the
+    /// peer authenticates with in the handshake, requires `require_client_auth`
+    #[serde(default)]
+    pub pin_node_id: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -155,6 +188,30 @@ pub struct Leadership {
 #[serde(deny_unknown_fields)]
 pub struct Explorer {
     pub enabled: bool,
+    /// the limits applied to the GraphQL requests, no limit is applied by default
+    #[serde(default)]
+    pub limits: ExplorerLimits,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ExplorerLimits {
+    /// the maximum nesting depth of the fields of a query
+    pub max_query_depth: Option<usize>,
+    /// the maximum complexity of a query, each requested field counting for one
+    pub max_query_complexity: Option<usize>,
+    /// the maximum number of items in a page of a connection, the connections
+    /// queried without a page size give a page of this size
+    pub max_page_size: Option<usize>,
+    /// the maximum number of GraphQL requests and subscriptions per second
+    /// accepted from the same IP address
+    pub max_requests_per_second: Option<NonZeroU32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AddressIndex {
+    pub enabled: bool,
 }
 
 impl Default for P2pConfig {
@@ -174,6 +231,7 @@ impl Default for P2pConfig {
             gossip_interval: None,
             network_stuck_check: None,
             max_bootstrap_attempts: None,
+            tls: None,
         }
     }
 }
@@ -1,17 +1,26 @@
 pub mod config;
 pub mod network;
 
-use self::config::{Config, Leadership};
+use self::config::{Config, ExplorerLimits, Leadership};
 use self::network::{Protocol, TrustedPeer};
+use crate::network::p2p::Address;
 use crate::settings::logging::{LogFormat, LogInfoMsg, LogOutput, LogSettings, LogSettingsEntry};
 use crate::settings::{command_arguments::*, Block0Info};
-use crate::topology::layers::{self, LayersConfig, PreferredListConfig, RingsConfig};
+use crate::topology::{
+    layers::{self, LayersConfig, PreferredListConfig, RingsConfig},
+    WhitelistError,
+};
 use chain_crypto::Ed25519;
 use jormungandr_lib::crypto::key::SigningKey;
 pub use jormungandr_lib::interfaces::{Cors, Mempool, Rest, Tls};
 use jormungandr_lib::multiaddr;
+use std::collections::HashSet;
 use std::convert::TryFrom;
-use std::{fs::File, path::PathBuf};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 use thiserror::Error;
 use tracing::level_filters::LevelFilter;
 
@@ -20,6 +29,9 @@ const DEFAULT_LOG_FORMAT: LogFormat = LogFormat::Default;
 const DEFAULT_LOG_OUTPUT: LogOutput = LogOutput::Stderr;
 const DEFAULT_NO_BLOCKCHAIN_UPDATES_WARNING_INTERVAL: u64 = 1800; // 30 min
 const DEFAULT_BLOCK_HARD_DEADLINE: u32 = 50;
+/// the file in the storage directory keeping the node key, unless a node key
+/// file is configured
+const NODE_KEY_FILE: &str = "p2p/node_key";
 const DEFAULT_LOG_SETTINGS_ENTRY: LogSettingsEntry = LogSettingsEntry {
     level: DEFAULT_FILTER_LEVEL,
     format: DEFAULT_LOG_FORMAT,
@@ -38,8 +50,16 @@ pub enum Error {
     InvalidMultiaddr(#[from] multiaddr::Error),
     #[error("cannot deserialize node key from file")]
     InvalidKey(#[from] chain_crypto::bech32::Error),
+    #[error("Cannot read or write the node key kept in the storage: {0}")]
+    NodeKeyIo(#[source] std::io::Error),
+    #[error("Cannot read the certificates or the private key of the P2P TLS settings: {0}")]
+    P2pTlsIo(#[source] std::io::Error),
+    #[error("The P2P TLS setting pin_node_id requires require_client_auth to be set")]
+    PinNodeIdWithoutClientAuth,
     #[error(transparent)]
     InvalidLayersConfig(#[from] layers::ParseError),
+    #[error(transparent)]
+    InvalidQuarantineWhitelist(#[from] WhitelistError),
 }
 
 /// Overall Settings for node
@@ -53,25 +73,80 @@ pub struct Settings {
     pub rewards_report_all: bool,
     pub leadership: Leadership,
     pub explorer: bool,
+    pub explorer_limits: ExplorerLimits,
+    pub address_index: bool,
     pub no_blockchain_updates_warning_interval: std::time::Duration,
     pub block_hard_deadline: u32,
 }
 
+/// The settings that can be changed without restarting the node
+pub struct ReloadableSettings {
+    pub log_level: LevelFilter,
+    pub mempool: Mempool,
+    pub trusted_peers: Vec<TrustedPeer>,
+    pub quarantine_whitelist: HashSet<Address>,
+    pub preferred_list: PreferredListConfig,
+}
+
 pub struct RawSettings {
     command_line: CommandLine,
     config: Option<Config>,
+    /// the content of the config file, kept to find the settings
+    /// that changed when the file is reloaded
+    raw_config: Option<serde_yaml::Value>,
 }
 
 impl RawSettings {
     pub fn load(command_line: CommandLine) -> Result<Self, Error> {
-        let config = if let Some(node_config) = &command_line.start_arguments.node_config {
-            Some(serde_yaml::from_reader(File::open(node_config)?)?)
-        } else {
-            None
-        };
+        let (config, raw_config) =
+            if let Some(node_config) = &command_line.start_arguments.node_config {
+                let content = fs::read_to_string(node_config)?;
+                (
+                    Some(serde_yaml::from_str(&content)?),
+                    Some(serde_yaml::from_str(&content)?),
+                )
+            } else {
+                (None, None)
+            };
         Ok(Self {
             command_line,
             config,
+            raw_config,
+        })
+    }
+
+    pub fn command_line(&self) -> &CommandLine {
+        &self.command_line
+    }
+
+    pub fn raw_config(&self) -> Option<&serde_yaml::Value> {
+        self.raw_config.as_ref()
+    }
+
+    /// the settings that can be changed on a running node, resolving the
+    /// DNS names of the configured peers may block
+    pub fn reloadable_settings(&self) -> Result<ReloadableSettings, Error> {
+        let p2p = self
+            .config
+            .as_ref()
+            .map_or_else(config::P2pConfig::default, |cfg| cfg.p2p.clone());
+        let preferred_list = p2p.layers.preferred_list.unwrap_or_default();
+
+        Ok(ReloadableSettings {
+            log_level: self.log_settings().config.level,
+            mempool: self
+                .config
+                .as_ref()
+                .map_or(Mempool::default(), |cfg| cfg.mempool.clone()),
+            trusted_peers: resolve_trusted_peers(&trusted_peers(
+                &self.command_line.start_arguments,
+                &p2p,
+            )),
+            quarantine_whitelist: p2p.policy.whitelist()?,
+            preferred_list: PreferredListConfig {
+                view_max: preferred_list.view_max.into(),
+                peers: resolve_trusted_peers(&preferred_list.peers),
+            },
         })
     }
 
@@ -147,6 +222,7 @@ impl RawSettings {
                 listen: cmd_listen,
                 tls: None,
                 cors: None,
+                admin: None,
             }),
             (None, None) => None,
         }
@@ -162,9 +238,9 @@ impl RawSettings {
         let RawSettings {
             command_line,
             config,
+            ..
         } = self;
         let command_arguments = &command_line.start_arguments;
-        let network = generate_network(&command_arguments, &config)?;
 
         let storage = match (
             command_arguments.storage.as_ref(),
@@ -175,6 +251,8 @@ impl RawSettings {
             (None, None) => None,
         };
 
+        let network = generate_network(&command_arguments, &config, storage.as_deref())?;
+
         let mut secrets = command_arguments.secret.clone();
         if let Some(secret_files) = config.as_ref().map(|cfg| cfg.secret_files.clone()) {
             secrets.extend(secret_files);
@@ -208,7 +286,8 @@ impl RawSettings {
             block_0,
             network,
             secrets,
-            rewards_report_all: command_line.rewards_report_all,
+            // the explorer indexes the rewards of every account
+            rewards_report_all: command_line.rewards_report_all || explorer,
             rest,
             mempool: config
                 .as_ref()
@@ -217,6 +296,17 @@ impl RawSettings {
                 .as_ref()
                 .map_or(Leadership::default(), |cfg| cfg.leadership.clone()),
             explorer,
+            explorer_limits: config
+                .as_ref()
+                .and_then(|cfg| cfg.explorer.as_ref())
+                .map_or(ExplorerLimits::default(), |settings| {
+                    settings.limits.clone()
+                }),
+            address_index: config.as_ref().map_or(false, |cfg| {
+                cfg.address_index
+                    .as_ref()
+                    .map_or(false, |settings| settings.enabled)
+            }),
             no_blockchain_updates_warning_interval: config
                 .as_ref()
                 .and_then(|config| config.no_blockchain_updates_warning_interval)
@@ -256,12 +346,83 @@ fn resolve_trusted_peers(peers: &[jormungandr_lib::interfaces::TrustedPeer]) ->
         .collect()
 }
 
+/// the trusted peers of the config file followed by the ones given
+/// on the command line
+fn trusted_peers(
+    command_arguments: &StartArguments,
+    p2p: &config::P2pConfig,
+) -> Vec<jormungandr_lib::interfaces::TrustedPeer> {
+    p2p.trusted_peers
+        .iter()
+        .flatten()
+        .chain(command_arguments.trusted_peer.iter())
+        .cloned()
+        .collect()
+}
+
+/// load the node key kept in the storage, generating and storing a new one
+/// on the first start, so the node keeps the same identity in the p2p network
+/// across restarts
+fn load_or_generate_node_key(storage: &Path) -> Result<SigningKey<Ed25519>, Error> {
+    let path = storage.join(NODE_KEY_FILE);
+    match fs::read_to_string(&path) {
+        Ok(key) => Ok(<SigningKey<Ed25519>>::from_bech32_str(key.trim())?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key = SigningKey::generate(rand::thread_rng());
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(Error::NodeKeyIo)?;
+            }
+            store_node_key(&path, &key).map_err(Error::NodeKeyIo)?;
+            tracing::info!("generated a new node key stored in '{}'", path.display());
+            Ok(key)
+        }
+        Err(e) => Err(Error::NodeKeyIo(e)),
+    }
+}
+
+/// write the key in a temporary file renamed once its content is synced on
+/// disk, so an interrupted start never leaves a partially written key
+fn store_node_key(path: &Path, key: &SigningKey<Ed25519>) -> std::io::Result<()> {
+    let path_tmp = path.with_extension("tmp");
+    // the temporary file of an interrupted start is replaced
+    match fs::remove_file(&path_tmp) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+
+    let mut file = create_key_file(&path_tmp)?;
+    file.write_all(key.to_bech32_str().as_bytes())?;
+    file.sync_all()?;
+    fs::rename(path_tmp, path)
+}
+
+/// create the file of a new key, readable by the user running the node only
+#[cfg(unix)]
+fn create_key_file(path: &Path) -> std::io::Result<fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn create_key_file(path: &Path) -> std::io::Result<fs::File> {
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+}
+
 #[allow(deprecated)]
 fn generate_network(
     command_arguments: &StartArguments,
     config: &Option<Config>,
+    storage: Option<&Path>,
 ) -> Result<network::Configuration, Error> {
-    let (mut p2p, http_fetch_block0_service, skip_bootstrap, bootstrap_from_trusted_peers) =
+    let (p2p, http_fetch_block0_service, skip_bootstrap, bootstrap_from_trusted_peers) =
         if let Some(cfg) = config {
             (
                 cfg.p2p.clone(),
@@ -273,18 +434,7 @@ fn generate_network(
             (config::P2pConfig::default(), Vec::new(), false, false)
         };
 
-    if p2p.trusted_peers.is_some() {
-        if let Some(peers) = p2p.trusted_peers.as_mut() {
-            peers.extend(command_arguments.trusted_peer.clone())
-        }
-    } else if !command_arguments.trusted_peer.is_empty() {
-        p2p.trusted_peers = Some(command_arguments.trusted_peer.clone())
-    }
-
-    let trusted_peers = p2p
-        .trusted_peers
-        .as_ref()
-        .map_or_else(Vec::new, |peers| resolve_trusted_peers(peers));
+    let trusted_peers = resolve_trusted_peers(&trusted_peers(command_arguments, &p2p));
 
     // Layers config
     let preferred_list_config = p2p.layers.preferred_list.unwrap_or_default();
@@ -312,9 +462,20 @@ fn generate_network(
         Some(node_key_file) => {
             <SigningKey<Ed25519>>::from_bech32_str(&std::fs::read_to_string(&node_key_file)?)?
         }
-        None => SigningKey::generate(rand::thread_rng()),
+        None => match storage {
+            Some(storage) => load_or_generate_node_key(storage)?,
+            None => SigningKey::generate(rand::thread_rng()),
+        },
     };
 
+    // the node ID of a client is only checked against its certificate if the
+    // client has to present one
+    if let Some(tls) = &p2p.tls {
+        if tls.pin_node_id && !tls.require_client_auth {
+            return Err(Error::PinNodeIdWithoutClientAuth);
+        }
+    }
+
     let p2p_listen_address = p2p.listen.as_ref();
     let listen_address = command_arguments
         .listen_address
@@ -327,6 +488,7 @@ fn generate_network(
         public_address,
         trusted_peers,
         node_key,
+        quarantine_whitelist: p2p.policy.whitelist()?,
         policy: p2p.policy.clone(),
         protocol: Protocol::Grpc,
         layers: LayersConfig {
@@ -354,6 +516,12 @@ fn generate_network(
         http_fetch_block0_service,
         bootstrap_from_trusted_peers,
         skip_bootstrap,
+        tls: p2p
+            .tls
+            .as_ref()
+            .map(network::Tls::load)
+            .transpose()
+            .map_err(Error::P2pTlsIo)?,
     };
 
     if network.max_inbound_connections > network.max_connections {
@@ -4,9 +4,10 @@ use crate::topology::{layers::LayersConfig, NodeId, QuarantineConfig};
 
 use chain_crypto::Ed25519;
 use jormungandr_lib::{crypto::key::SigningKey, multiaddr};
+use std::collections::HashSet;
 use std::net::SocketAddr;
-use std::str;
 use std::time::Duration;
+use std::{fmt, fs, io, str};
 
 /// Protocol to use for a connection.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -24,6 +25,27 @@ pub struct Peer {
     pub protocol: Protocol,
     /// a timeout in case of inactivity or timout between request.
     pub timeout: Duration,
+    /// TLS settings of the connection, the connection is not encrypted if
+    /// not set
+    #[serde(skip)]
+    pub tls: Option<Tls>,
+    /// the node ID of the peer, if known
+    #[serde(skip)]
+    pub id: Option<NodeId>,
+}
+
+/// TLS settings of the node to node connections, see `config::P2pTls`
+#[derive(Clone, PartialEq, Eq)]
+pub struct Tls {
+    /// PEM-encoded certificate chain presented to the peers
+    pub cert: Vec<u8>,
+    /// PEM-encoded private key of the certificate
+    pub priv_key: Vec<u8>,
+    /// PEM-encoded certificates of the authorities the certificates of the
+    /// peers are verified against
+    pub ca_cert: Vec<u8>,
+    pub require_client_auth: bool,
+    pub pin_node_id: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -81,6 +103,9 @@ pub struct Configuration {
 
     pub policy: QuarantineConfig,
 
+    /// the resolved addresses of `policy`'s quarantine whitelist
+    pub quarantine_whitelist: HashSet<Address>,
+
     pub layers: LayersConfig,
 
     /// Whether to allow non-public IP addresses in gossip
@@ -101,6 +126,8 @@ pub struct Configuration {
     pub skip_bootstrap: bool,
 
     pub http_fetch_block0_service: Vec<String>,
+
+    pub tls: Option<Tls>,
 }
 
 /// Trusted peer with DNS address resolved.
@@ -140,9 +167,17 @@ impl Peer {
             connection,
             protocol: Protocol::Grpc,
             timeout,
+            tls: None,
+            id: None,
         }
     }
 
+    /// secure the connection to the peer with the given TLS settings, `id`
+    /// is the node ID of the peer if known
+    pub fn with_tls(self, tls: Option<Tls>, id: Option<NodeId>) -> Self {
+        Peer { tls, id, ..self }
+    }
+
     pub fn address(&self) -> SocketAddr {
         self.connection
     }
@@ -172,4 +207,32 @@ impl Configuration {
     pub fn listen(&self) -> Option<Listen> {
         self.listen_address.or(self.public_address).map(Listen::new)
     }
+
+    /// the settings of the connection to the peer at the given address,
+    /// `id` is the node ID of the peer if known
+    pub fn peer(&self, connection: SocketAddr, id: Option<NodeId>) -> Peer {
+        Peer::new(connection).with_tls(self.tls.clone(), id)
+    }
+}
+
+impl Tls {
+    pub fn load(config: &config::P2pTls) -> io::Result<Self> {
+        Ok(Tls {
+            cert: fs::read(&config.cert_file)?,
+            priv_key: fs::read(&config.priv_key_file)?,
+            ca_cert: fs::read(&config.ca_file)?,
+            require_client_auth: config.require_client_auth,
+            pin_node_id: config.pin_node_id,
+        })
+    }
+}
+
+// the private key is kept out of the logs
+impl fmt::Debug for Tls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tls")
+            .field("require_client_auth", &self.require_client_auth)
+            .field("pin_node_id", &self.pin_node_id)
+            .finish()
+    }
 }
@@ -14,6 +14,12 @@ pub enum ErrorKind {
     BlockStorage,
     #[error("Block0")]
     Block0,
+    #[error("ledger snapshots")]
+    LedgerSnapshots,
+    #[error("explorer store")]
+    ExplorerStore,
+    #[error("REST admin tokens")]
+    RestAdminTokens,
 }
 
 #[derive(Debug, Error)]
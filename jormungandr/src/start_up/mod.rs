@@ -5,7 +5,8 @@ use tracing::{span, Level};
 pub use self::error::{Error, ErrorKind};
 use crate::{
     blockcfg::{Block, HeaderId},
-    blockchain::{Blockchain, Error as BlockchainError, Storage, Tip},
+    blockchain::{Blockchain, Error as BlockchainError, Snapshots, Storage, Tip},
+    explorer::StableStore,
     network,
     settings::start::Settings,
 };
@@ -29,6 +30,40 @@ pub fn prepare_storage(setting: &Settings) -> Result<Storage, Error> {
     }
 }
 
+/// prepare the ledger snapshots directory, next to the block storage.
+///
+/// No snapshots are taken if the blockchain is stored in memory.
+pub fn prepare_snapshots(setting: &Settings) -> Result<Option<Snapshots>, Error> {
+    setting
+        .storage
+        .as_ref()
+        .map(|dir| {
+            Snapshots::new(dir.join("snapshots")).map_err(|err| Error::Io {
+                source: err,
+                reason: ErrorKind::LedgerSnapshots,
+            })
+        })
+        .transpose()
+}
+
+/// prepare the directory of the confirmed explorer states, next to the block
+/// storage.
+///
+/// The explorer is rebuilt from the genesis block on every start if the
+/// blockchain is stored in memory.
+pub fn prepare_explorer_store(setting: &Settings) -> Result<Option<StableStore>, Error> {
+    setting
+        .storage
+        .as_ref()
+        .map(|dir| {
+            StableStore::new(dir.join("explorer")).map_err(|err| Error::Io {
+                source: err,
+                reason: ErrorKind::ExplorerStore,
+            })
+        })
+        .transpose()
+}
+
 /// Try to fetch the block0_id from the HTTP base URL (services) in the array
 ///
 /// The HTTP url is expecting to be of the form: URL/<hash-id>.block0
@@ -149,12 +184,14 @@ pub async fn prepare_block_0(settings: &Settings, storage: &Storage) -> Result<B
 pub async fn load_blockchain(
     block0: Block,
     storage: Storage,
+    snapshots: Option<Snapshots>,
     cache_capacity: usize,
     rewards_report_all: bool,
 ) -> Result<(Blockchain, Tip), Error> {
     let blockchain = Blockchain::new(
         block0.header.hash(),
         storage,
+        snapshots,
         cache_capacity,
         rewards_report_all,
     );
@@ -174,3 +211,25 @@ pub async fn load_blockchain(
     );
     Ok((blockchain, tip))
 }
+
+/// replay the blockchain from the storage and check the ledger snapshots
+/// against the replayed ledger states.
+pub async fn verify_ledger_snapshots(
+    block0: Block,
+    storage: Storage,
+    snapshots: Option<Snapshots>,
+    cache_capacity: usize,
+    rewards_report_all: bool,
+) -> Result<(), Error> {
+    let blockchain = Blockchain::new(
+        block0.header.hash(),
+        storage,
+        snapshots,
+        cache_capacity,
+        rewards_report_all,
+    );
+
+    let verified = blockchain.verify_snapshots(block0).await?;
+    tracing::info!("{} ledger snapshot(s) verified", verified);
+    Ok(())
+}
@@ -3,7 +3,7 @@ use chain_impl_mockchain::block::Block;
 use jormungandr_lib::time::SecondsSinceUnixEpoch;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 const SLOT_START_TIME_UNDEFINED: u64 = u64::max_value();
 
@@ -16,10 +16,15 @@ pub struct StatsCounter {
 struct StatsCounterImpl {
     tx_recv_cnt: AtomicUsize,
     block_recv_cnt: AtomicUsize,
+    rollback_refused_cnt: AtomicUsize,
     start_time: Instant,
     slot_start_time: AtomicU64,
     tip_block: ArcSwapOption<Block>,
     peers_connected_cnt: AtomicUsize,
+    mempool_usage_cnt: AtomicUsize,
+    fragment_logs_cnt: AtomicUsize,
+    block_apply_cnt: AtomicU64,
+    block_apply_time_us: AtomicU64,
 }
 
 impl Default for StatsCounterImpl {
@@ -27,10 +32,15 @@ impl Default for StatsCounterImpl {
         Self {
             tx_recv_cnt: AtomicUsize::default(),
             block_recv_cnt: AtomicUsize::default(),
+            rollback_refused_cnt: AtomicUsize::default(),
             start_time: Instant::now(),
             slot_start_time: AtomicU64::new(SLOT_START_TIME_UNDEFINED),
             tip_block: ArcSwapOption::from(None),
             peers_connected_cnt: AtomicUsize::default(),
+            mempool_usage_cnt: AtomicUsize::default(),
+            fragment_logs_cnt: AtomicUsize::default(),
+            block_apply_cnt: AtomicU64::default(),
+            block_apply_time_us: AtomicU64::default(),
         }
     }
 }
@@ -55,6 +65,18 @@ impl StatsCounter {
         self.stats.block_recv_cnt.load(Ordering::Relaxed) as u64
     }
 
+    pub fn add_rollback_refused_cnt(&self, count: usize) {
+        self.stats
+            .rollback_refused_cnt
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// number of candidate branches refused because switching to them would
+    /// roll back the current tip deeper than the epoch stability depth
+    pub fn rollback_refused_cnt(&self) -> u64 {
+        self.stats.rollback_refused_cnt.load(Ordering::Relaxed) as u64
+    }
+
     pub fn add_peer_connected_cnt(&self, count: usize) -> usize {
         self.stats
             .peers_connected_cnt
@@ -71,6 +93,41 @@ impl StatsCounter {
         self.stats.peers_connected_cnt.load(Ordering::Relaxed)
     }
 
+    pub fn set_mempool_usage_cnt(&self, count: usize) {
+        self.stats.mempool_usage_cnt.store(count, Ordering::Relaxed);
+    }
+
+    /// number of fragments pending in the mempool
+    pub fn mempool_usage_cnt(&self) -> usize {
+        self.stats.mempool_usage_cnt.load(Ordering::Relaxed)
+    }
+
+    pub fn set_fragment_logs_cnt(&self, count: usize) {
+        self.stats.fragment_logs_cnt.store(count, Ordering::Relaxed);
+    }
+
+    /// number of entries in the fragment logs
+    pub fn fragment_logs_cnt(&self) -> usize {
+        self.stats.fragment_logs_cnt.load(Ordering::Relaxed)
+    }
+
+    /// record the time taken to check and apply a block received from the network
+    pub fn add_block_apply_time(&self, duration: Duration) {
+        self.stats.block_apply_cnt.fetch_add(1, Ordering::Relaxed);
+        self.stats
+            .block_apply_time_us
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn block_apply_cnt(&self) -> u64 {
+        self.stats.block_apply_cnt.load(Ordering::Relaxed)
+    }
+
+    /// cumulated time spent applying the `block_apply_cnt` blocks
+    pub fn block_apply_time(&self) -> Duration {
+        Duration::from_micros(self.stats.block_apply_time_us.load(Ordering::Relaxed))
+    }
+
     pub fn uptime_sec(&self) -> u64 {
         self.stats.start_time.elapsed().as_secs()
     }
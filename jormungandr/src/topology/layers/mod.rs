@@ -2,7 +2,7 @@ mod preferred_list;
 mod rings;
 
 pub use self::preferred_list::PreferredListConfig;
-pub(super) use self::preferred_list::PreferredListLayer;
+pub(super) use self::preferred_list::{PreferredList, PreferredListLayer};
 pub(super) use self::rings::Rings;
 pub use self::rings::{ParseError, RingsConfig};
 
@@ -6,8 +6,9 @@ use poldercast::{
 };
 use rand::seq::IteratorRandom;
 use rand_chacha::ChaChaRng;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
 
 #[derive(Clone)]
 pub struct PreferredListConfig {
@@ -15,15 +16,24 @@ pub struct PreferredListConfig {
     pub peers: Vec<TrustedPeer>,
 }
 
-/// This layer always return a view containing only a subset
-/// of the preferred peers that are known to the topology
-pub struct PreferredListLayer {
+struct PreferredPeers {
     /// the max number of entries to add in the list of the view
     view_max: usize,
     /// the preferred peers list
     peers: HashMap<SocketAddr, Option<NodeId>>,
+}
+
+/// The preferred peers list, shared by the layers built for the topology
+/// so it can be updated while the node is running
+#[derive(Clone)]
+pub struct PreferredList(Arc<RwLock<PreferredPeers>>);
+
+/// This layer always return a view containing only a subset
+/// of the preferred peers that are known to the topology
+pub struct PreferredListLayer {
+    list: PreferredList,
     /// actual peers that are known to the topology
-    current_peers: HashSet<keynesis::key::ed25519::PublicKey>,
+    current_peers: HashMap<keynesis::key::ed25519::PublicKey, SocketAddr>,
     /// a pseudo random number generator, this will help with
     /// testing and reproducing issues.
     ///
@@ -33,8 +43,8 @@ pub struct PreferredListLayer {
     prng: rand_chacha::ChaChaRng,
 }
 
-impl PreferredListLayer {
-    pub fn new(config: &PreferredListConfig, prng: ChaChaRng) -> Self {
+impl PreferredPeers {
+    fn from_config(config: &PreferredListConfig) -> Self {
         Self {
             view_max: config.view_max,
             peers: config
@@ -42,7 +52,35 @@ impl PreferredListLayer {
                 .iter()
                 .map(|peer| (peer.addr, peer.id.clone()))
                 .collect(),
-            current_peers: HashSet::new(),
+        }
+    }
+
+    fn contains(&self, addr: &SocketAddr, id: &keynesis::key::ed25519::PublicKey) -> bool {
+        match self.peers.get(addr) {
+            Some(None) => true,
+            Some(Some(trusted_id)) => trusted_id.as_ref() == id,
+            None => false,
+        }
+    }
+}
+
+impl PreferredList {
+    pub fn new(config: &PreferredListConfig) -> Self {
+        Self(Arc::new(RwLock::new(PreferredPeers::from_config(config))))
+    }
+
+    /// replace the preferred peers, the peers added to the list are only
+    /// taken into account once a gossip about them is received
+    pub fn update(&self, config: &PreferredListConfig) {
+        *self.0.write().unwrap() = PreferredPeers::from_config(config);
+    }
+}
+
+impl PreferredListLayer {
+    pub fn new(list: PreferredList, prng: ChaChaRng) -> Self {
+        Self {
+            list,
+            current_peers: HashMap::new(),
             prng,
         }
     }
@@ -54,9 +92,12 @@ impl Layer for PreferredListLayer {
     }
 
     fn view(&mut self, builder: &mut ViewBuilder) {
+        let list = self.list.0.read().unwrap();
         self.current_peers
             .iter()
-            .choose_multiple(&mut self.prng, self.view_max)
+            .filter(|(id, addr)| list.contains(addr, id))
+            .map(|(id, _)| id)
+            .choose_multiple(&mut self.prng, list.view_max)
             .into_iter()
             .for_each(|id| builder.add(id));
     }
@@ -77,10 +118,8 @@ impl Layer for PreferredListLayer {
     fn populate(&mut self, _: &Profile, new_profile: &Profile) {
         let addr = new_profile.address();
         let id = new_profile.id();
-        if let Some(trusted_id) = self.peers.get(&addr) {
-            if trusted_id.is_none() || trusted_id.as_ref().map(AsRef::as_ref) == Some(&id) {
-                self.current_peers.insert(id);
-            }
+        if self.list.0.read().unwrap().contains(&addr, &id) {
+            self.current_peers.insert(id, addr);
         }
     }
 }
@@ -13,13 +13,17 @@ mod gossip;
 pub mod layers;
 mod process;
 mod quarantine;
+mod reputation;
+mod snapshot;
 #[allow(clippy::module_inception)]
 mod topology;
 
 pub use self::gossip::{Gossip, Gossips};
-pub use self::process::{start, TaskData, DEFAULT_NETWORK_STUCK_INTERVAL};
+pub use self::process::{start, store_snapshot, TaskData, DEFAULT_NETWORK_STUCK_INTERVAL};
 pub use self::topology::{P2pTopology, View};
-pub use quarantine::{QuarantineConfig, ReportRecords};
+pub use quarantine::{QuarantineConfig, ReportRecords, WhitelistError};
+pub use reputation::{PeerReputation, ReportReason};
+pub use snapshot::{QuarantinedPeer, TopologySnapshot, TopologyStore};
 
 /**
 # topics definition for p2p interest subscriptions
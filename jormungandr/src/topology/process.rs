@@ -1,7 +1,8 @@
-use super::{Gossip, Gossips, P2pTopology, Peer};
-use crate::intercom::{NetworkMsg, PropagateMsg, TopologyMsg};
+use super::{Gossip, Gossips, P2pTopology, Peer, TopologyStore};
+use crate::intercom::{self, NetworkMsg, PropagateMsg, TopologyMsg};
 use crate::settings::start::network::Configuration;
 use crate::utils::async_msg::{MessageBox, MessageQueue};
+use futures::SinkExt;
 use std::time::Duration;
 use tokio::time::{Instant, Interval};
 use tokio_stream::StreamExt;
@@ -9,6 +10,9 @@ use tokio_stream::StreamExt;
 pub const DEFAULT_NETWORK_STUCK_INTERVAL: Duration = Duration::from_secs(60 * 5); // 5 min
 const QUARANTINE_CHECK: Duration = Duration::from_secs(60);
 const MAX_GOSSIP_SIZE: usize = 10;
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+// how long the shutdown of the node waits for the topology to be stored
+const SHUTDOWN_SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(10);
 
 struct Process {
     input: MessageQueue<TopologyMsg>,
@@ -16,6 +20,7 @@ struct Process {
     gossip_interval: Interval,
     network_stuck_check: Duration,
     topology: P2pTopology,
+    store: Option<TopologyStore>,
 }
 
 pub struct TaskData {
@@ -23,6 +28,8 @@ pub struct TaskData {
     pub topology_queue: MessageQueue<TopologyMsg>,
     pub initial_peers: Vec<Peer>,
     pub config: Configuration,
+    /// where the topology is kept across restarts, if anywhere
+    pub store: Option<TopologyStore>,
 }
 
 pub async fn start(task_data: TaskData) {
@@ -31,6 +38,7 @@ pub async fn start(task_data: TaskData) {
         topology_queue,
         initial_peers,
         config,
+        store,
     } = task_data;
 
     let mut topology = P2pTopology::new(&config);
@@ -42,20 +50,56 @@ pub async fn start(task_data: TaskData) {
             .collect::<Vec<_>>(),
     ));
 
+    if let Some(store) = &store {
+        match store.load() {
+            Ok(Some(snapshot)) => {
+                tracing::info!(
+                    "restoring {} known peers and {} quarantined peers",
+                    snapshot.peers.len(),
+                    snapshot.quarantined.len()
+                );
+                topology.restore(snapshot);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(reason = %e, "cannot load the stored topology"),
+        }
+    }
+
     let mut process = Process {
         input: topology_queue,
         gossip_interval: tokio::time::interval(config.gossip_interval),
         network_stuck_check: config.network_stuck_check,
         network_msgbox,
         topology,
+        store,
     };
     process.handle_input().await;
 }
 
+/// store the topology through the topology task, called when the node shuts
+/// down so the peers learnt since the last periodic snapshot are kept
+pub async fn store_snapshot(mut topology_msgbox: MessageBox<TopologyMsg>) {
+    let (reply_handle, reply_future) = intercom::unary_reply();
+    if let Err(e) = topology_msgbox
+        .send(TopologyMsg::StoreSnapshot(reply_handle))
+        .await
+    {
+        tracing::warn!(reason = %e, "cannot store the topology on shutdown");
+        return;
+    }
+    match tokio::time::timeout(SHUTDOWN_SNAPSHOT_TIMEOUT, reply_future).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => tracing::warn!(reason = %e, "cannot store the topology on shutdown"),
+        Err(_) => tracing::warn!("timed out storing the topology on shutdown"),
+    }
+}
+
 impl Process {
     async fn handle_input(&mut self) {
         let mut last_update = Instant::now();
         let mut quarantine_check = tokio::time::interval(QUARANTINE_CHECK);
+        let mut snapshot_interval =
+            tokio::time::interval_at(Instant::now() + SNAPSHOT_INTERVAL, SNAPSHOT_INTERVAL);
 
         loop {
             tokio::select! {
@@ -65,8 +109,12 @@ impl Process {
                             self.topology.accept_gossips(gossip);
                             last_update = Instant::now();
                         },
-                        TopologyMsg::DemotePeer(id) => self.topology.report_node(&id),
+                        TopologyMsg::DemotePeer(id, reason) => self.topology.report_node(&id, reason),
                         TopologyMsg::PromotePeer(id) => self.topology.promote_node(&id),
+                        TopologyMsg::ReportPeer(address, reason) => {
+                            self.topology.report_address(address, reason)
+                        }
+                        TopologyMsg::RewardPeer(address) => self.topology.reward_address(address),
                         TopologyMsg::View(selection, handle) => {
                             handle.reply_ok(self.topology.view(selection))
                         }
@@ -79,6 +127,19 @@ impl Process {
                         TopologyMsg::ListQuarantined(handle) => {
                             handle.reply_ok(self.topology.list_quarantined())
                         }
+                        TopologyMsg::ListReputation(handle) => {
+                            handle.reply_ok(self.topology.list_reputation())
+                        }
+                        TopologyMsg::SetQuarantineWhitelist(whitelist) => {
+                            self.topology.set_quarantine_whitelist(whitelist)
+                        }
+                        TopologyMsg::SetPreferredList(config) => {
+                            self.topology.set_preferred_list(&config)
+                        }
+                        TopologyMsg::StoreSnapshot(handle) => {
+                            self.store_snapshot().await;
+                            handle.reply_ok(())
+                        }
                     }
                 },
                 _ = self.gossip_interval.tick() => {
@@ -107,6 +168,19 @@ impl Process {
 
                     self.send_gossip_messages(nodes_to_contact);
                 }
+                _ = snapshot_interval.tick() => self.store_snapshot().await,
+            }
+        }
+    }
+
+    /// write the snapshot in a blocking task, the file is synced on disk
+    async fn store_snapshot(&self) {
+        if let Some(store) = self.store.clone() {
+            let snapshot = self.topology.snapshot();
+            match tokio::task::spawn_blocking(move || store.store(&snapshot)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => tracing::warn!(reason = %e, "cannot store the topology"),
+                Err(e) => tracing::warn!(reason = %e, "cannot store the topology"),
             }
         }
     }
@@ -5,14 +5,16 @@
 /// by a call to 'promote_peer', not to ban a node forever from the topology.
 ///
 /// It is also responsible for determining wheter a report is to be accounted for
-/// according to the node configs.
+/// according to the node configs, and for how long, the quarantine duration being
+/// proportional to the severity of the report.
 use crate::network::p2p::Address;
 use crate::topology::{NodeId, Peer, PeerInfo};
-use jormungandr_lib::time::Duration;
+use jormungandr_lib::{multiaddr::Error as MultiaddrError, time::Duration};
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::time::{Duration as StdDuration, Instant, SystemTime};
+use thiserror::Error;
 
 /// default quarantine duration is 10min
 const DEFAULT_QUARANTINE_DURATION: StdDuration = StdDuration::from_secs(10 * 60);
@@ -27,12 +29,15 @@ const DEFAULT_MAX_NUM_QUARANTINE_RECORDS: usize = 24_000;
 struct ReportRecord {
     peer_info: PeerInfo,
     report_time: Instant,
+    duration: StdDuration,
 }
 
 /// Forgive nodes we demoted after some time
 pub struct ReportRecords {
-    /// A report will be lifted after 'report_duration'
+    /// A report will be lifted after 'report_duration' times the severity of the report
     report_duration: StdDuration,
+    /// No report lasts longer than 'max_report_duration'
+    max_report_duration: StdDuration,
     report_whitelist: HashSet<Address>,
     /// To avoid cycling down nodes back and and forth(and as such prevent them
     /// from being evicted from the lru cache), do not report again nodes that were recently
@@ -45,24 +50,69 @@ pub struct ReportRecords {
 }
 
 impl ReportRecords {
-    pub fn from_config(config: QuarantineConfig) -> Self {
+    pub fn from_config(config: QuarantineConfig, whitelist: HashSet<Address>) -> Self {
         let max_num_quarantine_records = config
             .max_num_quarantine_records
             .unwrap_or(DEFAULT_MAX_NUM_QUARANTINE_RECORDS);
+        let report_duration = StdDuration::from(config.quarantine_duration);
+        let max_report_duration = config
+            .max_quarantine
+            .map_or(DEFAULT_MAX_QUARANTINE_DURATION, StdDuration::from)
+            .max(report_duration);
         Self {
-            report_duration: StdDuration::from(config.quarantine_duration),
-            report_whitelist: config
-                .quarantine_whitelist
-                .into_iter()
-                .map(|addr| jormungandr_lib::multiaddr::to_tcp_socket_addr(&addr).unwrap())
-                .collect(),
+            report_duration,
+            max_report_duration,
+            report_whitelist: whitelist,
             report_grace: LruCache::new(max_num_quarantine_records),
             report_records: LruCache::new(max_num_quarantine_records),
         }
     }
 
+    pub fn set_whitelist(&mut self, whitelist: HashSet<Address>) {
+        self.report_whitelist = whitelist;
+    }
+
+    /// Returns whether the node has been quarantined or not.
+    pub fn report_node(
+        &mut self,
+        topology: &mut poldercast::Topology,
+        node: Peer,
+        severity: u32,
+    ) -> bool {
+        let duration = self
+            .report_duration
+            .checked_mul(severity)
+            .unwrap_or(self.max_report_duration)
+            .min(self.max_report_duration);
+        self.report_node_at(topology, node, SystemTime::now(), duration)
+    }
+
+    /// Quarantine again a node reported at `reported_at` before a restart,
+    /// for the remaining of the quarantine duration.
     /// Returns whether the node has been quarantined or not.
-    pub fn report_node(&mut self, topology: &mut poldercast::Topology, node: Peer) -> bool {
+    pub fn restore_report(
+        &mut self,
+        topology: &mut poldercast::Topology,
+        node: Peer,
+        reported_at: SystemTime,
+        duration: StdDuration,
+    ) -> bool {
+        let elapsed = SystemTime::now()
+            .duration_since(reported_at)
+            .unwrap_or_default();
+        if elapsed >= duration {
+            return false;
+        }
+        self.report_node_at(topology, node, reported_at, duration)
+    }
+
+    fn report_node_at(
+        &mut self,
+        topology: &mut poldercast::Topology,
+        node: Peer,
+        reported_at: SystemTime,
+        duration: StdDuration,
+    ) -> bool {
         if self.report_whitelist.contains(&node.address()) {
             tracing::debug!(
                 node = %node.address(),
@@ -75,9 +125,7 @@ impl ReportRecords {
             false
         } else {
             let mut peer_info = PeerInfo::from(node);
-            tracing::debug!(node = %peer_info.address, id=?peer_info.id, ?self.report_duration, "reporting node");
-            // If we'll handle report reasons other that a connectivity issue in the future, we may want to
-            // demote a peer all the way down to dirty in case of a serious violation.
+            tracing::debug!(node = %peer_info.address, id=?peer_info.id, ?duration, "reporting node");
             topology.remove_peer(peer_info.id.as_ref());
 
             // Not all reports will quarantine a node (which is, put it in the dirty pool). For example,
@@ -89,7 +137,7 @@ impl ReportRecords {
             // For this reason, we keep track of those reports as well so that we will try to contact such
             // nodes again after some time if we haven't heard from them sooner (and avoid network splits).
             if topology.peers().dirty().contains(peer_info.id.as_ref()) {
-                peer_info.quarantined = Some(SystemTime::now().into());
+                peer_info.quarantined = Some(reported_at.into());
                 tracing::debug!(node = %peer_info.address, id=?peer_info.id, "node has been quarantined");
             }
 
@@ -97,7 +145,8 @@ impl ReportRecords {
                 peer_info.id.clone(),
                 ReportRecord {
                     peer_info,
-                    report_time: Instant::now(),
+                    report_time: instant_of(reported_at),
+                    duration,
                 },
             );
 
@@ -112,32 +161,59 @@ impl ReportRecords {
             .collect()
     }
 
+    /// the reported nodes with the time they have been reported at and the
+    /// duration of the report
+    pub fn reports(&self) -> impl Iterator<Item = (&NodeId, SystemTime, StdDuration)> + '_ {
+        self.report_records.iter().map(|(id, record)| {
+            let reported_at = SystemTime::now()
+                .checked_sub(record.report_time.elapsed())
+                .unwrap_or_else(SystemTime::now);
+            (id, reported_at, record.duration)
+        })
+    }
+
     pub fn record_new_gossip(&mut self, node: &NodeId) {
         self.report_grace.pop(node);
     }
 
     pub fn lift_reports(&mut self) -> Vec<PeerInfo> {
-        let mut res = Vec::new();
-        // This is basically a FIFO queue, a lru cache is being used just to
-        // avoid keeping another data structure to know if an address was already quarantined
-        while let Some((_id, record)) = self.report_records.peek_lru() {
-            if record.report_time.elapsed() < self.report_duration {
-                break;
-            }
-
-            let (id, record) = self.report_records.pop_lru().unwrap();
-            self.report_grace.put(id, ());
-            res.push(record.peer_info);
-        }
+        // The reports do not last for the same duration, so all of them are
+        // checked. A lru cache is being used just to avoid keeping another
+        // data structure to know if an address was already quarantined
+        let lifted: Vec<NodeId> = self
+            .report_records
+            .iter()
+            .filter(|(_, record)| record.report_time.elapsed() >= record.duration)
+            .map(|(id, _)| id.clone())
+            .collect();
 
-        res
+        lifted
+            .into_iter()
+            .filter_map(|id| {
+                let record = self.report_records.pop(&id)?;
+                self.report_grace.put(id, ());
+                Some(record.peer_info)
+            })
+            .collect()
     }
 }
 
+/// the instant corresponding to a past system time, or now if the system
+/// time is in the future
+fn instant_of(time: SystemTime) -> Instant {
+    let now = Instant::now();
+    SystemTime::now()
+        .duration_since(time)
+        .ok()
+        .and_then(|elapsed| now.checked_sub(elapsed))
+        .unwrap_or(now)
+}
+
 impl Default for ReportRecords {
     fn default() -> Self {
         Self {
             report_duration: DEFAULT_QUARANTINE_DURATION,
+            max_report_duration: DEFAULT_MAX_QUARANTINE_DURATION,
             report_whitelist: HashSet::new(),
             report_grace: LruCache::new(DEFAULT_MAX_NUM_QUARANTINE_RECORDS),
             report_records: LruCache::new(DEFAULT_MAX_NUM_QUARANTINE_RECORDS),
@@ -157,6 +233,35 @@ pub struct QuarantineConfig {
     quarantine_whitelist: HashSet<multiaddr::Multiaddr>,
 }
 
+#[derive(Debug, Error)]
+#[error("cannot resolve the quarantine whitelist address `{address}`")]
+pub struct WhitelistError {
+    address: multiaddr::Multiaddr,
+    #[source]
+    source: MultiaddrError,
+}
+
+impl QuarantineConfig {
+    /// addresses of the nodes that are never reported, resolving the DNS
+    /// names of the configured addresses may block
+    pub fn whitelist(&self) -> Result<HashSet<Address>, WhitelistError> {
+        self.quarantine_whitelist
+            .iter()
+            .map(|address| {
+                jormungandr_lib::multiaddr::resolve_dns(address)
+                    .and_then(|resolved| {
+                        jormungandr_lib::multiaddr::to_tcp_socket_addr(&resolved)
+                            .ok_or(MultiaddrError::InvalidMultiaddr)
+                    })
+                    .map_err(|source| WhitelistError {
+                        address: address.clone(),
+                        source,
+                    })
+            })
+            .collect()
+    }
+}
+
 impl Default for QuarantineConfig {
     fn default() -> Self {
         Self {
@@ -167,3 +272,72 @@ impl Default for QuarantineConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::net::SocketAddr;
+
+    fn key(seed: u8) -> keynesis::key::ed25519::SecretKey {
+        keynesis::key::ed25519::SecretKey::new(rand_chacha::ChaChaRng::from_seed([seed; 32]))
+    }
+
+    fn peer(seed: u8) -> Peer {
+        Peer::from(poldercast::Gossip::new(
+            SocketAddr::from(([10, 0, 0, seed], 3000)),
+            &key(seed),
+            poldercast::Subscriptions::new().as_slice(),
+        ))
+    }
+
+    fn report_duration(records: &ReportRecords, peer: &Peer) -> StdDuration {
+        records
+            .reports()
+            .find(|(id, _, _)| **id == peer.id())
+            .map(|(_, _, duration)| duration)
+            .unwrap()
+    }
+
+    #[test]
+    fn report_duration_is_scaled_by_severity_and_capped() {
+        let config = QuarantineConfig {
+            quarantine_duration: Duration::from(StdDuration::from_secs(10)),
+            max_quarantine: Some(Duration::from(StdDuration::from_secs(60))),
+            ..QuarantineConfig::default()
+        };
+        let mut records = ReportRecords::from_config(config, HashSet::new());
+        let mut topology =
+            poldercast::Topology::new(SocketAddr::from(([127, 0, 0, 1], 3000)), &key(0));
+
+        let (minor, major, fatal) = (peer(1), peer(2), peer(3));
+        assert!(records.report_node(&mut topology, minor.clone(), 4));
+        assert!(records.report_node(&mut topology, major.clone(), 8));
+        assert!(records.report_node(&mut topology, fatal.clone(), u32::MAX));
+
+        assert_eq!(
+            report_duration(&records, &minor),
+            StdDuration::from_secs(40)
+        );
+        assert_eq!(
+            report_duration(&records, &major),
+            StdDuration::from_secs(60)
+        );
+        assert_eq!(
+            report_duration(&records, &fatal),
+            StdDuration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn whitelisted_node_is_not_reported() {
+        let whitelisted = peer(1);
+        let whitelist = std::iter::once(whitelisted.address()).collect();
+        let mut records = ReportRecords::from_config(QuarantineConfig::default(), whitelist);
+        let mut topology =
+            poldercast::Topology::new(SocketAddr::from(([127, 0, 0, 1], 3000)), &key(0));
+
+        assert!(!records.report_node(&mut topology, whitelisted, 8));
+        assert_eq!(records.reports().count(), 0);
+    }
+}
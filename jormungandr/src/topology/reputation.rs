@@ -0,0 +1,199 @@
+//! Reputation of the peers, scoring what they have been sending to the node.
+//!
+//! Every peer starts with a neutral score. Useful data delivered by a peer
+//! raises its score while every report against it lowers the score by a
+//! penalty depending on the reason of the report. The reason of a report
+//! also determines for how long the peer is quarantined, and a peer whose
+//! score went too low is no longer promoted in the topology.
+use crate::network::p2p::Address;
+use crate::topology::NodeId;
+use lru::LruCache;
+use serde::Serialize;
+
+/// the number of peers the reputation is kept for
+const MAX_REPUTATION_RECORDS: usize = 24_000;
+
+/// the points given for some useful data delivered
+const USEFUL_DATA_REWARD: i64 = 1;
+
+/// the score of a peer can not go above this value, so a peer can not build
+/// up a reputation allowing it to misbehave for a long time afterwards
+const MAX_SCORE: i64 = 100;
+
+/// the score below which a peer is no longer promoted in the topology
+const DISTRUSTED_SCORE: i64 = -100;
+
+/// the reason a peer is reported for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    /// the connection to the peer failed
+    Connectivity,
+    /// the peer sent a header not passing the verification
+    InvalidHeader,
+    /// the peer sent a block not passing the verification
+    InvalidBlock,
+    /// the peer relayed a fragment rejected as invalid by the mempool
+    InvalidFragment,
+    /// the peer follows another blockchain
+    Block0Mismatch,
+    /// the peer did not follow the node to node protocol
+    ProtocolViolation,
+}
+
+impl ReportReason {
+    /// the points taken from the score of the peer
+    pub fn penalty(self) -> i64 {
+        match self {
+            ReportReason::Connectivity => 1,
+            ReportReason::InvalidFragment => 2,
+            ReportReason::InvalidHeader => 10,
+            ReportReason::InvalidBlock => 20,
+            ReportReason::ProtocolViolation => 20,
+            ReportReason::Block0Mismatch => 1_000,
+        }
+    }
+
+    /// how many times the configured quarantine duration the peer is
+    /// quarantined for, `None` if the peer is not quarantined.
+    ///
+    /// An invalid fragment is not a reason to quarantine a peer, a valid
+    /// fragment may have become invalid by the time the peer relayed it.
+    pub fn quarantine_severity(self) -> Option<u32> {
+        match self {
+            ReportReason::Connectivity => Some(1),
+            ReportReason::InvalidFragment => None,
+            ReportReason::InvalidHeader => Some(4),
+            ReportReason::InvalidBlock => Some(8),
+            ReportReason::ProtocolViolation => Some(8),
+            ReportReason::Block0Mismatch => Some(u32::MAX),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerReputation {
+    pub id: NodeId,
+    pub address: Address,
+    pub score: i64,
+    pub useful_data: u64,
+    pub reports: u64,
+    pub last_report: Option<ReportReason>,
+}
+
+/// Scores of the peers the node has heard of
+pub struct Reputation {
+    records: LruCache<NodeId, PeerReputation>,
+}
+
+impl Reputation {
+    pub fn new() -> Self {
+        Reputation {
+            records: LruCache::new(MAX_REPUTATION_RECORDS),
+        }
+    }
+
+    fn record(&mut self, id: &NodeId, address: Address) -> &mut PeerReputation {
+        if !self.records.contains(id) {
+            self.records.put(
+                id.clone(),
+                PeerReputation {
+                    id: id.clone(),
+                    address,
+                    score: 0,
+                    useful_data: 0,
+                    reports: 0,
+                    last_report: None,
+                },
+            );
+        }
+        let record = self.records.get_mut(id).unwrap();
+        record.address = address;
+        record
+    }
+
+    pub fn record_report(&mut self, id: &NodeId, address: Address, reason: ReportReason) {
+        let record = self.record(id, address);
+        record.score = record.score.saturating_sub(reason.penalty());
+        record.reports += 1;
+        record.last_report = Some(reason);
+    }
+
+    pub fn record_useful_data(&mut self, id: &NodeId, address: Address) {
+        let record = self.record(id, address);
+        record.score = (record.score + USEFUL_DATA_REWARD).min(MAX_SCORE);
+        record.useful_data += 1;
+    }
+
+    /// whether the peer has misbehaved too often to be promoted
+    pub fn is_distrusted(&self, id: &NodeId) -> bool {
+        self.records
+            .peek(id)
+            .map_or(false, |record| record.score < DISTRUSTED_SCORE)
+    }
+
+    pub fn list(&self) -> Vec<PeerReputation> {
+        self.records
+            .iter()
+            .map(|(_, record)| record.clone())
+            .collect()
+    }
+}
+
+impl Default for Reputation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn node_id(seed: u8) -> NodeId {
+        let key =
+            keynesis::key::ed25519::SecretKey::new(rand_chacha::ChaChaRng::from_seed([seed; 32]));
+        NodeId(key.public_key())
+    }
+
+    #[test]
+    fn reports_lower_the_score_until_distrusted() {
+        let mut reputation = Reputation::new();
+        let id = node_id(1);
+        let address = "127.0.0.1:3000".parse().unwrap();
+
+        reputation.record_useful_data(&id, address);
+        for _ in 0..5 {
+            reputation.record_report(&id, address, ReportReason::InvalidBlock);
+        }
+        assert!(!reputation.is_distrusted(&id));
+
+        reputation.record_report(&id, address, ReportReason::InvalidBlock);
+        assert!(reputation.is_distrusted(&id));
+
+        let records = reputation.list();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].score,
+            1 - 6 * ReportReason::InvalidBlock.penalty()
+        );
+        assert_eq!(records[0].reports, 6);
+        assert_eq!(records[0].useful_data, 1);
+        assert_eq!(records[0].last_report, Some(ReportReason::InvalidBlock));
+    }
+
+    #[test]
+    fn useful_data_score_is_capped() {
+        let mut reputation = Reputation::new();
+        let id = node_id(2);
+        let address = "127.0.0.1:3000".parse().unwrap();
+
+        for _ in 0..(2 * MAX_SCORE) {
+            reputation.record_useful_data(&id, address);
+        }
+        reputation.record_report(&id, address, ReportReason::Block0Mismatch);
+        assert!(reputation.is_distrusted(&id));
+        assert!(!reputation.is_distrusted(&node_id(3)));
+    }
+}
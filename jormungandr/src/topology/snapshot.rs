@@ -0,0 +1,174 @@
+//! Snapshot of the p2p topology, kept in the storage directory so the node
+//! knows the peers of the network again after a restart instead of relying
+//! on its trusted peers only.
+
+use super::Peer;
+use crate::utils::block_files::{invalid_data, write_file};
+use chain_core::property::{Deserialize as _, Serialize as _};
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const SNAPSHOT_VERSION: u8 = 1;
+/// the file in the storage directory keeping the topology snapshot
+const SNAPSHOT_FILE: &str = "p2p/topology";
+
+pub struct TopologySnapshot {
+    /// the peers available in the topology
+    pub peers: Vec<Peer>,
+    pub quarantined: Vec<QuarantinedPeer>,
+}
+
+pub struct QuarantinedPeer {
+    pub peer: Peer,
+    pub reported_at: SystemTime,
+    /// the duration of the quarantine from `reported_at`
+    pub duration: Duration,
+}
+
+/// handle on the file keeping the topology snapshot
+#[derive(Clone)]
+pub struct TopologyStore {
+    path: PathBuf,
+}
+
+impl TopologyStore {
+    pub fn new<P: AsRef<Path>>(storage_dir: P) -> Self {
+        TopologyStore {
+            path: storage_dir.as_ref().join(SNAPSHOT_FILE),
+        }
+    }
+
+    /// load the stored snapshot, `None` if no snapshot has been stored yet
+    pub fn load(&self) -> io::Result<Option<TopologySnapshot>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        read_snapshot(&mut BufReader::new(file)).map(Some)
+    }
+
+    /// write the snapshot on disk, replacing the previous one
+    pub fn store(&self, snapshot: &TopologySnapshot) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_file(&self.path, |writer| write_snapshot(writer, snapshot))
+    }
+}
+
+fn write_snapshot<W: Write>(writer: &mut W, snapshot: &TopologySnapshot) -> io::Result<()> {
+    writer.write_all(&[SNAPSHOT_VERSION])?;
+    writer.write_all(&(snapshot.peers.len() as u32).to_be_bytes())?;
+    for peer in &snapshot.peers {
+        peer.serialize(&mut *writer).map_err(invalid_data)?;
+    }
+    writer.write_all(&(snapshot.quarantined.len() as u32).to_be_bytes())?;
+    for quarantined in &snapshot.quarantined {
+        quarantined
+            .peer
+            .serialize(&mut *writer)
+            .map_err(invalid_data)?;
+        let reported_at = quarantined
+            .reported_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        writer.write_all(&reported_at.to_be_bytes())?;
+        writer.write_all(&quarantined.duration.as_secs().to_be_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_snapshot<R: BufRead>(reader: &mut R) -> io::Result<TopologySnapshot> {
+    let mut version = [0; 1];
+    reader.read_exact(&mut version)?;
+    if version[0] != SNAPSHOT_VERSION {
+        return Err(invalid_data(format!(
+            "unsupported topology snapshot version {}",
+            version[0]
+        )));
+    }
+
+    let peers = (0..read_u32(reader)?)
+        .map(|_| Peer::deserialize(&mut *reader).map_err(invalid_data))
+        .collect::<io::Result<_>>()?;
+
+    let quarantined = (0..read_u32(reader)?)
+        .map(|_| {
+            let peer = Peer::deserialize(&mut *reader).map_err(invalid_data)?;
+            let reported_at = UNIX_EPOCH + Duration::from_secs(read_u64(reader)?);
+            let duration = Duration::from_secs(read_u64(reader)?);
+            Ok(QuarantinedPeer {
+                peer,
+                reported_at,
+                duration,
+            })
+        })
+        .collect::<io::Result<_>>()?;
+
+    Ok(TopologySnapshot { peers, quarantined })
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::net::SocketAddr;
+
+    fn peer(seed: u8, port: u16) -> Peer {
+        Peer::from(poldercast::Gossip::new(
+            SocketAddr::from(([10, 0, 0, seed], port)),
+            &keynesis::key::ed25519::SecretKey::new(rand_chacha::ChaChaRng::from_seed([seed; 32])),
+            poldercast::Subscriptions::new().as_slice(),
+        ))
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TopologyStore::new(dir.path());
+        assert!(store.load().unwrap().is_none());
+
+        let reported_at = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let snapshot = TopologySnapshot {
+            peers: vec![peer(1, 3000), peer(2, 3001)],
+            quarantined: vec![QuarantinedPeer {
+                peer: peer(3, 3002),
+                reported_at,
+                duration: Duration::from_secs(1800),
+            }],
+        };
+        store.store(&snapshot).unwrap();
+        let loaded = store.load().unwrap().unwrap();
+
+        let ids = |peers: &[Peer]| {
+            peers
+                .iter()
+                .map(|peer| (peer.id(), peer.address()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&loaded.peers), ids(&snapshot.peers));
+        assert_eq!(loaded.quarantined.len(), 1);
+        let quarantined = &loaded.quarantined[0];
+        assert_eq!(quarantined.peer.id(), snapshot.quarantined[0].peer.id());
+        assert_eq!(quarantined.reported_at, reported_at);
+        assert_eq!(quarantined.duration, Duration::from_secs(1800));
+    }
+}
@@ -1,10 +1,13 @@
 //! module defining the p2p topology management objects
 //!
 use super::{
-    layers::{self, LayersConfig},
-    topic, Gossips, NodeId, Peer, PeerInfo, ReportRecords,
+    layers::{self, PreferredList, PreferredListConfig, RingsConfig},
+    reputation::Reputation,
+    topic, Gossips, NodeId, Peer, PeerInfo, PeerReputation, QuarantinedPeer, ReportReason,
+    ReportRecords, TopologySnapshot,
 };
 
+use crate::network::p2p::Address;
 use crate::settings::start::network::Configuration;
 use chain_crypto::Ed25519;
 use jormungandr_lib::crypto::key::SigningKey;
@@ -14,6 +17,7 @@ use poldercast::{
 };
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use tracing::instrument;
@@ -36,11 +40,17 @@ pub struct View {
 pub struct P2pTopology {
     topology: Topology,
     quarantine: ReportRecords,
+    reputation: Reputation,
+    preferred_list: PreferredList,
+    /// the node IDs of the peers by address, to find the peers reported
+    /// by the connections
+    addresses: HashMap<Address, NodeId>,
     key: keynesis::key::ed25519::SecretKey,
 }
 
 struct CustomLayerBuilder {
-    config: LayersConfig,
+    rings: RingsConfig,
+    preferred_list: PreferredList,
 }
 
 impl CustomLayerBuilder {
@@ -51,18 +61,12 @@ impl CustomLayerBuilder {
     const GOSSIP_SIZE: u8 = 10;
 }
 
-impl From<LayersConfig> for CustomLayerBuilder {
-    fn from(config: LayersConfig) -> Self {
-        Self { config }
-    }
-}
-
 impl CustomLayerBuilder {
     fn build_layers(&self, rings: u8, _vicinity: usize, cyclon: usize) -> Vec<Box<dyn Layer>> {
         let mut layers: Vec<Box<dyn Layer>> = Vec::with_capacity(4);
 
         layers.push(Box::new(layers::Rings::new(
-            self.config.rings.clone(),
+            self.rings.clone(),
             poldercast_layer::Rings::new(rings),
         )));
         // disabled until https://github.com/primetype/poldercast/pull/36 is fixed and merged
@@ -72,7 +76,7 @@ impl CustomLayerBuilder {
         let mut seed = [0; 32];
         rand::thread_rng().fill(&mut seed);
         layers.push(Box::new(layers::PreferredListLayer::new(
-            &self.config.preferred_list,
+            self.preferred_list.clone(),
             ChaChaRng::from_seed(seed),
         )));
 
@@ -103,18 +107,36 @@ impl P2pTopology {
         let addr = config.public_address.or(Some(*LOCAL_ADDR)).unwrap();
         let key = secret_key_into_keynesis(config.node_key.clone());
 
-        let quarantine = ReportRecords::from_config(config.policy.clone());
-        let custom_builder = CustomLayerBuilder::from(config.layers.clone());
+        let quarantine =
+            ReportRecords::from_config(config.policy.clone(), config.quarantine_whitelist.clone());
+        let preferred_list = PreferredList::new(&config.layers.preferred_list);
+        let custom_builder = CustomLayerBuilder {
+            rings: config.layers.rings.clone(),
+            preferred_list: preferred_list.clone(),
+        };
         let mut topology = Topology::new_with(addr, &key, custom_builder);
         topology.subscribe_topic(topic::MESSAGES);
         topology.subscribe_topic(topic::BLOCKS);
         P2pTopology {
             topology,
             quarantine,
+            reputation: Reputation::new(),
+            preferred_list,
+            addresses: HashMap::new(),
             key,
         }
     }
 
+    /// replace the peers that cannot be quarantined
+    pub fn set_quarantine_whitelist(&mut self, whitelist: HashSet<Address>) {
+        self.quarantine.set_whitelist(whitelist);
+    }
+
+    /// replace the peers of the preferred list layer
+    pub fn set_preferred_list(&mut self, config: &PreferredListConfig) {
+        self.preferred_list.update(config);
+    }
+
     /// Returns a list of neighbors selected in this turn
     /// to contact for event dissemination.
     pub fn view(&mut self, selection: poldercast::layer::Selection) -> View {
@@ -149,6 +171,7 @@ impl P2pTopology {
             let peer = Profile::from_gossip(gossip);
             let peer_id = NodeId(peer.id());
             tracing::trace!(node = %peer.address(), "received peer from gossip");
+            self.addresses.insert(peer.address(), peer_id.clone());
             if self.topology.add_peer(peer) {
                 self.quarantine.record_new_gossip(&peer_id);
             }
@@ -193,24 +216,128 @@ impl P2pTopology {
             })
     }
 
-    /// register that we were able to establish an handshake with given peer
+    pub fn list_reputation(&self) -> Vec<PeerReputation> {
+        self.reputation.list()
+    }
+
+    /// register that we were able to establish an handshake with given peer,
+    /// the peers with a bad reputation are not promoted
     pub fn promote_node(&mut self, node: &NodeId) {
+        if self.reputation.is_distrusted(node) {
+            tracing::debug!(id = ?node, "not promoting distrusted node");
+            return;
+        }
         self.topology.promote_peer(node.as_ref());
     }
 
-    /// register a strike against the given peer
+    /// register a strike against the given peer, the peer is quarantined
+    /// for a duration depending on the reason of the report
     #[instrument(skip(self), level = "debug")]
-    pub fn report_node(&mut self, node_id: &NodeId) {
+    pub fn report_node(&mut self, node_id: &NodeId, reason: ReportReason) {
         if let Some(node) = self.topology.get(node_id.as_ref()).cloned() {
-            self.quarantine
-                .report_node(&mut self.topology, Peer::from(node.gossip().clone()));
+            let peer = Peer::from(node.gossip().clone());
+            self.reputation
+                .record_report(node_id, peer.address(), reason);
+            if let Some(severity) = reason.quarantine_severity() {
+                self.quarantine
+                    .report_node(&mut self.topology, peer, severity);
+            }
         }
     }
 
+    /// register a strike against the peer at the given address
+    pub fn report_address(&mut self, address: Address, reason: ReportReason) {
+        if let Some(node_id) = self.find_node(address) {
+            self.report_node(&node_id, reason);
+        }
+    }
+
+    /// register that the peer at the given address delivered some useful data
+    pub fn reward_address(&mut self, address: Address) {
+        if let Some(node_id) = self.find_node(address) {
+            self.reputation.record_useful_data(&node_id, address);
+        }
+    }
+
+    fn find_node(&self, address: Address) -> Option<NodeId> {
+        let node_id = self.addresses.get(&address)?;
+        match self.peer_address(node_id) {
+            Some(peer_address) if peer_address == address => Some(node_id.clone()),
+            _ => None,
+        }
+    }
+
+    /// the address of the peer if it is still in the topology
+    fn peer_address(&self, node_id: &NodeId) -> Option<Address> {
+        let profiles = self.topology.peers();
+        profiles
+            .pool()
+            .peek(node_id.as_ref())
+            .or_else(|| profiles.trusted().peek(node_id.as_ref()))
+            .or_else(|| profiles.dirty().peek(node_id.as_ref()))
+            .map(|profile| profile.address())
+    }
+
     /// update our gossip so that other nodes can see that we are updating
     /// it and are alive
     pub fn update_gossip(&mut self) {
         self.topology.update_profile_subscriptions(&self.key);
+        self.forget_addresses();
+    }
+
+    /// forget the addresses of the peers evicted from the topology or
+    /// known now by another address
+    fn forget_addresses(&mut self) {
+        let mut addresses = std::mem::take(&mut self.addresses);
+        addresses.retain(|address, node_id| self.peer_address(node_id) == Some(*address));
+        self.addresses = addresses;
+    }
+
+    /// the known peers and the quarantined ones, to be restored after a
+    /// restart
+    pub fn snapshot(&self) -> TopologySnapshot {
+        let dirty = self.topology.peers().dirty();
+        let quarantined = self
+            .quarantine
+            .reports()
+            .filter_map(|(id, reported_at, duration)| {
+                dirty.peek(id.as_ref()).map(|profile| QuarantinedPeer {
+                    peer: Peer::from(profile.gossip().clone()),
+                    reported_at,
+                    duration,
+                })
+            })
+            .collect();
+        TopologySnapshot {
+            peers: self.list_available().collect(),
+            quarantined,
+        }
+    }
+
+    /// add the peers of a snapshot taken before a restart, the peers still
+    /// in quarantine are quarantined again for the remaining duration
+    pub fn restore(&mut self, snapshot: TopologySnapshot) {
+        let TopologySnapshot {
+            peers,
+            mut quarantined,
+        } = snapshot;
+
+        self.accept_gossips(Gossips::from(peers));
+
+        // the reports are lifted in the order they have been recorded
+        quarantined.sort_by_key(|quarantined| quarantined.reported_at);
+        for QuarantinedPeer {
+            peer,
+            reported_at,
+            duration,
+        } in quarantined
+        {
+            self.addresses.insert(peer.address(), peer.id());
+            self.topology
+                .add_peer(Profile::from_gossip(peer.clone().into()));
+            self.quarantine
+                .restore_report(&mut self.topology, peer, reported_at, duration);
+        }
     }
 
     pub fn lift_reports(&mut self) -> Vec<Peer> {
@@ -232,3 +359,83 @@ impl P2pTopology {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(seed: u8) -> keynesis::key::ed25519::SecretKey {
+        keynesis::key::ed25519::SecretKey::new(ChaChaRng::from_seed([seed; 32]))
+    }
+
+    fn peer(seed: u8) -> Peer {
+        Peer::from(poldercast::Gossip::new(
+            SocketAddr::from(([10, 0, 0, seed], 3000)),
+            &key(seed),
+            poldercast::Subscriptions::new().as_slice(),
+        ))
+    }
+
+    fn test_topology() -> P2pTopology {
+        let key = key(0);
+        let preferred_list = PreferredList::new(&PreferredListConfig {
+            view_max: 0,
+            peers: Vec::new(),
+        });
+        let custom_builder = CustomLayerBuilder {
+            rings: RingsConfig::default(),
+            preferred_list: preferred_list.clone(),
+        };
+        P2pTopology {
+            topology: Topology::new_with(*LOCAL_ADDR, &key, custom_builder),
+            quarantine: ReportRecords::default(),
+            reputation: Reputation::new(),
+            preferred_list,
+            addresses: HashMap::new(),
+            key,
+        }
+    }
+
+    fn is_quarantined(topology: &P2pTopology, peer: &Peer) -> bool {
+        topology
+            .topology
+            .peers()
+            .dirty()
+            .contains(peer.id().as_ref())
+    }
+
+    #[test]
+    fn reported_address_is_quarantined() {
+        let mut topology = test_topology();
+        let reported = peer(1);
+        topology.accept_gossips(Gossips::from(vec![reported.clone(), peer(2)]));
+
+        topology.report_address(reported.address(), ReportReason::InvalidBlock);
+        assert!(is_quarantined(&topology, &reported));
+        assert!(!is_quarantined(&topology, &peer(2)));
+    }
+
+    #[test]
+    fn distrusted_node_is_not_promoted() {
+        let mut topology = test_topology();
+        let (trusted, distrusted) = (peer(1), peer(2));
+        topology.accept_gossips(Gossips::from(vec![trusted.clone(), distrusted.clone()]));
+
+        for _ in 0..10 {
+            topology.reputation.record_report(
+                &distrusted.id(),
+                distrusted.address(),
+                ReportReason::InvalidBlock,
+            );
+        }
+        topology.report_node(&trusted.id(), ReportReason::InvalidBlock);
+        topology.report_node(&distrusted.id(), ReportReason::InvalidBlock);
+        assert!(is_quarantined(&topology, &trusted));
+        assert!(is_quarantined(&topology, &distrusted));
+
+        topology.promote_node(&trusted.id());
+        topology.promote_node(&distrusted.id());
+        assert!(!is_quarantined(&topology, &trusted));
+        assert!(is_quarantined(&topology, &distrusted));
+    }
+}
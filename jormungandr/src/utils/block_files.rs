@@ -0,0 +1,116 @@
+//! Files written at given blocks of the blockchain, such as the ledger
+//! snapshots or the explorer states, stored in a directory next to the blocks
+//! storage and named after the chain length and the id of their block.
+
+use crate::blockcfg::{ChainLength, HeaderHash};
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// handle on a directory of files named `<chain length>-<block id>.<extension>`
+#[derive(Clone)]
+pub struct BlockFiles {
+    dir: PathBuf,
+    extension: &'static str,
+}
+
+impl BlockFiles {
+    pub fn new<P: AsRef<Path>>(dir: P, extension: &'static str) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(BlockFiles { dir, extension })
+    }
+
+    fn path(&self, chain_length: ChainLength, block_id: HeaderHash) -> PathBuf {
+        self.dir.join(format!(
+            "{}-{}.{}",
+            u32::from(chain_length),
+            block_id,
+            self.extension
+        ))
+    }
+
+    pub fn contains(&self, chain_length: ChainLength, block_id: HeaderHash) -> bool {
+        self.path(chain_length, block_id).is_file()
+    }
+
+    /// list the blocks having a file, the most recent one first
+    pub fn list(&self) -> io::Result<Vec<(ChainLength, HeaderHash)>> {
+        let mut blocks = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(self.extension) {
+                continue;
+            }
+            let stem = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(stem) => stem,
+                None => continue,
+            };
+            let mut parts = stem.splitn(2, '-');
+            let chain_length = parts.next().and_then(|s| s.parse::<u32>().ok());
+            let block_id = parts.next().and_then(|s| s.parse::<HeaderHash>().ok());
+            if let (Some(chain_length), Some(block_id)) = (chain_length, block_id) {
+                blocks.push((ChainLength::from(chain_length), block_id));
+            }
+        }
+        blocks.sort_by(|(a, _), (b, _)| b.cmp(a));
+        Ok(blocks)
+    }
+
+    pub fn open(
+        &self,
+        chain_length: ChainLength,
+        block_id: HeaderHash,
+    ) -> io::Result<BufReader<File>> {
+        File::open(self.path(chain_length, block_id)).map(BufReader::new)
+    }
+
+    /// write the file of the given block and remove the oldest files, only
+    /// the `keep` most recent ones are kept
+    pub fn write<F>(
+        &self,
+        chain_length: ChainLength,
+        block_id: HeaderHash,
+        keep: usize,
+        write: F,
+    ) -> io::Result<()>
+    where
+        F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    {
+        write_file(&self.path(chain_length, block_id), write)?;
+
+        for (chain_length, block_id) in self.list()?.into_iter().skip(keep) {
+            fs::remove_file(self.path(chain_length, block_id))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// write the file through a temporary file renamed once its content is synced
+/// on disk, so an interrupted write never leaves a partially written file
+pub fn write_file<F>(path: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    let path_tmp = path.with_extension("tmp");
+
+    {
+        let file = File::create(&path_tmp)?;
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+    }
+
+    fs::rename(path_tmp, path)
+}
+
+pub fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
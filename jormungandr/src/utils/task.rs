@@ -171,8 +171,10 @@ impl Services {
     }
 
     /// select on all the started services. this function will block until first services returns
-    pub fn wait_any_finished(self) -> Result<(), ServiceError> {
-        let finish_listener = self.finish_listener;
+    /// the services keep running until `self` is dropped, the shutdown can
+    /// still run tasks with `block_on_task`
+    pub fn wait_any_finished(&mut self) -> Result<(), ServiceError> {
+        let finish_listener = std::mem::take(&mut self.finish_listener);
         let result = self
             .runtime
             .block_on(async move { finish_listener.into_future().await.0 });
@@ -35,6 +35,7 @@ pub fn test_mempool_pool_max_entries_limit() {
             pool_max_entries: 1.into(),
             log_max_entries: 100.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -106,6 +107,7 @@ pub fn test_mempool_pool_max_entries_equal_0() {
             pool_max_entries: 0.into(),
             log_max_entries: 100.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -163,6 +165,7 @@ pub fn test_mempool_log_max_entries_only_one_fragment() {
             pool_max_entries: 1.into(),
             log_max_entries: 1.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -234,6 +237,7 @@ pub fn test_mempool_log_max_entries_equals_0() {
             pool_max_entries: 0.into(),
             log_max_entries: 0.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -290,6 +294,7 @@ pub fn test_mempool_pool_max_entries_overrides_log_max_entries() {
             pool_max_entries: 2.into(),
             log_max_entries: 0.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -36,6 +36,7 @@ pub fn test_mempool_pool_max_entries_limit() {
             pool_max_entries: 1.into(),
             log_max_entries: 100.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -128,6 +129,7 @@ pub fn test_mempool_pool_max_entries_equal_0() {
             pool_max_entries: 0.into(),
             log_max_entries: 100.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -210,6 +212,7 @@ pub fn test_mempool_log_max_entries_only_one_fragment() {
             pool_max_entries: 1.into(),
             log_max_entries: 1.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -302,6 +305,7 @@ pub fn test_mempool_log_max_entries_equals_0() {
             pool_max_entries: 0.into(),
             log_max_entries: 0.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -385,6 +389,7 @@ pub fn test_mempool_pool_max_entries_overrides_log_max_entries() {
             pool_max_entries: 2.into(),
             log_max_entries: 0.into(),
             persistent_log: None,
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -5,7 +5,7 @@ use chain_impl_mockchain::fragment::FragmentId;
 use chain_impl_mockchain::key::Hash;
 use jormungandr_lib::interfaces::ActiveSlotCoefficient;
 use jormungandr_testing_utils::stake_pool::StakePool;
-use jormungandr_testing_utils::testing::node::Explorer;
+use jormungandr_testing_utils::testing::node::{time, Explorer};
 use jortestkit::process::Wait;
 use std::str::FromStr;
 use std::time::Duration;
@@ -93,6 +93,99 @@ pub fn explorer_sanity_test() {
     epoch(&explorer);
 }
 
+#[test]
+pub fn explorer_epoch_stake_distribution_test() {
+    let faucet = startup::create_new_account_address();
+
+    let mut config = ConfigurationBuilder::new();
+    config
+        .with_consensus_genesis_praos_active_slot_coeff(ActiveSlotCoefficient::MAXIMUM)
+        .with_slots_per_epoch(20)
+        .with_explorer();
+
+    let (jormungandr, initial_stake_pools) =
+        startup::start_stake_pool(&[faucet], &[], &mut config).unwrap();
+
+    time::wait_for_epoch(1, jormungandr.rest());
+
+    let explorer = jormungandr.explorer();
+    let distribution = explorer
+        .epoch_stake_distribution(1)
+        .unwrap()
+        .data
+        .unwrap()
+        .epoch
+        .stake_distribution;
+
+    let mut expected_pools: Vec<String> = initial_stake_pools
+        .iter()
+        .map(|x| x.id().to_string())
+        .collect();
+    let mut pools: Vec<String> = distribution
+        .pools
+        .iter()
+        .map(|x| x.pool.id.clone())
+        .collect();
+    expected_pools.sort();
+    pools.sort();
+    assert_eq!(expected_pools, pools, "pools in the stake distribution");
+    assert!(
+        distribution
+            .pools
+            .iter()
+            .all(|x| x.delegated_stake.parse::<u64>().unwrap() > 0),
+        "pools without stake in the stake distribution"
+    );
+
+    let not_indexed = explorer.epoch_stake_distribution(1_000).unwrap();
+    assert!(not_indexed.data.is_none(), "epoch not indexed yet");
+    assert!(
+        not_indexed
+            .errors
+            .unwrap()
+            .iter()
+            .any(|error| error.message.contains("not found")),
+        "stake distribution of an epoch not indexed yet"
+    );
+}
+
+#[test]
+pub fn explorer_account_rewards_test() {
+    let stake_pool_owner = startup::create_new_account_address();
+
+    // the rewards of every account are indexed without `--rewards-report-all`
+    let mut config = ConfigurationBuilder::new();
+    config
+        .with_consensus_genesis_praos_active_slot_coeff(ActiveSlotCoefficient::MAXIMUM)
+        .with_slots_per_epoch(20)
+        .with_slot_duration(3)
+        .with_total_rewards_supply(1_000_000.into())
+        .with_explorer();
+
+    let (jormungandr, _) =
+        startup::start_stake_pool(&[stake_pool_owner.clone()], &[], &mut config).unwrap();
+
+    time::wait_for_epoch(2, jormungandr.rest());
+
+    let rewards = jormungandr
+        .explorer()
+        .address_rewards(stake_pool_owner.address().to_string(), 100)
+        .unwrap()
+        .data
+        .unwrap()
+        .address
+        .rewards
+        .edges
+        .unwrap();
+
+    assert!(
+        rewards
+            .iter()
+            .any(|x| x.as_ref().unwrap().node.amount.parse::<u64>().unwrap() > 0),
+        "no rewards for the stake pool owner"
+    );
+}
+
 fn transaction_by_id(explorer: &Explorer, fragment_id: FragmentId) {
     let explorer_transaction = explorer
         .transaction(fragment_id.into())
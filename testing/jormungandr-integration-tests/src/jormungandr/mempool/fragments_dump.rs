@@ -39,6 +39,7 @@ pub fn dump_send_correct_fragments() {
                 persistent_log: Some(PersistentLog {
                     dir: persistent_log_path.path().to_path_buf(),
                 }),
+                ..Default::default()
             }),
     )
     .unwrap();
@@ -99,6 +100,7 @@ pub fn dump_send_invalid_fragments() {
                 persistent_log: Some(PersistentLog {
                     dir: persistent_log_path.path().to_path_buf(),
                 }),
+                ..Default::default()
             }),
     )
     .unwrap();
@@ -147,6 +149,7 @@ pub fn non_existing_folder() {
                 persistent_log: Some(PersistentLog {
                     dir: persistent_log_path.path().to_path_buf(),
                 }),
+                ..Default::default()
             }),
     )
     .unwrap();
@@ -171,6 +174,7 @@ pub fn invalid_folder() {
             persistent_log: Some(PersistentLog {
                 dir: persistent_log_path.path().to_path_buf(),
             }),
+            ..Default::default()
         })
         .build(&temp_dir);
 
@@ -199,6 +203,7 @@ pub fn fragment_which_reached_mempool_should_be_persisted() {
                 persistent_log: Some(PersistentLog {
                     dir: persistent_log_path.path().to_path_buf(),
                 }),
+                ..Default::default()
             }),
     )
     .unwrap();
@@ -238,6 +243,7 @@ pub fn fragment_which_is_not_in_fragment_log_should_be_persisted() {
                 persistent_log: Some(PersistentLog {
                     dir: persistent_log_path.path().to_path_buf(),
                 }),
+                ..Default::default()
             }),
     )
     .unwrap();
@@ -277,6 +283,7 @@ pub fn pending_fragment_should_be_persisted() {
                 persistent_log: Some(PersistentLog {
                     dir: persistent_log_path.path().to_path_buf(),
                 }),
+                ..Default::default()
             }),
     )
     .unwrap();
@@ -321,6 +328,7 @@ pub fn node_should_pickup_log_after_restart() {
             persistent_log: Some(PersistentLog {
                 dir: persistent_log_path.path().to_path_buf(),
             }),
+            ..Default::default()
         })
         .with_block0_consensus(ConsensusVersion::Bft)
         .with_funds(vec![
@@ -1,15 +1,19 @@
 use crate::common::{
+    configuration::get_jormungandr_app,
     jcli::JCli,
-    jormungandr::{ConfigurationBuilder, JormungandrProcess, Role, Starter},
+    jormungandr::{
+        get_command, ConfigurationBuilder, FromGenesis, JormungandrProcess, Role, Starter,
+    },
     startup,
 };
 
-use jormungandr_lib::interfaces::{AccountState, InitialUTxO, SettingsDto, UTxOInfo};
-use jormungandr_testing_utils::testing::SyncNode;
+use jormungandr_lib::interfaces::{AccountState, InitialUTxO, NodeConfig, SettingsDto, UTxOInfo};
+use jormungandr_testing_utils::testing::{node::time, JormungandrParams, SyncNode};
 use jormungandr_testing_utils::wallet::Wallet;
 
 use assert_fs::prelude::*;
 use assert_fs::TempDir;
+use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug, PartialEq)]
 struct LedgerSnapshot {
@@ -192,3 +196,112 @@ pub fn test_node_recovers_kill_signal() {
         snapshot_before, snapshot_after
     );
 }
+
+const SNAPSHOT_EXTENSION: &str = "snapshot";
+
+fn ledger_snapshots(snapshots_dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(snapshots_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension()
+                        .map_or(false, |ext| ext == SNAPSHOT_EXTENSION)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// the ledger snapshots are taken by the periodic garbage collection of the node,
+/// once an epoch end is deeper than the epoch stability depth
+fn wait_for_ledger_snapshot(snapshots_dir: &Path, jormungandr: &JormungandrProcess) {
+    let timeout = std::time::Duration::from_secs(300);
+    let started = std::time::Instant::now();
+    while ledger_snapshots(snapshots_dir).is_empty() {
+        if started.elapsed() > timeout {
+            panic!(
+                "no ledger snapshot taken after {:?}. \nNode logs: {}",
+                timeout,
+                jormungandr.log_content()
+            );
+        }
+        std::thread::sleep(std::time::Duration::from_secs(1));
+    }
+}
+
+fn snapshot_config(temp_dir: &TempDir) -> JormungandrParams<NodeConfig> {
+    ConfigurationBuilder::new()
+        .with_slots_per_epoch(10)
+        .with_slot_duration(1)
+        .with_epoch_stability_depth(5)
+        .with_storage(&temp_dir.child("storage"))
+        .build(temp_dir)
+}
+
+#[test]
+pub fn test_node_loads_the_ledger_from_a_snapshot() {
+    let temp_dir = TempDir::new().unwrap();
+    let snapshots_dir = temp_dir.child("storage").child("snapshots");
+    let config = snapshot_config(&temp_dir);
+
+    let jormungandr = Starter::new().config(config.clone()).start().unwrap();
+    time::wait_for_epoch(3, jormungandr.rest());
+    wait_for_ledger_snapshot(snapshots_dir.path(), &jormungandr);
+    let settings_before = jormungandr.rest().settings().unwrap();
+    jormungandr.shutdown();
+
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let jormungandr = Starter::new()
+        .temp_dir(temp_dir)
+        .config(config)
+        .role(Role::Leader)
+        .start()
+        .unwrap();
+
+    assert!(
+        jormungandr
+            .logger
+            .contains_any_of(&["loading the ledger from the snapshot"]),
+        "the ledger was not loaded from the snapshot. \nNode logs: {}",
+        jormungandr.log_content()
+    );
+    assert_eq!(settings_before, jormungandr.rest().settings().unwrap());
+}
+
+#[test]
+pub fn test_storage_check_rejects_a_corrupt_snapshot() {
+    let temp_dir = TempDir::new().unwrap();
+    let snapshots_dir = temp_dir.child("storage").child("snapshots");
+    let config = snapshot_config(&temp_dir);
+
+    let jormungandr = Starter::new().config(config.clone()).start().unwrap();
+    time::wait_for_epoch(3, jormungandr.rest());
+    wait_for_ledger_snapshot(snapshots_dir.path(), &jormungandr);
+    jormungandr.shutdown();
+
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    for snapshot in ledger_snapshots(snapshots_dir.path()) {
+        let mut bytes = std::fs::read(&snapshot).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        std::fs::write(&snapshot, bytes).unwrap();
+    }
+
+    let output = get_command(
+        &config,
+        get_jormungandr_app(),
+        Role::Leader,
+        FromGenesis::File,
+    )
+    .arg("--storage-check-snapshots")
+    .output()
+    .unwrap();
+
+    assert!(
+        !output.status.success(),
+        "the corrupt ledger snapshot was not rejected. \nNode output: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
@@ -24,6 +24,7 @@ pub fn accounts_funds_are_updated_after_transaction() {
                 pool_max_entries: 1_000_000usize.into(),
                 log_max_entries: 1_000_000usize.into(),
                 persistent_log: None,
+                ..Default::default()
             }),
     )
     .unwrap();
@@ -29,6 +29,7 @@ pub fn persistent_log_load_test() {
                 persistent_log: Some(PersistentLog {
                     dir: persistent_log_path.path().to_path_buf(),
                 }),
+                ..Default::default()
             }),
     )
     .unwrap();
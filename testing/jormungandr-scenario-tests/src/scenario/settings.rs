@@ -317,6 +317,7 @@ impl Prepare for Rest {
             listen: context.generate_new_rest_listen_address(),
             tls: None,
             cors: None,
+            admin: None,
         }
     }
 }
@@ -90,6 +90,7 @@ impl LegacyNodeConfigConverter {
                 listen: source.rest.listen,
                 cors: None,
                 tls: None,
+                admin: None,
             },
             p2p: P2p {
                 trusted_peers,
@@ -149,6 +150,7 @@ impl LegacyNodeConfigConverter {
                 listen: source.rest.listen,
                 cors: None,
                 tls: None,
+                admin: None,
             },
             p2p: P2p {
                 trusted_peers,
@@ -49,6 +49,7 @@ impl NodeConfigBuilder {
                     .unwrap(),
                 tls: None,
                 cors: None,
+                admin: None,
             },
             p2p: P2p {
                 node_key_file: None,
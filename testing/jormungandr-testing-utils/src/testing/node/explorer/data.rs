@@ -16,6 +16,14 @@ use graphql_client::GraphQLQuery;
 )]
 pub struct Address;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "resources/explorer/graphql/address_rewards.graphql",
+    schema_path = "resources/explorer/graphql/schema.graphql",
+    response_derives = "Debug"
+)]
+pub struct AddressRewards;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     query_path = "resources/explorer/graphql/allblocks.graphql",
@@ -48,6 +56,14 @@ pub struct BlocksByChainLength;
 )]
 pub struct Epoch;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "resources/explorer/graphql/epoch_stake_distribution.graphql",
+    schema_path = "resources/explorer/graphql/schema.graphql",
+    response_derives = "Debug"
+)]
+pub struct EpochStakeDistribution;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     query_path = "resources/explorer/graphql/lastblock.graphql",
@@ -1,9 +1,11 @@
 use self::{
     client::GraphQlClient,
     data::{
-        address, all_blocks, all_stake_pools, all_vote_plans, blocks_by_chain_length, epoch,
-        last_block, settings, stake_pool, transaction_by_id, Address, AllBlocks, AllStakePools,
-        AllVotePlans, BlocksByChainLength, Epoch, LastBlock, Settings, StakePool, TransactionById,
+        address, address_rewards, all_blocks, all_stake_pools, all_vote_plans,
+        blocks_by_chain_length, epoch, epoch_stake_distribution, last_block, settings, stake_pool,
+        transaction_by_id, Address, AddressRewards, AllBlocks, AllStakePools, AllVotePlans,
+        BlocksByChainLength, Epoch, EpochStakeDistribution, LastBlock, Settings, StakePool,
+        TransactionById,
     },
 };
 use chain_impl_mockchain::block::BlockDate as LibBlockDate;
@@ -87,6 +89,22 @@ impl Explorer {
         Ok(response_body)
     }
 
+    pub fn address_rewards<S: Into<String>>(
+        &self,
+        bech32_address: S,
+        limit: i64,
+    ) -> Result<Response<address_rewards::ResponseData>, ExplorerError> {
+        let query = AddressRewards::build_query(address_rewards::Variables {
+            bech32: bech32_address.into(),
+            first: limit,
+        });
+        self.print_request(&query);
+        let response = self.client.run(query).map_err(ExplorerError::ClientError)?;
+        let response_body = response.json()?;
+        self.print_log(&response_body);
+        Ok(response_body)
+    }
+
     pub fn stake_pools(
         &self,
         limit: i64,
@@ -147,6 +165,20 @@ impl Explorer {
         Ok(response_body)
     }
 
+    pub fn epoch_stake_distribution(
+        &self,
+        epoch_number: u32,
+    ) -> Result<Response<epoch_stake_distribution::ResponseData>, ExplorerError> {
+        let query = EpochStakeDistribution::build_query(epoch_stake_distribution::Variables {
+            id: epoch_number.to_string(),
+        });
+        self.print_request(&query);
+        let response = self.client.run(query).map_err(ExplorerError::ClientError)?;
+        let response_body = response.json()?;
+        self.print_log(&response_body);
+        Ok(response_body)
+    }
+
     pub fn stake_pool(
         &self,
         id: PoolId,
@@ -12,8 +12,9 @@ use jormungandr_lib::interfaces::FragmentStatus;
 use jormungandr_lib::{
     crypto::hash::Hash,
     interfaces::{
-        AccountState, EnclaveLeaderId, EpochRewardsInfo, FragmentLog, LeadershipLog, NodeStatsDto,
-        PeerRecord, PeerStats, SettingsDto, StakeDistributionDto, VotePlanStatus,
+        AccountState, EnclaveLeaderId, EpochRewardsInfo, FragmentLog, LeadershipLog,
+        LeadershipSchedule, NodeStatsDto, PeerRecord, PeerStats, SettingsDto, StakeDistributionDto,
+        VotePlanStatus,
     },
 };
 use std::collections::HashMap;
@@ -182,6 +183,10 @@ impl JormungandrRest {
         serde_json::from_str(&self.inner.leaders_log()?).map_err(RestError::CannotDeserialize)
     }
 
+    pub fn leaders_schedule(&self) -> Result<Vec<LeadershipSchedule>, RestError> {
+        serde_json::from_str(&self.inner.leaders_schedule()?).map_err(RestError::CannotDeserialize)
+    }
+
     pub fn leaders(&self) -> Result<Vec<EnclaveLeaderId>, RestError> {
         let leaders = self.inner.leaders()?;
         let leaders: Vec<EnclaveLeaderId> = if leaders.is_empty() {
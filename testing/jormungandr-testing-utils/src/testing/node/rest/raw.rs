@@ -156,6 +156,10 @@ impl RawRest {
         self.get("leaders/logs")
     }
 
+    pub fn leaders_schedule(&self) -> Result<Response, reqwest::Error> {
+        self.get("leaders/schedule")
+    }
+
     pub fn tip(&self) -> Result<Response, reqwest::Error> {
         self.get("tip")
     }